@@ -7,6 +7,8 @@ use nom::{do_parse, IResult};
 use std::fmt;
 use std::io::{Cursor, Write};
 
+use crate::util::parse_ip4addr;
+
 #[derive(Debug, PartialEq)]
 pub struct Arp {
     pub hardware_type:   u16,
@@ -113,12 +115,6 @@ fn parse_macaddr(bytes: &[u8]) -> IResult<&[u8], MacAddress> {
     Ok((bytes1, macaddr))
 }
 
-fn parse_ip4addr(bytes: &[u8]) -> IResult<&[u8], Ipv4Addr> {
-    let (bytes1, value) = take(4usize)(bytes)?;
-    let ip4addr = Ipv4Addr::new(value[0], value[1], value[2], value[3]);
-    Ok((bytes1, ip4addr))
-}
-
 #[cfg(test)]
 mod tests_arp {
     use eui48::MacAddress;
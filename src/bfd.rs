@@ -0,0 +1,304 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::number::complete::{be_u8, be_u32};
+use nom::{call, do_parse, IResult};
+use std::fmt;
+use std::io::{Cursor, Write};
+
+pub const BFD_SINGLE_HOP_PORT: u16 = 3784;
+pub const BFD_MULTI_HOP_PORT:  u16 = 4784;
+
+// Session states
+pub const BFD_STATE_ADMIN_DOWN: u8 = 0;
+pub const BFD_STATE_DOWN:       u8 = 1;
+pub const BFD_STATE_INIT:       u8 = 2;
+pub const BFD_STATE_UP:         u8 = 3;
+
+// Diagnostic codes
+pub const BFD_DIAG_NONE:                   u8 = 0;
+pub const BFD_DIAG_DETECTION_TIME_EXPIRED: u8 = 1;
+pub const BFD_DIAG_ECHO_FAILED:            u8 = 2;
+pub const BFD_DIAG_NEIGHBOR_DOWN:          u8 = 3;
+pub const BFD_DIAG_FORWARDING_RESET:       u8 = 4;
+pub const BFD_DIAG_PATH_DOWN:              u8 = 5;
+pub const BFD_DIAG_CONCATENATED_PATH_DOWN: u8 = 6;
+pub const BFD_DIAG_ADMIN_DOWN:             u8 = 7;
+pub const BFD_DIAG_REVERSE_PATH_DOWN:      u8 = 8;
+
+// Flags following the state
+pub const BFD_FLAG_POLL:        u8 = 0x20;
+pub const BFD_FLAG_FINAL:       u8 = 0x10;
+pub const BFD_FLAG_CPI:         u8 = 0x08;
+pub const BFD_FLAG_AUTH:        u8 = 0x04;
+pub const BFD_FLAG_DEMAND:      u8 = 0x02;
+pub const BFD_FLAG_MULTIPOINT:  u8 = 0x01;
+
+// Authentication types
+pub const BFD_AUTH_SIMPLE_PASSWORD:     u8 = 1;
+pub const BFD_AUTH_KEYED_MD5:           u8 = 2;
+pub const BFD_AUTH_METICULOUS_MD5:      u8 = 3;
+pub const BFD_AUTH_KEYED_SHA1:          u8 = 4;
+pub const BFD_AUTH_METICULOUS_SHA1:     u8 = 5;
+
+// Authentication section
+#[derive(Debug, PartialEq, Clone)]
+pub enum BfdAuth {
+    SimplePassword { key_id: u8, password: Vec<u8> },
+    // Keyed and meticulous keyed MD5 and SHA1
+    Keyed { auth_type: u8, key_id: u8, sequence: u32, digest: Vec<u8> },
+    Unknown { auth_type: u8, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl BfdAuth {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let data: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(data);
+        let auth_type = match self {
+            BfdAuth::SimplePassword { key_id, password } => {
+                cursor.write_u8(key_id);
+                cursor.write_all(&password);
+                BFD_AUTH_SIMPLE_PASSWORD
+            },
+            BfdAuth::Keyed { auth_type, key_id, sequence, digest } => {
+                cursor.write_u8(key_id);
+                cursor.write_u8(0);
+                cursor.write_u32::<NetworkEndian>(sequence);
+                cursor.write_all(&digest);
+                auth_type
+            },
+            BfdAuth::Unknown { auth_type, data } => {
+                cursor.write_all(&data);
+                auth_type
+            }
+        };
+        let data = cursor.into_inner();
+
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(auth_type);
+        cursor.write_u8(data.len() as u8 + 2);
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], BfdAuth> {
+        let (bytes1, auth_type) = be_u8(bytes)?;
+        let (bytes2, length)    = verify(be_u8, |length| *length >= 2)(bytes1)?;
+        let (bytes3, data)      = take(length - 2)(bytes2)?;
+        let auth = match (auth_type, data.len()) {
+            (BFD_AUTH_SIMPLE_PASSWORD, 2..=17) =>
+                BfdAuth::SimplePassword { key_id: data[0], password: data[1..].to_vec() },
+            (BFD_AUTH_KEYED_MD5..=BFD_AUTH_METICULOUS_SHA1, 6..=255) => BfdAuth::Keyed {
+                auth_type,
+                key_id:   data[0],
+                sequence: u32::from_be_bytes([data[2], data[3], data[4], data[5]]),
+                digest:   data[6..].to_vec()
+            },
+            _ =>
+                BfdAuth::Unknown { auth_type, data: data.to_vec() }
+        };
+        Ok((bytes3, auth))
+    }
+}
+
+// Bidirectional Forwarding Detection control packet (RFC 5880)
+#[derive(Debug, PartialEq)]
+pub struct Bfd {
+    pub version:                       u8,
+    pub diagnostic:                    u8,
+    pub state:                         u8,
+    // The A bit follows the presence of the authentication section
+    pub flags:                         u8,
+    pub detect_mult:                   u8,
+    pub my_discriminator:              u32,
+    pub your_discriminator:            u32,
+    // Intervals in microseconds
+    pub desired_min_tx_interval:       u32,
+    pub required_min_rx_interval:      u32,
+    pub required_min_echo_rx_interval: u32,
+    pub auth:                          Option<BfdAuth>
+}
+
+impl Default for Bfd {
+    fn default() -> Bfd {
+        Bfd {
+            version:                       1,
+            diagnostic:                    BFD_DIAG_NONE,
+            state:                         BFD_STATE_DOWN,
+            flags:                         0,
+            detect_mult:                   3,
+            my_discriminator:              0,
+            your_discriminator:            0,
+            desired_min_tx_interval:       1_000_000,
+            required_min_rx_interval:      1_000_000,
+            required_min_echo_rx_interval: 0,
+            auth:                          None
+        }
+    }
+}
+
+impl fmt::Display for Bfd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "BFD(\
+             state: {}, \
+             diag: {}, \
+             flags: 0x{:02x}, \
+             mult: {}, \
+             my_disc: {}, \
+             your_disc: {}, \
+             tx: {}, \
+             rx: {}\
+             )",
+            self.state,
+            self.diagnostic,
+            self.flags,
+            self.detect_mult,
+            self.my_discriminator,
+            self.your_discriminator,
+            self.desired_min_tx_interval,
+            self.required_min_rx_interval
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Bfd {
+    // Instantiate a new control packet of a session in Down state
+    pub fn new() -> Bfd { Bfd::default() }
+
+    // Instantiate a control packet with equal transmit and receive intervals
+    pub fn control(state: u8, my_discriminator: u32, your_discriminator: u32, interval: u32) -> Bfd {
+        Bfd {
+            state,
+            my_discriminator,
+            your_discriminator,
+            desired_min_tx_interval:  interval,
+            required_min_rx_interval: interval,
+            ..Bfd::default()
+        }
+    }
+
+    // Attach an authentication section
+    pub fn with_auth(self, auth: BfdAuth) -> Bfd {
+        Bfd { auth: Some(auth), ..self }
+    }
+
+    pub fn is_poll(&self) -> bool { self.flags & BFD_FLAG_POLL != 0 }
+
+    pub fn is_final(&self) -> bool { self.flags & BFD_FLAG_FINAL != 0 }
+
+    pub fn is_demand(&self) -> bool { self.flags & BFD_FLAG_DEMAND != 0 }
+
+    // Encode the control packet into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let auth = self.auth.map(|auth| auth.as_bytes()).unwrap_or_default();
+        let flags = match auth.len() {
+            0 => self.flags & !BFD_FLAG_AUTH,
+            _ => self.flags | BFD_FLAG_AUTH
+        };
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.version << 5 | (self.diagnostic & 0x1f));
+        cursor.write_u8(self.state << 6 | (flags & 0x3f));
+        cursor.write_u8(self.detect_mult);
+        cursor.write_u8(auth.len() as u8 + 24);
+        cursor.write_u32::<NetworkEndian>(self.my_discriminator);
+        cursor.write_u32::<NetworkEndian>(self.your_discriminator);
+        cursor.write_u32::<NetworkEndian>(self.desired_min_tx_interval);
+        cursor.write_u32::<NetworkEndian>(self.required_min_rx_interval);
+        cursor.write_u32::<NetworkEndian>(self.required_min_echo_rx_interval);
+        cursor.write_all(&auth);
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Bfd> {
+        let (bytes1, bfd) = do_parse!(
+            bytes,
+            vers_diag:                     be_u8 >>
+            state_flags:                   be_u8 >>
+            detect_mult:                   be_u8 >>
+            _length:                       call!(verify(be_u8, |length| *length >= 24)) >>
+            my_discriminator:              be_u32 >>
+            your_discriminator:            be_u32 >>
+            desired_min_tx_interval:       be_u32 >>
+            required_min_rx_interval:      be_u32 >>
+            required_min_echo_rx_interval: be_u32 >>
+                (
+                    Bfd {
+                        version:    vers_diag >> 5,
+                        diagnostic: vers_diag & 0x1f,
+                        state:      state_flags >> 6,
+                        flags:      state_flags & 0x3f,
+                        detect_mult,
+                        my_discriminator,
+                        your_discriminator,
+                        desired_min_tx_interval,
+                        required_min_rx_interval,
+                        required_min_echo_rx_interval,
+                        auth: None
+                    }
+                )
+        )?;
+
+        if bfd.flags & BFD_FLAG_AUTH == 0 {
+            return Ok((bytes1, bfd))
+        }
+
+        let (bytes2, auth) = BfdAuth::from_bytes(bytes1)?;
+        Ok((bytes2, Bfd { auth: Some(auth), ..bfd }))
+    }
+}
+
+#[cfg(test)]
+mod tests_bfd {
+    use crate::bfd::*;
+
+    #[test]
+    fn parse() {
+        let frame = &mut [
+            0x20,0xc0,0x03,0x18,0x00,0x00,0x00,0x01,
+            0x00,0x00,0x00,0x02,0x00,0x04,0x93,0xe0,
+            0x00,0x04,0x93,0xe0,0x00,0x00,0x00,0x00
+        ];
+
+        let (leftover, bfd) = Bfd::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(Bfd::control(BFD_STATE_UP, 1, 2, 300_000), bfd);
+        assert!(!bfd.is_poll());
+        assert_eq!(
+            "BFD(state: 3, diag: 0, flags: 0x00, mult: 3, my_disc: 1, your_disc: 2, tx: 300000, rx: 300000)",
+            format!("{}", bfd)
+        );
+        assert_eq!(frame.to_vec(), bfd.as_bytes());
+    }
+
+    #[test]
+    fn parse_auth() {
+        let frame = &mut [
+            0x23,0x64,0x03,0x30,0x00,0x00,0x00,0x01,
+            0x00,0x00,0x00,0x00,0x00,0x0f,0x42,0x40,
+            0x00,0x0f,0x42,0x40,0x00,0x00,0x00,0x00,
+            0x02,0x18,0x05,0x00,0x00,0x00,0x00,0x2a,
+            0x00,0x01,0x02,0x03,0x04,0x05,0x06,0x07,
+            0x08,0x09,0x0a,0x0b,0x0c,0x0d,0x0e,0x0f
+        ];
+
+        let (leftover, bfd) = Bfd::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(BFD_DIAG_NEIGHBOR_DOWN, bfd.diagnostic);
+        assert_eq!(BFD_STATE_DOWN, bfd.state);
+        assert!(bfd.is_poll());
+        assert_eq!(Some(BfdAuth::Keyed {
+            auth_type: BFD_AUTH_KEYED_MD5,
+            key_id:    5,
+            sequence:  42,
+            digest:    (0..16).collect()
+        }), bfd.auth);
+        assert_eq!(frame.to_vec(), bfd.as_bytes());
+    }
+}
@@ -0,0 +1,1145 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use eui48::MacAddress;
+use nom::bytes::complete::{tag, take};
+use nom::combinator::verify;
+use nom::number::complete::{be_u8, be_u16, be_u24, be_u32, be_u64};
+use nom::{call, do_parse, take, IResult};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Cursor, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::util::{ip_octets, parse_all, parse_ip};
+
+pub const BGP_PORT: u16 = 179;
+
+// Message types
+pub const BGP_OPEN:          u8 = 1;
+pub const BGP_UPDATE:        u8 = 2;
+pub const BGP_NOTIFICATION:  u8 = 3;
+pub const BGP_KEEPALIVE:     u8 = 4;
+pub const BGP_ROUTE_REFRESH: u8 = 5;
+
+// Address families
+pub const AFI_IPV4:  u16 = 1;
+pub const AFI_IPV6:  u16 = 2;
+pub const AFI_L2VPN: u16 = 25;
+
+pub const SAFI_UNICAST:   u8 = 1;
+pub const SAFI_MULTICAST: u8 = 2;
+pub const SAFI_EVPN:      u8 = 70;
+
+// Placeholder for 4-octet AS numbers in the 2-octet My AS field
+pub const AS_TRANS: u16 = 23456;
+
+// AS_PATH segment types
+pub const AS_SET:      u8 = 1;
+pub const AS_SEQUENCE: u8 = 2;
+
+// Path attribute flags
+pub const ATTR_FLAG_OPTIONAL:   u8 = 0x80;
+pub const ATTR_FLAG_TRANSITIVE: u8 = 0x40;
+pub const ATTR_FLAG_PARTIAL:    u8 = 0x20;
+pub const ATTR_FLAG_EXTENDED:   u8 = 0x10;
+
+// Origin values
+pub const ORIGIN_IGP:        u8 = 0;
+pub const ORIGIN_EGP:        u8 = 1;
+pub const ORIGIN_INCOMPLETE: u8 = 2;
+
+const BGP_MARKER: [u8; 16] = [0xff; 16];
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum BgpCapability {
+    Multiprotocol { afi: u16, safi: u8 },
+    RouteRefresh,
+    FourOctetAs(u32),
+    // AFI, SAFI and Send/Receive triples
+    AddPath(Vec<(u16, u8, u8)>),
+    Unknown { code: u8, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl BgpCapability {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let data: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(data);
+        let code = match self {
+            BgpCapability::Multiprotocol { afi, safi } => {
+                cursor.write_u16::<NetworkEndian>(afi);
+                cursor.write_u8(0);
+                cursor.write_u8(safi);
+                1
+            },
+            BgpCapability::RouteRefresh =>
+                2,
+            BgpCapability::FourOctetAs(asn) => {
+                cursor.write_u32::<NetworkEndian>(asn);
+                65
+            },
+            BgpCapability::AddPath(families) => {
+                for (afi, safi, send_receive) in families {
+                    cursor.write_u16::<NetworkEndian>(afi);
+                    cursor.write_u8(safi);
+                    cursor.write_u8(send_receive);
+                }
+                69
+            },
+            BgpCapability::Unknown { code, data } => {
+                cursor.write_all(&data);
+                code
+            }
+        };
+        let data = cursor.into_inner();
+
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(code);
+        cursor.write_u8(data.len() as u8);
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], BgpCapability> {
+        let (bytes1, code)   = be_u8(bytes)?;
+        let (bytes2, length) = be_u8(bytes1)?;
+        let (bytes3, data)   = take(length)(bytes2)?;
+        let capability = match (code, data.len()) {
+            (1, 4) => BgpCapability::Multiprotocol {
+                afi:  u16::from_be_bytes([data[0], data[1]]),
+                safi: data[3]
+            },
+            (2, 0) =>
+                BgpCapability::RouteRefresh,
+            (65, 4) =>
+                BgpCapability::FourOctetAs(u32::from_be_bytes([data[0], data[1], data[2], data[3]])),
+            (69, len) if len % 4 == 0 => BgpCapability::AddPath(
+                data.chunks_exact(4).map(|f| (u16::from_be_bytes([f[0], f[1]]), f[2], f[3])).collect()
+            ),
+            _ =>
+                BgpCapability::Unknown { code, data: data.to_vec() }
+        };
+        Ok((bytes3, capability))
+    }
+}
+
+// Optional parameter of an OPEN message
+#[derive(Debug, PartialEq, Clone)]
+pub enum BgpOpenParam {
+    Capabilities(Vec<BgpCapability>),
+    Unknown { param_type: u8, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl BgpOpenParam {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let (param_type, data) = match self {
+            BgpOpenParam::Capabilities(capabilities) =>
+                (2, capabilities.into_iter().flat_map(|c| c.as_bytes()).collect()),
+            BgpOpenParam::Unknown { param_type, data } =>
+                (param_type, data)
+        };
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(param_type);
+        cursor.write_u8(data.len() as u8);
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], BgpOpenParam> {
+        let (bytes1, param_type) = be_u8(bytes)?;
+        let (bytes2, length)     = be_u8(bytes1)?;
+        let (bytes3, data)       = take(length)(bytes2)?;
+        let param = match (param_type, parse_all(data, BgpCapability::from_bytes)) {
+            (2, Ok((_, capabilities))) => BgpOpenParam::Capabilities(capabilities),
+            _ => BgpOpenParam::Unknown { param_type, data: data.to_vec() }
+        };
+        Ok((bytes3, param))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BgpOpen {
+    pub version:        u8,
+    pub my_as:          u16,
+    pub hold_time:      u16,
+    pub bgp_identifier: Ipv4Addr,
+    pub params:         Vec<BgpOpenParam>
+}
+
+impl Default for BgpOpen {
+    fn default() -> BgpOpen {
+        BgpOpen {
+            version:        4,
+            my_as:          0,
+            hold_time:      180,
+            bgp_identifier: Ipv4Addr::UNSPECIFIED,
+            params:         Vec::new()
+        }
+    }
+}
+
+impl BgpOpen {
+    // Capabilities advertised over all optional parameters
+    pub fn capabilities(&self) -> Vec<&BgpCapability> {
+        self.params
+            .iter()
+            .flat_map(|param| match param {
+                BgpOpenParam::Capabilities(capabilities) => capabilities.iter().collect(),
+                _ => Vec::new()
+            })
+            .collect()
+    }
+
+    // AS number of the speaker, taken from the 4-octet AS capability if any
+    pub fn asn(&self) -> u32 {
+        self.capabilities()
+            .iter()
+            .find_map(|capability| match capability {
+                BgpCapability::FourOctetAs(asn) => Some(*asn),
+                _ => None
+            })
+            .unwrap_or(self.my_as as u32)
+    }
+}
+
+// Route prefix, the address bits past the prefix length are zero
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct IpPrefix {
+    pub length: u8,
+    pub prefix: IpAddr
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct AsPathSegment {
+    pub segment_type: u8,
+    pub asns:         Vec<u32>
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum EvpnRoute {
+    // Route type 2
+    MacIpAdvertisement {
+        rd:           u64,
+        esi:          [u8; 10],
+        ethernet_tag: u32,
+        mac:          MacAddress,
+        ip:           Option<IpAddr>,
+        // 24-bit label fields
+        label1:       u32,
+        label2:       Option<u32>
+    },
+    // Route type 3
+    InclusiveMulticast {
+        rd:           u64,
+        ethernet_tag: u32,
+        originator:   IpAddr
+    },
+    // Route type 5
+    IpPrefix {
+        rd:           u64,
+        esi:          [u8; 10],
+        ethernet_tag: u32,
+        prefix:       IpPrefix,
+        gateway:      IpAddr,
+        label:        u32
+    },
+    Unknown { route_type: u8, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl EvpnRoute {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let data: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(data);
+        let route_type = match self {
+            EvpnRoute::MacIpAdvertisement { rd, esi, ethernet_tag, mac, ip, label1, label2 } => {
+                cursor.write_u64::<NetworkEndian>(rd);
+                cursor.write_all(&esi);
+                cursor.write_u32::<NetworkEndian>(ethernet_tag);
+                cursor.write_u8(48);
+                cursor.write_all(mac.as_bytes());
+                match ip {
+                    Some(ip) => {
+                        let ip = ip_octets(ip);
+                        cursor.write_u8(ip.len() as u8 * 8);
+                        cursor.write_all(&ip);
+                    },
+                    None => {
+                        cursor.write_u8(0);
+                    }
+                }
+                cursor.write_u24::<NetworkEndian>(label1);
+                if let Some(label2) = label2 {
+                    cursor.write_u24::<NetworkEndian>(label2);
+                }
+                2
+            },
+            EvpnRoute::InclusiveMulticast { rd, ethernet_tag, originator } => {
+                let originator = ip_octets(originator);
+                cursor.write_u64::<NetworkEndian>(rd);
+                cursor.write_u32::<NetworkEndian>(ethernet_tag);
+                cursor.write_u8(originator.len() as u8 * 8);
+                cursor.write_all(&originator);
+                3
+            },
+            EvpnRoute::IpPrefix { rd, esi, ethernet_tag, prefix, gateway, label } => {
+                cursor.write_u64::<NetworkEndian>(rd);
+                cursor.write_all(&esi);
+                cursor.write_u32::<NetworkEndian>(ethernet_tag);
+                cursor.write_u8(prefix.length);
+                cursor.write_all(&ip_octets(prefix.prefix));
+                cursor.write_all(&ip_octets(gateway));
+                cursor.write_u24::<NetworkEndian>(label);
+                5
+            },
+            EvpnRoute::Unknown { route_type, data } => {
+                cursor.write_all(&data);
+                route_type
+            }
+        };
+        let data = cursor.into_inner();
+
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(route_type);
+        cursor.write_u8(data.len() as u8);
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], EvpnRoute> {
+        let (bytes1, route_type) = be_u8(bytes)?;
+        let (bytes2, length)     = be_u8(bytes1)?;
+        let (bytes3, data)       = take(length)(bytes2)?;
+        let route = match parse_evpn_route(data, route_type) {
+            Ok(([], route)) => route,
+            _ => EvpnRoute::Unknown { route_type, data: data.to_vec() }
+        };
+        Ok((bytes3, route))
+    }
+}
+
+// NLRI carried by MP_REACH_NLRI and MP_UNREACH_NLRI
+#[derive(Debug, PartialEq, Clone)]
+pub enum Nlri {
+    Prefix(IpPrefix),
+    Evpn(EvpnRoute),
+    // NLRI of an address family that is not decoded
+    Unknown(Vec<u8>)
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PathAttributeValue {
+    Origin(u8),
+    AsPath {
+        // 2-octet AS numbers are used between old BGP speakers
+        four_octet: bool,
+        segments:   Vec<AsPathSegment>
+    },
+    NextHop(Ipv4Addr),
+    MultiExitDisc(u32),
+    LocalPref(u32),
+    AtomicAggregate,
+    Communities(Vec<u32>),
+    MpReachNlri {
+        afi:      u16,
+        safi:     u8,
+        next_hop: Vec<IpAddr>,
+        nlri:     Vec<Nlri>
+    },
+    MpUnreachNlri {
+        afi:       u16,
+        safi:      u8,
+        withdrawn: Vec<Nlri>
+    },
+    ExtendedCommunities(Vec<u64>),
+    As4Path(Vec<AsPathSegment>),
+    // Global administrator, local data part 1 and 2
+    LargeCommunities(Vec<(u32, u32, u32)>),
+    Unknown { type_code: u8, data: Vec<u8> }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PathAttribute {
+    pub flags: u8,
+    pub value: PathAttributeValue
+}
+
+#[allow(unused_must_use)]
+impl PathAttribute {
+    // Instantiate a path attribute with the flags it is defined with
+    pub fn new(value: PathAttributeValue) -> PathAttribute {
+        let flags = match value {
+            PathAttributeValue::Origin(_) |
+            PathAttributeValue::AsPath { .. } |
+            PathAttributeValue::NextHop(_) |
+            PathAttributeValue::LocalPref(_) |
+            PathAttributeValue::AtomicAggregate =>
+                ATTR_FLAG_TRANSITIVE,
+            PathAttributeValue::MultiExitDisc(_) |
+            PathAttributeValue::MpReachNlri { .. } |
+            PathAttributeValue::MpUnreachNlri { .. } =>
+                ATTR_FLAG_OPTIONAL,
+            _ =>
+                ATTR_FLAG_OPTIONAL | ATTR_FLAG_TRANSITIVE
+        };
+        PathAttribute { flags, value }
+    }
+
+    pub fn type_code(&self) -> u8 {
+        match self.value {
+            PathAttributeValue::Origin(_) => 1,
+            PathAttributeValue::AsPath { .. } => 2,
+            PathAttributeValue::NextHop(_) => 3,
+            PathAttributeValue::MultiExitDisc(_) => 4,
+            PathAttributeValue::LocalPref(_) => 5,
+            PathAttributeValue::AtomicAggregate => 6,
+            PathAttributeValue::Communities(_) => 8,
+            PathAttributeValue::MpReachNlri { .. } => 14,
+            PathAttributeValue::MpUnreachNlri { .. } => 15,
+            PathAttributeValue::ExtendedCommunities(_) => 16,
+            PathAttributeValue::As4Path(_) => 17,
+            PathAttributeValue::LargeCommunities(_) => 32,
+            PathAttributeValue::Unknown { type_code, .. } => type_code
+        }
+    }
+
+    // Encode the attribute, the extended length is used when flagged or needed
+    pub fn as_bytes(self) -> Vec<u8> {
+        let type_code = self.type_code();
+        let data: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(data);
+        match self.value {
+            PathAttributeValue::Origin(origin) => {
+                cursor.write_u8(origin);
+            },
+            PathAttributeValue::AsPath { four_octet, segments } => {
+                cursor.write_all(&as_path_as_bytes(segments, four_octet));
+            },
+            PathAttributeValue::NextHop(next_hop) => {
+                cursor.write_all(&next_hop.octets());
+            },
+            PathAttributeValue::MultiExitDisc(value) |
+            PathAttributeValue::LocalPref(value) => {
+                cursor.write_u32::<NetworkEndian>(value);
+            },
+            PathAttributeValue::AtomicAggregate => (),
+            PathAttributeValue::Communities(communities) => {
+                for community in communities {
+                    cursor.write_u32::<NetworkEndian>(community);
+                }
+            },
+            PathAttributeValue::MpReachNlri { afi, safi, next_hop, nlri } => {
+                let next_hop: Vec<u8> = next_hop.into_iter().flat_map(ip_octets).collect();
+                cursor.write_u16::<NetworkEndian>(afi);
+                cursor.write_u8(safi);
+                cursor.write_u8(next_hop.len() as u8);
+                cursor.write_all(&next_hop);
+                cursor.write_u8(0);
+                cursor.write_all(&nlri_as_bytes(nlri));
+            },
+            PathAttributeValue::MpUnreachNlri { afi, safi, withdrawn } => {
+                cursor.write_u16::<NetworkEndian>(afi);
+                cursor.write_u8(safi);
+                cursor.write_all(&nlri_as_bytes(withdrawn));
+            },
+            PathAttributeValue::ExtendedCommunities(communities) => {
+                for community in communities {
+                    cursor.write_u64::<NetworkEndian>(community);
+                }
+            },
+            PathAttributeValue::As4Path(segments) => {
+                cursor.write_all(&as_path_as_bytes(segments, true));
+            },
+            PathAttributeValue::LargeCommunities(communities) => {
+                for (global_admin, local_data1, local_data2) in communities {
+                    cursor.write_u32::<NetworkEndian>(global_admin);
+                    cursor.write_u32::<NetworkEndian>(local_data1);
+                    cursor.write_u32::<NetworkEndian>(local_data2);
+                }
+            },
+            PathAttributeValue::Unknown { data, .. } => {
+                cursor.write_all(&data);
+            }
+        }
+        let data = cursor.into_inner();
+
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        if self.flags & ATTR_FLAG_EXTENDED != 0 || data.len() > 255 {
+            cursor.write_u8(self.flags | ATTR_FLAG_EXTENDED);
+            cursor.write_u8(type_code);
+            cursor.write_u16::<NetworkEndian>(data.len() as u16);
+        } else {
+            cursor.write_u8(self.flags);
+            cursor.write_u8(type_code);
+            cursor.write_u8(data.len() as u8);
+        }
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], PathAttribute> {
+        let (bytes1, flags)     = be_u8(bytes)?;
+        let (bytes2, type_code) = be_u8(bytes1)?;
+        let (bytes3, length)    = match flags & ATTR_FLAG_EXTENDED {
+            0 => be_u8(bytes2).map(|(b, length)| (b, length as u16))?,
+            _ => be_u16(bytes2)?
+        };
+        let (bytes4, data) = take(length)(bytes3)?;
+        let value = match parse_attribute_value(data, type_code) {
+            Ok(([], value)) => value,
+            _ => PathAttributeValue::Unknown { type_code, data: data.to_vec() }
+        };
+        Ok((bytes4, PathAttribute { flags, value }))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct BgpUpdate {
+    pub withdrawn_routes: Vec<IpPrefix>,
+    pub path_attributes:  Vec<PathAttribute>,
+    pub nlri:             Vec<IpPrefix>
+}
+
+impl BgpUpdate {
+    // Value of the first path attribute with the type code
+    pub fn attribute(&self, type_code: u8) -> Option<&PathAttributeValue> {
+        self.path_attributes
+            .iter()
+            .find(|attr| attr.type_code() == type_code)
+            .map(|attr| &attr.value)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BgpNotification {
+    pub error_code:    u8,
+    pub error_subcode: u8,
+    pub data:          Vec<u8>
+}
+
+// BGP-4 message (RFC 4271)
+#[derive(Debug, PartialEq, Clone, Default)]
+pub enum Bgp {
+    Open(BgpOpen),
+    Update(BgpUpdate),
+    Notification(BgpNotification),
+    #[default]
+    Keepalive,
+    RouteRefresh { afi: u16, subtype: u8, safi: u8 },
+    Unknown { msg_type: u8, data: Vec<u8> }
+}
+
+impl fmt::Display for Bgp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Bgp::Open(open) => write!(
+                f,
+                "BGP(\
+                 type: OPEN, \
+                 asn: {}, \
+                 hold_time: {}, \
+                 id: {}\
+                 )",
+                open.asn(),
+                open.hold_time,
+                open.bgp_identifier
+            ),
+            Bgp::Update(update) => write!(
+                f,
+                "BGP(\
+                 type: UPDATE, \
+                 withdrawn: {}, \
+                 attributes: {}, \
+                 nlri: {}\
+                 )",
+                update.withdrawn_routes.len(),
+                update.path_attributes.len(),
+                update.nlri.len()
+            ),
+            Bgp::Notification(notification) => write!(
+                f,
+                "BGP(type: NOTIFICATION, code: {}, subcode: {})",
+                notification.error_code,
+                notification.error_subcode
+            ),
+            Bgp::Keepalive =>
+                write!(f, "BGP(type: KEEPALIVE)"),
+            Bgp::RouteRefresh { afi, safi, .. } =>
+                write!(f, "BGP(type: ROUTE-REFRESH, afi: {}, safi: {})", afi, safi),
+            Bgp::Unknown { msg_type, .. } =>
+                write!(f, "BGP(type: {})", msg_type)
+        }
+    }
+}
+
+#[allow(unused_must_use)]
+impl Bgp {
+    // Instantiate a new KEEPALIVE message
+    pub fn new() -> Bgp { Bgp::default() }
+
+    // Instantiate an OPEN message advertising IPv4 unicast and the 4-octet AS
+    pub fn open(asn: u32, hold_time: u16, bgp_identifier: Ipv4Addr) -> Bgp {
+        Bgp::Open(BgpOpen {
+            my_as: u16::try_from(asn).unwrap_or(AS_TRANS),
+            hold_time,
+            bgp_identifier,
+            params: vec![BgpOpenParam::Capabilities(vec![
+                BgpCapability::Multiprotocol { afi: AFI_IPV4, safi: SAFI_UNICAST },
+                BgpCapability::RouteRefresh,
+                BgpCapability::FourOctetAs(asn)
+            ])],
+            ..BgpOpen::default()
+        })
+    }
+
+    // Instantiate a NOTIFICATION message
+    pub fn notification(error_code: u8, error_subcode: u8, data: Vec<u8>) -> Bgp {
+        Bgp::Notification(BgpNotification { error_code, error_subcode, data })
+    }
+
+    pub fn msg_type(&self) -> u8 {
+        match self {
+            Bgp::Open(_) => BGP_OPEN,
+            Bgp::Update(_) => BGP_UPDATE,
+            Bgp::Notification(_) => BGP_NOTIFICATION,
+            Bgp::Keepalive => BGP_KEEPALIVE,
+            Bgp::RouteRefresh { .. } => BGP_ROUTE_REFRESH,
+            Bgp::Unknown { msg_type, .. } => *msg_type
+        }
+    }
+
+    // Encode the BGP message into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let msg_type = self.msg_type();
+        let data: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(data);
+        match self {
+            Bgp::Open(open) => {
+                let params: Vec<u8> = open.params.into_iter().flat_map(|p| p.as_bytes()).collect();
+                cursor.write_u8(open.version);
+                cursor.write_u16::<NetworkEndian>(open.my_as);
+                cursor.write_u16::<NetworkEndian>(open.hold_time);
+                cursor.write_all(&open.bgp_identifier.octets());
+                cursor.write_u8(params.len() as u8);
+                cursor.write_all(&params);
+            },
+            Bgp::Update(update) => {
+                let withdrawn: Vec<u8> = update.withdrawn_routes.into_iter().flat_map(prefix_as_bytes).collect();
+                let attributes: Vec<u8> = update.path_attributes.into_iter().flat_map(|a| a.as_bytes()).collect();
+                cursor.write_u16::<NetworkEndian>(withdrawn.len() as u16);
+                cursor.write_all(&withdrawn);
+                cursor.write_u16::<NetworkEndian>(attributes.len() as u16);
+                cursor.write_all(&attributes);
+                for prefix in update.nlri {
+                    cursor.write_all(&prefix_as_bytes(prefix));
+                }
+            },
+            Bgp::Notification(notification) => {
+                cursor.write_u8(notification.error_code);
+                cursor.write_u8(notification.error_subcode);
+                cursor.write_all(&notification.data);
+            },
+            Bgp::Keepalive => (),
+            Bgp::RouteRefresh { afi, subtype, safi } => {
+                cursor.write_u16::<NetworkEndian>(afi);
+                cursor.write_u8(subtype);
+                cursor.write_u8(safi);
+            },
+            Bgp::Unknown { data, .. } => {
+                cursor.write_all(&data);
+            }
+        }
+        let data = cursor.into_inner();
+
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_all(&BGP_MARKER);
+        cursor.write_u16::<NetworkEndian>(data.len() as u16 + 19);
+        cursor.write_u8(msg_type);
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    // Parse one message, the following messages of a TCP segment are left over
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Bgp> {
+        let (bytes1, (length, msg_type)) = do_parse!(
+            bytes,
+            _marker:  call!(tag(&BGP_MARKER[..])) >>
+            length:   call!(verify(be_u16, |length| *length >= 19)) >>
+            msg_type: be_u8 >>
+                ((length, msg_type))
+        )?;
+        let (bytes2, data) = take(length - 19)(bytes1)?;
+        let message = match parse_message(data, msg_type) {
+            Ok(([], message)) => message,
+            _ => Bgp::Unknown { msg_type, data: data.to_vec() }
+        };
+        Ok((bytes2, message))
+    }
+}
+
+// private functions
+
+fn prefix_as_bytes(prefix: IpPrefix) -> Vec<u8> {
+    let octets = ip_octets(prefix.prefix);
+    let len = (prefix.length as usize).div_ceil(8);
+    [vec![prefix.length], octets[..len].to_vec()].concat()
+}
+
+fn nlri_as_bytes(nlri: Vec<Nlri>) -> Vec<u8> {
+    nlri.into_iter()
+        .flat_map(|nlri| match nlri {
+            Nlri::Prefix(prefix) => prefix_as_bytes(prefix),
+            Nlri::Evpn(route) => route.as_bytes(),
+            Nlri::Unknown(data) => data
+        })
+        .collect()
+}
+
+#[allow(unused_must_use)]
+fn as_path_as_bytes(segments: Vec<AsPathSegment>, four_octet: bool) -> Vec<u8> {
+    let buf: Vec<u8> = Vec::new();
+    let mut cursor = Cursor::new(buf);
+    for segment in segments {
+        cursor.write_u8(segment.segment_type);
+        cursor.write_u8(segment.asns.len() as u8);
+        for asn in segment.asns {
+            if four_octet {
+                cursor.write_u32::<NetworkEndian>(asn);
+            } else {
+                cursor.write_u16::<NetworkEndian>(asn as u16);
+            }
+        }
+    }
+    cursor.into_inner()
+}
+
+// Prefix length in bits followed by the significant octets
+fn parse_prefix(bytes: &[u8], afi: u16) -> IResult<&[u8], IpPrefix> {
+    let max_length = if afi == AFI_IPV6 { 128 } else { 32 };
+    let (bytes1, length) = verify(be_u8, |length| *length <= max_length)(bytes)?;
+    let (bytes2, octets) = take((length as usize).div_ceil(8))(bytes1)?;
+    let prefix = if afi == AFI_IPV6 {
+        let mut addr = [0; 16];
+        addr[..octets.len()].copy_from_slice(octets);
+        IpAddr::V6(Ipv6Addr::from(addr))
+    } else {
+        let mut addr = [0; 4];
+        addr[..octets.len()].copy_from_slice(octets);
+        IpAddr::V4(Ipv4Addr::from(addr))
+    };
+    Ok((bytes2, IpPrefix { length, prefix }))
+}
+
+fn parse_ipv4_prefix(bytes: &[u8]) -> IResult<&[u8], IpPrefix> { parse_prefix(bytes, AFI_IPV4) }
+
+fn parse_ipv6_prefix(bytes: &[u8]) -> IResult<&[u8], IpPrefix> { parse_prefix(bytes, AFI_IPV6) }
+
+fn parse_nlri(bytes: &[u8], afi: u16, safi: u8) -> IResult<&[u8], Vec<Nlri>> {
+    match (afi, safi) {
+        (AFI_IPV4, SAFI_UNICAST | SAFI_MULTICAST) => {
+            let (bytes1, prefixes) = parse_all(bytes, parse_ipv4_prefix)?;
+            Ok((bytes1, prefixes.into_iter().map(Nlri::Prefix).collect()))
+        },
+        (AFI_IPV6, SAFI_UNICAST | SAFI_MULTICAST) => {
+            let (bytes1, prefixes) = parse_all(bytes, parse_ipv6_prefix)?;
+            Ok((bytes1, prefixes.into_iter().map(Nlri::Prefix).collect()))
+        },
+        (AFI_L2VPN, SAFI_EVPN) => {
+            let (bytes1, routes) = parse_all(bytes, EvpnRoute::from_bytes)?;
+            Ok((bytes1, routes.into_iter().map(Nlri::Evpn).collect()))
+        },
+        _ if bytes.is_empty() =>
+            Ok((bytes, Vec::new())),
+        _ =>
+            Ok((&[], vec![Nlri::Unknown(bytes.to_vec())]))
+    }
+}
+
+fn parse_as_path(bytes: &[u8], four_octet: bool) -> IResult<&[u8], Vec<AsPathSegment>> {
+    let mut acc = Vec::new();
+    let mut b = bytes;
+    let width = if four_octet { 4 } else { 2 };
+
+    while !b.is_empty() {
+        let (bytes1, segment_type) = be_u8(b)?;
+        let (bytes2, count)        = be_u8(bytes1)?;
+        let (bytes3, asns)         = take(count as usize * width)(bytes2)?;
+        let asns = asns
+            .chunks_exact(width)
+            .map(|asn| asn.iter().fold(0u32, |acc, &b| acc << 8 | b as u32))
+            .collect();
+        acc.push(AsPathSegment { segment_type, asns });
+        b = bytes3;
+    }
+
+    Ok((b, acc))
+}
+
+fn parse_attribute_value(bytes: &[u8], type_code: u8) -> IResult<&[u8], PathAttributeValue> {
+    match (type_code, bytes.len()) {
+        (1, 1) =>
+            Ok((&[], PathAttributeValue::Origin(bytes[0]))),
+        // 4-octet AS numbers are tried first, then the 2-octet encoding
+        (2, _) => match parse_as_path(bytes, true) {
+            Ok((b, segments)) =>
+                Ok((b, PathAttributeValue::AsPath { four_octet: true, segments })),
+            Err(_e) => {
+                let (b, segments) = parse_as_path(bytes, false)?;
+                Ok((b, PathAttributeValue::AsPath { four_octet: false, segments }))
+            }
+        },
+        (3, 4) =>
+            Ok((&[], PathAttributeValue::NextHop(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3])))),
+        (4, 4) =>
+            be_u32(bytes).map(|(b, med)| (b, PathAttributeValue::MultiExitDisc(med))),
+        (5, 4) =>
+            be_u32(bytes).map(|(b, pref)| (b, PathAttributeValue::LocalPref(pref))),
+        (6, 0) =>
+            Ok((bytes, PathAttributeValue::AtomicAggregate)),
+        (8, len) if len % 4 == 0 => Ok((&[], PathAttributeValue::Communities(
+            bytes.chunks_exact(4).map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]])).collect()
+        ))),
+        (14, _) => {
+            let (bytes1, afi)      = be_u16(bytes)?;
+            let (bytes2, safi)     = be_u8(bytes1)?;
+            let (bytes3, nh_len)   = be_u8(bytes2)?;
+            let (bytes4, next_hop) = verify(take(nh_len), |nh: &[u8]| matches!(nh.len(), 4 | 16 | 32))(bytes3)?;
+            let (bytes5, _)        = be_u8(bytes4)?;
+            let (bytes6, nlri)     = parse_nlri(bytes5, afi, safi)?;
+            // IPv6 global address optionally followed by the link local one
+            let next_hop = next_hop.chunks(16).filter_map(parse_ip).collect();
+            Ok((bytes6, PathAttributeValue::MpReachNlri { afi, safi, next_hop, nlri }))
+        },
+        (15, _) => {
+            let (bytes1, afi)       = be_u16(bytes)?;
+            let (bytes2, safi)      = be_u8(bytes1)?;
+            let (bytes3, withdrawn) = parse_nlri(bytes2, afi, safi)?;
+            Ok((bytes3, PathAttributeValue::MpUnreachNlri { afi, safi, withdrawn }))
+        },
+        (16, len) if len % 8 == 0 => Ok((&[], PathAttributeValue::ExtendedCommunities(
+            bytes.chunks_exact(8).map(|c| u64::from_be_bytes(<[u8; 8]>::try_from(c).unwrap())).collect()
+        ))),
+        (17, _) => {
+            let (b, segments) = parse_as_path(bytes, true)?;
+            Ok((b, PathAttributeValue::As4Path(segments)))
+        },
+        (32, len) if len % 12 == 0 => Ok((&[], PathAttributeValue::LargeCommunities(
+            bytes
+                .chunks_exact(12)
+                .map(|c| (
+                    u32::from_be_bytes([c[0], c[1], c[2], c[3]]),
+                    u32::from_be_bytes([c[4], c[5], c[6], c[7]]),
+                    u32::from_be_bytes([c[8], c[9], c[10], c[11]])
+                ))
+                .collect()
+        ))),
+        _ =>
+            Ok((&[], PathAttributeValue::Unknown { type_code, data: bytes.to_vec() }))
+    }
+}
+
+fn parse_evpn_route(bytes: &[u8], route_type: u8) -> IResult<&[u8], EvpnRoute> {
+    match route_type {
+        2 => {
+            let (bytes1, rd)           = be_u64(bytes)?;
+            let (bytes2, esi)          = take(10usize)(bytes1)?;
+            let (bytes3, ethernet_tag) = be_u32(bytes2)?;
+            let (bytes4, _)            = tag(&[48u8][..])(bytes3)?;
+            let (bytes5, mac)          = take(6usize)(bytes4)?;
+            let (bytes6, ip_len)       = verify(be_u8, |len| matches!(len, 0 | 32 | 128))(bytes5)?;
+            let (bytes7, ip)           = take(ip_len / 8)(bytes6)?;
+            let (bytes8, label1)       = be_u24(bytes7)?;
+            let (bytes9, label2)       = match bytes8.len() {
+                0 => (bytes8, None),
+                _ => be_u24(bytes8).map(|(b, label)| (b, Some(label)))?
+            };
+            Ok((
+                bytes9,
+                EvpnRoute::MacIpAdvertisement {
+                    rd,
+                    esi: <[u8; 10]>::try_from(esi).unwrap(),
+                    ethernet_tag,
+                    mac: MacAddress::from_bytes(mac).unwrap(),
+                    ip: parse_ip(ip),
+                    label1,
+                    label2
+                }
+            ))
+        },
+        3 => do_parse!(
+            bytes,
+            rd:           be_u64 >>
+            ethernet_tag: be_u32 >>
+            ip_len:       call!(verify(be_u8, |len| matches!(len, 32 | 128))) >>
+            originator:   take!(ip_len / 8) >>
+                (
+                    EvpnRoute::InclusiveMulticast {
+                        rd,
+                        ethernet_tag,
+                        originator: parse_ip(originator).unwrap()
+                    }
+                )
+        ),
+        // The address family follows from the route length
+        5 => {
+            let ip_len = if bytes.len() == 58 { 16 } else { 4 };
+            let (bytes1, rd)           = be_u64(bytes)?;
+            let (bytes2, esi)          = take(10usize)(bytes1)?;
+            let (bytes3, ethernet_tag) = be_u32(bytes2)?;
+            let (bytes4, length)       = verify(be_u8, |len| *len as usize <= ip_len * 8)(bytes3)?;
+            let (bytes5, prefix)       = take(ip_len)(bytes4)?;
+            let (bytes6, gateway)      = take(ip_len)(bytes5)?;
+            let (bytes7, label)        = be_u24(bytes6)?;
+            Ok((
+                bytes7,
+                EvpnRoute::IpPrefix {
+                    rd,
+                    esi: <[u8; 10]>::try_from(esi).unwrap(),
+                    ethernet_tag,
+                    prefix: IpPrefix { length, prefix: parse_ip(prefix).unwrap() },
+                    gateway: parse_ip(gateway).unwrap(),
+                    label
+                }
+            ))
+        },
+        _ =>
+            Ok((&[], EvpnRoute::Unknown { route_type, data: bytes.to_vec() }))
+    }
+}
+
+fn parse_message(bytes: &[u8], msg_type: u8) -> IResult<&[u8], Bgp> {
+    match msg_type {
+        BGP_OPEN => {
+            let (bytes1, (version, my_as, hold_time, bgp_identifier, params_len)) = do_parse!(
+                bytes,
+                version:        be_u8 >>
+                my_as:          be_u16 >>
+                hold_time:      be_u16 >>
+                bgp_identifier: be_u32 >>
+                params_len:     be_u8 >>
+                    ((version, my_as, hold_time, Ipv4Addr::from(bgp_identifier), params_len))
+            )?;
+            let (bytes2, params) = take(params_len)(bytes1)?;
+            let (_, params) = parse_all(params, BgpOpenParam::from_bytes)?;
+            Ok((bytes2, Bgp::Open(BgpOpen { version, my_as, hold_time, bgp_identifier, params })))
+        },
+        BGP_UPDATE => {
+            let (bytes1, withdrawn_len)    = be_u16(bytes)?;
+            let (bytes2, withdrawn)        = take(withdrawn_len)(bytes1)?;
+            let (bytes3, attributes_len)   = be_u16(bytes2)?;
+            let (bytes4, attributes)       = take(attributes_len)(bytes3)?;
+            let (_, withdrawn_routes)      = parse_all(withdrawn, parse_ipv4_prefix)?;
+            let (_, path_attributes)       = parse_all(attributes, PathAttribute::from_bytes)?;
+            let (bytes5, nlri)             = parse_all(bytes4, parse_ipv4_prefix)?;
+            Ok((bytes5, Bgp::Update(BgpUpdate { withdrawn_routes, path_attributes, nlri })))
+        },
+        BGP_NOTIFICATION => {
+            let (bytes1, error_code)    = be_u8(bytes)?;
+            let (bytes2, error_subcode) = be_u8(bytes1)?;
+            Ok((&[], Bgp::notification(error_code, error_subcode, bytes2.to_vec())))
+        },
+        BGP_KEEPALIVE =>
+            Ok((bytes, Bgp::Keepalive)),
+        BGP_ROUTE_REFRESH => do_parse!(
+            bytes,
+            afi:     be_u16 >>
+            subtype: be_u8 >>
+            safi:    be_u8 >>
+                (Bgp::RouteRefresh { afi, subtype, safi })
+        ),
+        _ =>
+            Ok((&[], Bgp::Unknown { msg_type, data: bytes.to_vec() }))
+    }
+}
+
+#[cfg(test)]
+mod tests_bgp {
+    use crate::bgp::*;
+    use eui48::MacAddress;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use std::str::FromStr;
+
+    #[test]
+    fn parse_open() {
+        let frame = &mut [
+            0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+            0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+            0x00,0x2d,0x01,0x04,0x5b,0xa0,0x00,0xb4,
+            0x0a,0x00,0x00,0x01,0x10,0x02,0x0e,0x01,
+            0x04,0x00,0x01,0x00,0x01,0x02,0x00,0x41,
+            0x04,0x00,0x01,0x00,0x00
+        ];
+
+        let (leftover, bgp) = Bgp::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(Bgp::open(65536, 180, Ipv4Addr::new(10, 0, 0, 1)), bgp);
+        match &bgp {
+            Bgp::Open(open) => {
+                assert_eq!(AS_TRANS, open.my_as);
+                assert_eq!(65536, open.asn());
+                assert_eq!(3, open.capabilities().len());
+            },
+            other => panic!("unexpected message: {:?}", other)
+        }
+        assert_eq!("BGP(type: OPEN, asn: 65536, hold_time: 180, id: 10.0.0.1)", format!("{}", bgp));
+        assert_eq!(frame.to_vec(), bgp.as_bytes());
+    }
+
+    #[test]
+    fn parse_update() {
+        let frame = &mut [
+            0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+            0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+            0x00,0x4f,0x02,0x00,0x00,0x00,0x35,0x40,
+            0x01,0x01,0x00,0x40,0x02,0x0a,0x02,0x02,
+            0x00,0x00,0xfd,0xe9,0x00,0x00,0xfd,0xea,
+            0x40,0x03,0x04,0x0a,0x00,0x00,0x01,0x80,
+            0x04,0x04,0x00,0x00,0x00,0x64,0xc0,0x08,
+            0x04,0xfd,0xe9,0x00,0x64,0xc0,0x20,0x0c,
+            0x00,0x00,0xfd,0xe9,0x00,0x00,0x00,0x01,
+            0x00,0x00,0x00,0x02,0x10,0x0a,0x01,0xff,
+            0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+            0xff,0xff,0xff,0xff,0xff,0xff,0xff,0x00,
+            0x13,0x04
+        ];
+
+        let (leftover, bgp) = Bgp::from_bytes(frame).unwrap();
+        let update = match &bgp {
+            Bgp::Update(update) => update,
+            other => panic!("unexpected message: {:?}", other)
+        };
+        assert_eq!(6, update.path_attributes.len());
+        assert_eq!(Some(&PathAttributeValue::Origin(ORIGIN_IGP)), update.attribute(1));
+        assert_eq!(Some(&PathAttributeValue::AsPath {
+            four_octet: true,
+            segments:   vec![AsPathSegment { segment_type: AS_SEQUENCE, asns: vec![65001, 65002] }]
+        }), update.attribute(2));
+        assert_eq!(Some(&PathAttributeValue::NextHop(Ipv4Addr::new(10, 0, 0, 1))), update.attribute(3));
+        assert_eq!(Some(&PathAttributeValue::MultiExitDisc(100)), update.attribute(4));
+        assert_eq!(Some(&PathAttributeValue::Communities(vec![0xfde9_0064])), update.attribute(8));
+        assert_eq!(Some(&PathAttributeValue::LargeCommunities(vec![(65001, 1, 2)])), update.attribute(32));
+        assert_eq!(
+            vec![IpPrefix { length: 16, prefix: IpAddr::V4(Ipv4Addr::new(10, 1, 0, 0)) }],
+            update.nlri
+        );
+        assert_eq!(frame[..79].to_vec(), bgp.clone().as_bytes());
+
+        let (leftover, keepalive) = Bgp::from_bytes(leftover).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(Bgp::Keepalive, keepalive);
+        assert_eq!("BGP(type: UPDATE, withdrawn: 0, attributes: 6, nlri: 1)", format!("{}", bgp));
+    }
+
+    #[test]
+    fn parse_multiprotocol() {
+        let frame = &mut [
+            0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+            0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+            0x00,0x61,0x02,0x00,0x00,0x00,0x4a,0x40,
+            0x01,0x01,0x00,0x40,0x02,0x04,0x02,0x01,
+            0xfd,0xe9,0x90,0x0e,0x00,0x2c,0x00,0x02,
+            0x01,0x20,0x20,0x01,0x0d,0xb8,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x01,0xfe,0x80,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x01,0x00,0x30,0x20,0x01,0x0d,0xb8,
+            0x00,0x01,0x80,0x0f,0x0c,0x00,0x02,0x01,
+            0x40,0x20,0x01,0x0d,0xb8,0x00,0x02,0x00,
+            0x00
+        ];
+
+        let (_, bgp) = Bgp::from_bytes(frame).unwrap();
+        let update = match &bgp {
+            Bgp::Update(update) => update,
+            other => panic!("unexpected message: {:?}", other)
+        };
+        assert_eq!(Some(&PathAttributeValue::AsPath {
+            four_octet: false,
+            segments:   vec![AsPathSegment { segment_type: AS_SEQUENCE, asns: vec![65001] }]
+        }), update.attribute(2));
+        assert_eq!(Some(&PathAttributeValue::MpReachNlri {
+            afi:      AFI_IPV6,
+            safi:     SAFI_UNICAST,
+            next_hop: vec![
+                IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)),
+                IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1))
+            ],
+            nlri:     vec![Nlri::Prefix(IpPrefix {
+                length: 48,
+                prefix: IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 1, 0, 0, 0, 0, 0))
+            })]
+        }), update.attribute(14));
+        assert_eq!(Some(&PathAttributeValue::MpUnreachNlri {
+            afi:       AFI_IPV6,
+            safi:      SAFI_UNICAST,
+            withdrawn: vec![Nlri::Prefix(IpPrefix {
+                length: 64,
+                prefix: IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 2, 0, 0, 0, 0, 0))
+            })]
+        }), update.attribute(15));
+        assert_eq!(frame.to_vec(), bgp.as_bytes());
+    }
+
+    #[test]
+    fn parse_evpn() {
+        let frame = &mut [
+            0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+            0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+            0x00,0x82,0x02,0x00,0x00,0x00,0x6b,0x90,
+            0x0e,0x00,0x67,0x00,0x19,0x46,0x04,0x0a,
+            0x00,0x00,0x01,0x00,0x02,0x25,0x00,0x01,
+            0x0a,0x00,0x00,0x01,0x00,0x64,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x30,0x00,0x50,0x56,
+            0x9a,0x00,0x01,0x20,0xc0,0xa8,0x01,0x0a,
+            0x00,0x06,0x41,0x03,0x11,0x00,0x01,0x0a,
+            0x00,0x00,0x01,0x00,0x64,0x00,0x00,0x00,
+            0x00,0x20,0x0a,0x00,0x00,0x01,0x05,0x22,
+            0x00,0x01,0x0a,0x00,0x00,0x01,0x00,0x64,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x18,0xc0,
+            0xa8,0x02,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x06,0x41
+        ];
+
+        let (_, bgp) = Bgp::from_bytes(frame).unwrap();
+        let nlri = match &bgp {
+            Bgp::Update(update) => match update.attribute(14) {
+                Some(PathAttributeValue::MpReachNlri { afi: AFI_L2VPN, safi: SAFI_EVPN, nlri, .. }) => nlri,
+                other => panic!("unexpected attribute: {:?}", other)
+            },
+            other => panic!("unexpected message: {:?}", other)
+        };
+        let rd = 0x0001_0a00_0001_0064;
+        assert_eq!(vec![
+            Nlri::Evpn(EvpnRoute::MacIpAdvertisement {
+                rd,
+                esi:          [0; 10],
+                ethernet_tag: 0,
+                mac:          MacAddress::from_str("00:50:56:9a:00:01").unwrap(),
+                ip:           Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10))),
+                label1:       0x000641,
+                label2:       None
+            }),
+            Nlri::Evpn(EvpnRoute::InclusiveMulticast {
+                rd,
+                ethernet_tag: 0,
+                originator:   IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))
+            }),
+            Nlri::Evpn(EvpnRoute::IpPrefix {
+                rd,
+                esi:          [0; 10],
+                ethernet_tag: 0,
+                prefix:       IpPrefix { length: 24, prefix: IpAddr::V4(Ipv4Addr::new(192, 168, 2, 0)) },
+                gateway:      IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                label:        0x000641
+            })
+        ], *nlri);
+        assert_eq!(frame.to_vec(), bgp.as_bytes());
+    }
+}
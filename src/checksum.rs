@@ -0,0 +1,94 @@
+// Internet checksum (RFC 1071) of a byte slice, an odd trailing octet is
+// padded with zero
+pub(crate) fn checksum(bytes: &[u8]) -> u16 {
+    let mut sum = bytes
+        .chunks(2)
+        .map(|word| match word {
+            [hi, lo] => u16::from_be_bytes([*hi, *lo]) as u32,
+            [hi] => (*hi as u32) << 8,
+            _ => 0
+        })
+        .sum::<u32>();
+
+    while sum > 0xffff { sum = (sum >> 16) + (sum & 0xffff) }
+    !sum as u16
+}
+
+// CRC32c (Castagnoli) lookup table, reflected polynomial 0x82f63b78
+const CRC32C_TABLE: [u32; 256] = crc32c_table();
+
+const fn crc32c_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0x82f6_3b78 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+// CRC32c (RFC 3309) of a byte slice
+pub(crate) fn crc32c(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &b| {
+        CRC32C_TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+// Fletcher checksum (ISO 8473 Annex C) to be stored at `offset`, the two
+// octets at `offset` are taken as zero
+pub(crate) fn fletcher_checksum(bytes: &[u8], offset: usize) -> u16 {
+    let (c0, c1) = bytes.iter().enumerate().fold((0i64, 0i64), |(c0, c1), (i, &b)| {
+        let b = if i == offset || i == offset + 1 { 0 } else { b as i64 };
+        let c0 = (c0 + b) % 255;
+        (c0, (c1 + c0) % 255)
+    });
+
+    let x = match (((bytes.len() - offset - 1) as i64) * c0 - c1).rem_euclid(255) {
+        0 => 255,
+        x => x
+    };
+    let y = match 510 - c0 - x {
+        y if y > 255 => y - 255,
+        y => y
+    };
+    (x as u16) << 8 | y as u16
+}
+
+#[cfg(test)]
+mod tests_checksum {
+    use crate::checksum::{checksum, crc32c, fletcher_checksum};
+
+    #[test]
+    fn calculate() {
+        let bytes = &[0x00, 0x01, 0xf2, 0x03, 0xf4, 0xf5, 0xf6, 0xf7];
+        assert_eq!(!0xddf2, checksum(bytes));
+        assert_eq!(checksum(&[0x12, 0x34, 0x56, 0x00]), checksum(&[0x12, 0x34, 0x56]));
+        assert_eq!(0, checksum(&[0x12, 0x34, 0xed, 0xcb]));
+    }
+
+    #[test]
+    fn calculate_crc32c() {
+        assert_eq!(0xe306_9283, crc32c(b"123456789"));
+        assert_eq!(0x8a91_36aa, crc32c(&[0x00; 32]));
+    }
+
+    #[test]
+    fn calculate_fletcher() {
+        let mut bytes = vec![0x01, 0x02, 0x03, 0x04, 0x00, 0x00, 0x05, 0x06, 0x07];
+        let sum = fletcher_checksum(&bytes, 4);
+        bytes[4..6].copy_from_slice(&sum.to_be_bytes());
+
+        // both running sums of a checksummed message are zero
+        let (c0, c1) = bytes.iter().fold((0u32, 0u32), |(c0, c1), &b| {
+            ((c0 + b as u32) % 255, (c1 + c0 + b as u32) % 255)
+        });
+        assert_eq!((0, 0), (c0, c1));
+        assert_eq!(sum, fletcher_checksum(&bytes, 4));
+    }
+}
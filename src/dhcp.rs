@@ -0,0 +1,634 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use eui48::MacAddress;
+use nom::bytes::complete::take;
+use nom::error::{Error, ErrorKind};
+use nom::number::complete::{be_u8, be_u16, be_u32};
+use nom::{do_parse, take, IResult};
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::io::{Cursor, Write};
+
+use crate::util::parse_ip4addr;
+
+pub const DHCP_MAGIC_COOKIE: u32 = 0x6382_5363;
+
+pub const BOOTREQUEST: u8 = 1;
+pub const BOOTREPLY:   u8 = 2;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DhcpMessageType {
+    Discover,
+    Offer,
+    Request,
+    Decline,
+    Ack,
+    Nak,
+    Release,
+    Inform,
+    Other(u8)
+}
+
+impl From<u8> for DhcpMessageType {
+    fn from(value: u8) -> DhcpMessageType {
+        match value {
+            1 => DhcpMessageType::Discover,
+            2 => DhcpMessageType::Offer,
+            3 => DhcpMessageType::Request,
+            4 => DhcpMessageType::Decline,
+            5 => DhcpMessageType::Ack,
+            6 => DhcpMessageType::Nak,
+            7 => DhcpMessageType::Release,
+            8 => DhcpMessageType::Inform,
+            other => DhcpMessageType::Other(other)
+        }
+    }
+}
+
+impl From<DhcpMessageType> for u8 {
+    fn from(value: DhcpMessageType) -> u8 {
+        match value {
+            DhcpMessageType::Discover => 1,
+            DhcpMessageType::Offer    => 2,
+            DhcpMessageType::Request  => 3,
+            DhcpMessageType::Decline  => 4,
+            DhcpMessageType::Ack      => 5,
+            DhcpMessageType::Nak      => 6,
+            DhcpMessageType::Release  => 7,
+            DhcpMessageType::Inform   => 8,
+            DhcpMessageType::Other(other) => other
+        }
+    }
+}
+
+// Sub-options of the Relay Agent Information option (RFC 3046)
+#[derive(Debug, PartialEq, Clone)]
+pub enum RelayAgentSubOption {
+    CircuitId(Vec<u8>),
+    RemoteId(Vec<u8>),
+    Unknown { code: u8, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl RelayAgentSubOption {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let (code, data) = match self {
+            RelayAgentSubOption::CircuitId(data) => (1, data),
+            RelayAgentSubOption::RemoteId(data)  => (2, data),
+            RelayAgentSubOption::Unknown { code, data } => (code, data)
+        };
+        split_as_bytes(code, data)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], RelayAgentSubOption> {
+        do_parse!(
+            bytes,
+            code:   be_u8 >>
+            length: be_u8 >>
+            data:   take!(length as usize) >>
+                (RelayAgentSubOption::from_data(code, data.to_vec()))
+        )
+    }
+
+    // private functions
+
+    fn from_data(code: u8, data: Vec<u8>) -> RelayAgentSubOption {
+        match code {
+            1 => RelayAgentSubOption::CircuitId(data),
+            2 => RelayAgentSubOption::RemoteId(data),
+            _ => RelayAgentSubOption::Unknown { code, data }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum DhcpOption {
+    Pad,
+    SubnetMask(Ipv4Addr),
+    Router(Vec<Ipv4Addr>),
+    DomainNameServer(Vec<Ipv4Addr>),
+    HostName(String),
+    RequestedIpAddress(Ipv4Addr),
+    LeaseTime(u32),
+    MessageType(DhcpMessageType),
+    ServerIdentifier(Ipv4Addr),
+    ParameterRequestList(Vec<u8>),
+    ClientIdentifier { hw_type: u8, id: Vec<u8> },
+    RelayAgentInformation(Vec<RelayAgentSubOption>),
+    Unknown { code: u8, data: Vec<u8> },
+    End
+}
+
+#[allow(unused_must_use)]
+impl DhcpOption {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let (code, data) = match self {
+            DhcpOption::Pad => return vec![0],
+            DhcpOption::End => return vec![255],
+            DhcpOption::SubnetMask(mask) =>
+                (1, mask.octets().to_vec()),
+            DhcpOption::Router(addrs) =>
+                (3, addrs.iter().flat_map(|addr| addr.octets().to_vec()).collect()),
+            DhcpOption::DomainNameServer(addrs) =>
+                (6, addrs.iter().flat_map(|addr| addr.octets().to_vec()).collect()),
+            DhcpOption::HostName(name) =>
+                (12, name.into_bytes()),
+            DhcpOption::RequestedIpAddress(addr) =>
+                (50, addr.octets().to_vec()),
+            DhcpOption::LeaseTime(time) =>
+                (51, time.to_be_bytes().to_vec()),
+            DhcpOption::MessageType(msg_type) =>
+                (53, vec![msg_type.into()]),
+            DhcpOption::ServerIdentifier(addr) =>
+                (54, addr.octets().to_vec()),
+            DhcpOption::ParameterRequestList(params) =>
+                (55, params),
+            DhcpOption::ClientIdentifier { hw_type, id } =>
+                (61, [vec![hw_type], id].concat()),
+            DhcpOption::RelayAgentInformation(subopts) =>
+                (82, subopts.into_iter().flat_map(|subopt| subopt.as_bytes()).collect()),
+            DhcpOption::Unknown { code, data } =>
+                (code, data)
+        };
+        split_as_bytes(code, data)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], DhcpOption> {
+        let (bytes1, code) = be_u8(bytes)?;
+        match code {
+            0   => Ok((bytes1, DhcpOption::Pad)),
+            255 => Ok((bytes1, DhcpOption::End)),
+            _   => {
+                let (bytes2, length) = be_u8(bytes1)?;
+                let (bytes3, data) = take(length as usize)(bytes2)?;
+                Ok((bytes3, parse_option_data(code, data)))
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Dhcp {
+    pub op:      u8,
+    pub htype:   u8,
+    pub hlen:    u8,
+    pub hops:    u8,
+    pub xid:     u32,
+    pub secs:    u16,
+    pub flags:   u16,
+    pub ciaddr:  Ipv4Addr,
+    pub yiaddr:  Ipv4Addr,
+    pub siaddr:  Ipv4Addr,
+    pub giaddr:  Ipv4Addr,
+    pub chaddr:  MacAddress,
+    pub sname:   String,
+    pub file:    String,
+    pub options: Vec<DhcpOption>
+}
+
+impl Default for Dhcp {
+    fn default() -> Dhcp {
+        Dhcp {
+            op:      BOOTREQUEST,
+            htype:   1,
+            hlen:    6,
+            hops:    0,
+            xid:     0,
+            secs:    0,
+            flags:   0,
+            ciaddr:  Ipv4Addr::UNSPECIFIED,
+            yiaddr:  Ipv4Addr::UNSPECIFIED,
+            siaddr:  Ipv4Addr::UNSPECIFIED,
+            giaddr:  Ipv4Addr::UNSPECIFIED,
+            chaddr:  MacAddress::nil(),
+            sname:   String::new(),
+            file:    String::new(),
+            options: vec![DhcpOption::End]
+        }
+    }
+}
+
+impl fmt::Display for Dhcp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg_type = match self.message_type() {
+            Some(msg_type) => format!("{:?}", msg_type),
+            None => String::from("BOOTP")
+        };
+
+        write!(
+            f,
+            "DHCP(\
+             type: {}, \
+             xid: 0x{:08x}, \
+             chaddr: \"{}\", \
+             yiaddr: \"{}\"\
+             )",
+            msg_type,
+            self.xid,
+            self.chaddr.to_hex_string(),
+            self.yiaddr
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Dhcp {
+    // Instantiate a new DHCP message
+    pub fn new() -> Dhcp { Dhcp::default() }
+
+    // Build a DHCPDISCOVER
+    pub fn discover(xid: u32, chaddr: MacAddress) -> Dhcp {
+        Dhcp {
+            xid,
+            chaddr,
+            flags:   0x8000,
+            options: vec![
+                DhcpOption::MessageType(DhcpMessageType::Discover),
+                DhcpOption::ParameterRequestList(vec![1, 3, 6, 51, 54]),
+                DhcpOption::End
+            ],
+            ..Dhcp::default()
+        }
+    }
+
+    // Build a DHCPOFFER in reply to a DHCPDISCOVER
+    pub fn offer(discover: &Dhcp, yiaddr: Ipv4Addr, server_id: Ipv4Addr, lease_time: u32) -> Dhcp {
+        Self::reply(discover, DhcpMessageType::Offer, yiaddr, server_id, lease_time)
+    }
+
+    // Build a DHCPREQUEST selecting the address of a DHCPOFFER
+    pub fn request(offer: &Dhcp) -> Dhcp {
+        let mut options = vec![
+            DhcpOption::MessageType(DhcpMessageType::Request),
+            DhcpOption::RequestedIpAddress(offer.yiaddr)
+        ];
+        if let Some(server_id) = offer.server_identifier() {
+            options.push(DhcpOption::ServerIdentifier(server_id));
+        }
+        options.push(DhcpOption::ParameterRequestList(vec![1, 3, 6, 51, 54]));
+        options.push(DhcpOption::End);
+
+        Dhcp {
+            xid:     offer.xid,
+            flags:   offer.flags,
+            giaddr:  offer.giaddr,
+            chaddr:  offer.chaddr,
+            options,
+            ..Dhcp::default()
+        }
+    }
+
+    // Build a DHCPACK in reply to a DHCPREQUEST
+    pub fn ack(request: &Dhcp, yiaddr: Ipv4Addr, server_id: Ipv4Addr, lease_time: u32) -> Dhcp {
+        Self::reply(request, DhcpMessageType::Ack, yiaddr, server_id, lease_time)
+    }
+
+    // Insert an option before the End option
+    pub fn push_option(&mut self, option: DhcpOption) {
+        match self.options.iter().position(|opt| *opt == DhcpOption::End) {
+            Some(pos) => self.options.insert(pos, option),
+            None => self.options.push(option)
+        }
+    }
+
+    // DHCP message type, None for plain BOOTP
+    pub fn message_type(&self) -> Option<DhcpMessageType> {
+        self.options.iter().find_map(|opt| match opt {
+            DhcpOption::MessageType(msg_type) => Some(*msg_type),
+            _ => None
+        })
+    }
+
+    pub fn server_identifier(&self) -> Option<Ipv4Addr> {
+        self.options.iter().find_map(|opt| match opt {
+            DhcpOption::ServerIdentifier(addr) => Some(*addr),
+            _ => None
+        })
+    }
+
+    // Encode the DHCP message into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.op);
+        cursor.write_u8(self.htype);
+        cursor.write_u8(self.hlen);
+        cursor.write_u8(self.hops);
+        cursor.write_u32::<NetworkEndian>(self.xid);
+        cursor.write_u16::<NetworkEndian>(self.secs);
+        cursor.write_u16::<NetworkEndian>(self.flags);
+        cursor.write_all(&self.ciaddr.octets());
+        cursor.write_all(&self.yiaddr.octets());
+        cursor.write_all(&self.siaddr.octets());
+        cursor.write_all(&self.giaddr.octets());
+        cursor.write_all(self.chaddr.as_bytes());
+        cursor.write_all(&[0; 10]);
+        cursor.write_all(&padded(self.sname, 64));
+        cursor.write_all(&padded(self.file, 128));
+        cursor.write_u32::<NetworkEndian>(DHCP_MAGIC_COOKIE);
+        for option in self.options {
+            cursor.write_all(&option.as_bytes());
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Dhcp> {
+        do_parse!(
+            bytes,
+            op:      be_u8  >>
+            htype:   be_u8  >>
+            hlen:    be_u8  >>
+            hops:    be_u8  >>
+            xid:     be_u32 >>
+            secs:    be_u16 >>
+            flags:   be_u16 >>
+            ciaddr:  parse_ip4addr >>
+            yiaddr:  parse_ip4addr >>
+            siaddr:  parse_ip4addr >>
+            giaddr:  parse_ip4addr >>
+            chaddr:  take!(16) >>
+            sname:   take!(64) >>
+            file:    take!(128) >>
+            _cookie: parse_magic_cookie >>
+            options: parse_options >>
+                (
+                    Dhcp {
+                        op,
+                        htype,
+                        hlen,
+                        hops,
+                        xid,
+                        secs,
+                        flags,
+                        ciaddr,
+                        yiaddr,
+                        siaddr,
+                        giaddr,
+                        chaddr:  MacAddress::from_bytes(&chaddr[..6]).unwrap(),
+                        sname:   null_terminated(sname),
+                        file:    null_terminated(file),
+                        options
+                    }
+                )
+        )
+    }
+
+    // private functions
+
+    fn reply(msg: &Dhcp, msg_type: DhcpMessageType, yiaddr: Ipv4Addr, server_id: Ipv4Addr, lease_time: u32) -> Dhcp {
+        Dhcp {
+            op:      BOOTREPLY,
+            xid:     msg.xid,
+            flags:   msg.flags,
+            giaddr:  msg.giaddr,
+            chaddr:  msg.chaddr,
+            yiaddr,
+            options: vec![
+                DhcpOption::MessageType(msg_type),
+                DhcpOption::ServerIdentifier(server_id),
+                DhcpOption::LeaseTime(lease_time),
+                DhcpOption::End
+            ],
+            ..Dhcp::default()
+        }
+    }
+}
+
+// private functions
+
+fn parse_ip4addrs(bytes: &[u8]) -> Vec<Ipv4Addr> {
+    bytes
+        .chunks_exact(4)
+        .map(|addr| Ipv4Addr::new(addr[0], addr[1], addr[2], addr[3]))
+        .collect()
+}
+
+fn parse_magic_cookie(bytes: &[u8]) -> IResult<&[u8], u32> {
+    let (bytes1, cookie) = be_u32(bytes)?;
+    if cookie != DHCP_MAGIC_COOKIE {
+        return Err(nom::Err::Error(Error::new(bytes, ErrorKind::Tag)))
+    }
+    Ok((bytes1, cookie))
+}
+
+// Options of the same code are concatenated before decoding (RFC 3396)
+fn parse_options(bytes: &[u8]) -> IResult<&[u8], Vec<DhcpOption>> {
+    let mut acc: Vec<(u8, Vec<u8>)> = Vec::new();
+    let mut b = bytes;
+
+    loop {
+        let (bytes1, code) = be_u8(b)?;
+        match code {
+            0 => {
+                acc.push((code, Vec::new()));
+                b = bytes1;
+            },
+            255 => {
+                let mut options: Vec<DhcpOption> = acc
+                    .into_iter()
+                    .map(|(code, data)| match code {
+                        0 => DhcpOption::Pad,
+                        _ => parse_option_data(code, &data)
+                    })
+                    .collect();
+                options.push(DhcpOption::End);
+                return Ok((bytes1, options))
+            },
+            _ => {
+                let (bytes2, length) = be_u8(bytes1)?;
+                let (bytes3, data) = take(length as usize)(bytes2)?;
+                match acc.iter_mut().find(|(c, _)| *c == code) {
+                    Some((_, acc_data)) => acc_data.extend_from_slice(data),
+                    None => acc.push((code, data.to_vec()))
+                }
+                b = bytes3;
+            }
+        }
+    }
+}
+
+// Encode the code and length of an option, data longer than 255 bytes is
+// split into consecutive instances of the code (RFC 3396)
+#[allow(unused_must_use)]
+fn split_as_bytes(code: u8, data: Vec<u8>) -> Vec<u8> {
+    let buf: Vec<u8> = Vec::new();
+    let mut cursor = Cursor::new(buf);
+    if data.is_empty() {
+        cursor.write_all(&[code, 0]);
+    }
+    for chunk in data.chunks(255) {
+        cursor.write_all(&[code, chunk.len() as u8]);
+        cursor.write_all(chunk);
+    }
+    cursor.into_inner()
+}
+
+fn parse_option_data(code: u8, data: &[u8]) -> DhcpOption {
+    match (code, data.len()) {
+        (1, 4) =>
+            DhcpOption::SubnetMask(parse_ip4addrs(data)[0]),
+        (3, len) if len % 4 == 0 =>
+            DhcpOption::Router(parse_ip4addrs(data)),
+        (6, len) if len % 4 == 0 =>
+            DhcpOption::DomainNameServer(parse_ip4addrs(data)),
+        (12, _) =>
+            DhcpOption::HostName(String::from_utf8_lossy(data).into_owned()),
+        (50, 4) =>
+            DhcpOption::RequestedIpAddress(parse_ip4addrs(data)[0]),
+        (51, 4) =>
+            DhcpOption::LeaseTime(u32::from_be_bytes([data[0], data[1], data[2], data[3]])),
+        (53, 1) =>
+            DhcpOption::MessageType(DhcpMessageType::from(data[0])),
+        (54, 4) =>
+            DhcpOption::ServerIdentifier(parse_ip4addrs(data)[0]),
+        (55, _) =>
+            DhcpOption::ParameterRequestList(data.to_vec()),
+        (61, len) if len > 0 =>
+            DhcpOption::ClientIdentifier { hw_type: data[0], id: data[1..].to_vec() },
+        (82, _) => {
+            // Sub-options split on encoding are concatenated as well
+            let mut subopts: Vec<(u8, Vec<u8>)> = Vec::new();
+            let mut b = data;
+            while let [code, length, rest @ ..] = b {
+                if *length as usize > rest.len() {
+                    break;
+                }
+                let (subopt, leftover) = rest.split_at(*length as usize);
+                match subopts.last_mut() {
+                    Some((c, acc)) if c == code => acc.extend_from_slice(subopt),
+                    _ => subopts.push((*code, subopt.to_vec()))
+                }
+                b = leftover;
+            }
+            let subopts = subopts
+                .into_iter()
+                .map(|(code, data)| RelayAgentSubOption::from_data(code, data))
+                .collect();
+            if b.is_empty() {
+                DhcpOption::RelayAgentInformation(subopts)
+            } else {
+                DhcpOption::Unknown { code, data: data.to_vec() }
+            }
+        },
+        _ =>
+            DhcpOption::Unknown { code, data: data.to_vec() }
+    }
+}
+
+fn null_terminated(bytes: &[u8]) -> String {
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+fn padded(value: String, len: usize) -> Vec<u8> {
+    let mut bytes = value.into_bytes();
+    bytes.resize(len, 0);
+    bytes
+}
+
+#[cfg(test)]
+mod tests_dhcp {
+    use crate::dhcp::*;
+    use eui48::MacAddress;
+    use std::net::Ipv4Addr;
+    use std::str::FromStr;
+
+    #[test]
+    fn parse() {
+        let mut frame = vec![
+            0x01,0x01,0x06,0x00,0xde,0xad,0xbe,0xef,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x24,0xdb,0xac,0x41,
+            0xe5,0x5b
+        ];
+        frame.extend(vec![0; 202]);
+        frame.extend(vec![
+            0x63,0x82,0x53,0x63,0x35,0x01,0x01,0x3d,
+            0x07,0x01,0x24,0xdb,0xac,0x41,0xe5,0x5b,
+            0x32,0x04,0x00,0x00,0x00,0x00,0x37,0x04,
+            0x01,0x03,0x06,0x2a,0xff,0x00,0x00,0x00
+        ]);
+
+        let (leftover, dhcp) = Dhcp::from_bytes(&frame).unwrap();
+        let chaddr = MacAddress::from_str("24:db:ac:41:e5:5b").unwrap();
+        assert_eq!(BOOTREQUEST, dhcp.op);
+        assert_eq!(0xdeadbeef, dhcp.xid);
+        assert_eq!(chaddr, dhcp.chaddr);
+        assert_eq!(Some(DhcpMessageType::Discover), dhcp.message_type());
+        assert_eq!(vec![
+            DhcpOption::MessageType(DhcpMessageType::Discover),
+            DhcpOption::ClientIdentifier { hw_type: 1, id: chaddr.as_bytes().to_vec() },
+            DhcpOption::RequestedIpAddress(Ipv4Addr::new(0, 0, 0, 0)),
+            DhcpOption::ParameterRequestList(vec![1, 3, 6, 42]),
+            DhcpOption::End
+        ], dhcp.options);
+        assert_eq!(3, leftover.len());
+        assert_eq!(
+            "DHCP(type: Discover, xid: 0xdeadbeef, chaddr: \"24:db:ac:41:e5:5b\", yiaddr: \"0.0.0.0\")",
+            format!("{}", dhcp)
+        );
+        assert_eq!(frame[..frame.len() - 3].to_vec(), dhcp.as_bytes());
+    }
+
+    #[test]
+    fn parse_relay_agent_information() {
+        let option = &mut [
+            0x52,0x0a,0x01,0x04,0x00,0x01,0x00,0x0a,
+            0x02,0x02,0xab,0xcd
+        ];
+
+        let option = DhcpOption::from_bytes(option).unwrap().1;
+        assert_eq!(DhcpOption::RelayAgentInformation(vec![
+            RelayAgentSubOption::CircuitId(vec![0x00, 0x01, 0x00, 0x0a]),
+            RelayAgentSubOption::RemoteId(vec![0xab, 0xcd])
+        ]), option);
+    }
+
+    #[test]
+    fn build() {
+        let chaddr = MacAddress::from_str("02:00:00:00:00:01").unwrap();
+        let server = Ipv4Addr::new(192, 168, 0, 1);
+        let client = Ipv4Addr::new(192, 168, 0, 10);
+
+        let discover = Dhcp::discover(0x1234, chaddr);
+        let mut offer = Dhcp::offer(&discover, client, server, 3600);
+        offer.push_option(DhcpOption::Router(vec![server]));
+        let request = Dhcp::request(&offer);
+        let ack = Dhcp::ack(&request, client, server, 3600);
+
+        let offer = Dhcp::from_bytes(&offer.as_bytes()).unwrap().1;
+        assert_eq!(BOOTREPLY, offer.op);
+        assert_eq!(Some(DhcpMessageType::Offer), offer.message_type());
+        assert_eq!(DhcpOption::Router(vec![server]), offer.options[3]);
+        assert_eq!(DhcpOption::End, offer.options[4]);
+
+        let request = Dhcp::from_bytes(&request.as_bytes()).unwrap().1;
+        assert_eq!(Some(DhcpMessageType::Request), request.message_type());
+        assert_eq!(Some(server), request.server_identifier());
+        assert_eq!(DhcpOption::RequestedIpAddress(client), request.options[1]);
+
+        let ack = Dhcp::from_bytes(&ack.as_bytes()).unwrap().1;
+        assert_eq!(Some(DhcpMessageType::Ack), ack.message_type());
+        assert_eq!(0x1234, ack.xid);
+        assert_eq!(client, ack.yiaddr);
+        assert_eq!(chaddr, ack.chaddr);
+    }
+
+    #[test]
+    fn build_long_option() {
+        let mut dhcp = Dhcp::discover(0x1234, MacAddress::nil());
+        dhcp.push_option(DhcpOption::HostName("h".repeat(300)));
+        dhcp.push_option(DhcpOption::RelayAgentInformation(vec![RelayAgentSubOption::CircuitId(vec![0x01; 300])]));
+
+        let option = DhcpOption::HostName("h".repeat(300)).as_bytes();
+        assert_eq!(304, option.len());
+        assert_eq!(&[12, 255], &option[..2]);
+        assert_eq!(&[12, 45], &option[257..259]);
+
+        let bytes = dhcp.as_bytes();
+        let dhcp = Dhcp::from_bytes(&bytes).unwrap().1;
+        assert_eq!(DhcpOption::HostName("h".repeat(300)), dhcp.options[2]);
+        assert_eq!(DhcpOption::RelayAgentInformation(vec![RelayAgentSubOption::CircuitId(vec![0x01; 300])]), dhcp.options[3]);
+    }
+}
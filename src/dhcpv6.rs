@@ -0,0 +1,737 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::number::complete::{be_u8, be_u16, be_u32};
+use nom::IResult;
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::Ipv6Addr;
+use std::io::{Cursor, Write};
+
+use crate::util::parse_ip6addr;
+
+pub const DHCPV6_CLIENT_PORT: u16 = 546;
+pub const DHCPV6_SERVER_PORT: u16 = 547;
+
+// Maximum number of relays (RFC 8415), also bounds the nesting of options
+pub const HOP_COUNT_LIMIT: usize = 32;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Dhcpv6MessageType {
+    Solicit,
+    Advertise,
+    Request,
+    Confirm,
+    Renew,
+    Rebind,
+    Reply,
+    Release,
+    Decline,
+    Reconfigure,
+    InformationRequest,
+    RelayForw,
+    RelayRepl,
+    Other(u8)
+}
+
+impl From<u8> for Dhcpv6MessageType {
+    fn from(value: u8) -> Dhcpv6MessageType {
+        match value {
+            1  => Dhcpv6MessageType::Solicit,
+            2  => Dhcpv6MessageType::Advertise,
+            3  => Dhcpv6MessageType::Request,
+            4  => Dhcpv6MessageType::Confirm,
+            5  => Dhcpv6MessageType::Renew,
+            6  => Dhcpv6MessageType::Rebind,
+            7  => Dhcpv6MessageType::Reply,
+            8  => Dhcpv6MessageType::Release,
+            9  => Dhcpv6MessageType::Decline,
+            10 => Dhcpv6MessageType::Reconfigure,
+            11 => Dhcpv6MessageType::InformationRequest,
+            12 => Dhcpv6MessageType::RelayForw,
+            13 => Dhcpv6MessageType::RelayRepl,
+            other => Dhcpv6MessageType::Other(other)
+        }
+    }
+}
+
+impl From<Dhcpv6MessageType> for u8 {
+    fn from(value: Dhcpv6MessageType) -> u8 {
+        match value {
+            Dhcpv6MessageType::Solicit            => 1,
+            Dhcpv6MessageType::Advertise          => 2,
+            Dhcpv6MessageType::Request            => 3,
+            Dhcpv6MessageType::Confirm            => 4,
+            Dhcpv6MessageType::Renew              => 5,
+            Dhcpv6MessageType::Rebind             => 6,
+            Dhcpv6MessageType::Reply              => 7,
+            Dhcpv6MessageType::Release            => 8,
+            Dhcpv6MessageType::Decline            => 9,
+            Dhcpv6MessageType::Reconfigure        => 10,
+            Dhcpv6MessageType::InformationRequest => 11,
+            Dhcpv6MessageType::RelayForw          => 12,
+            Dhcpv6MessageType::RelayRepl          => 13,
+            Dhcpv6MessageType::Other(other) => other
+        }
+    }
+}
+
+// DHCP Unique Identifier (RFC 8415 11)
+#[derive(Debug, PartialEq, Clone)]
+pub enum Duid {
+    // link-layer address plus time
+    Llt { hw_type: u16, time: u32, link_layer: Vec<u8> },
+    // vendor-assigned unique ID based on enterprise number
+    En { enterprise: u32, id: Vec<u8> },
+    // link-layer address
+    Ll { hw_type: u16, link_layer: Vec<u8> },
+    Uuid([u8; 16]),
+    Unknown { duid_type: u16, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl Duid {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        match self {
+            Duid::Llt { hw_type, time, link_layer } => {
+                cursor.write_u16::<NetworkEndian>(1);
+                cursor.write_u16::<NetworkEndian>(hw_type);
+                cursor.write_u32::<NetworkEndian>(time);
+                cursor.write_all(&link_layer);
+            },
+            Duid::En { enterprise, id } => {
+                cursor.write_u16::<NetworkEndian>(2);
+                cursor.write_u32::<NetworkEndian>(enterprise);
+                cursor.write_all(&id);
+            },
+            Duid::Ll { hw_type, link_layer } => {
+                cursor.write_u16::<NetworkEndian>(3);
+                cursor.write_u16::<NetworkEndian>(hw_type);
+                cursor.write_all(&link_layer);
+            },
+            Duid::Uuid(uuid) => {
+                cursor.write_u16::<NetworkEndian>(4);
+                cursor.write_all(&uuid);
+            },
+            Duid::Unknown { duid_type, data } => {
+                cursor.write_u16::<NetworkEndian>(duid_type);
+                cursor.write_all(&data);
+            }
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a whole option data as a DUID
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Duid> {
+        let (bytes1, duid_type) = be_u16(bytes)?;
+        let duid = match (duid_type, bytes1.len()) {
+            (1, len) if len >= 6 => Duid::Llt {
+                hw_type:    u16::from_be_bytes([bytes1[0], bytes1[1]]),
+                time:       u32::from_be_bytes([bytes1[2], bytes1[3], bytes1[4], bytes1[5]]),
+                link_layer: bytes1[6..].to_vec()
+            },
+            (2, len) if len >= 4 => Duid::En {
+                enterprise: u32::from_be_bytes([bytes1[0], bytes1[1], bytes1[2], bytes1[3]]),
+                id:         bytes1[4..].to_vec()
+            },
+            (3, len) if len >= 2 => Duid::Ll {
+                hw_type:    u16::from_be_bytes([bytes1[0], bytes1[1]]),
+                link_layer: bytes1[2..].to_vec()
+            },
+            (4, 16) =>
+                Duid::Uuid(<[u8; 16]>::try_from(bytes1).unwrap()),
+            _ =>
+                Duid::Unknown { duid_type, data: bytes1.to_vec() }
+        };
+        Ok((&[], duid))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Dhcpv6Option {
+    ClientId(Duid),
+    ServerId(Duid),
+    IaNa { iaid: u32, t1: u32, t2: u32, options: Vec<Dhcpv6Option> },
+    IaAddr {
+        addr:               Ipv6Addr,
+        preferred_lifetime: u32,
+        valid_lifetime:     u32,
+        options:            Vec<Dhcpv6Option>
+    },
+    Oro(Vec<u16>),
+    ElapsedTime(u16),
+    RelayMessage(Box<Dhcpv6>),
+    InterfaceId(Vec<u8>),
+    DnsServers(Vec<Ipv6Addr>),
+    IaPd { iaid: u32, t1: u32, t2: u32, options: Vec<Dhcpv6Option> },
+    IaPrefix {
+        preferred_lifetime: u32,
+        valid_lifetime:     u32,
+        prefix_length:      u8,
+        prefix:             Ipv6Addr,
+        options:            Vec<Dhcpv6Option>
+    },
+    Unknown { code: u16, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl Dhcpv6Option {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let data: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(data);
+        let code = match self {
+            Dhcpv6Option::ClientId(duid) => {
+                cursor.write_all(&duid.as_bytes());
+                1
+            },
+            Dhcpv6Option::ServerId(duid) => {
+                cursor.write_all(&duid.as_bytes());
+                2
+            },
+            Dhcpv6Option::IaNa { iaid, t1, t2, options } => {
+                cursor.write_u32::<NetworkEndian>(iaid);
+                cursor.write_u32::<NetworkEndian>(t1);
+                cursor.write_u32::<NetworkEndian>(t2);
+                cursor.write_all(&options_as_bytes(options));
+                3
+            },
+            Dhcpv6Option::IaAddr { addr, preferred_lifetime, valid_lifetime, options } => {
+                cursor.write_all(&addr.octets());
+                cursor.write_u32::<NetworkEndian>(preferred_lifetime);
+                cursor.write_u32::<NetworkEndian>(valid_lifetime);
+                cursor.write_all(&options_as_bytes(options));
+                5
+            },
+            Dhcpv6Option::Oro(codes) => {
+                for code in codes {
+                    cursor.write_u16::<NetworkEndian>(code);
+                }
+                6
+            },
+            Dhcpv6Option::ElapsedTime(time) => {
+                cursor.write_u16::<NetworkEndian>(time);
+                8
+            },
+            Dhcpv6Option::RelayMessage(msg) => {
+                cursor.write_all(&msg.as_bytes());
+                9
+            },
+            Dhcpv6Option::InterfaceId(id) => {
+                cursor.write_all(&id);
+                18
+            },
+            Dhcpv6Option::DnsServers(addrs) => {
+                for addr in addrs {
+                    cursor.write_all(&addr.octets());
+                }
+                23
+            },
+            Dhcpv6Option::IaPd { iaid, t1, t2, options } => {
+                cursor.write_u32::<NetworkEndian>(iaid);
+                cursor.write_u32::<NetworkEndian>(t1);
+                cursor.write_u32::<NetworkEndian>(t2);
+                cursor.write_all(&options_as_bytes(options));
+                25
+            },
+            Dhcpv6Option::IaPrefix { preferred_lifetime, valid_lifetime, prefix_length, prefix, options } => {
+                cursor.write_u32::<NetworkEndian>(preferred_lifetime);
+                cursor.write_u32::<NetworkEndian>(valid_lifetime);
+                cursor.write_u8(prefix_length);
+                cursor.write_all(&prefix.octets());
+                cursor.write_all(&options_as_bytes(options));
+                26
+            },
+            Dhcpv6Option::Unknown { code, data } => {
+                cursor.write_all(&data);
+                code
+            }
+        };
+        let data = cursor.into_inner();
+
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u16::<NetworkEndian>(code);
+        cursor.write_u16::<NetworkEndian>(data.len() as u16);
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Dhcpv6Option> {
+        parse_option(bytes, 0)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Dhcpv6 {
+    pub msg_type:       Dhcpv6MessageType,
+    // client/server messages only
+    pub transaction_id: u32,
+    // relay messages only
+    pub hop_count:      u8,
+    pub link_address:   Ipv6Addr,
+    pub peer_address:   Ipv6Addr,
+    pub options:        Vec<Dhcpv6Option>
+}
+
+impl Default for Dhcpv6 {
+    fn default() -> Dhcpv6 {
+        Dhcpv6 {
+            msg_type:       Dhcpv6MessageType::Solicit,
+            transaction_id: 0,
+            hop_count:      0,
+            link_address:   Ipv6Addr::UNSPECIFIED,
+            peer_address:   Ipv6Addr::UNSPECIFIED,
+            options:        Vec::new()
+        }
+    }
+}
+
+impl fmt::Display for Dhcpv6 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_relay() {
+            write!(
+                f,
+                "DHCPv6(\
+                 type: {:?}, \
+                 hops: {}, \
+                 link: \"{}\", \
+                 peer: \"{}\"\
+                 )",
+                self.msg_type,
+                self.hop_count,
+                self.link_address,
+                self.peer_address
+            )
+        } else {
+            write!(
+                f,
+                "DHCPv6(\
+                 type: {:?}, \
+                 xid: 0x{:06x}\
+                 )",
+                self.msg_type,
+                self.transaction_id
+            )
+        }
+    }
+}
+
+#[allow(unused_must_use)]
+impl Dhcpv6 {
+    // Instantiate a new DHCPv6 message
+    pub fn new() -> Dhcpv6 { Dhcpv6::default() }
+
+    // Build a Solicit asking for a non-temporary address
+    pub fn solicit(transaction_id: u32, client_id: Duid, iaid: u32) -> Dhcpv6 {
+        Dhcpv6 {
+            msg_type: Dhcpv6MessageType::Solicit,
+            transaction_id,
+            options:  vec![
+                Dhcpv6Option::ClientId(client_id),
+                Dhcpv6Option::ElapsedTime(0),
+                Dhcpv6Option::Oro(vec![23]),
+                Dhcpv6Option::IaNa { iaid, t1: 0, t2: 0, options: Vec::new() }
+            ],
+            ..Dhcpv6::default()
+        }
+    }
+
+    // Build an Advertise in reply to a Solicit
+    pub fn advertise(solicit: &Dhcpv6, server_id: Duid, addr: Ipv6Addr, lifetime: u32) -> Dhcpv6 {
+        Self::reply_with(solicit, Dhcpv6MessageType::Advertise, server_id, addr, lifetime)
+    }
+
+    // Build a Request for the addresses of an Advertise
+    pub fn request(advertise: &Dhcpv6) -> Dhcpv6 {
+        let options = advertise.options
+            .iter()
+            .filter(|opt| matches!(opt,
+                Dhcpv6Option::ClientId(_) |
+                Dhcpv6Option::ServerId(_) |
+                Dhcpv6Option::IaNa { .. } |
+                Dhcpv6Option::IaPd { .. }
+            ))
+            .cloned()
+            .chain(vec![Dhcpv6Option::ElapsedTime(0), Dhcpv6Option::Oro(vec![23])])
+            .collect();
+
+        Dhcpv6 {
+            msg_type:       Dhcpv6MessageType::Request,
+            transaction_id: advertise.transaction_id,
+            options,
+            ..Dhcpv6::default()
+        }
+    }
+
+    // Build a Reply to a Request
+    pub fn reply(request: &Dhcpv6, server_id: Duid, addr: Ipv6Addr, lifetime: u32) -> Dhcpv6 {
+        Self::reply_with(request, Dhcpv6MessageType::Reply, server_id, addr, lifetime)
+    }
+
+    // Encapsulate a message into a Relay-forward message. None when the
+    // relayed message already reached the hop count limit
+    pub fn relay_forward(msg: Dhcpv6, link_address: Ipv6Addr, peer_address: Ipv6Addr, interface_id: Option<Vec<u8>>) -> Option<Dhcpv6> {
+        if msg.is_relay() && msg.hop_count as usize >= HOP_COUNT_LIMIT {
+            return None
+        }
+        let hop_count = if msg.is_relay() { msg.hop_count + 1 } else { 0 };
+        let mut options = vec![Dhcpv6Option::RelayMessage(Box::new(msg))];
+        if let Some(id) = interface_id {
+            options.push(Dhcpv6Option::InterfaceId(id));
+        }
+
+        Some(Dhcpv6 {
+            msg_type: Dhcpv6MessageType::RelayForw,
+            hop_count,
+            link_address,
+            peer_address,
+            options,
+            ..Dhcpv6::default()
+        })
+    }
+
+    // Relay-forward and Relay-reply messages use a different header
+    pub fn is_relay(&self) -> bool {
+        matches!(self.msg_type, Dhcpv6MessageType::RelayForw | Dhcpv6MessageType::RelayRepl)
+    }
+
+    pub fn client_id(&self) -> Option<&Duid> {
+        self.options.iter().find_map(|opt| match opt {
+            Dhcpv6Option::ClientId(duid) => Some(duid),
+            _ => None
+        })
+    }
+
+    pub fn server_id(&self) -> Option<&Duid> {
+        self.options.iter().find_map(|opt| match opt {
+            Dhcpv6Option::ServerId(duid) => Some(duid),
+            _ => None
+        })
+    }
+
+    // Message encapsulated by a relay
+    pub fn relay_message(&self) -> Option<&Dhcpv6> {
+        self.options.iter().find_map(|opt| match opt {
+            Dhcpv6Option::RelayMessage(msg) => Some(msg.as_ref()),
+            _ => None
+        })
+    }
+
+    // Encode the DHCPv6 message into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        let is_relay = self.is_relay();
+        cursor.write_u8(self.msg_type.into());
+        if is_relay {
+            cursor.write_u8(self.hop_count);
+            cursor.write_all(&self.link_address.octets());
+            cursor.write_all(&self.peer_address.octets());
+        } else {
+            cursor.write_u24::<NetworkEndian>(self.transaction_id & 0x00ff_ffff);
+        }
+        cursor.write_all(&options_as_bytes(self.options));
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice holding a whole DHCPv6 message
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Dhcpv6> {
+        parse_message(bytes, 0)
+    }
+
+    // private functions
+
+    fn reply_with(msg: &Dhcpv6, msg_type: Dhcpv6MessageType, server_id: Duid, addr: Ipv6Addr, lifetime: u32) -> Dhcpv6 {
+        let mut options = Vec::new();
+        if let Some(client_id) = msg.client_id() {
+            options.push(Dhcpv6Option::ClientId(client_id.clone()));
+        }
+        options.push(Dhcpv6Option::ServerId(server_id));
+        for option in &msg.options {
+            if let Dhcpv6Option::IaNa { iaid, .. } = option {
+                options.push(Dhcpv6Option::IaNa {
+                    iaid:    *iaid,
+                    t1:      lifetime / 2,
+                    t2:      lifetime / 5 * 4,
+                    options: vec![Dhcpv6Option::IaAddr {
+                        addr,
+                        preferred_lifetime: lifetime,
+                        valid_lifetime:     lifetime,
+                        options:            Vec::new()
+                    }]
+                });
+            }
+        }
+
+        Dhcpv6 {
+            msg_type,
+            transaction_id: msg.transaction_id,
+            options,
+            ..Dhcpv6::default()
+        }
+    }
+}
+
+// private functions
+
+// Nested relay messages and option lists carry the depth, beyond the
+// limit they are decoded as unknown options
+fn parse_message(bytes: &[u8], depth: usize) -> IResult<&[u8], Dhcpv6> {
+    let (bytes1, msg_type) = be_u8(bytes)?;
+    let msg_type = Dhcpv6MessageType::from(msg_type);
+
+    let (bytes2, msg) = match msg_type {
+        Dhcpv6MessageType::RelayForw | Dhcpv6MessageType::RelayRepl => {
+            let (b1, hop_count)    = be_u8(bytes1)?;
+            let (b2, link_address) = parse_ip6addr(b1)?;
+            let (b3, peer_address) = parse_ip6addr(b2)?;
+            (b3, Dhcpv6 { msg_type, hop_count, link_address, peer_address, ..Dhcpv6::default() })
+        },
+        _ => {
+            let (b1, xid) = take(3usize)(bytes1)?;
+            let transaction_id = u32::from_be_bytes([0, xid[0], xid[1], xid[2]]);
+            (b1, Dhcpv6 { msg_type, transaction_id, ..Dhcpv6::default() })
+        }
+    };
+
+    let (bytes3, options) = parse_options(bytes2, depth)?;
+    Ok((bytes3, Dhcpv6 { options, ..msg }))
+}
+
+fn parse_option(bytes: &[u8], depth: usize) -> IResult<&[u8], Dhcpv6Option> {
+    let (bytes1, code)   = be_u16(bytes)?;
+    let (bytes2, length) = be_u16(bytes1)?;
+    let (bytes3, data)   = take(length as usize)(bytes2)?;
+    let option = match parse_option_data(code, data, depth) {
+        Ok(option) => option,
+        Err(_e) => Dhcpv6Option::Unknown { code, data: data.to_vec() }
+    };
+    Ok((bytes3, option))
+}
+
+fn parse_options(bytes: &[u8], depth: usize) -> IResult<&[u8], Vec<Dhcpv6Option>> {
+    let mut acc = Vec::new();
+    let mut b = bytes;
+
+    while !b.is_empty() {
+        let (leftover, option) = parse_option(b, depth)?;
+        acc.push(option);
+        b = leftover;
+    }
+
+    Ok((b, acc))
+}
+
+fn options_as_bytes(options: Vec<Dhcpv6Option>) -> Vec<u8> {
+    options
+        .into_iter()
+        .flat_map(|option| option.as_bytes())
+        .collect()
+}
+
+// Identity Association for Non-temporary Addresses / Prefix Delegation
+fn parse_ia(data: &[u8], depth: usize) -> IResult<&[u8], (u32, u32, u32, Vec<Dhcpv6Option>)> {
+    let (bytes1, iaid)    = be_u32(data)?;
+    let (bytes2, t1)      = be_u32(bytes1)?;
+    let (bytes3, t2)      = be_u32(bytes2)?;
+    let (bytes4, options) = parse_options(bytes3, depth)?;
+    Ok((bytes4, (iaid, t1, t2, options)))
+}
+
+fn parse_option_data(code: u16, data: &[u8], depth: usize) -> Result<Dhcpv6Option, nom::Err<nom::error::Error<&[u8]>>> {
+    let option = match (code, data.len()) {
+        (3 | 5 | 9 | 25 | 26, _) if depth >= HOP_COUNT_LIMIT =>
+            Dhcpv6Option::Unknown { code, data: data.to_vec() },
+        (1, _) => Dhcpv6Option::ClientId(Duid::from_bytes(data)?.1),
+        (2, _) => Dhcpv6Option::ServerId(Duid::from_bytes(data)?.1),
+        (3, _) => {
+            let (iaid, t1, t2, options) = parse_ia(data, depth + 1)?.1;
+            Dhcpv6Option::IaNa { iaid, t1, t2, options }
+        },
+        (5, _) => {
+            let (bytes1, addr)               = parse_ip6addr(data)?;
+            let (bytes2, preferred_lifetime) = be_u32(bytes1)?;
+            let (bytes3, valid_lifetime)     = be_u32(bytes2)?;
+            let (_, options)                 = parse_options(bytes3, depth + 1)?;
+            Dhcpv6Option::IaAddr { addr, preferred_lifetime, valid_lifetime, options }
+        },
+        (6, len) if len % 2 == 0 => Dhcpv6Option::Oro(
+            data.chunks_exact(2)
+                .map(|code| u16::from_be_bytes([code[0], code[1]]))
+                .collect()
+        ),
+        (8, 2) =>
+            Dhcpv6Option::ElapsedTime(u16::from_be_bytes([data[0], data[1]])),
+        (9, _) =>
+            Dhcpv6Option::RelayMessage(Box::new(parse_message(data, depth + 1)?.1)),
+        (18, _) =>
+            Dhcpv6Option::InterfaceId(data.to_vec()),
+        (23, len) if len % 16 == 0 => Dhcpv6Option::DnsServers(
+            data.chunks_exact(16)
+                .map(|addr| Ipv6Addr::from(<[u8; 16]>::try_from(addr).unwrap()))
+                .collect()
+        ),
+        (25, _) => {
+            let (iaid, t1, t2, options) = parse_ia(data, depth + 1)?.1;
+            Dhcpv6Option::IaPd { iaid, t1, t2, options }
+        },
+        (26, _) => {
+            let (bytes1, preferred_lifetime) = be_u32(data)?;
+            let (bytes2, valid_lifetime)     = be_u32(bytes1)?;
+            let (bytes3, prefix_length)      = be_u8(bytes2)?;
+            let (bytes4, prefix)             = parse_ip6addr(bytes3)?;
+            let (_, options)                 = parse_options(bytes4, depth + 1)?;
+            Dhcpv6Option::IaPrefix { preferred_lifetime, valid_lifetime, prefix_length, prefix, options }
+        },
+        _ =>
+            Dhcpv6Option::Unknown { code, data: data.to_vec() }
+    };
+    Ok(option)
+}
+
+#[cfg(test)]
+mod tests_dhcpv6 {
+    use crate::dhcpv6::*;
+    use std::net::Ipv6Addr;
+
+    #[test]
+    fn parse() {
+        let frame = &mut [
+            0x01,0x10,0x08,0x74,0x00,0x01,0x00,0x0e,
+            0x00,0x01,0x00,0x01,0x1c,0x39,0xcf,0x88,
+            0x08,0x00,0x27,0xfe,0x8f,0x95,0x00,0x06,
+            0x00,0x04,0x00,0x17,0x00,0x18,0x00,0x08,
+            0x00,0x02,0x00,0x00,0x00,0x19,0x00,0x29,
+            0x27,0xfe,0x8f,0x95,0x00,0x00,0x0e,0x10,
+            0x00,0x00,0x15,0x18,0x00,0x1a,0x00,0x19,
+            0x00,0x00,0x1c,0x20,0x00,0x00,0x1d,0x4c,
+            0x38,0x20,0x01,0x0d,0xb8,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00
+        ];
+
+        let (leftover, dhcpv6) = Dhcpv6::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(Dhcpv6MessageType::Solicit, dhcpv6.msg_type);
+        assert_eq!(0x100874, dhcpv6.transaction_id);
+        assert_eq!(Some(&Duid::Llt {
+            hw_type:    1,
+            time:       0x1c39cf88,
+            link_layer: vec![0x08, 0x00, 0x27, 0xfe, 0x8f, 0x95]
+        }), dhcpv6.client_id());
+        assert_eq!(vec![
+            Dhcpv6Option::Oro(vec![23, 24]),
+            Dhcpv6Option::ElapsedTime(0),
+            Dhcpv6Option::IaPd {
+                iaid:    0x27fe8f95,
+                t1:      3600,
+                t2:      5400,
+                options: vec![Dhcpv6Option::IaPrefix {
+                    preferred_lifetime: 7200,
+                    valid_lifetime:     7500,
+                    prefix_length:      56,
+                    prefix:             "2001:db8::".parse().unwrap(),
+                    options:            Vec::new()
+                }]
+            }
+        ], dhcpv6.options[1..].to_vec());
+        assert_eq!("DHCPv6(type: Solicit, xid: 0x100874)", format!("{}", dhcpv6));
+        assert_eq!(frame.to_vec(), dhcpv6.as_bytes());
+    }
+
+    #[test]
+    fn parse_relay() {
+        let frame = &mut [
+            0x0c,0x00,0x20,0x01,0x0d,0xb8,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x01,0xfe,0x80,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x02,0x00,0x09,0x00,0x10,0x0b,0x00,
+            0x00,0x01,0x00,0x01,0x00,0x08,0x00,0x03,
+            0x00,0x01,0x02,0x00,0x00,0x12,0x00,0x12,
+            0x00,0x04,0x65,0x74,0x68,0x30
+        ];
+
+        let (leftover, dhcpv6) = Dhcpv6::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(Dhcpv6MessageType::RelayForw, dhcpv6.msg_type);
+        assert_eq!(0, dhcpv6.hop_count);
+        assert_eq!("2001:db8::1".parse::<Ipv6Addr>().unwrap(), dhcpv6.link_address);
+        assert_eq!("fe80::2".parse::<Ipv6Addr>().unwrap(), dhcpv6.peer_address);
+
+        let inner = dhcpv6.relay_message().unwrap();
+        assert_eq!(Dhcpv6MessageType::InformationRequest, inner.msg_type);
+        assert_eq!(1, inner.transaction_id);
+        assert_eq!(Some(&Duid::Ll { hw_type: 1, link_layer: vec![0x02, 0x00, 0x00, 0x12] }), inner.client_id());
+        assert_eq!(Dhcpv6Option::InterfaceId(b"eth0".to_vec()), dhcpv6.options[1]);
+        assert_eq!(
+            "DHCPv6(type: RelayForw, hops: 0, link: \"2001:db8::1\", peer: \"fe80::2\")",
+            format!("{}", dhcpv6)
+        );
+        assert_eq!(frame.to_vec(), dhcpv6.as_bytes());
+    }
+
+    #[test]
+    fn build() {
+        let client_id = Duid::Ll { hw_type: 1, link_layer: vec![0x02, 0x00, 0x00, 0x00, 0x00, 0x01] };
+        let server_id = Duid::En { enterprise: 32473, id: vec![0x01] };
+        let addr: Ipv6Addr = "2001:db8::10".parse().unwrap();
+
+        let solicit = Dhcpv6::solicit(0xabcdef, client_id.clone(), 1);
+        let advertise = Dhcpv6::advertise(&solicit, server_id.clone(), addr, 3600);
+        let request = Dhcpv6::request(&advertise);
+        let reply = Dhcpv6::reply(&request, server_id.clone(), addr, 3600);
+
+        assert_eq!(Dhcpv6MessageType::Request, request.msg_type);
+        assert_eq!(Some(&server_id), request.server_id());
+        assert_eq!(Some(&client_id), reply.client_id());
+        assert_eq!(0xabcdef, reply.transaction_id);
+        assert_eq!(Dhcpv6Option::IaNa {
+            iaid:    1,
+            t1:      1800,
+            t2:      2880,
+            options: vec![Dhcpv6Option::IaAddr {
+                addr,
+                preferred_lifetime: 3600,
+                valid_lifetime:     3600,
+                options:            Vec::new()
+            }]
+        }, reply.options[2]);
+
+        let relay = Dhcpv6::relay_forward(reply.clone(), Ipv6Addr::UNSPECIFIED, "fe80::1".parse().unwrap(), None).unwrap();
+        let relay = Dhcpv6::from_bytes(&relay.as_bytes()).unwrap().1;
+        assert_eq!(Some(&reply), relay.relay_message());
+    }
+
+    #[test]
+    fn parse_relay_depth() {
+        let mut relay = Dhcpv6::solicit(1, Duid::En { enterprise: 32473, id: vec![0x01] }, 1);
+        for _ in 0..64 {
+            relay = Dhcpv6 {
+                msg_type: Dhcpv6MessageType::RelayForw,
+                options:  vec![Dhcpv6Option::RelayMessage(Box::new(relay))],
+                ..Dhcpv6::default()
+            };
+        }
+
+        let bytes = relay.as_bytes();
+        let (leftover, mut dhcpv6) = Dhcpv6::from_bytes(&bytes).unwrap();
+        assert!(leftover.is_empty());
+        for _ in 0..HOP_COUNT_LIMIT {
+            dhcpv6 = dhcpv6.relay_message().unwrap().clone();
+        }
+        assert_eq!(None, dhcpv6.relay_message());
+        assert!(matches!(dhcpv6.options[0], Dhcpv6Option::Unknown { code: 9, .. }));
+    }
+
+    #[test]
+    fn build_relay_hop_count() {
+        let mut relay = Dhcpv6::solicit(1, Duid::En { enterprise: 32473, id: vec![0x01] }, 1);
+        for hop_count in 0..=HOP_COUNT_LIMIT {
+            relay = Dhcpv6::relay_forward(relay, Ipv6Addr::UNSPECIFIED, Ipv6Addr::UNSPECIFIED, None).unwrap();
+            assert_eq!(hop_count as u8, relay.hop_count);
+        }
+        assert_eq!(None, Dhcpv6::relay_forward(relay, Ipv6Addr::UNSPECIFIED, Ipv6Addr::UNSPECIFIED, None));
+    }
+}
@@ -0,0 +1,771 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::error::{Error, ErrorKind};
+use nom::number::complete::{be_u8, be_u16, be_u32};
+use nom::{call, do_parse, take, IResult};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::io::Cursor;
+
+pub const DNS_TYPE_A:     u16 = 1;
+pub const DNS_TYPE_NS:    u16 = 2;
+pub const DNS_TYPE_CNAME: u16 = 5;
+pub const DNS_TYPE_SOA:   u16 = 6;
+pub const DNS_TYPE_PTR:   u16 = 12;
+pub const DNS_TYPE_MX:    u16 = 15;
+pub const DNS_TYPE_TXT:   u16 = 16;
+pub const DNS_TYPE_AAAA:  u16 = 28;
+pub const DNS_TYPE_SRV:   u16 = 33;
+pub const DNS_TYPE_OPT:   u16 = 41;
+
+pub const DNS_CLASS_IN: u16 = 1;
+
+// A name is at most 255 octets on the wire (RFC 1035 2.3.4)
+const MAX_NAME_LENGTH: usize = 255;
+// and a label at most 63 octets
+const MAX_LABEL_LENGTH: usize = 63;
+// A character string is at most 255 octets
+const MAX_STRING_LENGTH: usize = 255;
+// Upper bound of compression pointers followed while decoding a single name
+const MAX_POINTERS: usize = 64;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct EdnsOption {
+    pub code: u16,
+    pub data: Vec<u8>
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum DnsRData {
+    A(Ipv4Addr),
+    AAAA(Ipv6Addr),
+    CNAME(String),
+    NS(String),
+    PTR(String),
+    MX { preference: u16, exchange: String },
+    TXT(Vec<Vec<u8>>),
+    SOA {
+        mname:   String,
+        rname:   String,
+        serial:  u32,
+        refresh: u32,
+        retry:   u32,
+        expire:  u32,
+        minimum: u32
+    },
+    SRV { priority: u16, weight: u16, port: u16, target: String },
+    OPT(Vec<EdnsOption>),
+    Unknown(Vec<u8>)
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct DnsQuestion {
+    pub name:   String,
+    pub qtype:  u16,
+    pub qclass: u16
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct DnsResourceRecord {
+    pub name:   String,
+    pub rtype:  u16,
+    // requestor's UDP payload size for OPT records
+    pub rclass: u16,
+    // extended RCODE, version and flags for OPT records
+    pub ttl:    u32,
+    pub data:   DnsRData
+}
+
+impl DnsRData {
+    // Whether the names and character strings fit their length limits
+    pub fn is_valid(&self) -> bool {
+        match self {
+            DnsRData::CNAME(name) |
+            DnsRData::NS(name) |
+            DnsRData::PTR(name) |
+            DnsRData::MX { exchange: name, .. } |
+            DnsRData::SRV { target: name, .. } =>
+                is_valid_name(name),
+            DnsRData::SOA { mname, rname, .. } =>
+                is_valid_name(mname) && is_valid_name(rname),
+            DnsRData::TXT(strings) =>
+                strings.iter().all(|string| string.len() <= MAX_STRING_LENGTH),
+            _ =>
+                true
+        }
+    }
+}
+
+impl DnsResourceRecord {
+    // Instantiate a record of the IN class, None when the name or the data
+    // does not fit its length limits
+    pub fn new(name: &str, rtype: u16, ttl: u32, data: DnsRData) -> Option<DnsResourceRecord> {
+        if !is_valid_name(name) || !data.is_valid() {
+            return None
+        }
+        Some(DnsResourceRecord {
+            name:   name.to_string(),
+            rtype,
+            rclass: DNS_CLASS_IN,
+            ttl,
+            data
+        })
+    }
+
+    // Instantiate an EDNS(0) OPT pseudo-record
+    pub fn opt(udp_payload_size: u16, dnssec_ok: bool, options: Vec<EdnsOption>) -> DnsResourceRecord {
+        DnsResourceRecord {
+            name:   String::new(),
+            rtype:  DNS_TYPE_OPT,
+            rclass: udp_payload_size,
+            ttl:    if dnssec_ok { 0x8000 } else { 0x0000 },
+            data:   DnsRData::OPT(options)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Dns {
+    pub id:          u16,
+    pub qr:          bool,
+    pub opcode:      u8,
+    pub aa:          bool,
+    pub tc:          bool,
+    pub rd:          bool,
+    pub ra:          bool,
+    pub z:           bool,
+    pub ad:          bool,
+    pub cd:          bool,
+    pub rcode:       u8,
+    pub questions:   Vec<DnsQuestion>,
+    pub answers:     Vec<DnsResourceRecord>,
+    pub authorities: Vec<DnsResourceRecord>,
+    pub additionals: Vec<DnsResourceRecord>
+}
+
+impl Default for Dns {
+    fn default() -> Dns {
+        Dns {
+            id:          0,
+            qr:          false,
+            opcode:      0,
+            aa:          false,
+            tc:          false,
+            rd:          true,
+            ra:          false,
+            z:           false,
+            ad:          false,
+            cd:          false,
+            rcode:       0,
+            questions:   Vec::new(),
+            answers:     Vec::new(),
+            authorities: Vec::new(),
+            additionals: Vec::new()
+        }
+    }
+}
+
+impl fmt::Display for Dns {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "DNS(\
+             id: 0x{:04x}, \
+             qr: {}, \
+             rcode: {}, \
+             qd: {}, \
+             an: {}, \
+             ns: {}, \
+             ar: {}\
+             )",
+            self.id,
+            self.qr as u8,
+            self.rcode,
+            self.questions.len(),
+            self.answers.len(),
+            self.authorities.len(),
+            self.additionals.len()
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Dns {
+    // Instantiate a new DNS message
+    pub fn new() -> Dns { Dns::default() }
+
+    // Build a recursive query for a single name, None when the name does
+    // not fit its length limits
+    pub fn query(id: u16, name: &str, qtype: u16) -> Option<Dns> {
+        if !is_valid_name(name) {
+            return None
+        }
+        Some(Dns {
+            id,
+            questions: vec![DnsQuestion { name: name.to_string(), qtype, qclass: DNS_CLASS_IN }],
+            ..Dns::default()
+        })
+    }
+
+    // Whether all names and character strings fit their length limits, the
+    // encoding of an invalid message is undefined
+    pub fn is_valid(&self) -> bool {
+        let records = self.answers
+            .iter()
+            .chain(&self.authorities)
+            .chain(&self.additionals);
+        self.questions.iter().all(|question| is_valid_name(&question.name)) &&
+            records.into_iter().all(|record| is_valid_name(&record.name) && record.data.is_valid())
+    }
+
+    // Encode the DNS message into a byte slice, compressing names
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        let mut names: HashMap<String, usize> = HashMap::new();
+        cursor.write_u16::<NetworkEndian>(self.id);
+        cursor.write_u16::<NetworkEndian>(
+            (self.qr as u16) << 15 |
+            ((self.opcode & 0x0f) as u16) << 11 |
+            (self.aa as u16) << 10 |
+            (self.tc as u16) << 9 |
+            (self.rd as u16) << 8 |
+            (self.ra as u16) << 7 |
+            (self.z as u16) << 6 |
+            (self.ad as u16) << 5 |
+            (self.cd as u16) << 4 |
+            (self.rcode & 0x0f) as u16
+        );
+        cursor.write_u16::<NetworkEndian>(self.questions.len() as u16);
+        cursor.write_u16::<NetworkEndian>(self.answers.len() as u16);
+        cursor.write_u16::<NetworkEndian>(self.authorities.len() as u16);
+        cursor.write_u16::<NetworkEndian>(self.additionals.len() as u16);
+        let mut buf = cursor.into_inner();
+
+        for question in self.questions {
+            write_name(&mut buf, &question.name, Some(&mut names));
+            buf.write_u16::<NetworkEndian>(question.qtype);
+            buf.write_u16::<NetworkEndian>(question.qclass);
+        }
+
+        let records = self.answers
+            .into_iter()
+            .chain(self.authorities)
+            .chain(self.additionals);
+        for record in records {
+            write_record(&mut buf, record, &mut names);
+        }
+
+        buf
+    }
+
+    // Encode the DNS message with the 2 byte length prefix used over TCP
+    pub fn as_tcp_bytes(self) -> Vec<u8> {
+        let msg = self.as_bytes();
+        [(msg.len() as u16).to_be_bytes().to_vec(), msg].concat()
+    }
+
+    // Parse a byte slice holding a whole DNS message
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Dns> {
+        let msg = bytes;
+        do_parse!(
+            bytes,
+            id:          be_u16 >>
+            flags:       be_u16 >>
+            qdcount:     be_u16 >>
+            ancount:     be_u16 >>
+            nscount:     be_u16 >>
+            arcount:     be_u16 >>
+            questions:   call!(parse_questions, msg, qdcount) >>
+            answers:     call!(parse_records, msg, ancount) >>
+            authorities: call!(parse_records, msg, nscount) >>
+            additionals: call!(parse_records, msg, arcount) >>
+                (
+                    Dns {
+                        id,
+                        qr:     flags & 0x8000 != 0,
+                        opcode: ((flags >> 11) & 0x0f) as u8,
+                        aa:     flags & 0x0400 != 0,
+                        tc:     flags & 0x0200 != 0,
+                        rd:     flags & 0x0100 != 0,
+                        ra:     flags & 0x0080 != 0,
+                        z:      flags & 0x0040 != 0,
+                        ad:     flags & 0x0020 != 0,
+                        cd:     flags & 0x0010 != 0,
+                        rcode:  (flags & 0x000f) as u8,
+                        questions,
+                        answers,
+                        authorities,
+                        additionals
+                    }
+                )
+        )
+    }
+
+    // Parse a DNS message prefixed with the 2 byte length used over TCP
+    pub fn from_tcp_bytes(bytes: &[u8]) -> IResult<&[u8], Dns> {
+        let (bytes1, length) = be_u16(bytes)?;
+        let (bytes2, msg) = take(length as usize)(bytes1)?;
+        let (_, dns) = Self::from_bytes(msg)?;
+        Ok((bytes2, dns))
+    }
+}
+
+// private functions
+
+fn parse_error<T>(bytes: &[u8]) -> IResult<&[u8], T> {
+    Err(nom::Err::Error(Error::new(bytes, ErrorKind::Verify)))
+}
+
+// Offset of a sub slice from the start of the message
+fn offset(msg: &[u8], bytes: &[u8]) -> usize {
+    bytes.as_ptr() as usize - msg.as_ptr() as usize
+}
+
+fn parse_name<'a>(bytes: &'a [u8], msg: &[u8]) -> IResult<&'a [u8], String> {
+    let start = offset(msg, bytes);
+    let mut pos = start;
+    let mut end = None;
+    let mut pointers = 0;
+    let mut length = 1;
+    let mut labels: Vec<String> = Vec::new();
+
+    loop {
+        let len = match msg.get(pos) {
+            Some(len) => *len as usize,
+            None => return parse_error(bytes)
+        };
+
+        match len & 0xc0 {
+            0x00 if len == 0 => {
+                pos += 1;
+                break
+            },
+            0x00 => {
+                let label = match msg.get(pos + 1..pos + 1 + len) {
+                    Some(label) => label,
+                    None => return parse_error(bytes)
+                };
+                length += len + 1;
+                if length > MAX_NAME_LENGTH { return parse_error(bytes) }
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                pos += len + 1;
+            },
+            0xc0 => {
+                let low = match msg.get(pos + 1) {
+                    Some(low) => *low as usize,
+                    None => return parse_error(bytes)
+                };
+                let target = (len & 0x3f) << 8 | low;
+                // pointers must refer to a prior occurrence
+                pointers += 1;
+                if target >= pos || pointers > MAX_POINTERS { return parse_error(bytes) }
+                if end.is_none() { end = Some(pos + 2) }
+                pos = target;
+            },
+            _ => return parse_error(bytes)
+        }
+    }
+
+    // a name in RDATA must not run past the end of it
+    let consumed = end.unwrap_or(pos) - start;
+    if consumed > bytes.len() { return parse_error(bytes) }
+    Ok((&bytes[consumed..], labels.join(".")))
+}
+
+fn parse_questions<'a>(bytes: &'a [u8], msg: &[u8], count: u16) -> IResult<&'a [u8], Vec<DnsQuestion>> {
+    let mut acc = Vec::new();
+    let mut b = bytes;
+
+    for _ in 0..count {
+        let (leftover, question) = do_parse!(
+            b,
+            name:   call!(parse_name, msg) >>
+            qtype:  be_u16 >>
+            qclass: be_u16 >>
+                (DnsQuestion { name, qtype, qclass })
+        )?;
+        acc.push(question);
+        b = leftover;
+    }
+
+    Ok((b, acc))
+}
+
+fn parse_records<'a>(bytes: &'a [u8], msg: &[u8], count: u16) -> IResult<&'a [u8], Vec<DnsResourceRecord>> {
+    let mut acc = Vec::new();
+    let mut b = bytes;
+
+    for _ in 0..count {
+        let (leftover, record) = do_parse!(
+            b,
+            name:     call!(parse_name, msg) >>
+            rtype:    be_u16 >>
+            rclass:   be_u16 >>
+            ttl:      be_u32 >>
+            rdlength: be_u16 >>
+            rdata:    take!(rdlength as usize) >>
+            data:     call!(parse_rdata_or_unknown, rdata, msg, rtype) >>
+                (DnsResourceRecord { name, rtype, rclass, ttl, data })
+        )?;
+        acc.push(record);
+        b = leftover;
+    }
+
+    Ok((b, acc))
+}
+
+// Decode well known RDATA, keeping the raw bytes when it is malformed
+fn parse_rdata_or_unknown<'a>(bytes: &'a [u8], rdata: &[u8], msg: &[u8], rtype: u16) -> IResult<&'a [u8], DnsRData> {
+    match parse_rdata(rdata, msg, rtype) {
+        Ok(([], data)) => Ok((bytes, data)),
+        _ => Ok((bytes, DnsRData::Unknown(rdata.to_vec())))
+    }
+}
+
+fn parse_rdata<'a>(rdata: &'a [u8], msg: &[u8], rtype: u16) -> IResult<&'a [u8], DnsRData> {
+    match rtype {
+        DNS_TYPE_A if rdata.len() == 4 =>
+            Ok((&[], DnsRData::A(Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3])))),
+        DNS_TYPE_AAAA if rdata.len() == 16 =>
+            Ok((&[], DnsRData::AAAA(Ipv6Addr::from(<[u8; 16]>::try_from(rdata).unwrap())))),
+        DNS_TYPE_CNAME => {
+            let (b, name) = parse_name(rdata, msg)?;
+            Ok((b, DnsRData::CNAME(name)))
+        },
+        DNS_TYPE_NS => {
+            let (b, name) = parse_name(rdata, msg)?;
+            Ok((b, DnsRData::NS(name)))
+        },
+        DNS_TYPE_PTR => {
+            let (b, name) = parse_name(rdata, msg)?;
+            Ok((b, DnsRData::PTR(name)))
+        },
+        DNS_TYPE_MX => do_parse!(
+            rdata,
+            preference: be_u16 >>
+            exchange:   call!(parse_name, msg) >>
+                (DnsRData::MX { preference, exchange })
+        ),
+        DNS_TYPE_TXT => {
+            let mut strings = Vec::new();
+            let mut b = rdata;
+            while !b.is_empty() {
+                let (b1, len) = be_u8(b)?;
+                let (b2, string) = take(len as usize)(b1)?;
+                strings.push(string.to_vec());
+                b = b2;
+            }
+            Ok((b, DnsRData::TXT(strings)))
+        },
+        DNS_TYPE_SOA => do_parse!(
+            rdata,
+            mname:   call!(parse_name, msg) >>
+            rname:   call!(parse_name, msg) >>
+            serial:  be_u32 >>
+            refresh: be_u32 >>
+            retry:   be_u32 >>
+            expire:  be_u32 >>
+            minimum: be_u32 >>
+                (DnsRData::SOA { mname, rname, serial, refresh, retry, expire, minimum })
+        ),
+        DNS_TYPE_SRV => do_parse!(
+            rdata,
+            priority: be_u16 >>
+            weight:   be_u16 >>
+            port:     be_u16 >>
+            target:   call!(parse_name, msg) >>
+                (DnsRData::SRV { priority, weight, port, target })
+        ),
+        DNS_TYPE_OPT => {
+            let mut options = Vec::new();
+            let mut b = rdata;
+            while !b.is_empty() {
+                let (b1, code) = be_u16(b)?;
+                let (b2, len) = be_u16(b1)?;
+                let (b3, data) = take(len as usize)(b2)?;
+                options.push(EdnsOption { code, data: data.to_vec() });
+                b = b3;
+            }
+            Ok((b, DnsRData::OPT(options)))
+        },
+        _ => Ok((&[], DnsRData::Unknown(rdata.to_vec())))
+    }
+}
+
+// Whether the labels and the encoded name fit their length limits
+fn is_valid_name(name: &str) -> bool {
+    let labels: Vec<&str> = name
+        .split('.')
+        .filter(|label| !label.is_empty())
+        .collect();
+    labels.iter().all(|label| label.len() <= MAX_LABEL_LENGTH) &&
+        labels.iter().map(|label| label.len() + 1).sum::<usize>() < MAX_NAME_LENGTH
+}
+
+// Write a name, reusing a previous occurrence of any of its suffixes when
+// a compression table is given
+fn write_name(buf: &mut Vec<u8>, name: &str, names: Option<&mut HashMap<String, usize>>) {
+    let labels: Vec<&str> = name
+        .split('.')
+        .filter(|label| !label.is_empty())
+        .collect();
+
+    match names {
+        None => {
+            for label in labels {
+                buf.push(label.len() as u8);
+                buf.extend_from_slice(label.as_bytes());
+            }
+        },
+        Some(names) => {
+            for i in 0..labels.len() {
+                let suffix = labels[i..].join(".").to_lowercase();
+                if let Some(ptr) = names.get(&suffix) {
+                    buf.extend_from_slice(&(0xc000 | *ptr as u16).to_be_bytes());
+                    return
+                }
+                if buf.len() < 0x4000 { names.insert(suffix, buf.len()); }
+                buf.push(labels[i].len() as u8);
+                buf.extend_from_slice(labels[i].as_bytes());
+            }
+        }
+    }
+
+    buf.push(0);
+}
+
+#[allow(unused_must_use)]
+fn write_record(buf: &mut Vec<u8>, record: DnsResourceRecord, names: &mut HashMap<String, usize>) {
+    write_name(buf, &record.name, Some(names));
+    buf.write_u16::<NetworkEndian>(record.rtype);
+    buf.write_u16::<NetworkEndian>(record.rclass);
+    buf.write_u32::<NetworkEndian>(record.ttl);
+    let rdlength_pos = buf.len();
+    buf.write_u16::<NetworkEndian>(0);
+
+    match record.data {
+        DnsRData::A(addr) => buf.extend_from_slice(&addr.octets()),
+        DnsRData::AAAA(addr) => buf.extend_from_slice(&addr.octets()),
+        DnsRData::CNAME(name) |
+        DnsRData::NS(name) |
+        DnsRData::PTR(name) => write_name(buf, &name, Some(names)),
+        DnsRData::MX { preference, exchange } => {
+            buf.write_u16::<NetworkEndian>(preference);
+            write_name(buf, &exchange, Some(names));
+        },
+        DnsRData::TXT(strings) => {
+            for string in strings {
+                buf.push(string.len() as u8);
+                buf.extend_from_slice(&string);
+            }
+        },
+        DnsRData::SOA { mname, rname, serial, refresh, retry, expire, minimum } => {
+            write_name(buf, &mname, Some(names));
+            write_name(buf, &rname, Some(names));
+            buf.write_u32::<NetworkEndian>(serial);
+            buf.write_u32::<NetworkEndian>(refresh);
+            buf.write_u32::<NetworkEndian>(retry);
+            buf.write_u32::<NetworkEndian>(expire);
+            buf.write_u32::<NetworkEndian>(minimum);
+        },
+        // RFC 2782: the target must not be compressed
+        DnsRData::SRV { priority, weight, port, target } => {
+            buf.write_u16::<NetworkEndian>(priority);
+            buf.write_u16::<NetworkEndian>(weight);
+            buf.write_u16::<NetworkEndian>(port);
+            write_name(buf, &target, None);
+        },
+        DnsRData::OPT(options) => {
+            for option in options {
+                buf.write_u16::<NetworkEndian>(option.code);
+                buf.write_u16::<NetworkEndian>(option.data.len() as u16);
+                buf.extend_from_slice(&option.data);
+            }
+        },
+        DnsRData::Unknown(data) => buf.extend_from_slice(&data)
+    }
+
+    let rdlength = (buf.len() - rdlength_pos - 2) as u16;
+    buf[rdlength_pos..rdlength_pos + 2].copy_from_slice(&rdlength.to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests_dns {
+    use crate::dns::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn parse() {
+        let frame = &mut [
+            0x12,0x34,0x81,0x80,0x00,0x01,0x00,0x02,
+            0x00,0x00,0x00,0x01,0x03,0x77,0x77,0x77,
+            0x07,0x65,0x78,0x61,0x6d,0x70,0x6c,0x65,
+            0x03,0x63,0x6f,0x6d,0x00,0x00,0x01,0x00,
+            0x01,0xc0,0x0c,0x00,0x05,0x00,0x01,0x00,
+            0x00,0x0e,0x10,0x00,0x02,0xc0,0x10,0xc0,
+            0x10,0x00,0x01,0x00,0x01,0x00,0x00,0x0e,
+            0x10,0x00,0x04,0x5d,0xb8,0xd8,0x22,0x00,
+            0x00,0x29,0x10,0x00,0x00,0x00,0x80,0x00,
+            0x00,0x00
+        ];
+
+        let (leftover, dns) = Dns::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(0x1234, dns.id);
+        assert!(dns.qr);
+        assert!(dns.rd);
+        assert!(dns.ra);
+        assert_eq!(0, dns.rcode);
+        assert_eq!(vec![DnsQuestion {
+            name:   String::from("www.example.com"),
+            qtype:  DNS_TYPE_A,
+            qclass: DNS_CLASS_IN
+        }], dns.questions);
+        assert_eq!(vec![
+            DnsResourceRecord {
+                name:   String::from("www.example.com"),
+                rtype:  DNS_TYPE_CNAME,
+                rclass: DNS_CLASS_IN,
+                ttl:    3600,
+                data:   DnsRData::CNAME(String::from("example.com"))
+            },
+            DnsResourceRecord {
+                name:   String::from("example.com"),
+                rtype:  DNS_TYPE_A,
+                rclass: DNS_CLASS_IN,
+                ttl:    3600,
+                data:   DnsRData::A(Ipv4Addr::new(93, 184, 216, 34))
+            }
+        ], dns.answers);
+        assert_eq!(vec![DnsResourceRecord::opt(4096, true, vec![])], dns.additionals);
+        assert_eq!("DNS(id: 0x1234, qr: 1, rcode: 0, qd: 1, an: 2, ns: 0, ar: 1)", format!("{}", dns));
+
+        // encoding compresses the names the same way
+        assert_eq!(frame.to_vec(), dns.as_bytes());
+    }
+
+    #[test]
+    fn records() {
+        let mut dns = Dns::new();
+        dns.qr = true;
+        dns.answers = vec![
+            DnsResourceRecord {
+                name:   String::from("example.com"),
+                rtype:  DNS_TYPE_MX,
+                rclass: DNS_CLASS_IN,
+                ttl:    300,
+                data:   DnsRData::MX { preference: 10, exchange: String::from("mail.example.com") }
+            },
+            DnsResourceRecord {
+                name:   String::from("example.com"),
+                rtype:  DNS_TYPE_TXT,
+                rclass: DNS_CLASS_IN,
+                ttl:    300,
+                data:   DnsRData::TXT(vec![b"v=spf1 -all".to_vec()])
+            },
+            DnsResourceRecord {
+                name:   String::from("_sip._udp.example.com"),
+                rtype:  DNS_TYPE_SRV,
+                rclass: DNS_CLASS_IN,
+                ttl:    300,
+                data:   DnsRData::SRV { priority: 1, weight: 2, port: 5060, target: String::from("sip.example.com") }
+            },
+            DnsResourceRecord {
+                name:   String::from("1.0.0.127.in-addr.arpa"),
+                rtype:  DNS_TYPE_PTR,
+                rclass: DNS_CLASS_IN,
+                ttl:    300,
+                data:   DnsRData::PTR(String::from("localhost"))
+            },
+            DnsResourceRecord {
+                name:   String::from("example.com"),
+                rtype:  DNS_TYPE_AAAA,
+                rclass: DNS_CLASS_IN,
+                ttl:    300,
+                data:   DnsRData::AAAA("2001:db8::1".parse().unwrap())
+            }
+        ];
+        dns.authorities = vec![
+            DnsResourceRecord {
+                name:   String::from("example.com"),
+                rtype:  DNS_TYPE_SOA,
+                rclass: DNS_CLASS_IN,
+                ttl:    300,
+                data:   DnsRData::SOA {
+                    mname:   String::from("ns.example.com"),
+                    rname:   String::from("hostmaster.example.com"),
+                    serial:  2020010101,
+                    refresh: 7200,
+                    retry:   3600,
+                    expire:  1209600,
+                    minimum: 300
+                }
+            },
+            DnsResourceRecord {
+                name:   String::from("example.com"),
+                rtype:  DNS_TYPE_NS,
+                rclass: DNS_CLASS_IN,
+                ttl:    300,
+                data:   DnsRData::NS(String::from("ns.example.com"))
+            }
+        ];
+
+        let answers = dns.answers.clone();
+        let authorities = dns.authorities.clone();
+        let bytes = dns.as_tcp_bytes();
+        let dns = Dns::from_tcp_bytes(&bytes).unwrap().1;
+        assert_eq!(answers, dns.answers);
+        assert_eq!(authorities, dns.authorities);
+    }
+
+    #[test]
+    fn compression_loop() {
+        let frame = &mut [
+            0x00,0x01,0x00,0x00,0x00,0x01,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x01,0x61,0xc0,0x0c,
+            0x00,0x01,0x00,0x01
+        ];
+        assert!(Dns::from_bytes(frame).is_err());
+
+        let frame = &mut [
+            0x00,0x01,0x00,0x00,0x00,0x01,0x00,0x00,
+            0x00,0x00,0x00,0x00,0xc0,0x0c,0x00,0x01,
+            0x00,0x01
+        ];
+        assert!(Dns::from_bytes(frame).is_err());
+    }
+
+    #[test]
+    fn rdata_name_overrun() {
+        let frame = &mut [
+            0x00,0x01,0x81,0x80,0x00,0x00,0x00,0x01,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x05,0x00,
+            0x01,0x00,0x00,0x00,0x00,0x00,0x01,0x03,
+            0x61,0x62,0x63,0x00
+        ];
+        let (leftover, dns) = Dns::from_bytes(frame).unwrap();
+        assert_eq!(&[0x61, 0x62, 0x63, 0x00], leftover);
+        assert_eq!(DnsRData::Unknown(vec![0x03]), dns.answers[0].data);
+
+    }
+
+    #[test]
+    fn build_length() {
+        assert_eq!(None, Dns::query(1, &format!("{}.com", "a".repeat(64)), DNS_TYPE_A));
+        assert_eq!(None, Dns::query(1, &vec!["a".repeat(63); 4].join("."), DNS_TYPE_A));
+        assert!(Dns::query(1, &["a".repeat(63), "a".repeat(63), "a".repeat(63), "a".repeat(61)].join("."), DNS_TYPE_A).is_some());
+        assert_eq!(None, DnsResourceRecord::new("example.com", DNS_TYPE_TXT, 300, DnsRData::TXT(vec![vec![0x61; 256]])));
+
+        let mut dns = Dns::query(1, "example.com", DNS_TYPE_A).unwrap();
+        dns.answers.push(DnsResourceRecord::new("example.com", DNS_TYPE_TXT, 300, DnsRData::TXT(vec![vec![0x61; 255]])).unwrap());
+        assert!(dns.is_valid());
+        dns.answers.push(DnsResourceRecord {
+            data: DnsRData::CNAME("a".repeat(64)),
+            ..dns.answers[0].clone()
+        });
+        assert!(!dns.is_valid());
+    }
+}
@@ -0,0 +1,222 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::number::complete::{be_u16, be_u32, be_u64};
+use nom::{call, do_parse, IResult};
+use std::fmt;
+use std::io::Cursor;
+
+pub const ERSPAN_TYPE2: u8 = 1;
+pub const ERSPAN_TYPE3: u8 = 2;
+
+// Optional platform specific sub-header of ERSPAN type III
+#[derive(Debug, PartialEq, Clone)]
+pub struct ErspanPlatform {
+    pub platform_id: u8,
+    pub info:        u64
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Erspan {
+    pub version:     u8,
+    pub vlan:        u16,
+    pub cos:         u8,
+    // En (type II) or BSO (type III)
+    pub encap_type:  u8,
+    pub truncated:   bool,
+    pub session_id:  u16,
+    // type II only
+    pub index:       u32,
+    // type III only
+    pub timestamp:   u32,
+    pub sgt:         u16,
+    pub p:           bool,
+    pub frame_type:  u8,
+    pub hw_id:       u8,
+    pub direction:   bool,
+    pub granularity: u8,
+    pub platform:    Option<ErspanPlatform>
+}
+
+impl Default for Erspan {
+    fn default() -> Erspan {
+        Erspan {
+            version:     ERSPAN_TYPE2,
+            vlan:        0,
+            cos:         0,
+            encap_type:  0,
+            truncated:   false,
+            session_id:  0,
+            index:       0,
+            timestamp:   0,
+            sgt:         0,
+            p:           false,
+            frame_type:  0,
+            hw_id:       0,
+            direction:   false,
+            granularity: 0,
+            platform:    None
+        }
+    }
+}
+
+impl fmt::Display for Erspan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ERSPAN(\
+             type: {}, \
+             session: {}, \
+             vlan: {}, \
+             truncated: {}\
+             )",
+            if self.version == ERSPAN_TYPE3 { "III" } else { "II" },
+            self.session_id,
+            self.vlan,
+            self.truncated
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Erspan {
+    // Instantiate a new ERSPAN type II header
+    pub fn new() -> Erspan { Erspan::default() }
+
+    // Encode the ERSPAN header into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        let truncated = if self.truncated { 0x0400 } else { 0x0000 };
+        cursor.write_u16::<NetworkEndian>((self.version as u16) << 12 | (self.vlan & 0x0fff));
+        cursor.write_u16::<NetworkEndian>(
+            ((self.cos & 0x07) as u16) << 13 |
+            ((self.encap_type & 0x03) as u16) << 11 |
+            truncated |
+            (self.session_id & 0x03ff)
+        );
+        if self.version == ERSPAN_TYPE3 {
+            let p         = if self.p { 0x8000 } else { 0x0000 };
+            let direction = if self.direction { 0x0008 } else { 0x0000 };
+            let has_platf = if self.platform.is_some() { 0x0001 } else { 0x0000 };
+            cursor.write_u32::<NetworkEndian>(self.timestamp);
+            cursor.write_u16::<NetworkEndian>(self.sgt);
+            cursor.write_u16::<NetworkEndian>(
+                p |
+                ((self.frame_type & 0x1f) as u16) << 10 |
+                ((self.hw_id & 0x3f) as u16) << 4 |
+                direction |
+                ((self.granularity & 0x03) as u16) << 1 |
+                has_platf
+            );
+            if let Some(platform) = self.platform {
+                cursor.write_u64::<NetworkEndian>(
+                    (platform.platform_id as u64) << 58 |
+                    (platform.info & 0x03ff_ffff_ffff_ffff)
+                );
+            }
+        } else {
+            cursor.write_u32::<NetworkEndian>(self.index & 0x000f_ffff);
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Erspan> {
+        do_parse!(
+            bytes,
+            ver_vlan: be_u16 >>
+            session:  be_u16 >>
+            rest:     call!(parse_rest, ver_vlan >> 12) >>
+                (
+                    Erspan {
+                        version:    (ver_vlan >> 12) as u8,
+                        vlan:       ver_vlan & 0x0fff,
+                        cos:        (session >> 13) as u8,
+                        encap_type: ((session >> 11) & 0x03) as u8,
+                        truncated:  session & 0x0400 != 0,
+                        session_id: session & 0x03ff,
+                        ..rest
+                    }
+                )
+        )
+    }
+}
+
+// private functions
+
+fn parse_rest(bytes: &[u8], version: u16) -> IResult<&[u8], Erspan> {
+    if version as u8 != ERSPAN_TYPE3 {
+        let (bytes1, index) = be_u32(bytes)?;
+        return Ok((bytes1, Erspan { index: index & 0x000f_ffff, ..Erspan::default() }))
+    }
+
+    let (bytes1, timestamp) = be_u32(bytes)?;
+    let (bytes2, sgt)       = be_u16(bytes1)?;
+    let (bytes3, flags)     = be_u16(bytes2)?;
+    let (bytes4, platform)  = if flags & 0x0001 != 0 {
+        let (b, word) = be_u64(bytes3)?;
+        (b, Some(ErspanPlatform { platform_id: (word >> 58) as u8, info: word & 0x03ff_ffff_ffff_ffff }))
+    } else {
+        (bytes3, None)
+    };
+
+    Ok((bytes4, Erspan {
+        timestamp,
+        sgt,
+        p:           flags & 0x8000 != 0,
+        frame_type:  ((flags >> 10) & 0x1f) as u8,
+        hw_id:       ((flags >> 4) & 0x3f) as u8,
+        direction:   flags & 0x0008 != 0,
+        granularity: ((flags >> 1) & 0x03) as u8,
+        platform,
+        ..Erspan::default()
+    }))
+}
+
+#[cfg(test)]
+mod tests_erspan {
+    use crate::erspan::*;
+
+    #[test]
+    fn parse_type2() {
+        let frame = &mut [
+            0x10,0x64,0x24,0x05,0x00,0x00,0x00,0x2a,
+            0xff,0xff
+        ];
+
+        let (leftover, erspan) = Erspan::from_bytes(frame).unwrap();
+        assert_eq!(ERSPAN_TYPE2, erspan.version);
+        assert_eq!(100, erspan.vlan);
+        assert_eq!(1, erspan.cos);
+        assert_eq!(0, erspan.encap_type);
+        assert!(erspan.truncated);
+        assert_eq!(5, erspan.session_id);
+        assert_eq!(42, erspan.index);
+        assert_eq!(2, leftover.len());
+        assert_eq!("ERSPAN(type: II, session: 5, vlan: 100, truncated: true)", format!("{}", erspan));
+        assert_eq!(frame[..8].to_vec(), erspan.as_bytes());
+    }
+
+    #[test]
+    fn parse_type3() {
+        let frame = &mut [
+            0x20,0x0a,0x00,0x07,0x12,0x34,0x56,0x78,
+            0x00,0x10,0x00,0x1b,0x0c,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x01
+        ];
+
+        let (leftover, erspan) = Erspan::from_bytes(frame).unwrap();
+        assert_eq!(ERSPAN_TYPE3, erspan.version);
+        assert_eq!(10, erspan.vlan);
+        assert_eq!(7, erspan.session_id);
+        assert_eq!(0x1234_5678, erspan.timestamp);
+        assert_eq!(16, erspan.sgt);
+        assert!(!erspan.p);
+        assert_eq!(0, erspan.frame_type);
+        assert_eq!(1, erspan.hw_id);
+        assert!(erspan.direction);
+        assert_eq!(1, erspan.granularity);
+        assert_eq!(Some(ErspanPlatform { platform_id: 3, info: 1 }), erspan.platform);
+        assert!(leftover.is_empty());
+        assert_eq!(frame.to_vec(), erspan.as_bytes());
+    }
+}
@@ -0,0 +1,284 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::combinator::all_consuming;
+use nom::multi::many0;
+use nom::number::complete::{be_u8, be_u16, be_u24};
+use nom::{call, do_parse, IResult};
+use std::fmt;
+use std::io::{Cursor, Write};
+
+// Option class and type used by OVN to carry the logical port keys
+pub const OVN_GENEVE_CLASS: u16 = 0x0102;
+pub const OVN_GENEVE_TYPE:  u8  = 0x80;
+
+// Largest option data and total option length, in 4 byte words
+pub const GENEVE_MAX_OPTION_LENGTH:  u8 = 0x1f;
+pub const GENEVE_MAX_OPTIONS_LENGTH: u8 = 0x3f;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum GeneveOptionData {
+    // OVN metadata: 15 bit logical ingress port, 16 bit logical egress port
+    OvnMetadata { ingress_port: u16, egress_port: u16 },
+    Raw(Vec<u8>)
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct GeneveOption {
+    pub class:    u16,
+    pub opt_type: u8,
+    // length of the option data in 4 byte words
+    pub length:   u8,
+    pub data:     GeneveOptionData
+}
+
+#[allow(unused_must_use)]
+impl GeneveOption {
+    // Instantiate an OVN metadata option
+    pub fn ovn_metadata(ingress_port: u16, egress_port: u16) -> GeneveOption {
+        GeneveOption {
+            class:    OVN_GENEVE_CLASS,
+            opt_type: OVN_GENEVE_TYPE,
+            length:   1,
+            data:     GeneveOptionData::OvnMetadata { ingress_port, egress_port }
+        }
+    }
+
+    // Instantiate an option carrying opaque data. None unless the data is a
+    // multiple of 4 bytes and fits the 5 bit length
+    pub fn raw(class: u16, opt_type: u8, data: Vec<u8>) -> Option<GeneveOption> {
+        if !data.len().is_multiple_of(4) || data.len() / 4 > GENEVE_MAX_OPTION_LENGTH as usize {
+            return None
+        }
+        Some(GeneveOption {
+            class,
+            opt_type,
+            length: (data.len() / 4) as u8,
+            data:   GeneveOptionData::Raw(data)
+        })
+    }
+
+    // Whether the receiver must drop the packet if it does not understand this option
+    pub fn is_critical(&self) -> bool { self.opt_type & 0x80 != 0 }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u16::<NetworkEndian>(self.class);
+        cursor.write_u8(self.opt_type);
+        cursor.write_u8(self.length & 0x1f);
+        match self.data {
+            GeneveOptionData::OvnMetadata { ingress_port, egress_port } => {
+                cursor.write_u16::<NetworkEndian>(ingress_port & 0x7fff);
+                cursor.write_u16::<NetworkEndian>(egress_port);
+            },
+            GeneveOptionData::Raw(data) => {
+                cursor.write_all(&data);
+            }
+        }
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], GeneveOption> {
+        do_parse!(
+            bytes,
+            class:    be_u16 >>
+            opt_type: be_u8  >>
+            length:   be_u8  >>
+            data:     call!(parse_option_data, class, opt_type, length & 0x1f) >>
+                (
+                    GeneveOption {
+                        class,
+                        opt_type,
+                        length: length & 0x1f,
+                        data
+                    }
+                )
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Geneve {
+    pub version:       u8,
+    // length of the options in 4 byte words
+    pub opt_len:       u8,
+    pub oam:           bool,
+    pub critical:      bool,
+    pub protocol_type: u16,
+    pub vni:           u32,
+    pub options:       Vec<GeneveOption>
+}
+
+impl Default for Geneve {
+    fn default() -> Geneve {
+        Geneve {
+            version:       0,
+            opt_len:       0,
+            oam:           false,
+            critical:      false,
+            protocol_type: 0x6558,
+            vni:           0,
+            options:       Vec::new()
+        }
+    }
+}
+
+impl fmt::Display for Geneve {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Geneve(\
+             vni: {}, \
+             type: 0x{:x}, \
+             options: {}\
+             )",
+            self.vni,
+            self.protocol_type,
+            self.options.len()
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Geneve {
+    // Instantiate a new Geneve header
+    pub fn new() -> Geneve { Geneve::default() }
+
+    // Append an option and update the option length and critical bit. None
+    // when the options would exceed the 6 bit length
+    pub fn push_option(&mut self, option: GeneveOption) -> Option<()> {
+        let opt_len = self.opt_len as usize + option.length as usize + 1;
+        if option.length > GENEVE_MAX_OPTION_LENGTH || opt_len > GENEVE_MAX_OPTIONS_LENGTH as usize {
+            return None
+        }
+        self.opt_len = opt_len as u8;
+        self.critical |= option.is_critical();
+        self.options.push(option);
+        Some(())
+    }
+
+    // Encode the Geneve header into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        let oam      = if self.oam { 0x80 } else { 0x00 };
+        let critical = if self.critical { 0x40 } else { 0x00 };
+        cursor.write_u8((self.version << 6) | (self.opt_len & 0x3f));
+        cursor.write_u8(oam | critical);
+        cursor.write_u16::<NetworkEndian>(self.protocol_type);
+        cursor.write_u24::<NetworkEndian>(self.vni);
+        cursor.write_u8(0x00);
+        for option in self.options {
+            cursor.write_all(&option.as_bytes());
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Geneve> {
+        do_parse!(
+            bytes,
+            ver_opt_len:   be_u8  >>
+            flags:         be_u8  >>
+            protocol_type: be_u16 >>
+            vni:           be_u24 >>
+            _res:          be_u8  >>
+            options:       call!(parse_options, (ver_opt_len & 0x3f) as usize * 4) >>
+                (
+                    Geneve {
+                        version:  ver_opt_len >> 6,
+                        opt_len:  ver_opt_len & 0x3f,
+                        oam:      flags & 0x80 != 0,
+                        critical: flags & 0x40 != 0,
+                        protocol_type,
+                        vni,
+                        options
+                    }
+                )
+        )
+    }
+}
+
+// private functions
+
+fn parse_options(bytes: &[u8], length: usize) -> IResult<&[u8], Vec<GeneveOption>> {
+    let (bytes1, options) = take(length)(bytes)?;
+    let (_, options) = all_consuming(many0(GeneveOption::from_bytes))(options)?;
+    Ok((bytes1, options))
+}
+
+fn parse_option_data(bytes: &[u8], class: u16, opt_type: u8, length: u8) -> IResult<&[u8], GeneveOptionData> {
+    let (bytes1, data) = take(length as usize * 4)(bytes)?;
+    match (class, opt_type, length) {
+        (OVN_GENEVE_CLASS, OVN_GENEVE_TYPE, 1) => {
+            let ingress_port = u16::from_be_bytes([data[0], data[1]]) & 0x7fff;
+            let egress_port  = u16::from_be_bytes([data[2], data[3]]);
+            Ok((bytes1, GeneveOptionData::OvnMetadata { ingress_port, egress_port }))
+        },
+        _ => Ok((bytes1, GeneveOptionData::Raw(data.to_vec())))
+    }
+}
+
+#[cfg(test)]
+mod tests_geneve {
+    use crate::geneve::*;
+
+    #[test]
+    fn parse() {
+        let frame = &mut [
+            0x02,0x40,0x65,0x58,0x00,0x00,0x0c,0x00,
+            0x01,0x02,0x80,0x01,0x00,0x02,0x00,0x03,
+            0x00,0x00,0x00,0x00,0x00,0x01,0x02,0x00,
+            0x00,0x00,0x00,0x02,0x08,0x00
+        ];
+
+        let (leftover, geneve) = Geneve::from_bytes(frame).unwrap();
+        assert_eq!(0, geneve.version);
+        assert_eq!(2, geneve.opt_len);
+        assert!(!geneve.oam);
+        assert!(geneve.critical);
+        assert_eq!(0x6558, geneve.protocol_type);
+        assert_eq!(12, geneve.vni);
+        assert_eq!(vec![GeneveOption::ovn_metadata(2, 3)], geneve.options);
+        assert_eq!(14, leftover.len());
+        assert_eq!("Geneve(vni: 12, type: 0x6558, options: 1)", format!("{}", geneve));
+
+        let geneve2 = Geneve::from_bytes(&geneve.as_bytes()).unwrap().1;
+        assert_eq!(GeneveOption::ovn_metadata(2, 3), geneve2.options[0]);
+    }
+
+    #[test]
+    fn build() {
+        let mut geneve = Geneve::new();
+        geneve.vni = 5000;
+        geneve.push_option(GeneveOption::ovn_metadata(1, 2)).unwrap();
+        geneve.push_option(GeneveOption::raw(0xffff, 0x01, vec![0xaa, 0xbb, 0x00, 0x00]).unwrap()).unwrap();
+
+        let bytes = geneve.as_bytes();
+        assert_eq!(24, bytes.len());
+
+        let geneve = Geneve::from_bytes(&bytes).unwrap().1;
+        assert_eq!(4, geneve.opt_len);
+        assert!(geneve.critical);
+        assert_eq!(GeneveOption {
+            class:    0xffff,
+            opt_type: 0x01,
+            length:   1,
+            data:     GeneveOptionData::Raw(vec![0xaa, 0xbb, 0x00, 0x00])
+        }, geneve.options[1]);
+    }
+
+    #[test]
+    fn build_option_length() {
+        assert!(GeneveOption::raw(0xffff, 0x01, vec![0xaa, 0xbb]).is_none());
+        assert!(GeneveOption::raw(0xffff, 0x01, vec![0x00; 128]).is_none());
+
+        let mut geneve = Geneve::new();
+        let option = GeneveOption::raw(0xffff, 0x01, vec![0x00; 124]).unwrap();
+        geneve.push_option(option.clone()).unwrap();
+        assert_eq!(32, geneve.opt_len);
+        assert!(geneve.push_option(option).is_none());
+        assert_eq!(32, geneve.opt_len);
+        assert_eq!(1, geneve.options.len());
+    }
+}
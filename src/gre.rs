@@ -1,53 +1,123 @@
 use byteorder::{NetworkEndian, WriteBytesExt};
 use nom::number::complete::{be_u8, be_u16, be_u32};
-use nom::{call, do_parse, IResult};
+use nom::{call, do_parse, take, IResult};
 use std::io::{Cursor, Write};
 
-#[derive(Debug, PartialEq)]
+// Source Route Entry (RFC 1701)
+#[derive(Debug, PartialEq, Clone)]
+pub struct GreSre {
+    pub address_family: u16,
+    pub sre_offset:     u8,
+    pub sre_length:     u8,
+    pub routing:        Vec<u8>
+}
+
+#[allow(unused_must_use)]
+impl GreSre {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u16::<NetworkEndian>(self.address_family);
+        cursor.write_u8(self.sre_offset);
+        cursor.write_u8(self.sre_length);
+        cursor.write_all(&self.routing);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], GreSre> {
+        do_parse!(
+            bytes,
+            address_family: be_u16 >>
+            sre_offset:     be_u8  >>
+            sre_length:     be_u8  >>
+            routing:        take!(sre_length as usize) >>
+                (
+                    GreSre {
+                        address_family,
+                        sre_offset,
+                        sre_length,
+                        routing: routing.to_vec()
+                    }
+                )
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Default)]
 pub struct Gre {
     pub has_csum:     bool,
+    pub has_routing:  bool,
     pub has_key:      bool,
     pub has_sequence: bool,
+    pub strict_route: bool,
+    pub recursion:    u8,
+    // enhanced GRE (version 1) only
+    pub has_ack:      bool,
     pub version:      u8,
     pub protocol:     u16,
     // optional
-    pub checksum: u16,
-    pub key:      u32,
-    pub sequence: u32
+    pub checksum:        u16,
+    pub offset:          u16,
+    pub key:             u32,
+    pub sequence:        u32,
+    pub acknowledgement: u32,
+    pub routing:         Vec<GreSre>
 }
 
-impl Default for Gre {
-    fn default() -> Gre {
+#[allow(unused_must_use)]
+impl Gre {
+    // Instantiate a new GRE header
+    pub fn new() -> Gre { Gre::default() }
+
+    // Instantiate an enhanced GRE header used by PPTP
+    pub fn pptp(call_id: u16, payload_length: u16) -> Gre {
         Gre {
-            has_csum:     false,
-            has_key:      false,
-            has_sequence: false,
-            version:      u8::default(),
-            protocol:     u16::default(),
-            checksum:     u16::default(),
-            key:          u32::default(),
-            sequence:     u32::default()
+            has_key:  true,
+            version:  1,
+            protocol: 0x880b,
+            key:      (payload_length as u32) << 16 | call_id as u32,
+            ..Gre::default()
         }
     }
-}
 
-#[allow(unused_must_use)]
-impl Gre {
+    // Payload length of an enhanced GRE header
+    pub fn payload_length(&self) -> u16 { (self.key >> 16) as u16 }
+
+    // Call ID of an enhanced GRE header
+    pub fn call_id(&self) -> u16 { self.key as u16 }
+
+    // Virtual Subnet ID of an NVGRE header
+    pub fn vsid(&self) -> u32 { self.key >> 8 }
+
+    // FlowID of an NVGRE header
+    pub fn flow_id(&self) -> u8 { self.key as u8 }
+
     pub fn as_bytes(self) -> Vec<u8> {
         let buf: Vec<u8> = Vec::new();
         let mut cursor = Cursor::new(buf);
-        let has_csum = if self.has_csum { 0x80 } else { 0x00 };
-        let has_key  = if self.has_key { 0x20 } else { 0x00 };
-        let has_seq  = if self.has_sequence { 0x10 } else { 0x00 };
-        let version  = self.version;
-        cursor.write(&vec![has_csum | has_key | has_seq, version]);
+        let has_csum    = if self.has_csum { 0x80 } else { 0x00 };
+        let has_routing = if self.has_routing { 0x40 } else { 0x00 };
+        let has_key     = if self.has_key { 0x20 } else { 0x00 };
+        let has_seq     = if self.has_sequence { 0x10 } else { 0x00 };
+        let strict      = if self.strict_route { 0x08 } else { 0x00 };
+        let has_ack     = if self.has_ack { 0x80 } else { 0x00 };
+        cursor.write_u8(has_csum | has_routing | has_key | has_seq | strict | (self.recursion & 0x07));
+        cursor.write_u8(has_ack | (self.version & 0x07));
         cursor.write_u16::<NetworkEndian>(self.protocol);
-        if self.has_csum {
+        if self.has_csum || self.has_routing {
             cursor.write_u16::<NetworkEndian>(self.checksum);
-            cursor.write_u16::<NetworkEndian>(0);
+            cursor.write_u16::<NetworkEndian>(self.offset);
         }
         if self.has_key { cursor.write_u32::<NetworkEndian>(self.key); }
         if self.has_sequence { cursor.write_u32::<NetworkEndian>(self.sequence); }
+        if self.has_ack { cursor.write_u32::<NetworkEndian>(self.acknowledgement); }
+        if self.has_routing {
+            for sre in self.routing {
+                cursor.write_all(&sre.as_bytes());
+            }
+            // NULL SRE
+            cursor.write_u32::<NetworkEndian>(0);
+        }
         cursor.into_inner()
     }
 
@@ -57,17 +127,25 @@ impl Gre {
             flags:     be_u8  >>
             version:   be_u8  >>
             protocol:  be_u16 >>
-            options:   call!(Self::parse_options, flags) >>
+            options:   call!(Self::parse_options, flags, version) >>
+            routing:   call!(Self::parse_routing, flags) >>
                 (
                     Gre {
-                        has_csum:      (flags >> 7) != 0,
-                        has_key:       (flags >> 5) != 0,
-                        has_sequence:  (flags >> 4) != 0,
-                        version:       version & 0x07,
-                        protocol:      protocol,
-                        checksum:      options.0,
-                        key:           options.1,
-                        sequence:      options.2,
+                        has_csum:        flags & 0x80 != 0,
+                        has_routing:     flags & 0x40 != 0,
+                        has_key:         flags & 0x20 != 0,
+                        has_sequence:    flags & 0x10 != 0,
+                        strict_route:    flags & 0x08 != 0,
+                        recursion:       flags & 0x07,
+                        has_ack:         version & 0x80 != 0,
+                        version:         version & 0x07,
+                        protocol,
+                        checksum:        options.0,
+                        offset:          options.1,
+                        key:             options.2,
+                        sequence:        options.3,
+                        acknowledgement: options.4,
+                        routing
                     }
                 )
         )
@@ -75,18 +153,36 @@ impl Gre {
 
     // private functions
 
-    fn parse_options(bytes0: &[u8], flags: u8) -> IResult<&[u8], (u16, u32, u32)> {
-        let (bytes1, csum) = if (flags & 0x80) != 0 { be_u16(bytes0)? } else { (bytes0, 0) };
-        let (bytes2, _res) = if (flags & 0x80) != 0 { be_u16(bytes1)? } else { (bytes1, 0) };
+    fn parse_options(bytes0: &[u8], flags: u8, version: u8) -> IResult<&[u8], (u16, u16, u32, u32, u32)> {
+        let has_csum_ofs = (flags & 0xc0) != 0;
+        let (bytes1, csum) = if has_csum_ofs { be_u16(bytes0)? } else { (bytes0, 0) };
+        let (bytes2, ofs)  = if has_csum_ofs { be_u16(bytes1)? } else { (bytes1, 0) };
         let (bytes3, key)  = if (flags & 0x20) != 0 { be_u32(bytes2)? } else { (bytes2, 0) };
         let (bytes4, seq)  = if (flags & 0x10) != 0 { be_u32(bytes3)? } else { (bytes3, 0) };
-        Ok((bytes4, (csum, key, seq)))
+        let (bytes5, ack)  = if (version & 0x80) != 0 { be_u32(bytes4)? } else { (bytes4, 0) };
+        Ok((bytes5, (csum, ofs, key, seq, ack)))
+    }
+
+    fn parse_routing(bytes: &[u8], flags: u8) -> IResult<&[u8], Vec<GreSre>> {
+        let mut acc = Vec::new();
+        let mut b = bytes;
+
+        if (flags & 0x40) == 0 { return Ok((b, acc)) }
+
+        loop {
+            let (leftover, sre) = GreSre::from_bytes(b)?;
+            b = leftover;
+            if sre.address_family == 0 && sre.sre_length == 0 {
+                return Ok((b, acc))
+            }
+            acc.push(sre);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests_gre {
-    use crate::gre::Gre;
+    use crate::gre::{Gre, GreSre};
 
     #[test]
     fn parse() {
@@ -109,7 +205,70 @@ mod tests_gre {
             protocol:   0x0800,
             checksum:     0x80,
             key:         12345,
-            sequence:        1
+            sequence:        1,
+            ..Gre::default()
         }, gre1);
     }
+
+    #[test]
+    fn parse_checksum_only() {
+        let frame = &mut [
+            0x80,0x00,0x08,0x00,0xab,0xcd,0x00,0x00,
+            0x45,0x00
+        ];
+
+        let (leftover, gre) = Gre::from_bytes(frame).unwrap();
+        assert!(gre.has_csum);
+        assert!(!gre.has_key);
+        assert!(!gre.has_sequence);
+        assert_eq!(0xabcd, gre.checksum);
+        assert_eq!(vec![0x45, 0x00], leftover.to_vec());
+    }
+
+    #[test]
+    fn parse_routing() {
+        let frame = &mut [
+            0x40,0x00,0x08,0x00,0x00,0x00,0x00,0x04,
+            0x08,0x00,0x00,0x08,0x0a,0x00,0x00,0x01,
+            0x0a,0x00,0x00,0x02,0x00,0x00,0x00,0x00,
+            0x45,0x00
+        ];
+
+        let (leftover, gre) = Gre::from_bytes(frame).unwrap();
+        assert!(gre.has_routing);
+        assert_eq!(4, gre.offset);
+        assert_eq!(vec![GreSre {
+            address_family: 0x0800,
+            sre_offset:     0,
+            sre_length:     8,
+            routing:        vec![0x0a, 0x00, 0x00, 0x01, 0x0a, 0x00, 0x00, 0x02]
+        }], gre.routing);
+        assert_eq!(2, leftover.len());
+        assert_eq!(frame[..24].to_vec(), gre.as_bytes());
+    }
+
+    #[test]
+    fn parse_enhanced() {
+        let frame = &mut [
+            0x30,0x81,0x88,0x0b,0x00,0x04,0x01,0x2c,
+            0x00,0x00,0x00,0x0a,0x00,0x00,0x00,0x09,
+            0xff,0x03,0x00,0x21
+        ];
+
+        let (leftover, gre) = Gre::from_bytes(frame).unwrap();
+        assert_eq!(1, gre.version);
+        assert!(gre.has_ack);
+        assert_eq!(4, gre.payload_length());
+        assert_eq!(300, gre.call_id());
+        assert_eq!(10, gre.sequence);
+        assert_eq!(9, gre.acknowledgement);
+        assert_eq!(4, leftover.len());
+
+        let mut pptp = Gre::pptp(300, 4);
+        pptp.has_sequence = true;
+        pptp.sequence = 10;
+        pptp.has_ack = true;
+        pptp.acknowledgement = 9;
+        assert_eq!(frame[..16].to_vec(), pptp.as_bytes());
+    }
 }
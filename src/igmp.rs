@@ -0,0 +1,347 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::number::complete::{be_u8, be_u16};
+use nom::IResult;
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::io::{Cursor, Write};
+
+use crate::checksum::checksum;
+
+pub const IGMP_MEMBERSHIP_QUERY:     u8 = 0x11;
+pub const IGMP_V1_MEMBERSHIP_REPORT: u8 = 0x12;
+pub const IGMP_V2_MEMBERSHIP_REPORT: u8 = 0x16;
+pub const IGMP_V2_LEAVE_GROUP:       u8 = 0x17;
+pub const IGMP_V3_MEMBERSHIP_REPORT: u8 = 0x22;
+
+// IGMPv3 group record types
+pub const MODE_IS_INCLUDE:        u8 = 1;
+pub const MODE_IS_EXCLUDE:        u8 = 2;
+pub const CHANGE_TO_INCLUDE_MODE: u8 = 3;
+pub const CHANGE_TO_EXCLUDE_MODE: u8 = 4;
+pub const ALLOW_NEW_SOURCES:      u8 = 5;
+pub const BLOCK_OLD_SOURCES:      u8 = 6;
+
+// IGMPv3 specific fields of a membership query
+#[derive(Debug, PartialEq, Clone)]
+pub struct IgmpV3Query {
+    // suppress router-side processing
+    pub s_flag:  bool,
+    // querier's robustness variable
+    pub qrv:     u8,
+    // querier's query interval code
+    pub qqic:    u8,
+    pub sources: Vec<Ipv4Addr>
+}
+
+#[allow(unused_must_use)]
+impl IgmpV3Query {
+    // Querier's query interval in seconds
+    pub fn qqi(&self) -> u32 { decode_exp(self.qqic) }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        let s_flag = if self.s_flag { 0x08 } else { 0x00 };
+        cursor.write_u8(s_flag | (self.qrv & 0x07));
+        cursor.write_u8(self.qqic);
+        cursor.write_u16::<NetworkEndian>(self.sources.len() as u16);
+        for source in self.sources {
+            cursor.write_all(&source.octets());
+        }
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], IgmpV3Query> {
+        let (bytes1, flags)   = be_u8(bytes)?;
+        let (bytes2, qqic)    = be_u8(bytes1)?;
+        let (bytes3, count)   = be_u16(bytes2)?;
+        let (bytes4, sources) = parse_ip4addrs(bytes3, count as usize)?;
+        Ok((bytes4, IgmpV3Query { s_flag: flags & 0x08 != 0, qrv: flags & 0x07, qqic, sources }))
+    }
+}
+
+// IGMPv3 group record
+#[derive(Debug, PartialEq, Clone)]
+pub struct GroupRecord {
+    pub record_type: u8,
+    pub group:       Ipv4Addr,
+    pub sources:     Vec<Ipv4Addr>,
+    pub aux_data:    Vec<u8>
+}
+
+#[allow(unused_must_use)]
+impl GroupRecord {
+    // Instantiate a new group record
+    pub fn new(record_type: u8, group: Ipv4Addr, sources: Vec<Ipv4Addr>) -> GroupRecord {
+        GroupRecord { record_type, group, sources, aux_data: Vec::new() }
+    }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.record_type);
+        cursor.write_u8((self.aux_data.len() / 4) as u8);
+        cursor.write_u16::<NetworkEndian>(self.sources.len() as u16);
+        cursor.write_all(&self.group.octets());
+        for source in self.sources {
+            cursor.write_all(&source.octets());
+        }
+        cursor.write_all(&self.aux_data);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], GroupRecord> {
+        let (bytes1, record_type) = be_u8(bytes)?;
+        let (bytes2, aux_len)     = be_u8(bytes1)?;
+        let (bytes3, count)       = be_u16(bytes2)?;
+        let (bytes4, group)       = parse_ip4addrs(bytes3, 1)?;
+        let (bytes5, sources)     = parse_ip4addrs(bytes4, count as usize)?;
+        let (bytes6, aux_data)    = take(aux_len as usize * 4)(bytes5)?;
+        Ok((bytes6, GroupRecord { record_type, group: group[0], sources, aux_data: aux_data.to_vec() }))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Igmp {
+    pub igmp_type:     u8,
+    pub max_resp_code: u8,
+    pub checksum:      u16,
+    // unused by IGMPv3 reports
+    pub group:         Ipv4Addr,
+    // IGMPv3 membership query only
+    pub query:         Option<IgmpV3Query>,
+    // IGMPv3 membership report only
+    pub records:       Vec<GroupRecord>
+}
+
+impl Default for Igmp {
+    fn default() -> Igmp {
+        Igmp {
+            igmp_type:     IGMP_MEMBERSHIP_QUERY,
+            max_resp_code: 0,
+            checksum:      0,
+            group:         Ipv4Addr::UNSPECIFIED,
+            query:         None,
+            records:       Vec::new()
+        }
+    }
+}
+
+impl fmt::Display for Igmp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "IGMP(\
+             version: {}, \
+             type: 0x{:02x}, \
+             group: \"{}\", \
+             records: {}\
+             )",
+            self.version(),
+            self.igmp_type,
+            self.group,
+            self.records.len()
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Igmp {
+    // Instantiate a new IGMP message
+    pub fn new() -> Igmp { Igmp::default() }
+
+    // Build an IGMPv2 general (group 0.0.0.0) or group-specific query
+    pub fn query(group: Ipv4Addr, max_resp_code: u8) -> Igmp {
+        Self::with_checksum(Igmp { max_resp_code, group, ..Igmp::default() })
+    }
+
+    // Build an IGMPv3 query, group-and-source-specific when sources are given
+    pub fn query_v3(group: Ipv4Addr, max_resp_code: u8, query: IgmpV3Query) -> Igmp {
+        Self::with_checksum(Igmp { max_resp_code, group, query: Some(query), ..Igmp::default() })
+    }
+
+    // Build an IGMPv2 membership report
+    pub fn report(group: Ipv4Addr) -> Igmp {
+        Self::with_checksum(Igmp { igmp_type: IGMP_V2_MEMBERSHIP_REPORT, group, ..Igmp::default() })
+    }
+
+    // Build an IGMPv2 leave group
+    pub fn leave(group: Ipv4Addr) -> Igmp {
+        Self::with_checksum(Igmp { igmp_type: IGMP_V2_LEAVE_GROUP, group, ..Igmp::default() })
+    }
+
+    // Build an IGMPv3 membership report
+    pub fn report_v3(records: Vec<GroupRecord>) -> Igmp {
+        Self::with_checksum(Igmp { igmp_type: IGMP_V3_MEMBERSHIP_REPORT, records, ..Igmp::default() })
+    }
+
+    // Protocol version of the message (RFC 3376 7.1)
+    pub fn version(&self) -> u8 {
+        match self.igmp_type {
+            IGMP_MEMBERSHIP_QUERY if self.query.is_some() => 3,
+            IGMP_MEMBERSHIP_QUERY if self.max_resp_code == 0 => 1,
+            IGMP_V1_MEMBERSHIP_REPORT => 1,
+            IGMP_V3_MEMBERSHIP_REPORT => 3,
+            _ => 2
+        }
+    }
+
+    // Maximum response time in units of 1/10 second
+    pub fn max_resp_time(&self) -> u32 {
+        if self.query.is_some() {
+            decode_exp(self.max_resp_code)
+        } else {
+            self.max_resp_code as u32
+        }
+    }
+
+    pub fn calculate_igmp_checksum(&self) -> u16 {
+        let igmp = Igmp {
+            checksum: 0,
+            query:    self.query.clone(),
+            records:  self.records.clone(),
+            ..*self
+        };
+        checksum(&igmp.as_bytes())
+    }
+
+    pub fn verify_checksum(&self) -> bool {
+        self.checksum == self.calculate_igmp_checksum()
+    }
+
+    // Encode the IGMP message into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.igmp_type);
+        cursor.write_u8(self.max_resp_code);
+        cursor.write_u16::<NetworkEndian>(self.checksum);
+        if self.igmp_type == IGMP_V3_MEMBERSHIP_REPORT {
+            cursor.write_u16::<NetworkEndian>(0);
+            cursor.write_u16::<NetworkEndian>(self.records.len() as u16);
+            for record in self.records {
+                cursor.write_all(&record.as_bytes());
+            }
+            return cursor.into_inner()
+        }
+        cursor.write_all(&self.group.octets());
+        if let Some(query) = self.query {
+            cursor.write_all(&query.as_bytes());
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice holding a whole IGMP message
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Igmp> {
+        let (bytes1, igmp_type)     = be_u8(bytes)?;
+        let (bytes2, max_resp_code) = be_u8(bytes1)?;
+        let (bytes3, checksum)      = be_u16(bytes2)?;
+        let igmp = Igmp { igmp_type, max_resp_code, checksum, ..Igmp::default() };
+
+        if igmp_type == IGMP_V3_MEMBERSHIP_REPORT {
+            let (bytes4, _reserved) = be_u16(bytes3)?;
+            let (bytes5, count)     = be_u16(bytes4)?;
+            let mut records = Vec::new();
+            let mut b = bytes5;
+            for _ in 0..count {
+                let (leftover, record) = GroupRecord::from_bytes(b)?;
+                records.push(record);
+                b = leftover;
+            }
+            return Ok((b, Igmp { records, ..igmp }))
+        }
+
+        let (bytes4, group) = parse_ip4addrs(bytes3, 1)?;
+        let group = group[0];
+
+        // IGMPv3 queries are at least 12 octets long
+        if igmp_type == IGMP_MEMBERSHIP_QUERY && bytes4.len() >= 4 {
+            let (bytes5, query) = IgmpV3Query::from_bytes(bytes4)?;
+            return Ok((bytes5, Igmp { group, query: Some(query), ..igmp }))
+        }
+
+        Ok((bytes4, Igmp { group, ..igmp }))
+    }
+
+    // private functions
+
+    fn with_checksum(igmp: Igmp) -> Igmp {
+        let checksum = igmp.calculate_igmp_checksum();
+        Igmp { checksum, ..igmp }
+    }
+}
+
+// private functions
+
+fn parse_ip4addrs(bytes: &[u8], count: usize) -> IResult<&[u8], Vec<Ipv4Addr>> {
+    let (bytes1, addrs) = take(count * 4)(bytes)?;
+    let addrs = addrs
+        .chunks_exact(4)
+        .map(|addr| Ipv4Addr::new(addr[0], addr[1], addr[2], addr[3]))
+        .collect();
+    Ok((bytes1, addrs))
+}
+
+// Decode the floating point Max Resp Code and QQIC (RFC 3376 4.1.1)
+fn decode_exp(code: u8) -> u32 {
+    if code < 128 {
+        code as u32
+    } else {
+        let exp  = (code >> 4) & 0x07;
+        let mant = code & 0x0f;
+        ((mant as u32) | 0x10) << (exp + 3)
+    }
+}
+
+#[cfg(test)]
+mod tests_igmp {
+    use crate::igmp::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn parse_query() {
+        let frame = &mut [
+            0x11,0x64,0xec,0x1e,0x00,0x00,0x00,0x00,
+            0x02,0x7d,0x00,0x00
+        ];
+
+        let (leftover, igmp) = Igmp::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(3, igmp.version());
+        assert_eq!(100, igmp.max_resp_time());
+        assert_eq!(Some(IgmpV3Query { s_flag: false, qrv: 2, qqic: 125, sources: vec![] }), igmp.query);
+        assert!(igmp.verify_checksum());
+        assert_eq!("IGMP(version: 3, type: 0x11, group: \"0.0.0.0\", records: 0)", format!("{}", igmp));
+        assert_eq!(frame.to_vec(), igmp.as_bytes());
+
+        let query = Igmp::query(Ipv4Addr::UNSPECIFIED, 100);
+        assert_eq!(2, query.version());
+        assert_eq!(vec![0x11, 0x64, 0xee, 0x9b, 0x00, 0x00, 0x00, 0x00], query.as_bytes());
+    }
+
+    #[test]
+    fn parse_report() {
+        let frame = &mut [
+            0x22,0x00,0xf5,0xf4,0x00,0x00,0x00,0x02,
+            0x04,0x00,0x00,0x00,0xef,0x01,0x01,0x01,
+            0x01,0x00,0x00,0x01,0xe8,0x01,0x01,0x02,
+            0x0a,0x00,0x00,0x01
+        ];
+
+        let (leftover, igmp) = Igmp::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(3, igmp.version());
+        assert_eq!(vec![
+            GroupRecord::new(CHANGE_TO_EXCLUDE_MODE, Ipv4Addr::new(239, 1, 1, 1), vec![]),
+            GroupRecord::new(MODE_IS_INCLUDE, Ipv4Addr::new(232, 1, 1, 2), vec![Ipv4Addr::new(10, 0, 0, 1)])
+        ], igmp.records);
+        assert!(igmp.verify_checksum());
+        assert_eq!(Igmp::report_v3(igmp.records.clone()), igmp);
+
+        let leave = Igmp::leave(Ipv4Addr::new(239, 1, 1, 1));
+        assert!(leave.verify_checksum());
+        assert_eq!(2, leave.version());
+        assert_eq!(IGMP_V2_LEAVE_GROUP, leave.as_bytes()[0]);
+    }
+}
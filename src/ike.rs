@@ -0,0 +1,705 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::number::complete::{be_u8, be_u16, be_u32, be_u64};
+use nom::{call, do_parse, take, IResult};
+use std::fmt;
+use std::io::{Cursor, Write};
+use std::net::IpAddr;
+#[cfg(feature = "decrypt")]
+use std::convert::TryFrom;
+
+use crate::util::{ip_octets, parse_ip};
+#[cfg(feature = "decrypt")]
+use crate::ipsec::EspCipher;
+
+pub const IKE_PORT: u16 = 500;
+
+// Exchange types
+pub const IKE_SA_INIT:         u8 = 34;
+pub const IKE_AUTH:            u8 = 35;
+pub const IKE_CREATE_CHILD_SA: u8 = 36;
+pub const IKE_INFORMATIONAL:   u8 = 37;
+
+// Header flags
+pub const IKE_FLAG_INITIATOR: u8 = 0x08;
+pub const IKE_FLAG_VERSION:   u8 = 0x10;
+pub const IKE_FLAG_RESPONSE:  u8 = 0x20;
+
+// Payload types
+pub const IKE_PAYLOAD_NONE:      u8 = 0;
+pub const IKE_PAYLOAD_SA:        u8 = 33;
+pub const IKE_PAYLOAD_KE:        u8 = 34;
+pub const IKE_PAYLOAD_IDI:       u8 = 35;
+pub const IKE_PAYLOAD_IDR:       u8 = 36;
+pub const IKE_PAYLOAD_CERT:      u8 = 37;
+pub const IKE_PAYLOAD_CERTREQ:   u8 = 38;
+pub const IKE_PAYLOAD_AUTH:      u8 = 39;
+pub const IKE_PAYLOAD_NONCE:     u8 = 40;
+pub const IKE_PAYLOAD_NOTIFY:    u8 = 41;
+pub const IKE_PAYLOAD_DELETE:    u8 = 42;
+pub const IKE_PAYLOAD_VENDOR_ID: u8 = 43;
+pub const IKE_PAYLOAD_TSI:       u8 = 44;
+pub const IKE_PAYLOAD_TSR:       u8 = 45;
+pub const IKE_PAYLOAD_SK:        u8 = 46;
+
+// Security protocol identifiers
+pub const IKE_PROTOCOL_IKE: u8 = 1;
+pub const IKE_PROTOCOL_AH:  u8 = 2;
+pub const IKE_PROTOCOL_ESP: u8 = 3;
+
+// Transform types
+pub const IKE_TRANSFORM_ENCR:  u8 = 1;
+pub const IKE_TRANSFORM_PRF:   u8 = 2;
+pub const IKE_TRANSFORM_INTEG: u8 = 3;
+pub const IKE_TRANSFORM_DH:    u8 = 4;
+pub const IKE_TRANSFORM_ESN:   u8 = 5;
+
+// Key Length attribute in TV format
+pub const IKE_ATTRIBUTE_KEY_LENGTH: u16 = 0x800e;
+
+// Traffic selector types
+pub const IKE_TS_IPV4_ADDR_RANGE: u8 = 7;
+pub const IKE_TS_IPV6_ADDR_RANGE: u8 = 8;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct IkeTransform {
+    pub transform_type: u8,
+    pub transform_id:   u16,
+    // Attribute type with the format bit, TV values being two octets
+    pub attributes:     Vec<(u16, Vec<u8>)>
+}
+
+#[allow(unused_must_use)]
+impl IkeTransform {
+    pub fn new(transform_type: u8, transform_id: u16) -> IkeTransform {
+        IkeTransform { transform_type, transform_id, attributes: Vec::new() }
+    }
+
+    pub fn key_length(self, key_length: u16) -> IkeTransform {
+        let mut attributes = self.attributes;
+        attributes.push((IKE_ATTRIBUTE_KEY_LENGTH, key_length.to_be_bytes().to_vec()));
+        IkeTransform { attributes, ..self }
+    }
+
+    pub fn as_bytes(self, last: bool) -> Vec<u8> {
+        let attributes: Vec<u8> = self.attributes.into_iter().flat_map(|(attribute_type, value)| {
+            match attribute_type & 0x8000 {
+                0 => [attribute_type.to_be_bytes().to_vec(), (value.len() as u16).to_be_bytes().to_vec(), value].concat(),
+                _ => [attribute_type.to_be_bytes().to_vec(), value].concat()
+            }
+        }).collect();
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(if last { 0 } else { 3 });
+        cursor.write_u8(0);
+        cursor.write_u16::<NetworkEndian>(attributes.len() as u16 + 8);
+        cursor.write_u8(self.transform_type);
+        cursor.write_u8(0);
+        cursor.write_u16::<NetworkEndian>(self.transform_id);
+        cursor.write_all(&attributes);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], IkeTransform> {
+        let (bytes1, (transform_type, transform_id, mut b)) = do_parse!(
+            bytes,
+            _last:          be_u8 >>
+            _reserved:      be_u8 >>
+            length:         call!(verify(be_u16, |length| *length >= 8)) >>
+            transform_type: be_u8 >>
+            _reserved:      be_u8 >>
+            transform_id:   be_u16 >>
+            attributes:     take!(length - 8) >>
+                ((transform_type, transform_id, attributes))
+        )?;
+        let mut attributes = Vec::new();
+        while !b.is_empty() {
+            let (leftover, attribute_type) = be_u16(b)?;
+            let (leftover, value) = match attribute_type & 0x8000 {
+                0 => {
+                    let (leftover, length) = be_u16(leftover)?;
+                    take(length)(leftover)?
+                },
+                _ => take(2usize)(leftover)?
+            };
+            attributes.push((attribute_type, value.to_vec()));
+            b = leftover;
+        }
+        Ok((bytes1, IkeTransform { transform_type, transform_id, attributes }))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct IkeProposal {
+    pub number:      u8,
+    pub protocol_id: u8,
+    pub spi:         Vec<u8>,
+    pub transforms:  Vec<IkeTransform>
+}
+
+#[allow(unused_must_use)]
+impl IkeProposal {
+    pub fn new(number: u8, protocol_id: u8, spi: &[u8], transforms: Vec<IkeTransform>) -> IkeProposal {
+        IkeProposal { number, protocol_id, spi: spi.to_vec(), transforms }
+    }
+
+    pub fn as_bytes(self, last: bool) -> Vec<u8> {
+        let count = self.transforms.len();
+        let transforms: Vec<u8> = self.transforms.into_iter()
+            .enumerate()
+            .flat_map(|(index, transform)| transform.as_bytes(index + 1 == count))
+            .collect();
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(if last { 0 } else { 2 });
+        cursor.write_u8(0);
+        cursor.write_u16::<NetworkEndian>((8 + self.spi.len() + transforms.len()) as u16);
+        cursor.write_u8(self.number);
+        cursor.write_u8(self.protocol_id);
+        cursor.write_u8(self.spi.len() as u8);
+        cursor.write_u8(count as u8);
+        cursor.write_all(&self.spi);
+        cursor.write_all(&transforms);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], IkeProposal> {
+        let (bytes1, (number, protocol_id, spi, count, b)) = do_parse!(
+            bytes,
+            _last:       be_u8 >>
+            _reserved:   be_u8 >>
+            length:      call!(verify(be_u16, |length| *length >= 8)) >>
+            number:      be_u8 >>
+            protocol_id: be_u8 >>
+            spi_size:    be_u8 >>
+            count:       be_u8 >>
+            spi:         take!(spi_size) >>
+            transforms:  call!(take((length as usize).saturating_sub(8 + spi_size as usize))) >>
+                ((number, protocol_id, spi, count, transforms))
+        )?;
+        let (_, transforms) = nom::multi::count(IkeTransform::from_bytes, count as usize)(b)?;
+        Ok((bytes1, IkeProposal::new(number, protocol_id, spi, transforms)))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TrafficSelector {
+    pub ts_type:       u8,
+    pub ip_protocol:   u8,
+    pub start_port:    u16,
+    pub end_port:      u16,
+    pub start_address: IpAddr,
+    pub end_address:   IpAddr
+}
+
+#[allow(unused_must_use)]
+impl TrafficSelector {
+    // Instantiate a selector of all ports and protocols between addresses
+    pub fn range(start_address: IpAddr, end_address: IpAddr) -> TrafficSelector {
+        TrafficSelector {
+            ts_type: match start_address {
+                IpAddr::V4(_) => IKE_TS_IPV4_ADDR_RANGE,
+                IpAddr::V6(_) => IKE_TS_IPV6_ADDR_RANGE
+            },
+            ip_protocol: 0,
+            start_port:  0,
+            end_port:    0xffff,
+            start_address,
+            end_address
+        }
+    }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let addresses = [ip_octets(self.start_address), ip_octets(self.end_address)].concat();
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.ts_type);
+        cursor.write_u8(self.ip_protocol);
+        cursor.write_u16::<NetworkEndian>(addresses.len() as u16 + 8);
+        cursor.write_u16::<NetworkEndian>(self.start_port);
+        cursor.write_u16::<NetworkEndian>(self.end_port);
+        cursor.write_all(&addresses);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], TrafficSelector> {
+        do_parse!(
+            bytes,
+            ts_type:       call!(verify(be_u8, |ts_type| *ts_type == 7 || *ts_type == 8)) >>
+            ip_protocol:   be_u8 >>
+            _length:       be_u16 >>
+            start_port:    be_u16 >>
+            end_port:      be_u16 >>
+            start_address: call!(take(if ts_type == 7 { 4usize } else { 16 })) >>
+            end_address:   call!(take(start_address.len())) >>
+                (
+                    TrafficSelector {
+                        ts_type,
+                        ip_protocol,
+                        start_port,
+                        end_port,
+                        start_address: parse_ip(start_address).unwrap(),
+                        end_address:   parse_ip(end_address).unwrap()
+                    }
+                )
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum IkePayload {
+    Sa(Vec<IkeProposal>),
+    KeyExchange { dh_group: u16, data: Vec<u8> },
+    IdInitiator { id_type: u8, data: Vec<u8> },
+    IdResponder { id_type: u8, data: Vec<u8> },
+    Certificate { encoding: u8, data: Vec<u8> },
+    CertificateRequest { encoding: u8, data: Vec<u8> },
+    Auth { method: u8, data: Vec<u8> },
+    Nonce(Vec<u8>),
+    Notify { protocol_id: u8, notify_type: u16, spi: Vec<u8>, data: Vec<u8> },
+    Delete { protocol_id: u8, spi_size: u8, spis: Vec<Vec<u8>> },
+    VendorId(Vec<u8>),
+    TsInitiator(Vec<TrafficSelector>),
+    TsResponder(Vec<TrafficSelector>),
+    // The next payload field names the first inner payload, which are
+    // decoded when keys are supplied
+    Encrypted { next_payload: u8, data: Vec<u8>, payloads: Vec<IkePayload> },
+    Unknown { payload_type: u8, critical: bool, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl IkePayload {
+    pub fn payload_type(&self) -> u8 {
+        match self {
+            IkePayload::Sa(_) => IKE_PAYLOAD_SA,
+            IkePayload::KeyExchange { .. } => IKE_PAYLOAD_KE,
+            IkePayload::IdInitiator { .. } => IKE_PAYLOAD_IDI,
+            IkePayload::IdResponder { .. } => IKE_PAYLOAD_IDR,
+            IkePayload::Certificate { .. } => IKE_PAYLOAD_CERT,
+            IkePayload::CertificateRequest { .. } => IKE_PAYLOAD_CERTREQ,
+            IkePayload::Auth { .. } => IKE_PAYLOAD_AUTH,
+            IkePayload::Nonce(_) => IKE_PAYLOAD_NONCE,
+            IkePayload::Notify { .. } => IKE_PAYLOAD_NOTIFY,
+            IkePayload::Delete { .. } => IKE_PAYLOAD_DELETE,
+            IkePayload::VendorId(_) => IKE_PAYLOAD_VENDOR_ID,
+            IkePayload::TsInitiator(_) => IKE_PAYLOAD_TSI,
+            IkePayload::TsResponder(_) => IKE_PAYLOAD_TSR,
+            IkePayload::Encrypted { .. } => IKE_PAYLOAD_SK,
+            IkePayload::Unknown { payload_type, .. } => *payload_type
+        }
+    }
+
+    // Encode the payload with its generic header
+    pub fn as_bytes(self, next_payload: u8) -> Vec<u8> {
+        let (next_payload, critical) = match self {
+            IkePayload::Encrypted { next_payload, .. } => (next_payload, false),
+            IkePayload::Unknown { critical, .. } => (next_payload, critical),
+            _ => (next_payload, false)
+        };
+        let body = self.body();
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(next_payload);
+        cursor.write_u8(if critical { 0x80 } else { 0 });
+        cursor.write_u16::<NetworkEndian>(body.len() as u16 + 4);
+        cursor.write_all(&body);
+        cursor.into_inner()
+    }
+
+    // Parse a chain of payloads starting with the given type, the Encrypted
+    // payload ends the chain
+    pub fn parse_chain(bytes: &[u8], first: u8) -> IResult<&[u8], Vec<IkePayload>> {
+        let mut payloads = Vec::new();
+        let mut payload_type = first;
+        let mut b = bytes;
+        while payload_type != IKE_PAYLOAD_NONE {
+            let (leftover, (next_payload, flags, body)) = do_parse!(
+                b,
+                next_payload: be_u8 >>
+                flags:        be_u8 >>
+                length:       call!(verify(be_u16, |length| *length >= 4)) >>
+                body:         take!(length - 4) >>
+                    ((next_payload, flags, body))
+            )?;
+            b = leftover;
+            if payload_type == IKE_PAYLOAD_SK {
+                payloads.push(IkePayload::Encrypted { next_payload, data: body.to_vec(), payloads: Vec::new() });
+                break
+            }
+            payloads.push(match Self::parse_body(payload_type, body) {
+                Ok((_, payload)) => payload,
+                Err(_e) => IkePayload::Unknown { payload_type, critical: flags & 0x80 != 0, data: body.to_vec() }
+            });
+            payload_type = next_payload;
+        }
+        Ok((b, payloads))
+    }
+
+    // private functions
+
+    fn body(self) -> Vec<u8> {
+        match self {
+            IkePayload::Sa(proposals) => {
+                let count = proposals.len();
+                proposals.into_iter()
+                    .enumerate()
+                    .flat_map(|(index, proposal)| proposal.as_bytes(index + 1 == count))
+                    .collect()
+            },
+            IkePayload::KeyExchange { dh_group, data } =>
+                [dh_group.to_be_bytes().to_vec(), vec![0, 0], data].concat(),
+            IkePayload::IdInitiator { id_type, data } |
+            IkePayload::IdResponder { id_type, data } =>
+                [vec![id_type, 0, 0, 0], data].concat(),
+            IkePayload::Certificate { encoding, data } |
+            IkePayload::CertificateRequest { encoding, data } =>
+                [vec![encoding], data].concat(),
+            IkePayload::Auth { method, data } =>
+                [vec![method, 0, 0, 0], data].concat(),
+            IkePayload::Nonce(data) |
+            IkePayload::VendorId(data) =>
+                data,
+            IkePayload::Notify { protocol_id, notify_type, spi, data } =>
+                [vec![protocol_id, spi.len() as u8], notify_type.to_be_bytes().to_vec(), spi, data].concat(),
+            IkePayload::Delete { protocol_id, spi_size, spis } =>
+                [vec![protocol_id, spi_size], (spis.len() as u16).to_be_bytes().to_vec(), spis.concat()].concat(),
+            IkePayload::TsInitiator(selectors) |
+            IkePayload::TsResponder(selectors) =>
+                [
+                    vec![selectors.len() as u8, 0, 0, 0],
+                    selectors.into_iter().flat_map(|selector| selector.as_bytes()).collect()
+                ].concat(),
+            IkePayload::Encrypted { data, .. } |
+            IkePayload::Unknown { data, .. } =>
+                data
+        }
+    }
+
+    fn parse_body(payload_type: u8, body: &[u8]) -> IResult<&[u8], IkePayload> {
+        match payload_type {
+            IKE_PAYLOAD_SA => {
+                let mut proposals = Vec::new();
+                let mut b = body;
+                while !b.is_empty() {
+                    let (leftover, proposal) = IkeProposal::from_bytes(b)?;
+                    proposals.push(proposal);
+                    b = leftover;
+                }
+                Ok((b, IkePayload::Sa(proposals)))
+            },
+            IKE_PAYLOAD_KE => do_parse!(
+                body,
+                dh_group:  be_u16 >>
+                _reserved: be_u16 >>
+                data:      call!(nom::combinator::rest) >>
+                    (IkePayload::KeyExchange { dh_group, data: data.to_vec() })
+            ),
+            IKE_PAYLOAD_IDI | IKE_PAYLOAD_IDR | IKE_PAYLOAD_AUTH => {
+                let (b, value) = be_u8(body)?;
+                let (data, _reserved) = take(3usize)(b)?;
+                let data = data.to_vec();
+                Ok((&[], match payload_type {
+                    IKE_PAYLOAD_IDI => IkePayload::IdInitiator { id_type: value, data },
+                    IKE_PAYLOAD_IDR => IkePayload::IdResponder { id_type: value, data },
+                    _ => IkePayload::Auth { method: value, data }
+                }))
+            },
+            IKE_PAYLOAD_CERT | IKE_PAYLOAD_CERTREQ => {
+                let (data, encoding) = be_u8(body)?;
+                let data = data.to_vec();
+                Ok((&[], match payload_type {
+                    IKE_PAYLOAD_CERT => IkePayload::Certificate { encoding, data },
+                    _ => IkePayload::CertificateRequest { encoding, data }
+                }))
+            },
+            IKE_PAYLOAD_NONCE =>
+                Ok((&[], IkePayload::Nonce(body.to_vec()))),
+            IKE_PAYLOAD_VENDOR_ID =>
+                Ok((&[], IkePayload::VendorId(body.to_vec()))),
+            IKE_PAYLOAD_NOTIFY => do_parse!(
+                body,
+                protocol_id: be_u8 >>
+                spi_size:    be_u8 >>
+                notify_type: be_u16 >>
+                spi:         take!(spi_size) >>
+                data:        call!(nom::combinator::rest) >>
+                    (IkePayload::Notify { protocol_id, notify_type, spi: spi.to_vec(), data: data.to_vec() })
+            ),
+            IKE_PAYLOAD_DELETE => {
+                let (b, (protocol_id, spi_size, count)) = do_parse!(
+                    body,
+                    protocol_id: be_u8 >>
+                    spi_size:    be_u8 >>
+                    count:       be_u16 >>
+                        ((protocol_id, spi_size, count))
+                )?;
+                let (b, spis) = nom::multi::count(take(spi_size), count as usize)(b)?;
+                let spis = spis.into_iter().map(|spi: &[u8]| spi.to_vec()).collect();
+                Ok((b, IkePayload::Delete { protocol_id, spi_size, spis }))
+            },
+            IKE_PAYLOAD_TSI | IKE_PAYLOAD_TSR => {
+                let (b, count) = be_u8(body)?;
+                let (b, _)     = take(3usize)(b)?;
+                let (b, selectors) = nom::multi::count(TrafficSelector::from_bytes, count as usize)(b)?;
+                Ok((b, match payload_type {
+                    IKE_PAYLOAD_TSI => IkePayload::TsInitiator(selectors),
+                    _ => IkePayload::TsResponder(selectors)
+                }))
+            },
+            _ =>
+                Ok((&[], IkePayload::Unknown { payload_type, critical: false, data: body.to_vec() }))
+        }
+    }
+}
+
+// Internet Key Exchange version 2 message (RFC 7296)
+#[derive(Debug, PartialEq)]
+pub struct Ike {
+    pub initiator_spi: u64,
+    pub responder_spi: u64,
+    pub version:       u8,
+    pub exchange_type: u8,
+    pub flags:         u8,
+    pub message_id:    u32,
+    pub payloads:      Vec<IkePayload>
+}
+
+impl Default for Ike {
+    fn default() -> Ike {
+        Ike {
+            initiator_spi: 0,
+            responder_spi: 0,
+            version:       0x20,
+            exchange_type: IKE_SA_INIT,
+            flags:         IKE_FLAG_INITIATOR,
+            message_id:    0,
+            payloads:      Vec::new()
+        }
+    }
+}
+
+impl fmt::Display for Ike {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "IKEv2(\
+             ispi: 0x{:016x}, \
+             rspi: 0x{:016x}, \
+             exchange: {}, \
+             flags: 0x{:02x}, \
+             message_id: {}, \
+             payloads: {}\
+             )",
+            self.initiator_spi,
+            self.responder_spi,
+            self.exchange_type,
+            self.flags,
+            self.message_id,
+            self.payloads.len()
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Ike {
+    // Instantiate a new IKE_SA_INIT request
+    pub fn new() -> Ike { Ike::default() }
+
+    // Instantiate a message of the exchange sent by the initiator
+    pub fn request(initiator_spi: u64, responder_spi: u64, exchange_type: u8, message_id: u32, payloads: Vec<IkePayload>) -> Ike {
+        Ike {
+            initiator_spi,
+            responder_spi,
+            exchange_type,
+            message_id,
+            payloads,
+            ..Ike::default()
+        }
+    }
+
+    pub fn is_response(&self) -> bool { self.flags & IKE_FLAG_RESPONSE != 0 }
+
+    pub fn payload(&self, payload_type: u8) -> Option<&IkePayload> {
+        self.payloads.iter().find(|payload| payload.payload_type() == payload_type)
+    }
+
+    // Encode the message into a byte slice, the length and next payload
+    // fields are taken from the payloads
+    pub fn as_bytes(self) -> Vec<u8> {
+        let types: Vec<u8> = self.payloads.iter().map(|payload| payload.payload_type()).collect();
+        let payloads: Vec<u8> = self.payloads.into_iter()
+            .enumerate()
+            .flat_map(|(index, payload)| payload.as_bytes(*types.get(index + 1).unwrap_or(&IKE_PAYLOAD_NONE)))
+            .collect();
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u64::<NetworkEndian>(self.initiator_spi);
+        cursor.write_u64::<NetworkEndian>(self.responder_spi);
+        cursor.write_u8(*types.first().unwrap_or(&IKE_PAYLOAD_NONE));
+        cursor.write_u8(self.version);
+        cursor.write_u8(self.exchange_type);
+        cursor.write_u8(self.flags);
+        cursor.write_u32::<NetworkEndian>(self.message_id);
+        cursor.write_u32::<NetworkEndian>(payloads.len() as u32 + 28);
+        cursor.write_all(&payloads);
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Ike> {
+        let (bytes1, (ike, next_payload, body)) = do_parse!(
+            bytes,
+            initiator_spi: be_u64 >>
+            responder_spi: be_u64 >>
+            next_payload:  be_u8 >>
+            version:       call!(verify(be_u8, |version| *version >> 4 == 2)) >>
+            exchange_type: be_u8 >>
+            flags:         be_u8 >>
+            message_id:    be_u32 >>
+            length:        call!(verify(be_u32, |length| *length >= 28)) >>
+            body:          take!(length - 28) >>
+                (
+                    (
+                        Ike {
+                            initiator_spi,
+                            responder_spi,
+                            version,
+                            exchange_type,
+                            flags,
+                            message_id,
+                            payloads: Vec::new()
+                        },
+                        next_payload,
+                        body
+                    )
+                )
+        )?;
+        let (_, payloads) = IkePayload::parse_chain(body, next_payload)?;
+        Ok((bytes1, Ike { payloads, ..ike }))
+    }
+}
+
+// Keys of an IKE SA (SK_ei/SK_ai and SK_er/SK_ar) decrypting the Encrypted
+// payloads of its messages
+#[cfg(feature = "decrypt")]
+#[derive(Debug, PartialEq, Clone)]
+pub struct IkeSa {
+    pub initiator_spi: u64,
+    pub responder_spi: u64,
+    pub initiator:     EspCipher,
+    pub responder:     EspCipher
+}
+
+#[cfg(feature = "decrypt")]
+impl IkeSa {
+    pub fn new(initiator_spi: u64, responder_spi: u64, initiator: EspCipher, responder: EspCipher) -> IkeSa {
+        IkeSa { initiator_spi, responder_spi, initiator, responder }
+    }
+
+    // Decrypt the Encrypted payload of the message parsed out of the byte
+    // slice, returning its inner payloads
+    pub fn decrypt(&self, ike: &Ike, bytes: &[u8]) -> Option<Vec<IkePayload>> {
+        let (next_payload, data) = match ike.payloads.last() {
+            Some(IkePayload::Encrypted { next_payload, data, .. }) => (*next_payload, data),
+            _ => return None
+        };
+        let cipher = match ike.flags & IKE_FLAG_INITIATOR {
+            0 => &self.responder,
+            _ => &self.initiator
+        };
+        let length = u32::from_be_bytes(<[u8; 4]>::try_from(bytes.get(24..28)?).unwrap()) as usize;
+        let start = length.checked_sub(data.len())?;
+        let mut plaintext = cipher.decrypt(bytes.get(..start)?, bytes.get(start..length)?)?;
+        let pad_length = plaintext.pop()?;
+        plaintext.truncate(plaintext.len().checked_sub(pad_length as usize)?);
+        IkePayload::parse_chain(&plaintext, next_payload).ok().map(|(_, payloads)| payloads)
+    }
+}
+
+#[cfg(test)]
+mod tests_ike {
+    use crate::ike::*;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn parse_sa_init() {
+        let ike = Ike::request(0x0102030405060708, 0, IKE_SA_INIT, 0, vec![
+            IkePayload::Sa(vec![IkeProposal::new(1, IKE_PROTOCOL_IKE, &[], vec![
+                IkeTransform::new(IKE_TRANSFORM_ENCR, 20).key_length(256),
+                IkeTransform::new(IKE_TRANSFORM_PRF, 5),
+                IkeTransform::new(IKE_TRANSFORM_DH, 19)
+            ])]),
+            IkePayload::KeyExchange { dh_group: 19, data: vec![0xaa; 64] },
+            IkePayload::Nonce(vec![0xbb; 32]),
+            IkePayload::Notify { protocol_id: 0, notify_type: 16388, spi: Vec::new(), data: vec![0xcc; 20] },
+            IkePayload::VendorId(b"pkt-rs".to_vec())
+        ]);
+        let bytes = ike.as_bytes();
+        assert_eq!(&[0x21, 0x20, 0x22, 0x08], &bytes[16..20]);
+
+        let (leftover, ike) = Ike::from_bytes(&bytes).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(5, ike.payloads.len());
+        match ike.payload(IKE_PAYLOAD_SA) {
+            Some(IkePayload::Sa(proposals)) => {
+                assert_eq!(3, proposals[0].transforms.len());
+                assert_eq!(vec![(IKE_ATTRIBUTE_KEY_LENGTH, vec![0x01, 0x00])], proposals[0].transforms[0].attributes);
+            },
+            other => panic!("unexpected payload: {:?}", other)
+        }
+        assert_eq!(
+            "IKEv2(ispi: 0x0102030405060708, rspi: 0x0000000000000000, exchange: 34, flags: 0x08, message_id: 0, payloads: 5)",
+            format!("{}", ike)
+        );
+        assert_eq!(bytes, ike.as_bytes());
+    }
+
+    #[test]
+    fn parse_selectors() {
+        let ts = IkePayload::TsInitiator(vec![
+            TrafficSelector::range(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0)), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 255)))
+        ]);
+        let ike = Ike::request(1, 2, IKE_INFORMATIONAL, 3, vec![
+            ts.clone(),
+            IkePayload::Delete { protocol_id: IKE_PROTOCOL_ESP, spi_size: 4, spis: vec![vec![0, 0, 0x10, 0x01]] }
+        ]);
+        let bytes = ike.as_bytes();
+
+        let (_, ike) = Ike::from_bytes(&bytes).unwrap();
+        assert_eq!(Some(&ts), ike.payload(IKE_PAYLOAD_TSI));
+        assert_eq!(bytes, ike.as_bytes());
+    }
+
+    #[cfg(feature = "decrypt")]
+    #[test]
+    fn decrypt_auth() {
+        let frame = &mut [
+            0x01,0x02,0x03,0x04,0x05,0x06,0x07,0x08,
+            0x11,0x12,0x13,0x14,0x15,0x16,0x17,0x18,
+            0x2e,0x20,0x23,0x08,0x00,0x00,0x00,0x01,
+            0x00,0x00,0x00,0x4a,0x23,0x00,0x00,0x2e,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x01,
+            0x76,0x46,0xfa,0x9d,0xba,0x14,0x2e,0x16,
+            0x35,0x87,0xad,0x4c,0x1a,0x84,0xf2,0xe3,
+            0x93,0x90,0x46,0x29,0x99,0x16,0xfd,0x62,
+            0x90,0x04,0x42,0xe3,0x61,0xdb,0x5f,0xfa,
+            0x4d,0x0c
+        ];
+        let sa = IkeSa::new(
+            0x0102030405060708,
+            0x1112131415161718,
+            EspCipher::AesGcm { key: (0..20).collect() },
+            EspCipher::AesGcm { key: (20..40).collect() }
+        );
+
+        let (leftover, ike) = Ike::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(IKE_AUTH, ike.exchange_type);
+        assert_eq!(Some(vec![
+            IkePayload::IdInitiator { id_type: 1, data: vec![10, 0, 0, 1] },
+            IkePayload::Nonce(vec![0x42])
+        ]), sa.decrypt(&ike, frame));
+        assert_eq!(frame.to_vec(), ike.as_bytes());
+    }
+}
@@ -0,0 +1,357 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::number::complete::{be_u8, be_u16, be_u32};
+use nom::{call, do_parse, IResult};
+use std::fmt;
+use std::io::{Cursor, Write};
+
+#[cfg(feature = "decrypt")]
+use aes_gcm::aead::{AeadInPlace, KeyInit};
+#[cfg(feature = "decrypt")]
+use aes_gcm::{Aes128Gcm, Aes256Gcm, Nonce, Tag};
+#[cfg(feature = "decrypt")]
+use aes::cipher::{block_padding::NoPadding, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+#[cfg(feature = "decrypt")]
+use hmac::{Hmac, Mac};
+#[cfg(feature = "decrypt")]
+use sha2::Sha256;
+
+pub const IPSEC_NAT_T_PORT: u16 = 4500;
+
+// Encapsulating Security Payload header (RFC 4303)
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Esp {
+    pub spi:         u32,
+    pub sequence:    u32,
+    // Trailer and ICV, known after decryption only
+    pub decrypted:   bool,
+    pub pad_length:  u8,
+    pub next_header: u8,
+    pub icv:         Vec<u8>
+}
+
+impl fmt::Display for Esp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ESP(\
+             spi: 0x{:08x}, \
+             sequence: {}, \
+             next_header: {}\
+             )",
+            self.spi,
+            self.sequence,
+            self.next_header
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Esp {
+    // Instantiate a new ESP header
+    pub fn new() -> Esp { Esp::default() }
+
+    pub fn header(spi: u32, sequence: u32) -> Esp {
+        Esp { spi, sequence, ..Esp::default() }
+    }
+
+    // Encode the ESP header into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u32::<NetworkEndian>(self.spi);
+        cursor.write_u32::<NetworkEndian>(self.sequence);
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice, the encrypted payload is left over
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Esp> {
+        do_parse!(
+            bytes,
+            spi:      call!(verify(be_u32, |spi| *spi != 0)) >>
+            sequence: be_u32 >>
+                (Esp::header(spi, sequence))
+        )
+    }
+}
+
+// Authentication Header (RFC 4302)
+#[derive(Debug, PartialEq, Default)]
+pub struct Ah {
+    pub next_header: u8,
+    pub spi:         u32,
+    pub sequence:    u32,
+    pub icv:         Vec<u8>
+}
+
+impl fmt::Display for Ah {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "AH(\
+             next_header: {}, \
+             spi: 0x{:08x}, \
+             sequence: {}, \
+             icv: {} bytes\
+             )",
+            self.next_header,
+            self.spi,
+            self.sequence,
+            self.icv.len()
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Ah {
+    // Instantiate a new AH header
+    pub fn new() -> Ah { Ah::default() }
+
+    pub fn header(next_header: u8, spi: u32, sequence: u32, icv: &[u8]) -> Ah {
+        Ah { next_header, spi, sequence, icv: icv.to_vec() }
+    }
+
+    // Encode the AH header into a byte slice, the payload length is taken
+    // from the ICV
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.next_header);
+        cursor.write_u8(((self.icv.len() + 12) / 4 - 2) as u8);
+        cursor.write_u16::<NetworkEndian>(0);
+        cursor.write_u32::<NetworkEndian>(self.spi);
+        cursor.write_u32::<NetworkEndian>(self.sequence);
+        cursor.write_all(&self.icv);
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Ah> {
+        do_parse!(
+            bytes,
+            next_header: be_u8 >>
+            length:      call!(verify(be_u8, |length| *length >= 1)) >>
+            _reserved:   be_u16 >>
+            spi:         be_u32 >>
+            sequence:    be_u32 >>
+            icv:         call!(take((length as usize + 2) * 4 - 12)) >>
+                (Ah::header(next_header, spi, sequence, icv))
+        )
+    }
+}
+
+// Cipher suites of the security associations
+#[cfg(feature = "decrypt")]
+#[derive(Debug, PartialEq, Clone)]
+pub enum EspCipher {
+    // AES-GCM with a 16 octet ICV (RFC 4106), the key ends with the
+    // 4 octet salt
+    AesGcm { key: Vec<u8> },
+    // AES-CBC (RFC 3602) with HMAC-SHA-256-128 (RFC 4868)
+    AesCbcHmacSha256 { key: Vec<u8>, auth_key: Vec<u8> }
+}
+
+#[cfg(feature = "decrypt")]
+impl EspCipher {
+    // Verify and decrypt the IV, ciphertext and ICV following the
+    // authenticated header, the plaintext keeps its padding. ESP and the
+    // IKEv2 Encrypted payload share the layout
+    pub(crate) fn decrypt(&self, header: &[u8], bytes: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            EspCipher::AesGcm { key } => {
+                let (iv, ciphertext, icv) = split_payload(bytes, 8, 16, 1)?;
+                let nonce = gcm_nonce(key, iv)?;
+                let mut buf = ciphertext.to_vec();
+                let tag = Tag::from_slice(icv);
+                match key.len() - 4 {
+                    16 => Aes128Gcm::new_from_slice(&key[..16]).ok()?
+                        .decrypt_in_place_detached(&nonce, header, &mut buf, tag).ok()?,
+                    32 => Aes256Gcm::new_from_slice(&key[..32]).ok()?
+                        .decrypt_in_place_detached(&nonce, header, &mut buf, tag).ok()?,
+                    _ => return None
+                };
+                Some(buf)
+            },
+            EspCipher::AesCbcHmacSha256 { key, auth_key } => {
+                let (iv, ciphertext, icv) = split_payload(bytes, 16, 16, 16)?;
+                let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(auth_key).ok()?;
+                mac.update(header);
+                mac.update(&bytes[..bytes.len() - 16]);
+                mac.verify_truncated_left(icv).ok()?;
+                let mut buf = ciphertext.to_vec();
+                match key.len() {
+                    16 => cbc::Decryptor::<aes::Aes128>::new_from_slices(key, iv).ok()?
+                        .decrypt_padded_mut::<NoPadding>(&mut buf).ok()?,
+                    32 => cbc::Decryptor::<aes::Aes256>::new_from_slices(key, iv).ok()?
+                        .decrypt_padded_mut::<NoPadding>(&mut buf).ok()?,
+                    _ => return None
+                };
+                Some(buf)
+            }
+        }
+    }
+}
+
+// Security association used to decrypt the ESP payloads of its SPI
+#[cfg(feature = "decrypt")]
+#[derive(Debug, PartialEq, Clone)]
+pub struct EspSa {
+    pub spi:    u32,
+    pub cipher: EspCipher
+}
+
+#[cfg(feature = "decrypt")]
+impl EspSa {
+    pub fn new(spi: u32, cipher: EspCipher) -> EspSa {
+        EspSa { spi, cipher }
+    }
+
+    // Decrypt the payload following the ESP header, the header is returned
+    // completed with the trailer along with the inner packet. None when the
+    // ICV does not verify
+    pub fn decrypt(&self, esp: &Esp, bytes: &[u8]) -> Option<(Esp, Vec<u8>)> {
+        let header = Esp::header(esp.spi, esp.sequence).as_bytes();
+        let mut plaintext = self.cipher.decrypt(&header, bytes)?;
+        let icv = &bytes[bytes.len() - 16..];
+
+        let next_header = plaintext.pop()?;
+        let pad_length = plaintext.pop()?;
+        let length = plaintext.len().checked_sub(pad_length as usize)?;
+        plaintext.truncate(length);
+        Some((
+            Esp {
+                decrypted: true,
+                pad_length,
+                next_header,
+                icv: icv.to_vec(),
+                ..esp.clone()
+            },
+            plaintext
+        ))
+    }
+
+    // Encrypt the inner packet with the given IV, returning the ESP packet
+    // from the header to the ICV
+    pub fn encrypt(&self, esp: &Esp, next_header: u8, payload: &[u8], iv: &[u8]) -> Option<Vec<u8>> {
+        let aad = Esp::header(esp.spi, esp.sequence).as_bytes();
+        let block = match self.cipher {
+            EspCipher::AesGcm { .. } => 4,
+            EspCipher::AesCbcHmacSha256 { .. } => 16
+        };
+        let pad_length = (block - (payload.len() + 2) % block) % block;
+        let mut buf = payload.to_vec();
+        buf.extend((1..=pad_length as u8).collect::<Vec<u8>>());
+        buf.push(pad_length as u8);
+        buf.push(next_header);
+
+        match &self.cipher {
+            EspCipher::AesGcm { key } => {
+                let nonce = gcm_nonce(key, iv)?;
+                let tag = match key.len() - 4 {
+                    16 => Aes128Gcm::new_from_slice(&key[..16]).ok()?
+                        .encrypt_in_place_detached(&nonce, &aad, &mut buf).ok()?,
+                    32 => Aes256Gcm::new_from_slice(&key[..32]).ok()?
+                        .encrypt_in_place_detached(&nonce, &aad, &mut buf).ok()?,
+                    _ => return None
+                };
+                Some([aad, iv.to_vec(), buf, tag.to_vec()].concat())
+            },
+            EspCipher::AesCbcHmacSha256 { key, auth_key } => {
+                let length = buf.len();
+                match key.len() {
+                    16 => cbc::Encryptor::<aes::Aes128>::new_from_slices(key, iv).ok()?
+                        .encrypt_padded_mut::<NoPadding>(&mut buf, length).ok()?,
+                    32 => cbc::Encryptor::<aes::Aes256>::new_from_slices(key, iv).ok()?
+                        .encrypt_padded_mut::<NoPadding>(&mut buf, length).ok()?,
+                    _ => return None
+                };
+                let packet = [aad, iv.to_vec(), buf].concat();
+                let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(auth_key).ok()?;
+                mac.update(&packet);
+                let icv = mac.finalize().into_bytes();
+                Some([packet, icv[..16].to_vec()].concat())
+            }
+        }
+    }
+}
+
+// private functions
+
+// IV, ciphertext and ICV of the payload, the ciphertext being a multiple
+// of the block size
+#[cfg(feature = "decrypt")]
+fn split_payload(bytes: &[u8], iv_length: usize, icv_length: usize, block: usize) -> Option<(&[u8], &[u8], &[u8])> {
+    let length = bytes.len().checked_sub(iv_length + icv_length)?;
+    match length {
+        0 => None,
+        n if n % block != 0 => None,
+        n => Some((&bytes[..iv_length], &bytes[iv_length..iv_length + n], &bytes[iv_length + n..]))
+    }
+}
+
+#[cfg(feature = "decrypt")]
+fn gcm_nonce(key: &[u8], iv: &[u8]) -> Option<Nonce<aes_gcm::aead::consts::U12>> {
+    match (key.len(), iv.len()) {
+        (20 | 36, 8) => Some(*Nonce::from_slice(&[&key[key.len() - 4..], iv].concat())),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests_ipsec {
+    use crate::ipsec::*;
+
+    #[test]
+    fn parse_esp_ah() {
+        let frame = &mut [
+            0x00,0x00,0x10,0x01,0x00,0x00,0x00,0x07,
+            0xde,0xad,0xbe,0xef
+        ];
+
+        let (leftover, esp) = Esp::from_bytes(frame).unwrap();
+        assert_eq!(&[0xde, 0xad, 0xbe, 0xef], leftover);
+        assert_eq!(Esp::header(0x1001, 7), esp);
+        assert_eq!("ESP(spi: 0x00001001, sequence: 7, next_header: 0)", format!("{}", esp));
+        assert_eq!(frame[..8].to_vec(), esp.as_bytes());
+
+        let frame = &mut [
+            0x06,0x04,0x00,0x00,0x00,0x00,0x10,0x02,
+            0x00,0x00,0x00,0x01,0x00,0x01,0x02,0x03,
+            0x04,0x05,0x06,0x07,0x08,0x09,0x0a,0x0b
+        ];
+
+        let (leftover, ah) = Ah::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(Ah::header(6, 0x1002, 1, &(0..12).collect::<Vec<u8>>()), ah);
+        assert_eq!(frame.to_vec(), ah.as_bytes());
+    }
+
+    #[cfg(feature = "decrypt")]
+    #[test]
+    fn decrypt_esp() {
+        let payload = &[0x45, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x00, 0x40, 0x3b];
+        let esp = Esp::header(0x1001, 1);
+        let sas = vec![
+            EspSa::new(0x1001, EspCipher::AesGcm { key: (0..20).collect() }),
+            EspSa::new(0x1001, EspCipher::AesCbcHmacSha256 { key: (0..32).collect(), auth_key: vec![0x0b; 32] })
+        ];
+
+        for sa in sas {
+            let iv: Vec<u8> = match sa.cipher {
+                EspCipher::AesGcm { .. } => (0..8).collect(),
+                EspCipher::AesCbcHmacSha256 { .. } => (0..16).collect()
+            };
+            let bytes = sa.encrypt(&esp, 4, payload, &iv).unwrap();
+            let (leftover, esp) = Esp::from_bytes(&bytes).unwrap();
+            let (esp, plaintext) = sa.decrypt(&esp, leftover).unwrap();
+            assert!(esp.decrypted);
+            assert_eq!(4, esp.next_header);
+            assert_eq!(payload.to_vec(), plaintext);
+
+            let mut tampered = leftover.to_vec();
+            tampered[iv.len()] ^= 1;
+            assert_eq!(None, sa.decrypt(&esp, &tampered));
+        }
+    }
+}
@@ -1,11 +1,12 @@
 use byteorder::{NetworkEndian, WriteBytesExt};
-use nom::bytes::complete::take;
 use nom::number::complete::{be_u8, be_u16};
 use nom::{do_parse, take, IResult};
 use std::fmt;
 use std::net::Ipv4Addr;
 use std::io::{Cursor, Write};
 
+use crate::util::parse_ip4addr;
+
 #[derive(Debug, PartialEq)]
 pub struct IPv4 {
     pub version_ihl:     u8,
@@ -143,14 +144,6 @@ impl IPv4 {
     }
 }
 
-// private functions
-
-fn parse_ip4addr(bytes: &[u8]) -> IResult<&[u8], Ipv4Addr> {
-    let (bytes1, value) = take(4usize)(bytes)?;
-    let ip4addr = Ipv4Addr::new(value[0], value[1], value[2], value[3]);
-    Ok((bytes1, ip4addr))
-}
-
 #[cfg(test)]
 mod tests_ipv4{
     use crate::ipv4::IPv4;
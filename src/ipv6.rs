@@ -0,0 +1,140 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::combinator::verify;
+use nom::number::complete::{be_u8, be_u16, be_u32};
+use nom::{call, do_parse, IResult};
+use std::fmt;
+use std::net::Ipv6Addr;
+use std::io::{Cursor, Write};
+
+use crate::util::parse_ip6addr;
+
+#[derive(Debug, PartialEq)]
+pub struct IPv6 {
+    pub version:        u8,
+    pub traffic_class:  u8,
+    pub flow_label:     u32,
+    pub payload_length: u16,
+    pub next_header:    u8,
+    pub hop_limit:      u8,
+    pub source:         Ipv6Addr,
+    pub destination:    Ipv6Addr
+}
+
+impl Default for IPv6 {
+    fn default() -> IPv6 {
+        IPv6 {
+            version:        6,
+            traffic_class:  0,
+            flow_label:     0,
+            payload_length: 0,
+            next_header:    59,
+            hop_limit:      64,
+            source:         Ipv6Addr::UNSPECIFIED,
+            destination:    Ipv6Addr::UNSPECIFIED
+        }
+    }
+}
+
+impl fmt::Display for IPv6 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "IPv6(\
+             source: \"{}\", \
+             destination: \"{}\", \
+             payload_len: {}, \
+             hop_limit: {}, \
+             next_header: {}\
+             )",
+            self.source,
+            self.destination,
+            self.payload_length,
+            self.hop_limit,
+            self.next_header
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl IPv6 {
+    // Instantiate IPv6 header
+    pub fn new() -> IPv6 { IPv6::default() }
+
+    // Encode the IPv6 Header into a vec of u8
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u32::<NetworkEndian>(
+            (self.version as u32) << 28 |
+            (self.traffic_class as u32) << 20 |
+            self.flow_label & 0x000f_ffff
+        );
+        cursor.write_u16::<NetworkEndian>(self.payload_length);
+        cursor.write_u8(self.next_header);
+        cursor.write_u8(self.hop_limit);
+        cursor.write_all(&self.source.octets());
+        cursor.write_all(&self.destination.octets());
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice into an IPv6 header, the version must be 6
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], IPv6> {
+        do_parse!(
+            bytes,
+            ver_tc_flow:    call!(verify(be_u32, |ver_tc_flow| ver_tc_flow >> 28 == 6)) >>
+            payload_length: be_u16 >>
+            next_header:    be_u8  >>
+            hop_limit:      be_u8  >>
+            source:         parse_ip6addr >>
+            destination:    parse_ip6addr >>
+                (
+                    IPv6 {
+                        version:       (ver_tc_flow >> 28) as u8,
+                        traffic_class: (ver_tc_flow >> 20) as u8,
+                        flow_label:    ver_tc_flow & 0x000f_ffff,
+                        payload_length,
+                        next_header,
+                        hop_limit,
+                        source,
+                        destination
+                    }
+                )
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests_ipv6 {
+    use crate::ipv6::IPv6;
+    use std::net::Ipv6Addr;
+
+    #[test]
+    fn parse() {
+        let frame = &mut [
+            0x60,0x00,0x00,0x00,0x00,0x08,0x11,0x40,
+            0x20,0x01,0x0d,0xb8,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x01,
+            0x20,0x01,0x0d,0xb8,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x02,
+            0x82,0x75,0x7a,0x69,0x00,0x08,0x00,0x00
+        ];
+
+        let ipv6 = IPv6::from_bytes(frame).unwrap().1;
+        let ipv6 = IPv6::from_bytes(&ipv6.as_bytes()).unwrap().1;
+        assert_eq!(6, ipv6.version);
+        assert_eq!(8, ipv6.payload_length);
+        assert_eq!(17, ipv6.next_header);
+        assert_eq!(64, ipv6.hop_limit);
+        assert_eq!("2001:db8::1".parse::<Ipv6Addr>().unwrap(), ipv6.source);
+        assert_eq!("2001:db8::2".parse::<Ipv6Addr>().unwrap(), ipv6.destination);
+    }
+
+    #[test]
+    fn parse_version() {
+        let mut bytes = IPv6::new().as_bytes();
+        assert!(IPv6::from_bytes(&bytes).is_ok());
+
+        bytes[0] = 0x45;
+        assert!(IPv6::from_bytes(&bytes).is_err());
+    }
+}
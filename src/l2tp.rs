@@ -0,0 +1,528 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::number::complete::{be_u16, be_u32};
+use nom::IResult;
+use std::fmt;
+use std::io::{Cursor, Write};
+
+pub const L2TP_PORT: u16 = 1701;
+
+// Header flags
+pub const L2TP_FLAG_TYPE:     u16 = 0x8000;
+pub const L2TP_FLAG_LENGTH:   u16 = 0x4000;
+pub const L2TP_FLAG_SEQUENCE: u16 = 0x0800;
+pub const L2TP_FLAG_OFFSET:   u16 = 0x0200;
+pub const L2TP_FLAG_PRIORITY: u16 = 0x0100;
+
+// Control message types
+pub const L2TP_SCCRQ:   u16 = 1;
+pub const L2TP_SCCRP:   u16 = 2;
+pub const L2TP_SCCCN:   u16 = 3;
+pub const L2TP_STOPCCN: u16 = 4;
+pub const L2TP_HELLO:   u16 = 6;
+pub const L2TP_OCRQ:    u16 = 7;
+pub const L2TP_OCRP:    u16 = 8;
+pub const L2TP_OCCN:    u16 = 9;
+pub const L2TP_ICRQ:    u16 = 10;
+pub const L2TP_ICRP:    u16 = 11;
+pub const L2TP_ICCN:    u16 = 12;
+pub const L2TP_CDN:     u16 = 14;
+pub const L2TP_WEN:     u16 = 15;
+pub const L2TP_SLI:     u16 = 16;
+
+// Attribute types
+pub const L2TP_AVP_MESSAGE_TYPE:      u16 = 0;
+pub const L2TP_AVP_RESULT_CODE:       u16 = 1;
+pub const L2TP_AVP_PROTOCOL_VERSION:  u16 = 2;
+pub const L2TP_AVP_HOST_NAME:         u16 = 7;
+pub const L2TP_AVP_ASSIGNED_TUNNEL:   u16 = 9;
+pub const L2TP_AVP_ASSIGNED_SESSION:  u16 = 14;
+
+// Attribute value pair of control messages
+#[derive(Debug, PartialEq, Clone)]
+pub struct L2tpAvp {
+    pub mandatory:      bool,
+    pub hidden:         bool,
+    pub vendor_id:      u16,
+    pub attribute_type: u16,
+    pub value:          Vec<u8>
+}
+
+#[allow(unused_must_use)]
+impl L2tpAvp {
+    // Instantiate a mandatory IETF attribute
+    pub fn new(attribute_type: u16, value: &[u8]) -> L2tpAvp {
+        L2tpAvp {
+            mandatory:      true,
+            hidden:         false,
+            vendor_id:      0,
+            attribute_type,
+            value:          value.to_vec()
+        }
+    }
+
+    // Instantiate the Message Type attribute leading every control message
+    pub fn message_type(message_type: u16) -> L2tpAvp {
+        L2tpAvp::new(L2TP_AVP_MESSAGE_TYPE, &message_type.to_be_bytes())
+    }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let mut flags = (self.value.len() as u16 + 6) & 0x03ff;
+        if self.mandatory { flags |= 0x8000 }
+        if self.hidden { flags |= 0x4000 }
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u16::<NetworkEndian>(flags);
+        cursor.write_u16::<NetworkEndian>(self.vendor_id);
+        cursor.write_u16::<NetworkEndian>(self.attribute_type);
+        cursor.write_all(&self.value);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], L2tpAvp> {
+        let (bytes1, flags)          = verify(be_u16, |flags| *flags & 0x03ff >= 6)(bytes)?;
+        let (bytes2, vendor_id)      = be_u16(bytes1)?;
+        let (bytes3, attribute_type) = be_u16(bytes2)?;
+        let (bytes4, value)          = take((flags & 0x03ff) - 6)(bytes3)?;
+        Ok((
+            bytes4,
+            L2tpAvp {
+                mandatory: flags & 0x8000 != 0,
+                hidden:    flags & 0x4000 != 0,
+                vendor_id,
+                attribute_type,
+                value:     value.to_vec()
+            }
+        ))
+    }
+}
+
+// L2TPv2 header (RFC 2661), data messages carry a PPP frame
+#[derive(Debug, PartialEq)]
+pub struct L2tpv2 {
+    pub is_control:   bool,
+    pub has_length:   bool,
+    pub has_sequence: bool,
+    pub has_offset:   bool,
+    pub priority:     bool,
+    pub version:      u8,
+    // Fields below are zero unless flagged
+    pub length:       u16,
+    pub tunnel_id:    u16,
+    pub session_id:   u16,
+    pub ns:           u16,
+    pub nr:           u16,
+    pub offset_size:  u16,
+    // Control messages only
+    pub avps:         Vec<L2tpAvp>
+}
+
+impl Default for L2tpv2 {
+    fn default() -> L2tpv2 {
+        L2tpv2 {
+            is_control:   false,
+            has_length:   false,
+            has_sequence: false,
+            has_offset:   false,
+            priority:     false,
+            version:      2,
+            length:       0,
+            tunnel_id:    0,
+            session_id:   0,
+            ns:           0,
+            nr:           0,
+            offset_size:  0,
+            avps:         Vec::new()
+        }
+    }
+}
+
+impl fmt::Display for L2tpv2 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "L2TPv2(\
+             control: {}, \
+             tunnel_id: {}, \
+             session_id: {}, \
+             ns: {}, \
+             nr: {}, \
+             avps: {}\
+             )",
+            self.is_control,
+            self.tunnel_id,
+            self.session_id,
+            self.ns,
+            self.nr,
+            self.avps.len()
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl L2tpv2 {
+    // Instantiate a new data message header of tunnel 0
+    pub fn new() -> L2tpv2 { L2tpv2::default() }
+
+    // Instantiate a control message, the length is taken from the AVPs
+    pub fn control(tunnel_id: u16, session_id: u16, ns: u16, nr: u16, avps: Vec<L2tpAvp>) -> L2tpv2 {
+        let length = avps.iter().map(|avp| avp.value.len() as u16 + 6).sum::<u16>() + 12;
+        L2tpv2 {
+            is_control:   true,
+            has_length:   true,
+            has_sequence: true,
+            length,
+            tunnel_id,
+            session_id,
+            ns,
+            nr,
+            avps,
+            ..L2tpv2::default()
+        }
+    }
+
+    // Instantiate a data message header without the optional fields
+    pub fn data(tunnel_id: u16, session_id: u16) -> L2tpv2 {
+        L2tpv2 { tunnel_id, session_id, ..L2tpv2::default() }
+    }
+
+    // The Message Type AVP, None for a zero-length body acknowledgement
+    pub fn message_type(&self) -> Option<u16> {
+        message_type(&self.avps)
+    }
+
+    // Encode the L2TPv2 header and AVPs into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let mut flags = self.version as u16 & 0x000f;
+        if self.is_control { flags |= L2TP_FLAG_TYPE }
+        if self.has_length { flags |= L2TP_FLAG_LENGTH }
+        if self.has_sequence { flags |= L2TP_FLAG_SEQUENCE }
+        if self.has_offset { flags |= L2TP_FLAG_OFFSET }
+        if self.priority { flags |= L2TP_FLAG_PRIORITY }
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u16::<NetworkEndian>(flags);
+        if self.has_length {
+            cursor.write_u16::<NetworkEndian>(self.length);
+        }
+        cursor.write_u16::<NetworkEndian>(self.tunnel_id);
+        cursor.write_u16::<NetworkEndian>(self.session_id);
+        if self.has_sequence {
+            cursor.write_u16::<NetworkEndian>(self.ns);
+            cursor.write_u16::<NetworkEndian>(self.nr);
+        }
+        if self.has_offset {
+            cursor.write_u16::<NetworkEndian>(self.offset_size);
+            cursor.write_all(&vec![0; self.offset_size as usize]);
+        }
+        for avp in self.avps {
+            cursor.write_all(&avp.as_bytes());
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice, the PPP frame of a data message is left over
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], L2tpv2> {
+        let (mut b, flags) = be_u16(bytes)?;
+        let mut l2tp = L2tpv2 {
+            is_control:   flags & L2TP_FLAG_TYPE != 0,
+            has_length:   flags & L2TP_FLAG_LENGTH != 0,
+            has_sequence: flags & L2TP_FLAG_SEQUENCE != 0,
+            has_offset:   flags & L2TP_FLAG_OFFSET != 0,
+            priority:     flags & L2TP_FLAG_PRIORITY != 0,
+            version:      (flags & 0x000f) as u8,
+            ..L2tpv2::default()
+        };
+        if l2tp.has_length {
+            let (leftover, length) = be_u16(b)?;
+            l2tp.length = length;
+            b = leftover;
+        }
+        let (leftover, tunnel_id)  = be_u16(b)?;
+        let (leftover, session_id) = be_u16(leftover)?;
+        l2tp.tunnel_id = tunnel_id;
+        l2tp.session_id = session_id;
+        b = leftover;
+        if l2tp.has_sequence {
+            let (leftover, ns) = be_u16(b)?;
+            let (leftover, nr) = be_u16(leftover)?;
+            l2tp.ns = ns;
+            l2tp.nr = nr;
+            b = leftover;
+        }
+        if l2tp.has_offset {
+            let (leftover, offset_size) = be_u16(b)?;
+            let (leftover, _padding)    = take(offset_size)(leftover)?;
+            l2tp.offset_size = offset_size;
+            b = leftover;
+        }
+
+        // The length covers the header, anything beyond is padding
+        if l2tp.has_length {
+            let header_length = bytes.len() - b.len();
+            b = &b[..b.len().min((l2tp.length as usize).saturating_sub(header_length))];
+        }
+
+        if !l2tp.is_control {
+            return Ok((b, l2tp))
+        }
+
+        let (leftover, avps) = parse_avps(b)?;
+        Ok((leftover, L2tpv2 { avps, ..l2tp }))
+    }
+}
+
+// L2TPv3 data session settings, negotiated by the control connection
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct L2tpv3Session {
+    // 0, 4 or 8 octets
+    pub cookie_length:        usize,
+    // Default L2-Specific Sublayer
+    pub l2_specific_sublayer: bool
+}
+
+// L2TPv3 header (RFC 3931) over IP or UDP, data messages carry an
+// Ethernet pseudowire
+#[derive(Debug, PartialEq, Default)]
+pub struct L2tpv3 {
+    pub over_udp:              bool,
+    pub is_control:            bool,
+    // Data messages
+    pub session_id:            u32,
+    pub cookie:                Vec<u8>,
+    pub l2_specific_sublayer:  Option<u32>,
+    // Control messages
+    pub length:                u16,
+    pub control_connection_id: u32,
+    pub ns:                    u16,
+    pub nr:                    u16,
+    pub avps:                  Vec<L2tpAvp>
+}
+
+impl fmt::Display for L2tpv3 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "L2TPv3(\
+             control: {}, \
+             session_id: {}, \
+             cookie: {:02x?}, \
+             ccid: {}, \
+             avps: {}\
+             )",
+            self.is_control,
+            self.session_id,
+            self.cookie,
+            self.control_connection_id,
+            self.avps.len()
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl L2tpv3 {
+    // Instantiate a new data message header over IP
+    pub fn new() -> L2tpv3 { L2tpv3::default() }
+
+    // Instantiate a control message, the length is taken from the AVPs
+    pub fn control(control_connection_id: u32, ns: u16, nr: u16, avps: Vec<L2tpAvp>) -> L2tpv3 {
+        let length = avps.iter().map(|avp| avp.value.len() as u16 + 6).sum::<u16>() + 12;
+        L2tpv3 {
+            is_control: true,
+            length,
+            control_connection_id,
+            ns,
+            nr,
+            avps,
+            ..L2tpv3::default()
+        }
+    }
+
+    // Instantiate a data message header over IP
+    pub fn data(session_id: u32, cookie: &[u8]) -> L2tpv3 {
+        L2tpv3 { session_id, cookie: cookie.to_vec(), ..L2tpv3::default() }
+    }
+
+    // Switch to the UDP encapsulation
+    pub fn over_udp(self) -> L2tpv3 {
+        L2tpv3 { over_udp: true, ..self }
+    }
+
+    // The Message Type AVP, None for a zero-length body acknowledgement
+    pub fn message_type(&self) -> Option<u16> {
+        message_type(&self.avps)
+    }
+
+    // Encode the L2TPv3 header and AVPs into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        if self.is_control {
+            if !self.over_udp {
+                cursor.write_u32::<NetworkEndian>(0);
+            }
+            cursor.write_u16::<NetworkEndian>(L2TP_FLAG_TYPE | L2TP_FLAG_LENGTH | L2TP_FLAG_SEQUENCE | 3);
+            cursor.write_u16::<NetworkEndian>(self.length);
+            cursor.write_u32::<NetworkEndian>(self.control_connection_id);
+            cursor.write_u16::<NetworkEndian>(self.ns);
+            cursor.write_u16::<NetworkEndian>(self.nr);
+            for avp in self.avps {
+                cursor.write_all(&avp.as_bytes());
+            }
+        } else {
+            if self.over_udp {
+                cursor.write_u16::<NetworkEndian>(3);
+                cursor.write_u16::<NetworkEndian>(0);
+            }
+            cursor.write_u32::<NetworkEndian>(self.session_id);
+            cursor.write_all(&self.cookie);
+            if let Some(sublayer) = self.l2_specific_sublayer {
+                cursor.write_u32::<NetworkEndian>(sublayer);
+            }
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice, the cookie and sublayer of data messages are
+    // not self-describing and follow the session settings
+    pub fn from_bytes(bytes: &[u8], over_udp: bool, session: L2tpv3Session) -> IResult<&[u8], L2tpv3> {
+        let l2tp = L2tpv3 { over_udp, ..L2tpv3::default() };
+        let b = match over_udp {
+            true => {
+                let (leftover, flags) = be_u16(bytes)?;
+                if flags & L2TP_FLAG_TYPE != 0 {
+                    return Self::parse_control(bytes, l2tp)
+                }
+                let (leftover, _reserved) = be_u16(leftover)?;
+                leftover
+            },
+            false => {
+                let (leftover, session_id) = be_u32(bytes)?;
+                if session_id == 0 {
+                    return Self::parse_control(leftover, l2tp)
+                }
+                bytes
+            }
+        };
+
+        let (b, session_id) = be_u32(b)?;
+        let (b, cookie)     = take(session.cookie_length)(b)?;
+        let (b, sublayer)   = match session.l2_specific_sublayer {
+            true => be_u32(b).map(|(leftover, sublayer)| (leftover, Some(sublayer)))?,
+            false => (b, None)
+        };
+
+        Ok((
+            b,
+            L2tpv3 {
+                session_id,
+                cookie:               cookie.to_vec(),
+                l2_specific_sublayer: sublayer,
+                ..l2tp
+            }
+        ))
+    }
+
+    // Control message header following the session ID of zero over IP
+    fn parse_control(bytes: &[u8], l2tp: L2tpv3) -> IResult<&[u8], L2tpv3> {
+        let (b, _flags)                = be_u16(bytes)?;
+        let (b, length)                = verify(be_u16, |length| *length >= 12)(b)?;
+        let (b, control_connection_id) = be_u32(b)?;
+        let (b, ns)                    = be_u16(b)?;
+        let (b, nr)                    = be_u16(b)?;
+        let (b, body)                  = take(length - 12)(b)?;
+        let (_, avps)                  = parse_avps(body)?;
+        Ok((
+            b,
+            L2tpv3 {
+                is_control: true,
+                length,
+                control_connection_id,
+                ns,
+                nr,
+                avps,
+                ..l2tp
+            }
+        ))
+    }
+}
+
+// private functions
+
+fn parse_avps(bytes: &[u8]) -> IResult<&[u8], Vec<L2tpAvp>> {
+    let mut avps = Vec::new();
+    let mut b = bytes;
+    while !b.is_empty() {
+        let (leftover, avp) = L2tpAvp::from_bytes(b)?;
+        avps.push(avp);
+        b = leftover;
+    }
+    Ok((b, avps))
+}
+
+fn message_type(avps: &[L2tpAvp]) -> Option<u16> {
+    match avps.first() {
+        Some(L2tpAvp { vendor_id: 0, attribute_type: L2TP_AVP_MESSAGE_TYPE, value, .. }) if value.len() == 2 =>
+            Some(u16::from_be_bytes([value[0], value[1]])),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests_l2tp {
+    use crate::l2tp::*;
+
+    #[test]
+    fn parse_v2() {
+        let frame = &mut [
+            0xc8,0x02,0x00,0x1e,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x80,0x08,0x00,0x00,
+            0x00,0x00,0x00,0x01,0x80,0x0a,0x00,0x00,
+            0x00,0x09,0x00,0x00,0x00,0x05,0x00,0x00
+        ];
+
+        let (leftover, l2tp) = L2tpv2::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(L2tpv2::control(0, 0, 0, 0, vec![
+            L2tpAvp::message_type(L2TP_SCCRQ),
+            L2tpAvp::new(L2TP_AVP_ASSIGNED_TUNNEL, &[0x00, 0x00, 0x00, 0x05])
+        ]), l2tp);
+        assert_eq!(Some(L2TP_SCCRQ), l2tp.message_type());
+        assert_eq!("L2TPv2(control: true, tunnel_id: 0, session_id: 0, ns: 0, nr: 0, avps: 2)", format!("{}", l2tp));
+        assert_eq!(frame[..30].to_vec(), l2tp.as_bytes());
+
+        let frame = &mut [
+            0x00,0x02,0x00,0x05,0x00,0x07,0xff,0x03,
+            0x00,0x21
+        ];
+
+        let (leftover, l2tp) = L2tpv2::from_bytes(frame).unwrap();
+        assert_eq!(L2tpv2::data(5, 7), l2tp);
+        assert_eq!(&[0xff, 0x03, 0x00, 0x21], leftover);
+    }
+
+    #[test]
+    fn parse_v3() {
+        let session = L2tpv3Session { cookie_length: 4, l2_specific_sublayer: true };
+        let frame = &mut [
+            0x00,0x00,0x10,0x01,0xde,0xad,0xbe,0xef,
+            0x40,0x00,0x00,0x01
+        ];
+
+        let (leftover, l2tp) = L2tpv3::from_bytes(frame, false, session).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(0x1001, l2tp.session_id);
+        assert_eq!(vec![0xde, 0xad, 0xbe, 0xef], l2tp.cookie);
+        assert_eq!(Some(0x40000001), l2tp.l2_specific_sublayer);
+        assert_eq!(frame.to_vec(), l2tp.as_bytes());
+
+        let l2tp = L2tpv3::control(0x1234, 1, 0, vec![L2tpAvp::message_type(L2TP_HELLO)]).over_udp();
+        let bytes = l2tp.as_bytes();
+        assert_eq!(20, bytes.len());
+        let (leftover, l2tp) = L2tpv3::from_bytes(&bytes, true, session).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(Some(L2TP_HELLO), l2tp.message_type());
+        assert_eq!(0x1234, l2tp.control_connection_id);
+    }
+}
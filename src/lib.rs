@@ -3,42 +3,234 @@ extern crate libc;
 extern crate nom;
 
 pub mod arp;
+pub mod bfd;
+pub mod bgp;
+mod checksum;
+pub mod dhcp;
+pub mod dhcpv6;
+pub mod dns;
+pub mod erspan;
 pub mod ethernet;
+pub mod geneve;
 pub mod gre;
 pub mod gtp;
+pub mod l2tp;
 pub mod ipv4;
+pub mod ipv6;
+pub mod llc;
+pub mod macsec;
+pub mod lldp;
 pub mod icmpv4;
+pub mod igmp;
+pub mod ike;
+pub mod ipsec;
+pub mod mpls;
+pub mod nsh;
+pub mod ospf;
+pub mod ppp;
+pub mod pppoe;
+pub mod ptp;
+pub mod rtcp;
+pub mod rtp;
+pub mod sctp;
+pub mod slow;
+pub mod srh;
+pub mod stp;
 pub mod udp;
+mod util;
 pub mod tcp;
 pub mod vlan;
+pub mod vrrp;
 pub mod vxlan;
 
 use arp::Arp;
+use bfd::Bfd;
+use bgp::Bgp;
+use dhcp::Dhcp;
+use dhcpv6::Dhcpv6;
+use dns::Dns;
+use erspan::Erspan;
 use ethernet::Ethernet;
+use geneve::Geneve;
 use gre::Gre;
 use icmpv4::Icmpv4;
+use igmp::Igmp;
+use ike::Ike;
+#[cfg(feature = "decrypt")]
+use ike::{IkePayload, IkeSa};
+use ipsec::{Ah, Esp};
+#[cfg(feature = "decrypt")]
+use ipsec::EspSa;
 use ipv4::IPv4;
+use ipv6::IPv6;
+use l2tp::{L2tpv2, L2tpv3, L2tpv3Session};
+use llc::{Llc, Snap};
+use macsec::Macsec;
+#[cfg(feature = "decrypt")]
+use macsec::MacsecSa;
+use lldp::Lldp;
+use mpls::Mpls;
+use nsh::Nsh;
+use ospf::{Ospfv2, Ospfv3};
+use ppp::{Chap, Pap, Ppp, PppControl};
+use pppoe::Pppoe;
+use ptp::Ptp;
+use rtcp::Rtcp;
+use rtp::Rtp;
+use sctp::Sctp;
+use slow::{Lacp, Marker};
+use srh::Srh;
+use stp::Bpdu;
 use udp::Udp;
 use tcp::Tcp;
 use vlan::Dot1Q;
+use vrrp::Vrrp;
+use vxlan::VxlanGpe;
 
 use std::result::Result;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
 
 #[derive(Debug, PartialEq)]
 pub enum Packet {
     ETHER(Ethernet),
+    LLC(Llc),
+    SNAP(Snap),
+    STP(Bpdu),
+    LACP(Lacp),
+    MARKER(Marker),
     ARP(Arp),
     GRE(Gre),
+    GENEVE(Geneve),
+    VXLANGPE(VxlanGpe),
+    NSH(Nsh),
+    ERSPAN(Erspan),
     VLAN(Dot1Q),
+    MACSEC(Macsec),
+    L2TPv2(L2tpv2),
+    L2TPv3(L2tpv3),
+    PPPOE(Pppoe),
+    PPP(Ppp),
+    LCP(PppControl),
+    IPCP(PppControl),
+    IPv6CP(PppControl),
+    PAP(Pap),
+    CHAP(Chap),
     IPv4(IPv4),
+    IPv6(IPv6),
+    SRH(Srh),
+    MPLS(Mpls),
+    LLDP(Lldp),
     ICMP4(Icmpv4),
+    IGMP(Igmp),
+    AH(Ah),
+    ESP(Esp),
+    OSPFv2(Ospfv2),
+    OSPFv3(Ospfv3),
+    VRRP(Vrrp),
     UDP(Udp),
     TCP(Tcp),
+    SCTP(Sctp),
+    DHCP(Dhcp),
+    DHCPv6(Dhcpv6),
+    BFD(Bfd),
+    PTP(Ptp),
+    RTP(Rtp),
+    RTCP(Rtcp),
+    IKE(Ike),
+    DNS(Dns),
+    BGP(Bgp),
     Payload(Vec<u8>)
 }
 
+// Protocols without a well-known port
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DecodeAs {
+    // RTP, or RTCP multiplexed on the same port
+    Rtp,
+    Rtcp
+}
+
+// Settings for the protocols which cannot be decoded from the packets
+// alone, such as values negotiated out of band
+#[derive(Debug, Clone, Default)]
+pub struct ParseConfig {
+    pub l2tpv3_session: L2tpv3Session,
+    // UDP ports decoded as RTP
+    pub rtp_ports:      Option<RangeInclusive<u16>>,
+    // Protocols by UDP port
+    pub decode_as:      HashMap<u16, DecodeAs>,
+    // Security associations by SPI
+    #[cfg(feature = "decrypt")]
+    pub esp_sas:        HashMap<u32, EspSa>,
+    // IKE SAs by initiator SPI
+    #[cfg(feature = "decrypt")]
+    pub ike_sas:        HashMap<u64, IkeSa>,
+    // MACsec SAs by SCI and association number
+    #[cfg(feature = "decrypt")]
+    pub macsec_sas:     HashMap<(u64, u8), MacsecSa>
+}
+
+impl ParseConfig {
+    pub fn new() -> ParseConfig { ParseConfig::default() }
+
+    // Cookie length and L2-Specific Sublayer of L2TPv3 data sessions
+    pub fn l2tpv3_session(mut self, cookie_length: usize, l2_specific_sublayer: bool) -> ParseConfig {
+        self.l2tpv3_session = L2tpv3Session { cookie_length, l2_specific_sublayer };
+        self
+    }
+
+    // Decode the UDP ports of the range as RTP and RTCP
+    pub fn rtp_ports(mut self, ports: RangeInclusive<u16>) -> ParseConfig {
+        self.rtp_ports = Some(ports);
+        self
+    }
+
+    // Decode the UDP port as the protocol
+    pub fn decode_as(mut self, port: u16, protocol: DecodeAs) -> ParseConfig {
+        self.decode_as.insert(port, protocol);
+        self
+    }
+
+    // The protocol configured for the destination or source port
+    pub fn udp_protocol(&self, udp: &Udp) -> Option<DecodeAs> {
+        self.decode_as.get(&udp.destination)
+            .or_else(|| self.decode_as.get(&udp.source))
+            .cloned()
+            .or_else(|| match &self.rtp_ports {
+                Some(ports) if ports.contains(&udp.destination) || ports.contains(&udp.source) => Some(DecodeAs::Rtp),
+                _ => None
+            })
+    }
+
+    // Decrypt the ESP payloads of the SA
+    #[cfg(feature = "decrypt")]
+    pub fn esp_sa(mut self, sa: EspSa) -> ParseConfig {
+        self.esp_sas.insert(sa.spi, sa);
+        self
+    }
+
+    // Decrypt the Encrypted payloads of the IKE SA
+    #[cfg(feature = "decrypt")]
+    pub fn ike_sa(mut self, sa: IkeSa) -> ParseConfig {
+        self.ike_sas.insert(sa.initiator_spi, sa);
+        self
+    }
+
+    // Decrypt the MACsec frames of the SA
+    #[cfg(feature = "decrypt")]
+    pub fn macsec_sa(mut self, sa: MacsecSa) -> ParseConfig {
+        self.macsec_sas.insert((sa.sci, sa.an), sa);
+        self
+    }
+}
+
 impl Packet {
-    pub fn parse<'a>(bytes: &'a [u8]) -> Vec<Packet> {
+    pub fn parse(bytes: &[u8]) -> Vec<Packet> {
+        Self::parse_with(bytes, &ParseConfig::default())
+    }
+
+    pub fn parse_with(bytes: &[u8], config: &ParseConfig) -> Vec<Packet> {
         let mut headers: Vec<Packet> = Vec::new();
         let mut leftover: &[u8];
         match Self::parse_eth(bytes) {
@@ -48,7 +240,7 @@ impl Packet {
                 leftover = b;
                 headers.push(ethernet);
                 while leftover != &[] {
-                    leftover = Self::parse_next(leftover, &mut headers);
+                    leftover = Self::parse_next(leftover, &mut headers, config);
                 }
             }
         }
@@ -56,35 +248,268 @@ impl Packet {
         return headers
     }
 
-    fn parse_next<'a>(bytes: &'a [u8], pkt: &mut Vec<Packet>) -> &'a [u8] {
+    // Tunnel depth of the header at index, the number of IP headers
+    // encapsulating its IP layer. The outermost layer is at depth 0
+    pub fn tunnel_depth(headers: &[Packet], index: usize) -> usize {
+        headers.iter()
+            .take(index + 1)
+            .filter(|header| matches!(header, Packet::IPv4(_) | Packet::IPv6(_)))
+            .count()
+            .saturating_sub(1)
+    }
+
+    fn parse_next<'a>(bytes: &'a [u8], pkt: &mut Vec<Packet>, config: &ParseConfig) -> &'a [u8] {
         let result: Result<(&[u8], Packet), &[u8]> = match pkt.last().unwrap() {
+            // RTP and RTCP on the configured ports, ahead of the well-known ports
+            Packet::UDP(udp) if config.udp_protocol(udp) == Some(DecodeAs::Rtp) =>
+                Self::parse_rtp(bytes),
+            Packet::UDP(udp) if config.udp_protocol(udp) == Some(DecodeAs::Rtcp) =>
+                Self::parse_rtcp(bytes),
             // ETH_P_ARP
             Packet::ETHER(Ethernet{ eth_type: 0x0806, .. }) |
             Packet::GRE(Gre{ protocol: 0x0806, .. }) |
-            Packet::VLAN(Dot1Q{ tpid: 0x0806, .. }) =>
+            Packet::GENEVE(Geneve{ protocol_type: 0x0806, .. }) |
+            Packet::VLAN(Dot1Q{ eth_type: 0x0806, .. }) |
+            Packet::MACSEC(Macsec{ eth_type: 0x0806, .. }) |
+            Packet::SNAP(Snap{ oui: 0, protocol_id: 0x0806 }) =>
                 Self::parse_arp(bytes),
-            // ETH_P_802_1Q
-            Packet::ETHER(Ethernet{ eth_type: 0x8100, .. }) |
-            Packet::GRE(Gre{ protocol: 0x8100, .. }) |
-            Packet::VLAN(Dot1Q{ tpid: 0x8100, .. }) =>
-                Self::parse_vlan(bytes),
+            // ETH_P_8021Q, ETH_P_8021AD, ETH_P_QINQ1
+            Packet::ETHER(Ethernet{ eth_type: tpid @ (0x8100 | 0x88a8 | 0x9100), .. }) |
+            Packet::GRE(Gre{ protocol: tpid @ (0x8100 | 0x88a8 | 0x9100), .. }) |
+            Packet::GENEVE(Geneve{ protocol_type: tpid @ (0x8100 | 0x88a8 | 0x9100), .. }) |
+            Packet::VLAN(Dot1Q{ eth_type: tpid @ (0x8100 | 0x88a8 | 0x9100), .. }) |
+            Packet::MACSEC(Macsec{ eth_type: tpid @ (0x8100 | 0x88a8 | 0x9100), .. }) =>
+                Self::parse_vlan(bytes, *tpid),
             // ETH_P_IP
             Packet::ETHER(Ethernet{ eth_type: 0x0800, .. }) |
             Packet::GRE(Gre{ protocol: 0x0800, .. }) |
-            Packet::VLAN(Dot1Q{ tpid: 0x0800, .. }) =>
+            Packet::GENEVE(Geneve{ protocol_type: 0x0800, .. }) |
+            Packet::VLAN(Dot1Q{ eth_type: 0x0800, .. }) |
+            Packet::MACSEC(Macsec{ eth_type: 0x0800, .. }) |
+            Packet::SNAP(Snap{ oui: 0, protocol_id: 0x0800 }) |
+            Packet::VXLANGPE(VxlanGpe{ next_protocol: 0x01, .. }) |
+            Packet::NSH(Nsh{ next_protocol: 0x01, .. }) =>
                 Self::parse_ip4(bytes),
+            // ETH_P_IPV6
+            Packet::ETHER(Ethernet{ eth_type: 0x86dd, .. }) |
+            Packet::GRE(Gre{ protocol: 0x86dd, .. }) |
+            Packet::GENEVE(Geneve{ protocol_type: 0x86dd, .. }) |
+            Packet::VLAN(Dot1Q{ eth_type: 0x86dd, .. }) |
+            Packet::MACSEC(Macsec{ eth_type: 0x86dd, .. }) |
+            Packet::SNAP(Snap{ oui: 0, protocol_id: 0x86dd }) |
+            Packet::VXLANGPE(VxlanGpe{ next_protocol: 0x02, .. }) |
+            Packet::NSH(Nsh{ next_protocol: 0x02, .. }) =>
+                Self::parse_ip6(bytes),
+            // ETH_P_MPLS_UC, ETH_P_MPLS_MC
+            Packet::ETHER(Ethernet{ eth_type: 0x8847 | 0x8848, .. }) |
+            Packet::GRE(Gre{ protocol: 0x8847 | 0x8848, .. }) |
+            Packet::GENEVE(Geneve{ protocol_type: 0x8847 | 0x8848, .. }) |
+            Packet::VLAN(Dot1Q{ eth_type: 0x8847 | 0x8848, .. }) |
+            Packet::MACSEC(Macsec{ eth_type: 0x8847 | 0x8848, .. }) |
+            Packet::VXLANGPE(VxlanGpe{ next_protocol: 0x05, .. }) |
+            Packet::NSH(Nsh{ next_protocol: 0x05, .. }) =>
+                Self::parse_mpls(bytes),
+            // ETH_P_NSH
+            Packet::ETHER(Ethernet{ eth_type: 0x894f, .. }) |
+            Packet::GRE(Gre{ protocol: 0x894f, .. }) |
+            Packet::GENEVE(Geneve{ protocol_type: 0x894f, .. }) |
+            Packet::VLAN(Dot1Q{ eth_type: 0x894f, .. }) |
+            Packet::VXLANGPE(VxlanGpe{ next_protocol: 0x04, .. }) =>
+                Self::parse_nsh(bytes),
+            // ETH_P_MACSEC
+            Packet::ETHER(Ethernet{ eth_type: 0x88e5, .. }) =>
+                Self::parse_macsec(bytes),
+            // MACsec with a known SA, the inner packet follows the decrypted secure data
+            #[cfg(feature = "decrypt")]
+            Packet::MACSEC(Macsec{ encrypted: true, decrypted: false, .. }) if !config.macsec_sas.is_empty() =>
+                return Self::parse_macsec_payload(bytes, pkt, config),
+            // ETH_P_1588, PTP event and general messages over UDP
+            Packet::ETHER(Ethernet{ eth_type: 0x88f7, .. }) |
+            Packet::VLAN(Dot1Q{ eth_type: 0x88f7, .. }) |
+            Packet::MACSEC(Macsec{ eth_type: 0x88f7, .. }) |
+            Packet::UDP(Udp{ destination: 319 | 320, .. }) |
+            Packet::UDP(Udp{ source: 319 | 320, .. }) =>
+                Self::parse_ptp(bytes),
+            // IEEE 802.3 length field
+            Packet::ETHER(Ethernet{ eth_type: length @ 0..=0x05ff, .. }) |
+            Packet::VLAN(Dot1Q{ eth_type: length @ 0..=0x05ff, .. }) =>
+                Self::parse_llc(bytes, *length),
+            // SNAP
+            Packet::LLC(Llc{ dsap: 0xaa, ssap: 0xaa, control: 0x03, .. }) =>
+                Self::parse_snap(bytes),
+            // Spanning tree, Cisco PVST+
+            Packet::LLC(Llc{ dsap: 0x42, ssap: 0x42, .. }) |
+            Packet::SNAP(Snap{ oui: 0x00000c, protocol_id: 0x010b }) =>
+                Self::parse_stp(bytes),
+            // ETH_P_SLOW
+            Packet::ETHER(Ethernet{ eth_type: 0x8809, .. }) |
+            Packet::VLAN(Dot1Q{ eth_type: 0x8809, .. }) =>
+                Self::parse_slow(bytes),
+            // ETH_P_LLDP
+            Packet::ETHER(Ethernet{ eth_type: 0x88cc, .. }) |
+            Packet::VLAN(Dot1Q{ eth_type: 0x88cc, .. }) =>
+                Self::parse_lldp(bytes),
+            // ETH_P_PPP_DISC, ETH_P_PPP_SES
+            Packet::ETHER(Ethernet{ eth_type: 0x8863 | 0x8864, .. }) |
+            Packet::VLAN(Dot1Q{ eth_type: 0x8863 | 0x8864, .. }) =>
+                Self::parse_pppoe(bytes),
+            // PPP over PPPoE session, L2TPv2, PPTP
+            Packet::PPPOE(Pppoe{ code: 0, .. }) |
+            Packet::L2TPv2(L2tpv2{ is_control: false, .. }) |
+            Packet::GRE(Gre{ protocol: 0x880b, .. }) =>
+                Self::parse_ppp(bytes),
+            // IPv4/IPv6 over PPP
+            Packet::PPP(Ppp{ protocol: 0x0021, .. }) =>
+                Self::parse_ip4(bytes),
+            Packet::PPP(Ppp{ protocol: 0x0057, .. }) =>
+                Self::parse_ip6(bytes),
+            // LCP, IPCP, IPv6CP
+            Packet::PPP(Ppp{ protocol: protocol @ (0xc021 | 0x8021 | 0x8057), .. }) =>
+                Self::parse_ppp_control(bytes, *protocol),
+            // PAP, CHAP
+            Packet::PPP(Ppp{ protocol: 0xc023, .. }) =>
+                Self::parse_pap(bytes),
+            Packet::PPP(Ppp{ protocol: 0xc223, .. }) =>
+                Self::parse_chap(bytes),
+            // ERSPAN type II, III
+            Packet::GRE(Gre{ protocol: 0x88be, has_sequence: true, .. }) |
+            Packet::GRE(Gre{ protocol: 0x22eb, .. }) =>
+                Self::parse_erspan(bytes),
+            // Transparent Ethernet Bridging, NVGRE, ERSPAN, MPLS pseudowire
+            Packet::GRE(Gre{ protocol: 0x6558, .. }) |
+            Packet::GRE(Gre{ protocol: 0x88be, has_sequence: false, .. }) |
+            Packet::ERSPAN(Erspan{ frame_type: 0, .. }) |
+            Packet::GENEVE(Geneve{ protocol_type: 0x6558, .. }) |
+            Packet::MPLS(Mpls{ control_word: Some(_), .. }) |
+            Packet::L2TPv3(L2tpv3{ is_control: false, .. }) |
+            Packet::SRH(Srh{ next_header: 143, .. }) |
+            Packet::VXLANGPE(VxlanGpe{ next_protocol: 0x03, .. }) |
+            Packet::NSH(Nsh{ next_protocol: 0x03, .. }) =>
+                Self::parse_eth(bytes),
+            // IPv4/IPv6 over MPLS
+            Packet::MPLS(Mpls{ control_word: None, .. }) =>
+                Self::parse_mpls_payload(bytes),
+            // IPIP, 4in6 and IPsec tunnel mode
+            Packet::IPv4(IPv4{ protocol: 4, .. }) |
+            Packet::IPv6(IPv6{ next_header: 4, .. }) |
+            Packet::SRH(Srh{ next_header: 4, .. }) |
+            Packet::AH(Ah{ next_header: 4, .. }) |
+            Packet::ESP(Esp{ decrypted: true, next_header: 4, .. }) =>
+                Self::parse_ip4(bytes),
+            // 6in4, 6in6 and IPsec tunnel mode
+            Packet::IPv4(IPv4{ protocol: 41, .. }) |
+            Packet::IPv6(IPv6{ next_header: 41, .. }) |
+            Packet::SRH(Srh{ next_header: 41, .. }) |
+            Packet::AH(Ah{ next_header: 41, .. }) |
+            Packet::ESP(Esp{ decrypted: true, next_header: 41, .. }) =>
+                Self::parse_ip6(bytes),
+            // IPv6 Segment Routing Header
+            Packet::IPv6(IPv6{ next_header: 43, .. }) =>
+                Self::parse_srh(bytes),
             // IPPROTO_ICMP
-            Packet::IPv4(IPv4{ protocol: 1, .. }) =>
+            Packet::IPv4(IPv4{ protocol: 1, .. }) |
+            Packet::AH(Ah{ next_header: 1, .. }) |
+            Packet::ESP(Esp{ decrypted: true, next_header: 1, .. }) =>
                 Self::parse_icmp4(bytes),
+            // IPPROTO_IGMP
+            Packet::IPv4(ipv4 @ IPv4{ protocol: 2, .. }) =>
+                Self::parse_igmp(bytes, ipv4),
             // IPPROTO_TCP
-            Packet::IPv4(IPv4{ protocol: 6, .. }) =>
+            Packet::IPv4(IPv4{ protocol: 6, .. }) |
+            Packet::IPv6(IPv6{ next_header: 6, .. }) |
+            Packet::SRH(Srh{ next_header: 6, .. }) |
+            Packet::AH(Ah{ next_header: 6, .. }) |
+            Packet::ESP(Esp{ decrypted: true, next_header: 6, .. }) =>
                 Self::parse_tcp(bytes),
             // IPPROTO_TCP
-            Packet::IPv4(IPv4{ protocol: 17, .. }) =>
+            Packet::IPv4(IPv4{ protocol: 17, .. }) |
+            Packet::IPv6(IPv6{ next_header: 17, .. }) |
+            Packet::SRH(Srh{ next_header: 17, .. }) |
+            Packet::AH(Ah{ next_header: 17, .. }) |
+            Packet::ESP(Esp{ decrypted: true, next_header: 17, .. }) =>
                 Self::parse_udp(bytes),
             // IPPROTO_GRE
-            Packet::IPv4(IPv4{ protocol: 47, .. }) =>
+            Packet::IPv4(IPv4{ protocol: 47, .. }) |
+            Packet::IPv6(IPv6{ next_header: 47, .. }) =>
                 Self::parse_gre(bytes),
+            // IPPROTO_ESP
+            Packet::IPv4(IPv4{ protocol: 50, .. }) |
+            Packet::IPv6(IPv6{ next_header: 50, .. }) |
+            Packet::AH(Ah{ next_header: 50, .. }) =>
+                Self::parse_esp(bytes),
+            // ESP with a known SA, the inner packet follows the decrypted payload
+            #[cfg(feature = "decrypt")]
+            Packet::ESP(esp @ Esp{ decrypted: false, .. }) if config.esp_sas.contains_key(&esp.spi) =>
+                return Self::parse_esp_payload(bytes, pkt, config),
+            // IPPROTO_AH
+            Packet::IPv4(IPv4{ protocol: 51, .. }) |
+            Packet::IPv6(IPv6{ next_header: 51, .. }) =>
+                Self::parse_ah(bytes),
+            // IPPROTO_OSPF
+            Packet::IPv4(IPv4{ protocol: 89, .. }) =>
+                Self::parse_ospfv2(bytes),
+            Packet::IPv6(IPv6{ next_header: 89, .. }) =>
+                Self::parse_ospfv3(bytes),
+            // IPPROTO_VRRP
+            Packet::IPv4(ipv4 @ IPv4{ protocol: 112, .. }) =>
+                Self::parse_vrrp(Self::ipv4_payload(bytes, ipv4), Vrrp::from_bytes),
+            Packet::IPv6(ipv6 @ IPv6{ next_header: 112, .. }) =>
+                Self::parse_vrrp(Self::ipv6_payload(bytes, ipv6), Vrrp::from_bytes_v6),
+            // IPPROTO_L2TP
+            Packet::IPv4(IPv4{ protocol: 115, .. }) |
+            Packet::IPv6(IPv6{ next_header: 115, .. }) =>
+                Self::parse_l2tpv3(bytes, false, config),
+            // IPPROTO_SCTP
+            Packet::IPv4(IPv4{ protocol: 132, .. }) |
+            Packet::IPv6(IPv6{ next_header: 132, .. }) =>
+                Self::parse_sctp(bytes),
+            // IPPROTO_MPLS, MPLS over UDP
+            Packet::IPv4(IPv4{ protocol: 137, .. }) |
+            Packet::IPv6(IPv6{ next_header: 137, .. }) |
+            Packet::UDP(Udp{ destination: 6635, .. }) =>
+                Self::parse_mpls(bytes),
+            // VXLAN-GPE
+            Packet::UDP(Udp{ destination: 4790, .. }) =>
+                Self::parse_vxlan_gpe(bytes),
+            // Geneve
+            Packet::UDP(Udp{ destination: 6081, .. }) =>
+                Self::parse_geneve(bytes),
+            // DHCP
+            Packet::UDP(Udp{ destination: 67 | 68, .. }) |
+            Packet::UDP(Udp{ source: 67 | 68, .. }) =>
+                Self::parse_dhcp(bytes),
+            // DHCPv6
+            Packet::UDP(Udp{ destination: 546 | 547, .. }) |
+            Packet::UDP(Udp{ source: 546 | 547, .. }) =>
+                Self::parse_dhcpv6(bytes),
+            // BFD single hop and multihop control
+            Packet::UDP(Udp{ destination: 3784 | 4784, .. }) =>
+                Self::parse_bfd(bytes),
+            // L2TPv2, L2TPv3 over UDP
+            Packet::UDP(Udp{ destination: 1701, .. }) |
+            Packet::UDP(Udp{ source: 1701, .. }) =>
+                Self::parse_l2tp(bytes, config),
+            // IKEv2
+            Packet::UDP(Udp{ destination: 500, .. }) |
+            Packet::UDP(Udp{ source: 500, .. }) =>
+                Self::parse_ike(bytes, config),
+            // ESP or IKEv2 in UDP (NAT-T)
+            Packet::UDP(Udp{ destination: 4500, .. }) |
+            Packet::UDP(Udp{ source: 4500, .. }) =>
+                Self::parse_udp_encap(bytes, config),
+            // DNS, mDNS
+            Packet::UDP(Udp{ destination: 53 | 5353, .. }) |
+            Packet::UDP(Udp{ source: 53 | 5353, .. }) =>
+                Self::parse_dns(bytes),
+            // DNS over TCP
+            Packet::TCP(Tcp{ destination: 53, .. }) |
+            Packet::TCP(Tcp{ source: 53, .. }) =>
+                Self::parse_dns_tcp(bytes),
+            // BGP, a TCP segment may carry several messages
+            Packet::TCP(Tcp{ destination: 179, .. }) |
+            Packet::TCP(Tcp{ source: 179, .. }) |
+            Packet::BGP(_) =>
+                Self::parse_bgp(bytes),
             // Other
             _other => {
                 let packet = Packet::Payload(bytes.to_vec());
@@ -129,9 +554,9 @@ impl Packet {
         }
     }
 
-    // Parse 802.1q vlan tag header
-    fn parse_vlan(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
-        match Dot1Q::from_bytes(bytes) {
+    // Parse 802.1q/802.1ad vlan tag header
+    fn parse_vlan(bytes: &[u8], tpid: u16) -> Result<(&[u8], Packet), &[u8]> {
+        match Dot1Q::from_bytes_with_tpid(bytes, tpid) {
             Err(_e) =>
                 return Result::Err(bytes),
             Ok((leftover, vlan)) => {
@@ -153,6 +578,358 @@ impl Packet {
         }
     }
 
+    // Parse IPv6 Header
+    fn parse_ip6(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match IPv6::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, ipv6)) =>
+                Result::Ok((leftover, Packet::IPv6(ipv6)))
+        }
+    }
+
+    // Parse MPLS label stack
+    fn parse_mpls(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Mpls::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, mpls)) =>
+                Result::Ok((leftover, Packet::MPLS(mpls)))
+        }
+    }
+
+    // Parse the payload of the bottom of stack by its first nibble
+    fn parse_mpls_payload(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match bytes[0] >> 4 {
+            4 => Self::parse_ip4(bytes),
+            6 => Self::parse_ip6(bytes),
+            _ => Result::Err(bytes)
+        }
+    }
+
+    // Parse LLC header of an IEEE 802.3 frame, dropping the trailing padding
+    fn parse_llc(bytes: &[u8], length: u16) -> Result<(&[u8], Packet), &[u8]> {
+        let frame = &bytes[..bytes.len().min(length as usize)];
+        match Llc::from_bytes(frame) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, llc)) =>
+                Result::Ok((leftover, Packet::LLC(llc)))
+        }
+    }
+
+    // Parse SNAP header
+    fn parse_snap(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Snap::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, snap)) =>
+                Result::Ok((leftover, Packet::SNAP(snap)))
+        }
+    }
+
+    // Parse spanning tree BPDU
+    fn parse_stp(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Bpdu::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, bpdu)) =>
+                Result::Ok((leftover, Packet::STP(bpdu)))
+        }
+    }
+
+    // Parse Slow Protocols PDU by its subtype
+    fn parse_slow(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        let result = match bytes[0] {
+            1 => Lacp::from_bytes(bytes).map(|(leftover, lacp)| (leftover, Packet::LACP(lacp))),
+            2 => Marker::from_bytes(bytes).map(|(leftover, marker)| (leftover, Packet::MARKER(marker))),
+            _ => return Result::Err(bytes)
+        };
+
+        match result {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, pkt)) =>
+                Result::Ok((leftover, pkt))
+        }
+    }
+
+    // Parse LLDPDU
+    fn parse_lldp(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Lldp::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, lldp)) =>
+                Result::Ok((leftover, Packet::LLDP(lldp)))
+        }
+    }
+
+    // Parse L2TP header over UDP by the version field
+    fn parse_l2tp<'a>(bytes: &'a [u8], config: &ParseConfig) -> Result<(&'a [u8], Packet), &'a [u8]> {
+        match bytes.get(1).map(|flags| flags & 0x0f) {
+            Some(2) => match L2tpv2::from_bytes(bytes) {
+                Err(_e) =>
+                    Result::Err(bytes),
+                Ok((leftover, l2tp)) =>
+                    Result::Ok((leftover, Packet::L2TPv2(l2tp)))
+            },
+            Some(3) =>
+                Self::parse_l2tpv3(bytes, true, config),
+            _ =>
+                Result::Err(bytes)
+        }
+    }
+
+    // Parse L2TPv3 header
+    fn parse_l2tpv3<'a>(bytes: &'a [u8], over_udp: bool, config: &ParseConfig) -> Result<(&'a [u8], Packet), &'a [u8]> {
+        match L2tpv3::from_bytes(bytes, over_udp, config.l2tpv3_session) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, l2tp)) =>
+                Result::Ok((leftover, Packet::L2TPv3(l2tp)))
+        }
+    }
+
+    // Parse PPPoE header, discovery tags are parsed along
+    fn parse_pppoe(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Pppoe::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, pppoe)) =>
+                Result::Ok((leftover, Packet::PPPOE(pppoe)))
+        }
+    }
+
+    // Parse PPP header
+    fn parse_ppp(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Ppp::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, ppp)) =>
+                Result::Ok((leftover, Packet::PPP(ppp)))
+        }
+    }
+
+    // Parse LCP, IPCP or IPv6CP packet
+    fn parse_ppp_control(bytes: &[u8], protocol: u16) -> Result<(&[u8], Packet), &[u8]> {
+        match PppControl::from_bytes(bytes, protocol) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, control)) => {
+                let pkt = match protocol {
+                    0x8021 => Packet::IPCP(control),
+                    0x8057 => Packet::IPv6CP(control),
+                    _ => Packet::LCP(control)
+                };
+                Result::Ok((leftover, pkt))
+            }
+        }
+    }
+
+    // Parse PAP packet
+    fn parse_pap(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Pap::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, pap)) =>
+                Result::Ok((leftover, Packet::PAP(pap)))
+        }
+    }
+
+    // Parse CHAP packet
+    fn parse_chap(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Chap::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, chap)) =>
+                Result::Ok((leftover, Packet::CHAP(chap)))
+        }
+    }
+
+    // Parse ESP header, the payload is left encrypted
+    fn parse_esp(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Esp::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, esp)) =>
+                Result::Ok((leftover, Packet::ESP(esp)))
+        }
+    }
+
+    // Parse PTP message
+    fn parse_ptp(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Ptp::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, ptp)) =>
+                Result::Ok((leftover, Packet::PTP(ptp)))
+        }
+    }
+
+    // Parse RTP header, RTCP multiplexed on the port is told apart by its
+    // packet type (RFC 5761)
+    fn parse_rtp(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        if let [_, 200..=207, ..] = bytes {
+            return Self::parse_rtcp(bytes)
+        }
+        match Rtp::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, rtp)) =>
+                Result::Ok((leftover, Packet::RTP(rtp)))
+        }
+    }
+
+    // Parse RTCP compound packet
+    fn parse_rtcp(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Rtcp::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, rtcp)) =>
+                Result::Ok((leftover, Packet::RTCP(rtcp)))
+        }
+    }
+
+    // Parse MACsec SecTAG
+    fn parse_macsec(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Macsec::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, macsec)) =>
+                Result::Ok((leftover, Packet::MACSEC(macsec)))
+        }
+    }
+
+    // Decrypt the secure data of the last MACsec header with the SA of its
+    // SCI and parse the inner packet out of the plaintext
+    #[cfg(feature = "decrypt")]
+    fn parse_macsec_payload<'a>(bytes: &'a [u8], pkt: &mut Vec<Packet>, config: &ParseConfig) -> &'a [u8] {
+        let decrypted = match pkt.iter().rev().take(2).collect::<Vec<_>>()[..] {
+            [Packet::MACSEC(macsec), Packet::ETHER(ethernet)] => {
+                let sci = macsec.sci_or(ethernet.source.as_bytes());
+                config.macsec_sas.get(&(sci, macsec.an)).and_then(|sa| sa.decrypt(macsec, ethernet, bytes))
+            },
+            _ =>
+                None
+        };
+
+        match decrypted {
+            None =>
+                pkt.push(Packet::Payload(bytes.to_vec())),
+            Some((macsec, plaintext)) => {
+                *pkt.last_mut().unwrap() = Packet::MACSEC(macsec);
+                let mut leftover = &plaintext[..];
+                while !leftover.is_empty() {
+                    leftover = Self::parse_next(leftover, pkt, config);
+                }
+            }
+        }
+
+        &[]
+    }
+
+    // Decrypt the payload of the last ESP header and parse the inner packet
+    // out of the plaintext
+    #[cfg(feature = "decrypt")]
+    fn parse_esp_payload<'a>(bytes: &'a [u8], pkt: &mut Vec<Packet>, config: &ParseConfig) -> &'a [u8] {
+        let decrypted = match pkt.last() {
+            Some(Packet::ESP(esp)) =>
+                config.esp_sas.get(&esp.spi).and_then(|sa| sa.decrypt(esp, bytes)),
+            _ =>
+                None
+        };
+
+        match decrypted {
+            None =>
+                pkt.push(Packet::Payload(bytes.to_vec())),
+            Some((esp, plaintext)) => {
+                *pkt.last_mut().unwrap() = Packet::ESP(esp);
+                let mut leftover = &plaintext[..];
+                while !leftover.is_empty() {
+                    leftover = Self::parse_next(leftover, pkt, config);
+                }
+            }
+        }
+
+        &[]
+    }
+
+    // Parse UDP encapsulated ESP, IKE follows the non-ESP marker
+    fn parse_udp_encap<'a>(bytes: &'a [u8], config: &ParseConfig) -> Result<(&'a [u8], Packet), &'a [u8]> {
+        match bytes {
+            [0, 0, 0, 0, ike @ ..] =>
+                Self::parse_ike(ike, config),
+            // NAT-keepalive
+            [0xff] =>
+                Result::Err(bytes),
+            _ =>
+                Self::parse_esp(bytes)
+        }
+    }
+
+    // Parse IKEv2 message
+    fn parse_ike<'a>(bytes: &'a [u8], config: &ParseConfig) -> Result<(&'a [u8], Packet), &'a [u8]> {
+        match Ike::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, ike)) =>
+                Result::Ok((leftover, Packet::IKE(Self::decrypt_ike(ike, bytes, config))))
+        }
+    }
+
+    // Fill the Encrypted payload of a known IKE SA with its inner payloads
+    #[cfg(feature = "decrypt")]
+    fn decrypt_ike(mut ike: Ike, bytes: &[u8], config: &ParseConfig) -> Ike {
+        let inner = config.ike_sas.get(&ike.initiator_spi).and_then(|sa| sa.decrypt(&ike, bytes));
+        if let (Some(inner), Some(IkePayload::Encrypted { payloads, .. })) = (inner, ike.payloads.last_mut()) {
+            *payloads = inner;
+        }
+        ike
+    }
+
+    #[cfg(not(feature = "decrypt"))]
+    fn decrypt_ike(ike: Ike, _bytes: &[u8], _config: &ParseConfig) -> Ike { ike }
+
+    // Parse VXLAN-GPE header
+    fn parse_vxlan_gpe(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match VxlanGpe::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, vxlan)) =>
+                Result::Ok((leftover, Packet::VXLANGPE(vxlan)))
+        }
+    }
+
+    // Parse NSH header
+    fn parse_nsh(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Nsh::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, nsh)) =>
+                Result::Ok((leftover, Packet::NSH(nsh)))
+        }
+    }
+
+    // Parse IPv6 Segment Routing Header
+    fn parse_srh(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Srh::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, srh)) =>
+                Result::Ok((leftover, Packet::SRH(srh)))
+        }
+    }
+
+    // Parse AH header
+    fn parse_ah(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Ah::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, ah)) =>
+                Result::Ok((leftover, Packet::AH(ah)))
+        }
+    }
+
     // Parse ICMP4 Header
     fn parse_icmp4(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
         match Icmpv4::from_bytes(bytes) {
@@ -165,6 +942,28 @@ impl Packet {
         }
     }
 
+    // IPv4 payload without the Ethernet padding
+    fn ipv4_payload<'a>(bytes: &'a [u8], ipv4: &IPv4) -> &'a [u8] {
+        let header_length = ((ipv4.version_ihl & 0x0f) * 4) as usize;
+        let length = (ipv4.total_length as usize).saturating_sub(header_length);
+        &bytes[..bytes.len().min(length)]
+    }
+
+    // IPv6 payload without the Ethernet padding
+    fn ipv6_payload<'a>(bytes: &'a [u8], ipv6: &IPv6) -> &'a [u8] {
+        &bytes[..bytes.len().min(ipv6.payload_length as usize)]
+    }
+
+    // Parse IGMP message up to the end of the IPv4 payload
+    fn parse_igmp<'a>(bytes: &'a [u8], ipv4: &IPv4) -> Result<(&'a [u8], Packet), &'a [u8]> {
+        match Igmp::from_bytes(Self::ipv4_payload(bytes, ipv4)) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, igmp)) =>
+                Result::Ok((leftover, Packet::IGMP(igmp)))
+        }
+    }
+
     // Parse TCP Header
     fn parse_tcp(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
         match Tcp::from_bytes(bytes) {
@@ -177,6 +976,46 @@ impl Packet {
         }
     }
 
+    // Parse OSPFv2 packet
+    fn parse_ospfv2(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Ospfv2::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, ospf)) =>
+                Result::Ok((leftover, Packet::OSPFv2(ospf)))
+        }
+    }
+
+    // Parse OSPFv3 packet
+    fn parse_ospfv3(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Ospfv3::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, ospf)) =>
+                Result::Ok((leftover, Packet::OSPFv3(ospf)))
+        }
+    }
+
+    // Parse VRRP advertisement, the parser depends on the IP version
+    fn parse_vrrp(bytes: &[u8], parser: fn(&[u8]) -> nom::IResult<&[u8], Vrrp>) -> Result<(&[u8], Packet), &[u8]> {
+        match parser(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, vrrp)) =>
+                Result::Ok((leftover, Packet::VRRP(vrrp)))
+        }
+    }
+
+    // Parse SCTP packet
+    fn parse_sctp(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Sctp::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, sctp)) =>
+                Result::Ok((leftover, Packet::SCTP(sctp)))
+        }
+    }
+
     // Parse UDP Header
     fn parse_udp(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
         match Udp::from_bytes(bytes) {
@@ -189,6 +1028,66 @@ impl Packet {
         }
     }
 
+    // Parse DHCP message
+    fn parse_dhcp(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Dhcp::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, dhcp)) =>
+                Result::Ok((leftover, Packet::DHCP(dhcp)))
+        }
+    }
+
+    // Parse DHCPv6 message
+    fn parse_dhcpv6(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Dhcpv6::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, dhcpv6)) =>
+                Result::Ok((leftover, Packet::DHCPv6(dhcpv6)))
+        }
+    }
+
+    // Parse DNS message
+    fn parse_dns(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Dns::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, dns)) =>
+                Result::Ok((leftover, Packet::DNS(dns)))
+        }
+    }
+
+    // Parse DNS message with the 2 byte length prefix
+    fn parse_dns_tcp(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Dns::from_tcp_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, dns)) =>
+                Result::Ok((leftover, Packet::DNS(dns)))
+        }
+    }
+
+    // Parse BFD control packet
+    fn parse_bfd(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Bfd::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, bfd)) =>
+                Result::Ok((leftover, Packet::BFD(bfd)))
+        }
+    }
+
+    // Parse BGP message
+    fn parse_bgp(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Bgp::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, bgp)) =>
+                Result::Ok((leftover, Packet::BGP(bgp)))
+        }
+    }
+
     // Parse GRE Header
     fn parse_gre(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
         match Gre::from_bytes(bytes) {
@@ -200,21 +1099,97 @@ impl Packet {
             }
         }
     }
+
+    // Parse ERSPAN Header
+    fn parse_erspan(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Erspan::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, erspan)) =>
+                Result::Ok((leftover, Packet::ERSPAN(erspan)))
+        }
+    }
+
+    // Parse Geneve Header
+    fn parse_geneve(bytes: &[u8]) -> Result<(&[u8], Packet), &[u8]> {
+        match Geneve::from_bytes(bytes) {
+            Err(_e) =>
+                Result::Err(bytes),
+            Ok((leftover, geneve)) =>
+                Result::Ok((leftover, Packet::GENEVE(geneve)))
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests_pkt {
-    use std::net::Ipv4Addr;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
     use std::str::FromStr;
     use eui48::MacAddress;
     use crate::ethernet::*;
     use crate::arp::*;
+    use crate::bfd::*;
+    use crate::bgp::*;
     use crate::vlan::*;
-    use crate::Packet;
+    use crate::geneve::*;
+    use crate::dhcp::*;
+    use crate::dhcpv6::*;
+    use crate::dns::*;
+    use crate::igmp::*;
+    use crate::ike::*;
+    use crate::ipsec::*;
+    use crate::l2tp::*;
+    use crate::lldp::*;
+    use crate::llc::*;
+    use crate::macsec::*;
+    use crate::ospf::*;
+    use crate::ppp::*;
+    use crate::pppoe::*;
+    use crate::ptp::*;
+    use crate::sctp::*;
+    use crate::stp::*;
+    use crate::vrrp::*;
+    use crate::{DecodeAs, Packet, ParseConfig};
     use crate::Packet::{
         ETHER,
+        LLC,
+        SNAP,
+        STP,
+        LACP,
         VLAN,
+        MACSEC,
+        L2TPv2,
+        L2TPv3,
+        PPPOE,
+        PPP,
+        LCP,
         ARP,
+        GENEVE,
+        GRE,
+        ERSPAN,
+        MPLS,
+        LLDP,
+        IPv4,
+        IPv6,
+        SRH,
+        VXLANGPE,
+        NSH,
+        IGMP,
+        AH,
+        ESP,
+        OSPFv2,
+        VRRP,
+        UDP,
+        SCTP,
+        DHCP,
+        DHCPv6,
+        DNS,
+        BGP,
+        BFD,
+        PTP,
+        RTP,
+        RTCP,
+        IKE,
         Payload
     };
 
@@ -241,11 +1216,13 @@ mod tests_pkt {
                 }),
                 VLAN(Dot1Q {
                     tpid: 33024,
-                    tci: 100
+                    tci: 100,
+                    eth_type: 33024
                 }),
                 VLAN(Dot1Q {
-                    tpid: 2054,
-                    tci: 200
+                    tpid: 33024,
+                    tci: 200,
+                    eth_type: 2054
                 }),
                 ARP(Arp {
                     hardware_type: 1,
@@ -264,4 +1241,860 @@ mod tests_pkt {
             pkt
         )
     }
+
+    #[test]
+    fn parse_qinq() {
+        let frame = [
+            0x00,0x00,0x00,0x00,0x00,0x02,0x00,0x00,
+            0x00,0x00,0x00,0x01,0x88,0xa8,0xa0,0x0a,
+            0x81,0x00,0x00,0x14,0x08,0x06,0x00,0x01,
+            0x08,0x00,0x06,0x04,0x00,0x01,0x00,0x00,
+            0x00,0x00,0x00,0x01,0x0a,0x00,0x00,0x01,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x0a,0x00,
+            0x00,0x02
+        ];
+
+        let pkt = Packet::parse(&frame);
+        assert_eq!(4, pkt.len());
+        assert_eq!(VLAN(Dot1Q { tpid: ETH_P_8021AD, tci: 0xa00a, eth_type: ETH_P_8021Q }), pkt[1]);
+        assert_eq!(VLAN(Dot1Q { tpid: ETH_P_8021Q, tci: 20, eth_type: 0x0806 }), pkt[2]);
+
+        let bytes: Vec<u8> = pkt.into_iter().flat_map(|header| {
+            match header {
+                ETHER(ether) => ether.as_bytes(),
+                VLAN(vlan)   => vlan.as_bytes(),
+                ARP(arp)     => arp.as_bytes(),
+                _            => unreachable!()
+            }
+        }).collect();
+        assert_eq!(frame.to_vec(), bytes);
+    }
+
+    #[test]
+    fn parse_geneve() {
+        let frame = [
+            0x00,0x00,0x00,0x00,0x00,0x02,0x00,0x00,
+            0x00,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+            0x00,0x4e,0x00,0x00,0x40,0x00,0x40,0x11,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+            0x00,0x02,0xc3,0x50,0x17,0xc1,0x00,0x3a,
+            0x00,0x00,0x00,0x00,0x65,0x58,0x00,0x00,
+            0x64,0x00,0xff,0xff,0xff,0xff,0xff,0xff,
+            0x00,0x00,0x00,0x00,0x00,0x03,0x08,0x06,
+            0x00,0x01,0x08,0x00,0x06,0x04,0x00,0x01,
+            0x00,0x00,0x00,0x00,0x00,0x03,0x0a,0x01,
+            0x01,0x01,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x0a,0x01,0x01,0x02
+        ];
+
+        let pkt = Packet::parse(&frame);
+        assert_eq!(6, pkt.len());
+        assert_eq!(
+            GENEVE(Geneve {
+                version:       0,
+                opt_len:       0,
+                oam:           false,
+                critical:      false,
+                protocol_type: 0x6558,
+                vni:           100,
+                options:       vec![]
+            }),
+            pkt[3]
+        );
+        assert_eq!(
+            ETHER(Ethernet {
+                destination: MacAddress::from_str("ff:ff:ff:ff:ff:ff").unwrap(),
+                source: MacAddress::from_str("00:00:00:00:00:03").unwrap(),
+                eth_type: 0x0806
+            }),
+            pkt[4]
+        );
+        assert!(matches!(&pkt[5], ARP(arp) if arp.tpa == Ipv4Addr::new(10,1,1,2)));
+    }
+
+    #[test]
+    fn parse_mpls() {
+        let frame = [
+            0x00,0x00,0x00,0x00,0x00,0x02,0x00,0x00,
+            0x00,0x00,0x00,0x01,0x88,0x47,0x00,0x01,
+            0x20,0xfe,0x00,0x01,0x31,0xfe,0x60,0x00,
+            0x00,0x00,0x00,0x08,0x11,0x40,0x20,0x01,
+            0x0d,0xb8,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x01,0x20,0x01,
+            0x0d,0xb8,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x02,0x82,0x75,
+            0x7a,0x69,0x00,0x08,0x00,0x00
+        ];
+
+        let pkt = Packet::parse(&frame);
+        assert_eq!(4, pkt.len());
+        assert!(matches!(&pkt[1], MPLS(mpls) if mpls.labels.len() == 2));
+        assert!(matches!(&pkt[2], IPv6(ipv6) if ipv6.next_header == 17));
+        assert!(matches!(&pkt[3], UDP(udp) if udp.destination == 31337));
+    }
+
+    #[test]
+    fn parse_mpls_pseudowire() {
+        let frame = [
+            0x00,0x00,0x00,0x00,0x00,0x02,0x00,0x00,
+            0x00,0x00,0x00,0x01,0x88,0x47,0x00,0x3e,
+            0x81,0x40,0x00,0x00,0x00,0x07,0x00,0x00,
+            0x00,0x00,0x00,0x04,0x00,0x00,0x00,0x00,
+            0x00,0x03,0x08,0x00,0x45,0x00,0x00,0x14,
+            0x00,0x00,0x00,0x00,0x40,0xff,0x00,0x00,
+            0x7f,0x00,0x00,0x01,0x7f,0x00,0x00,0x01
+        ];
+
+        let pkt = Packet::parse(&frame);
+        assert_eq!(4, pkt.len());
+        assert!(matches!(&pkt[2], ETHER(ether) if ether.eth_type == 0x0800));
+        assert!(matches!(&pkt[3], IPv4(ipv4) if ipv4.protocol == 255));
+    }
+
+    #[test]
+    fn parse_nvgre() {
+        let frame = [
+            0x00,0x00,0x00,0x00,0x00,0x02,0x00,0x00,
+            0x00,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+            0x00,0x48,0x00,0x00,0x40,0x00,0x40,0x2f,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+            0x00,0x02,0x20,0x00,0x65,0x58,0x00,0x10,
+            0x00,0x05,0xff,0xff,0xff,0xff,0xff,0xff,
+            0x00,0x00,0x00,0x00,0x00,0x03,0x08,0x06,
+            0x00,0x01,0x08,0x00,0x06,0x04,0x00,0x01,
+            0x00,0x00,0x00,0x00,0x00,0x03,0x0a,0x01,
+            0x01,0x01,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x0a,0x01,0x01,0x02
+        ];
+
+        let pkt = Packet::parse(&frame);
+        assert_eq!(5, pkt.len());
+        assert!(matches!(&pkt[2], GRE(gre) if
+            gre.vsid() == 0x1000 &&
+            gre.flow_id() == 5));
+        assert!(matches!(&pkt[4], ARP(arp) if arp.tpa == Ipv4Addr::new(10,1,1,2)));
+    }
+
+    #[test]
+    fn parse_erspan() {
+        let frame = [
+            0x00,0x00,0x00,0x00,0x00,0x02,0x00,0x00,
+            0x00,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+            0x00,0x50,0x00,0x00,0x40,0x00,0x40,0x2f,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+            0x00,0x02,0x10,0x00,0x88,0xbe,0x00,0x00,
+            0x00,0x01,0x10,0x64,0x00,0x05,0x00,0x00,
+            0x00,0x02,0xff,0xff,0xff,0xff,0xff,0xff,
+            0x00,0x00,0x00,0x00,0x00,0x03,0x08,0x06,
+            0x00,0x01,0x08,0x00,0x06,0x04,0x00,0x01,
+            0x00,0x00,0x00,0x00,0x00,0x03,0x0a,0x01,
+            0x01,0x01,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x0a,0x01,0x01,0x02
+        ];
+
+        let pkt = Packet::parse(&frame);
+        assert_eq!(6, pkt.len());
+        assert!(matches!(&pkt[3], ERSPAN(erspan) if
+            erspan.session_id == 5 &&
+            erspan.vlan == 100 &&
+            erspan.index == 2));
+        assert!(matches!(&pkt[5], ARP(arp) if arp.tpa == Ipv4Addr::new(10,1,1,2)));
+    }
+
+    #[test]
+    fn parse_dhcp() {
+        let mut frame = vec![
+            0xff,0xff,0xff,0xff,0xff,0xff,0x24,0xdb,
+            0xac,0x41,0xe5,0x5b,0x08,0x00,0x45,0x00,
+            0x01,0x48,0x00,0x00,0x00,0x00,0x80,0x11,
+            0x39,0xa6,0x00,0x00,0x00,0x00,0xff,0xff,
+            0xff,0xff,0x00,0x44,0x00,0x43,0x01,0x34,
+            0x88,0x14,0x01,0x01,0x06,0x00,0xde,0xad,
+            0xbe,0xef,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x24,0xdb,
+            0xac,0x41,0xe5,0x5b
+        ];
+        frame.extend(vec![0; 202]);
+        frame.extend(vec![
+            0x63,0x82,0x53,0x63,0x35,0x01,0x01,0x3d,
+            0x07,0x01,0x24,0xdb,0xac,0x41,0xe5,0x5b,
+            0x32,0x04,0x00,0x00,0x00,0x00,0x37,0x04,
+            0x01,0x03,0x06,0x2a,0xff
+        ]);
+
+        let pkt = Packet::parse(&frame);
+        assert_eq!(4, pkt.len());
+        assert!(matches!(&pkt[3], DHCP(dhcp) if
+            dhcp.xid == 0xdeadbeef &&
+            dhcp.message_type() == Some(DhcpMessageType::Discover)));
+    }
+
+    #[test]
+    fn parse_dns() {
+        let frame = &mut [
+            0x00,0x00,0x5e,0x00,0x53,0x01,0x00,0x00,
+            0x5e,0x00,0x53,0x02,0x08,0x00,0x45,0x00,
+            0x00,0x39,0x00,0x00,0x00,0x00,0x40,0x11,
+            0x00,0x00,0xc0,0x00,0x02,0x01,0xc0,0x00,
+            0x02,0x35,0x30,0x39,0x00,0x35,0x00,0x25,
+            0x00,0x00,0xab,0xcd,0x01,0x00,0x00,0x01,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x07,0x65,
+            0x78,0x61,0x6d,0x70,0x6c,0x65,0x03,0x63,
+            0x6f,0x6d,0x00,0x00,0x01,0x00,0x01
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(4, pkt.len());
+        assert_eq!(DNS(Dns::query(0xabcd, "example.com", DNS_TYPE_A).unwrap()), pkt[3]);
+    }
+
+    #[test]
+    fn parse_dhcpv6() {
+        let frame = &mut [
+            0x33,0x33,0x00,0x01,0x00,0x02,0x02,0x00,
+            0x00,0x00,0x00,0x01,0x86,0xdd,0x60,0x00,
+            0x00,0x00,0x00,0x1e,0x11,0x01,0xfe,0x80,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x01,0xff,0x02,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x01,0x00,0x02,0x02,0x22,
+            0x02,0x23,0x00,0x1e,0x00,0x00,0x01,0x12,
+            0x34,0x56,0x00,0x01,0x00,0x0a,0x00,0x03,
+            0x00,0x01,0x02,0x00,0x00,0x00,0x00,0x01,
+            0x00,0x08,0x00,0x02,0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(4, pkt.len());
+        assert!(matches!(&pkt[3], DHCPv6(dhcpv6) if
+            dhcpv6.msg_type == Dhcpv6MessageType::Solicit &&
+            dhcpv6.transaction_id == 0x123456 &&
+            dhcpv6.options.len() == 2));
+    }
+
+    #[test]
+    fn parse_lldp() {
+        let frame = &mut [
+            0x01,0x80,0xc2,0x00,0x00,0x0e,0x02,0x00,
+            0x00,0x00,0x00,0x01,0x88,0xcc,0x02,0x07,
+            0x04,0x02,0x00,0x00,0x00,0x00,0x01,0x04,
+            0x05,0x05,0x65,0x74,0x68,0x30,0x06,0x02,
+            0x00,0x78,0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(2, pkt.len());
+        let chassis = MacAddress::from_str("02:00:00:00:00:01").unwrap();
+        assert_eq!(LLDP(Lldp::with_port(chassis, "eth0", 120)), pkt[1]);
+    }
+
+    #[test]
+    fn parse_stp() {
+        let frame = &mut [
+            0x01,0x80,0xc2,0x00,0x00,0x00,0x00,0x1c,
+            0x0e,0x87,0x85,0x04,0x00,0x27,0x42,0x42,
+            0x03,0x00,0x00,0x02,0x02,0x3c,0x80,0x00,
+            0x00,0x1c,0x0e,0x87,0x78,0x00,0x00,0x00,
+            0x00,0x04,0x80,0x00,0x00,0x1c,0x0e,0x87,
+            0x85,0x00,0x80,0x04,0x01,0x00,0x14,0x00,
+            0x02,0x00,0x0f,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(3, pkt.len());
+        assert_eq!(LLC(Llc::stp()), pkt[1]);
+        assert!(matches!(&pkt[2], STP(bpdu) if
+            bpdu.version == RSTP_VERSION &&
+            bpdu.port_role() == PORT_ROLE_DESIGNATED));
+    }
+
+    #[test]
+    fn parse_snap() {
+        let frame = &mut [
+            0xff,0xff,0xff,0xff,0xff,0xff,0x02,0x00,
+            0x00,0x00,0x00,0x01,0x00,0x24,0xaa,0xaa,
+            0x03,0x00,0x00,0x00,0x08,0x06,0x00,0x01,
+            0x08,0x00,0x06,0x04,0x00,0x01,0x02,0x00,
+            0x00,0x00,0x00,0x01,0xc0,0xa8,0x00,0x01,
+            0x00,0x00,0x00,0x00,0x00,0x00,0xc0,0xa8,
+            0x00,0x02,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(4, pkt.len());
+        assert!(matches!(&pkt[2], SNAP(snap) if snap.protocol_id == 0x0806));
+        assert!(matches!(&pkt[3], ARP(arp) if arp.tpa == Ipv4Addr::new(192, 168, 0, 2)));
+    }
+
+    #[test]
+    fn parse_lacp() {
+        let mut frame = vec![
+            0x01,0x80,0xc2,0x00,0x00,0x02,0x00,0x1b,
+            0x21,0x3c,0x9d,0xf8,0x88,0x09,0x01,0x01,
+            0x01,0x14,0xff,0xff,0x00,0x1b,0x21,0x3c,
+            0x9d,0xf8,0x00,0x0f,0x00,0xff,0x00,0x01,
+            0x3d,0x00,0x00,0x00,0x02,0x14
+        ];
+        frame.extend(vec![0; 18]);
+        frame.extend(vec![0x03, 0x10]);
+        frame.extend(vec![0; 66]);
+
+        let pkt = Packet::parse(&frame);
+        assert_eq!(2, pkt.len());
+        assert!(matches!(&pkt[1], LACP(lacp) if
+            lacp.actor.state == 0x3d &&
+            lacp.partner.system == MacAddress::nil()));
+    }
+
+    #[test]
+    fn parse_igmp() {
+        let frame = &mut [
+            0x01,0x00,0x5e,0x00,0x00,0x02,0x02,0x00,
+            0x00,0x00,0x00,0x01,0x08,0x00,0x46,0xc0,
+            0x00,0x20,0x00,0x00,0x40,0x00,0x01,0x02,
+            0x00,0x00,0xc0,0xa8,0x00,0x0a,0xe0,0x00,
+            0x00,0x02,0x94,0x04,0x00,0x00,0x17,0x00,
+            0xf8,0xfc,0xef,0x01,0x01,0x01,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(3, pkt.len());
+        assert_eq!(IGMP(Igmp::leave(Ipv4Addr::new(239, 1, 1, 1))), pkt[2]);
+    }
+
+    #[test]
+    fn parse_sctp() {
+        let frame = &mut [
+            0x00,0x00,0x00,0x00,0x00,0x02,0x00,0x00,
+            0x00,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+            0x00,0x24,0x00,0x00,0x40,0x00,0x40,0x84,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+            0x00,0x02,0x0b,0x59,0x0b,0x59,0x1c,0x2f,
+            0x6a,0x3e,0x00,0x00,0x00,0x00,0x0b,0x00,
+            0x00,0x04,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(4, pkt.len());
+        assert!(matches!(&pkt[2], SCTP(sctp) if
+            sctp.destination == 2905 &&
+            sctp.chunks == vec![SctpChunk::CookieAck]));
+        assert_eq!(Payload(vec![0; 10]), pkt[3]);
+    }
+
+    #[test]
+    fn parse_ospf() {
+        let frame = &mut [
+            0x01,0x00,0x5e,0x00,0x00,0x05,0x00,0x0c,
+            0x29,0x00,0x00,0x08,0x08,0x00,0x45,0xc0,
+            0x00,0x40,0x00,0x00,0x00,0x00,0x01,0x59,
+            0x00,0x00,0xc0,0xa8,0xaa,0x08,0xe0,0x00,
+            0x00,0x05,0x02,0x01,0x00,0x2c,0xc0,0xa8,
+            0xaa,0x08,0x00,0x00,0x00,0x01,0x27,0x3b,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0xff,0xff,0xff,0x00,0x00,0x0a,
+            0x02,0x01,0x00,0x00,0x00,0x28,0xc0,0xa8,
+            0xaa,0x08,0x00,0x00,0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(3, pkt.len());
+        assert!(matches!(&pkt[2], OSPFv2(ospf) if
+            ospf.router_id == Ipv4Addr::new(192, 168, 170, 8) &&
+            ospf.message.packet_type() == OSPF_HELLO &&
+            ospf.verify_checksum()));
+    }
+
+    #[test]
+    fn parse_bgp() {
+        let frame = &mut [
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x08,0x00,0x45,0xc0,
+            0x00,0x4e,0x00,0x00,0x40,0x00,0x01,0x06,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+            0x00,0x02,0x00,0xb3,0xc3,0x50,0x00,0x00,
+            0x00,0x01,0x00,0x00,0x00,0x01,0x50,0x18,
+            0x40,0x00,0x00,0x00,0x00,0x00,0xff,0xff,
+            0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+            0xff,0xff,0xff,0xff,0xff,0xff,0x00,0x13,
+            0x04,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+            0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+            0xff,0x00,0x13,0x04
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(5, pkt.len());
+        assert_eq!(BGP(Bgp::Keepalive), pkt[3]);
+        assert!(matches!(&pkt[4], BGP(bgp) if bgp.msg_type() == BGP_KEEPALIVE));
+    }
+
+    #[test]
+    fn parse_vrrp() {
+        let frame = &mut [
+            0x01,0x00,0x5e,0x00,0x00,0x12,0x00,0x00,
+            0x5e,0x00,0x01,0x01,0x08,0x00,0x45,0xc0,
+            0x00,0x28,0x00,0x00,0x00,0x00,0xff,0x70,
+            0x00,0x00,0xc0,0xa8,0x00,0x02,0xe0,0x00,
+            0x00,0x12,0x21,0x01,0x64,0x01,0x00,0x01,
+            0xba,0x52,0xc0,0xa8,0x00,0x01,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(3, pkt.len());
+        assert_eq!(VRRP(Vrrp::advertisement_v2(1, 100, vec![Ipv4Addr::new(192, 168, 0, 1)])), pkt[2]);
+    }
+
+    #[test]
+    fn parse_vrrp_v6() {
+        let frame = &mut [
+            0x33,0x33,0x00,0x00,0x00,0x12,0x00,0x00,
+            0x5e,0x00,0x02,0x0a,0x86,0xdd,0x60,0x00,
+            0x00,0x00,0x00,0x18,0x70,0xff,0xfe,0x80,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x01,0xff,0x02,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x12,0x31,0x0a,
+            0xc8,0x01,0x00,0x64,0x08,0xf0,0xfe,0x80,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x01,0x00,0x00,0x00,
+            0x00,0x00
+        ];
+        let source = Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1);
+        let address = Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0x100);
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(
+            vec![
+                ETHER(Ethernet {
+                    destination: MacAddress::from_str("33:33:00:00:00:12").unwrap(),
+                    source: MacAddress::from_str("00:00:5e:00:02:0a").unwrap(),
+                    eth_type: 0x86dd
+                }),
+                IPv6(crate::ipv6::IPv6 {
+                    payload_length: 24,
+                    next_header: 112,
+                    hop_limit: 255,
+                    source,
+                    destination: VRRP_MULTICAST_V6,
+                    ..crate::ipv6::IPv6::default()
+                }),
+                VRRP(Vrrp::advertisement_v3(10, 200, vec![IpAddr::V6(address)], IpAddr::V6(source)))
+            ],
+            pkt
+        );
+    }
+
+    #[test]
+    fn parse_bfd() {
+        let frame = &mut [
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x08,0x00,0x45,0xc0,
+            0x00,0x34,0x00,0x00,0x00,0x00,0xff,0x11,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+            0x00,0x02,0xc0,0x00,0x0e,0xc8,0x00,0x20,
+            0x00,0x00,0x20,0xc0,0x03,0x18,0x00,0x00,
+            0x00,0x01,0x00,0x00,0x00,0x02,0x00,0x04,
+            0x93,0xe0,0x00,0x04,0x93,0xe0,0x00,0x00,
+            0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(4, pkt.len());
+        assert_eq!(BFD(Bfd::control(BFD_STATE_UP, 1, 2, 300_000)), pkt[3]);
+    }
+
+    #[test]
+    fn parse_pppoe_lcp() {
+        let frame = &mut [
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x88,0x64,0x11,0x00,
+            0x00,0x2a,0x00,0x0a,0xc0,0x21,0x09,0x02,
+            0x00,0x08,0x12,0x34,0x56,0x78,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(4, pkt.len());
+        assert!(matches!((&pkt[1], &pkt[2], &pkt[3]), (PPPOE(pppoe), PPP(ppp), LCP(lcp)) if
+            *pppoe == Pppoe::session(0x2a, 10) &&
+            ppp.protocol == PPP_LCP &&
+            *lcp == PppControl::echo_request(2, 0x12345678)));
+    }
+
+    #[test]
+    fn parse_pppoe_ipv4() {
+        let frame = &mut [
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x88,0x64,0x11,0x00,
+            0x00,0x2a,0x00,0x1e,0x00,0x21,0x45,0x00,
+            0x00,0x1c,0x00,0x00,0x00,0x00,0x40,0x11,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+            0x00,0x02,0x04,0xd2,0x16,0x2e,0x00,0x08,
+            0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(5, pkt.len());
+        assert!(matches!((&pkt[2], &pkt[3], &pkt[4]), (PPP(ppp), IPv4(ipv4), UDP(udp)) if
+            ppp.protocol == PPP_IP &&
+            ipv4.destination == Ipv4Addr::new(10, 0, 0, 2) &&
+            udp.destination == 5678));
+    }
+
+    #[test]
+    fn parse_l2tpv2() {
+        let frame = &mut [
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+            0x00,0x3e,0x00,0x00,0x00,0x00,0x40,0x11,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+            0x00,0x02,0x06,0xa5,0x06,0xa5,0x00,0x2a,
+            0x00,0x00,0x00,0x02,0x00,0x05,0x00,0x07,
+            0xff,0x03,0x00,0x21,0x45,0x00,0x00,0x1c,
+            0x00,0x00,0x00,0x00,0x40,0x11,0x00,0x00,
+            0xc0,0xa8,0x00,0x01,0xc0,0xa8,0x00,0x02,
+            0x04,0xd2,0x16,0x2e,0x00,0x08,0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(7, pkt.len());
+        assert!(matches!((&pkt[3], &pkt[4], &pkt[5]), (L2TPv2(l2tp), PPP(ppp), IPv4(ipv4)) if
+            *l2tp == L2tpv2::data(5, 7) &&
+            ppp.address_control &&
+            ipv4.destination == Ipv4Addr::new(192, 168, 0, 2)));
+    }
+
+    #[test]
+    fn parse_l2tpv3() {
+        let frame = &mut [
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+            0x00,0x38,0x00,0x00,0x00,0x00,0x40,0x73,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+            0x00,0x02,0x00,0x00,0x10,0x01,0xde,0xad,
+            0xbe,0xef,0x00,0x0c,0x29,0x00,0x00,0x04,
+            0x00,0x0c,0x29,0x00,0x00,0x03,0x08,0x06,
+            0x00,0x01,0x08,0x00,0x06,0x04,0x00,0x01,
+            0x00,0x0c,0x29,0x00,0x00,0x03,0xc0,0xa8,
+            0x00,0x01,0x00,0x00,0x00,0x00,0x00,0x00,
+            0xc0,0xa8,0x00,0x02
+        ];
+
+        let config = ParseConfig::new().l2tpv3_session(4, false);
+        let pkt = Packet::parse_with(frame, &config);
+        assert_eq!(5, pkt.len());
+        assert!(matches!((&pkt[2], &pkt[3], &pkt[4]), (L2TPv3(l2tp), ETHER(ether), ARP(_)) if
+            *l2tp == L2tpv3::data(0x1001, &[0xde, 0xad, 0xbe, 0xef]) &&
+            ether.eth_type == 0x0806));
+
+        let pkt = Packet::parse(frame);
+        assert!(matches!(&pkt[3], ETHER(ether) if ether.eth_type != 0x0806));
+    }
+
+    #[test]
+    fn parse_ah_esp() {
+        let frame = &mut [
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+            0x00,0x38,0x00,0x00,0x00,0x00,0x40,0x33,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+            0x00,0x02,0x32,0x04,0x00,0x00,0x00,0x00,
+            0x10,0x02,0x00,0x00,0x00,0x01,0x00,0x01,
+            0x02,0x03,0x04,0x05,0x06,0x07,0x08,0x09,
+            0x0a,0x0b,0x00,0x00,0x10,0x01,0x00,0x00,
+            0x00,0x01,0xde,0xad,0xbe,0xef
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(5, pkt.len());
+        assert!(matches!((&pkt[2], &pkt[3], &pkt[4]), (AH(ah), ESP(esp), Payload(payload)) if
+            ah.next_header == 50 &&
+            *esp == Esp::header(0x1001, 1) &&
+            *payload == vec![0xde, 0xad, 0xbe, 0xef]));
+    }
+
+    #[cfg(feature = "decrypt")]
+    #[test]
+    fn parse_esp_decrypt() {
+        let inner = &[
+            0x45,0x00,0x00,0x1c,0x00,0x00,0x00,0x00,
+            0x40,0x11,0x00,0x00,0xc0,0xa8,0x00,0x01,
+            0xc0,0xa8,0x00,0x02,0x04,0xd2,0x16,0x2e,
+            0x00,0x08,0x00,0x00
+        ];
+        let sa = EspSa::new(0x1001, EspCipher::AesGcm { key: (0..20).collect() });
+        let esp = sa.encrypt(&Esp::header(0x1001, 1), 4, inner, &[0; 8]).unwrap();
+        let frame = [
+            &[
+                0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+                0x29,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+                0x00,0x5c,0x00,0x00,0x00,0x00,0x40,0x11,
+                0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+                0x00,0x02,0x11,0x94,0x11,0x94,0x00,0x48,
+                0x00,0x00
+            ][..],
+            &esp
+        ].concat();
+
+        let pkt = Packet::parse(&frame);
+        assert_eq!(5, pkt.len());
+        assert!(matches!((&pkt[3], &pkt[4]), (ESP(esp), Payload(_)) if !esp.decrypted));
+
+        let pkt = Packet::parse_with(&frame, &ParseConfig::new().esp_sa(sa));
+        assert_eq!(6, pkt.len());
+        assert!(matches!((&pkt[3], &pkt[4], &pkt[5]), (ESP(esp), IPv4(ipv4), UDP(udp)) if
+            esp.decrypted &&
+            esp.next_header == 4 &&
+            ipv4.destination == Ipv4Addr::new(192, 168, 0, 2) &&
+            udp.destination == 5678));
+    }
+
+    #[test]
+    fn parse_ike_nat_t() {
+        let ike = Ike::request(0x0102030405060708, 0, IKE_SA_INIT, 0, vec![IkePayload::Nonce(vec![0xbb; 16])]);
+        let frame = [
+            &[
+                0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+                0x29,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+                0x00,0x50,0x00,0x00,0x00,0x00,0x40,0x11,
+                0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+                0x00,0x02,0x11,0x94,0x11,0x94,0x00,0x3c,
+                0x00,0x00,0x00,0x00,0x00,0x00
+            ][..],
+            &ike.as_bytes()
+        ].concat();
+
+        let pkt = Packet::parse(&frame);
+        assert_eq!(4, pkt.len());
+        assert!(matches!(&pkt[3], IKE(ike) if
+            ike.exchange_type == IKE_SA_INIT &&
+            ike.payload(IKE_PAYLOAD_NONCE) == Some(&IkePayload::Nonce(vec![0xbb; 16]))));
+    }
+
+    #[test]
+    fn parse_ipip() {
+        let frame = &mut [
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+            0x00,0x30,0x00,0x00,0x00,0x00,0x40,0x04,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+            0x00,0x02,0x45,0x00,0x00,0x1c,0x00,0x00,
+            0x00,0x00,0x40,0x11,0x00,0x00,0xc0,0xa8,
+            0x00,0x01,0xc0,0xa8,0x00,0x02,0x04,0xd2,
+            0x16,0x2e,0x00,0x08,0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(4, pkt.len());
+        assert!(matches!((&pkt[1], &pkt[2], &pkt[3]), (IPv4(outer), IPv4(inner), UDP(_)) if
+            outer.destination == Ipv4Addr::new(10, 0, 0, 2) &&
+            inner.destination == Ipv4Addr::new(192, 168, 0, 2)));
+        assert_eq!(vec![0, 0, 1, 1], (0..pkt.len()).map(|index| Packet::tunnel_depth(&pkt, index)).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn parse_6in4() {
+        let frame = &mut [
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+            0x00,0x44,0x00,0x00,0x00,0x00,0x40,0x29,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+            0x00,0x02,0x60,0x00,0x00,0x00,0x00,0x08,
+            0x11,0x40,0x20,0x01,0x0d,0xb8,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x01,0x20,0x01,0x0d,0xb8,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x02,0x04,0xd2,0x16,0x2e,0x00,0x08,
+            0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(4, pkt.len());
+        assert!(matches!((&pkt[2], &pkt[3]), (IPv6(ipv6), UDP(udp)) if
+            ipv6.next_header == 17 &&
+            udp.destination == 5678));
+        assert_eq!(1, Packet::tunnel_depth(&pkt, 3));
+    }
+
+    #[test]
+    fn parse_srv6() {
+        let frame = &mut [
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x86,0xdd,0x60,0x00,
+            0x00,0x00,0x00,0x30,0x2b,0x40,0x20,0x01,
+            0x0d,0xb8,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x01,0xfc,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x01,0x11,0x04,
+            0x04,0x01,0x01,0x00,0x00,0x00,0x20,0x01,
+            0x0d,0xb8,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x02,0xfc,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x01,0x04,0xd2,
+            0x16,0x2e,0x00,0x08,0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(4, pkt.len());
+        assert!(matches!((&pkt[1], &pkt[2], &pkt[3]), (IPv6(ipv6), SRH(srh), UDP(udp)) if
+            srh.active_segment() == Some(ipv6.destination) &&
+            srh.remaining_segments().len() == 1 &&
+            udp.destination == 5678));
+    }
+
+    #[test]
+    fn parse_nsh_vxlan_gpe() {
+        let frame = &mut [
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+            0x00,0x58,0x00,0x00,0x00,0x00,0x40,0x11,
+            0x00,0x00,0xc0,0xa8,0x00,0x01,0xc0,0xa8,
+            0x00,0x02,0xc0,0x00,0x12,0xb6,0x00,0x44,
+            0x00,0x00,0x0c,0x00,0x00,0x04,0x00,0x00,
+            0x64,0x00,0x0f,0xc6,0x01,0x01,0x00,0x00,
+            0x2a,0xff,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x45,0x00,0x00,0x1c,0x00,0x00,
+            0x00,0x00,0x40,0x11,0x00,0x00,0x0a,0x00,
+            0x00,0x01,0x0a,0x00,0x00,0x02,0x04,0xd2,
+            0x16,0x2e,0x00,0x08,0x00,0x00
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(7, pkt.len());
+        assert!(matches!((&pkt[3], &pkt[4], &pkt[6]), (VXLANGPE(vxlan), NSH(nsh), UDP(udp)) if
+            vxlan.vni == 100 &&
+            nsh.spi == 42 &&
+            nsh.si == 255 &&
+            udp.destination == 5678));
+    }
+
+    #[test]
+    fn parse_macsec_integrity_only() {
+        let frame = &mut [
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x88,0xe5,0x40,0x00,
+            0x00,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+            0x00,0x1c,0x00,0x00,0x00,0x00,0x40,0x11,
+            0x00,0x00,0xc0,0xa8,0x00,0x01,0xc0,0xa8,
+            0x00,0x02,0x04,0xd2,0x16,0x2e,0x00,0x08,
+            0x00,0x00,0x00,0x01,0x02,0x03,0x04,0x05,
+            0x06,0x07,0x08,0x09,0x0a,0x0b,0x0c,0x0d,
+            0x0e,0x0f
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(4, pkt.len());
+        assert!(matches!((&pkt[1], &pkt[3]), (MACSEC(macsec), UDP(udp)) if
+            macsec.packet_number == 1 &&
+            macsec.icv.len() == MACSEC_ICV_LENGTH &&
+            udp.destination == 5678));
+    }
+
+    #[cfg(feature = "decrypt")]
+    #[test]
+    fn parse_macsec_decrypt() {
+        let inner = &[
+            0x45,0x00,0x00,0x1c,0x00,0x00,0x00,0x00,
+            0x40,0x11,0x00,0x00,0xc0,0xa8,0x00,0x01,
+            0xc0,0xa8,0x00,0x02,0x04,0xd2,0x16,0x2e,
+            0x00,0x08,0x00,0x00
+        ];
+        let header = &[
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x88,0xe5
+        ];
+        let ethernet = Ethernet::from_bytes(header).unwrap().1;
+        let sa = MacsecSa::new(0x000c_2900_0001_0001, 1, vec![0x01; 16]);
+        let macsec = sa.encrypt(&Macsec::encrypted(sa.sci, 1, 1), &ethernet, 0x0800, inner).unwrap();
+        let frame = [&header[..], &macsec].concat();
+
+        let pkt = Packet::parse(&frame);
+        assert_eq!(3, pkt.len());
+        assert!(matches!((&pkt[1], &pkt[2]), (MACSEC(macsec), Payload(_)) if !macsec.decrypted));
+
+        let pkt = Packet::parse_with(&frame, &ParseConfig::new().macsec_sa(sa));
+        assert_eq!(4, pkt.len());
+        assert!(matches!((&pkt[1], &pkt[2], &pkt[3]), (MACSEC(macsec), IPv4(ipv4), UDP(udp)) if
+            macsec.decrypted &&
+            macsec.eth_type == 0x0800 &&
+            ipv4.destination == Ipv4Addr::new(192, 168, 0, 2) &&
+            udp.destination == 5678));
+    }
+
+    #[test]
+    fn parse_ptp_udp() {
+        let frame = &mut [
+            0x01,0x00,0x5e,0x00,0x01,0x81,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+            0x00,0x48,0x00,0x00,0x00,0x00,0x01,0x11,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0xe0,0x00,
+            0x01,0x81,0x01,0x3f,0x01,0x3f,0x00,0x34,
+            0x00,0x00,0x00,0x02,0x00,0x2c,0x00,0x00,
+            0x02,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x0c,
+            0x29,0xff,0xfe,0x00,0x00,0x01,0x00,0x01,
+            0x00,0x07,0x00,0x00,0x00,0x00,0x65,0x53,
+            0xf1,0x00,0x00,0x00,0x01,0xf4
+        ];
+
+        let pkt = Packet::parse(frame);
+        assert_eq!(4, pkt.len());
+        assert!(matches!(&pkt[3], PTP(ptp) if
+            ptp.is_two_step() &&
+            ptp.sequence_id == 7 &&
+            ptp.body == PtpBody::Sync { origin_timestamp: PtpTimestamp::new(1_700_000_000, 500) }));
+    }
+
+    #[test]
+    fn parse_rtp_rtcp_ports() {
+        let rtp = &mut [
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+            0x00,0x2c,0x00,0x00,0x00,0x00,0x40,0x11,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+            0x00,0x02,0x13,0x8c,0x13,0x8c,0x00,0x18,
+            0x00,0x00,0x80,0x00,0x00,0x01,0x00,0x00,
+            0x00,0xa0,0xde,0xad,0xbe,0xef,0xd5,0xd5,
+            0xd5,0xd5
+        ];
+        let rtcp = &mut [
+            0x00,0x0c,0x29,0x00,0x00,0x02,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x08,0x00,0x45,0x00,
+            0x00,0x24,0x00,0x00,0x00,0x00,0x40,0x11,
+            0x00,0x00,0x0a,0x00,0x00,0x01,0x0a,0x00,
+            0x00,0x02,0x13,0x8c,0x13,0x8c,0x00,0x10,
+            0x00,0x00,0x81,0xcb,0x00,0x01,0xde,0xad,
+            0xbe,0xef
+        ];
+
+        let pkt = Packet::parse(rtp);
+        assert_eq!(4, pkt.len());
+        assert!(matches!(pkt[3], Payload(_)));
+
+        let config = ParseConfig::new().rtp_ports(5000..=5100);
+        let pkt = Packet::parse_with(rtp, &config);
+        assert_eq!(5, pkt.len());
+        assert!(matches!((&pkt[3], &pkt[4]), (RTP(rtp), Payload(payload)) if
+            rtp.payload_type == 0 &&
+            rtp.ssrc == 0xdeadbeef &&
+            *payload == vec![0xd5; 4]));
+
+        for config in [config, ParseConfig::new().decode_as(5004, DecodeAs::Rtcp)] {
+            let pkt = Packet::parse_with(rtcp, &config);
+            assert_eq!(4, pkt.len());
+            assert!(matches!(&pkt[3], RTCP(rtcp) if rtcp.packets.len() == 1));
+        }
+    }
 }
@@ -0,0 +1,174 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::number::complete::{be_u8, be_u16};
+use nom::{do_parse, IResult};
+use std::fmt;
+use std::io::Cursor;
+
+pub const LLC_SAP_STP:  u8 = 0x42;
+pub const LLC_SAP_SNAP: u8 = 0xaa;
+
+// Unnumbered Information
+pub const LLC_UI: u8 = 0x03;
+
+// IEEE 802.2 LLC header
+#[derive(Debug, PartialEq)]
+pub struct Llc {
+    pub dsap:        u8,
+    pub ssap:        u8,
+    pub control:     u8,
+    // second octet of I and S format control fields
+    pub control_ext: Option<u8>
+}
+
+impl Default for Llc {
+    fn default() -> Llc {
+        Llc {
+            dsap:        LLC_SAP_SNAP,
+            ssap:        LLC_SAP_SNAP,
+            control:     LLC_UI,
+            control_ext: None
+        }
+    }
+}
+
+impl fmt::Display for Llc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "LLC(dsap: 0x{:02x}, ssap: 0x{:02x}, control: 0x{:02x})",
+            self.dsap,
+            self.ssap,
+            self.control
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Llc {
+    // Instantiate a new LLC header for SNAP
+    pub fn new() -> Llc { Llc::default() }
+
+    // Instantiate a new LLC header for spanning tree BPDUs
+    pub fn stp() -> Llc {
+        Llc { dsap: LLC_SAP_STP, ssap: LLC_SAP_STP, ..Llc::default() }
+    }
+
+    // Unnumbered format control field is a single octet
+    pub fn is_unnumbered(&self) -> bool { self.control & 0x03 == 0x03 }
+
+    // Encode the LLC header into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.dsap);
+        cursor.write_u8(self.ssap);
+        cursor.write_u8(self.control);
+        if let Some(control_ext) = self.control_ext {
+            cursor.write_u8(control_ext);
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Llc> {
+        let (bytes1, (dsap, ssap, control)) = do_parse!(
+            bytes,
+            dsap:    be_u8 >>
+            ssap:    be_u8 >>
+            control: be_u8 >>
+                ((dsap, ssap, control))
+        )?;
+
+        if control & 0x03 == 0x03 {
+            return Ok((bytes1, Llc { dsap, ssap, control, control_ext: None }))
+        }
+
+        let (bytes2, control_ext) = be_u8(bytes1)?;
+        Ok((bytes2, Llc { dsap, ssap, control, control_ext: Some(control_ext) }))
+    }
+}
+
+// Subnetwork Access Protocol header
+#[derive(Debug, PartialEq, Default)]
+pub struct Snap {
+    pub oui:         u32,
+    pub protocol_id: u16
+}
+
+impl fmt::Display for Snap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "SNAP(oui: 0x{:06x}, pid: 0x{:04x})",
+            self.oui,
+            self.protocol_id
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Snap {
+    // Instantiate a new SNAP header
+    pub fn new() -> Snap { Snap::default() }
+
+    // Encode the SNAP header into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u24::<NetworkEndian>(self.oui & 0x00ff_ffff);
+        cursor.write_u16::<NetworkEndian>(self.protocol_id);
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Snap> {
+        do_parse!(
+            bytes,
+            oui_hi:      be_u8  >>
+            oui_lo:      be_u16 >>
+            protocol_id: be_u16 >>
+                (
+                    Snap {
+                        oui: (oui_hi as u32) << 16 | oui_lo as u32,
+                        protocol_id
+                    }
+                )
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests_llc {
+    use crate::llc::*;
+
+    #[test]
+    fn parse() {
+        let frame = &mut [
+            0xaa,0xaa,0x03,0x00,0x00,0x0c,0x01,0x0b,
+            0x00
+        ];
+
+        let (leftover, llc) = Llc::from_bytes(frame).unwrap();
+        assert_eq!(Llc::new(), llc);
+        assert!(llc.is_unnumbered());
+        assert_eq!("LLC(dsap: 0xaa, ssap: 0xaa, control: 0x03)", format!("{}", llc));
+
+        let (leftover, snap) = Snap::from_bytes(leftover).unwrap();
+        assert_eq!(Snap { oui: 0x00000c, protocol_id: 0x010b }, snap);
+        assert_eq!("SNAP(oui: 0x00000c, pid: 0x010b)", format!("{}", snap));
+        assert_eq!(1, leftover.len());
+        assert_eq!(frame[..8].to_vec(), [llc.as_bytes(), snap.as_bytes()].concat());
+    }
+
+    #[test]
+    fn parse_information_format() {
+        let frame = &mut [0xf0,0xf0,0x0a,0x0b];
+
+        let (leftover, llc) = Llc::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert!(!llc.is_unnumbered());
+        assert_eq!(0x0a, llc.control);
+        assert_eq!(Some(0x0b), llc.control_ext);
+        assert_eq!(frame.to_vec(), llc.as_bytes());
+    }
+}
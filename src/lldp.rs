@@ -0,0 +1,575 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use eui48::MacAddress;
+use nom::bytes::complete::take;
+use nom::number::complete::be_u16;
+use nom::IResult;
+use std::fmt;
+use std::io::{Cursor, Write};
+
+pub const LLDP_MULTICAST: [u8; 6] = [0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e];
+
+// Largest information string, management address, OID and VLAN name
+pub const LLDP_MAX_TLV_LENGTH:     usize = 511;
+pub const LLDP_MAX_ADDRESS_LENGTH: usize = 31;
+pub const LLDP_MAX_OID_LENGTH:     usize = 128;
+pub const LLDP_MAX_VLAN_NAME:      usize = 32;
+
+// Chassis ID subtypes
+pub const CHASSIS_ID_INTERFACE_ALIAS: u8 = 2;
+pub const CHASSIS_ID_MAC_ADDRESS:     u8 = 4;
+pub const CHASSIS_ID_NETWORK_ADDRESS: u8 = 5;
+pub const CHASSIS_ID_INTERFACE_NAME:  u8 = 6;
+pub const CHASSIS_ID_LOCAL:           u8 = 7;
+
+// Port ID subtypes
+pub const PORT_ID_INTERFACE_ALIAS: u8 = 1;
+pub const PORT_ID_MAC_ADDRESS:     u8 = 3;
+pub const PORT_ID_NETWORK_ADDRESS: u8 = 4;
+pub const PORT_ID_INTERFACE_NAME:  u8 = 5;
+pub const PORT_ID_LOCAL:           u8 = 7;
+
+// System capabilities
+pub const LLDP_CAP_OTHER:     u16 = 0x0001;
+pub const LLDP_CAP_REPEATER:  u16 = 0x0002;
+pub const LLDP_CAP_BRIDGE:    u16 = 0x0004;
+pub const LLDP_CAP_WLAN_AP:   u16 = 0x0008;
+pub const LLDP_CAP_ROUTER:    u16 = 0x0010;
+pub const LLDP_CAP_TELEPHONE: u16 = 0x0020;
+pub const LLDP_CAP_STATION:   u16 = 0x0080;
+
+pub const OUI_IEEE_8021: [u8; 3] = [0x00, 0x80, 0xc2];
+pub const OUI_IEEE_8023: [u8; 3] = [0x00, 0x12, 0x0f];
+
+// IEEE 802.1 organizationally specific TLVs
+#[derive(Debug, PartialEq, Clone)]
+pub enum Dot1Tlv {
+    PortVlanId(u16),
+    PortAndProtocolVlanId { flags: u8, vid: u16 },
+    VlanName { vid: u16, name: String },
+    Unknown { subtype: u8, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl Dot1Tlv {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        match self {
+            Dot1Tlv::PortVlanId(vid) => {
+                cursor.write_u8(1);
+                cursor.write_u16::<NetworkEndian>(vid);
+            },
+            Dot1Tlv::PortAndProtocolVlanId { flags, vid } => {
+                cursor.write_u8(2);
+                cursor.write_u8(flags);
+                cursor.write_u16::<NetworkEndian>(vid);
+            },
+            Dot1Tlv::VlanName { vid, name } => {
+                cursor.write_u8(3);
+                cursor.write_u16::<NetworkEndian>(vid);
+                cursor.write_u8(name.len() as u8);
+                cursor.write_all(name.as_bytes());
+            },
+            Dot1Tlv::Unknown { subtype, data } => {
+                cursor.write_u8(subtype);
+                cursor.write_all(&data);
+            }
+        }
+        cursor.into_inner()
+    }
+
+    // Parse the information string following the OUI, None when it lacks
+    // the subtype
+    pub fn from_bytes(bytes: &[u8]) -> Option<Dot1Tlv> {
+        let (&subtype, data) = bytes.split_first()?;
+        let tlv = match (subtype, data.len()) {
+            (1, 2) =>
+                Dot1Tlv::PortVlanId(u16::from_be_bytes([data[0], data[1]])),
+            (2, 3) =>
+                Dot1Tlv::PortAndProtocolVlanId { flags: data[0], vid: u16::from_be_bytes([data[1], data[2]]) },
+            (3, len) if len >= 3 && len == 3 + data[2] as usize => Dot1Tlv::VlanName {
+                vid:  u16::from_be_bytes([data[0], data[1]]),
+                name: String::from_utf8_lossy(&data[3..]).into_owned()
+            },
+            _ =>
+                Dot1Tlv::Unknown { subtype, data: data.to_vec() }
+        };
+        Some(tlv)
+    }
+}
+
+// IEEE 802.3 organizationally specific TLVs
+#[derive(Debug, PartialEq, Clone)]
+pub enum Dot3Tlv {
+    MacPhyConfig { autoneg: u8, pmd_capability: u16, mau_type: u16 },
+    LinkAggregation { status: u8, port_id: u32 },
+    MaxFrameSize(u16),
+    Unknown { subtype: u8, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl Dot3Tlv {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        match self {
+            Dot3Tlv::MacPhyConfig { autoneg, pmd_capability, mau_type } => {
+                cursor.write_u8(1);
+                cursor.write_u8(autoneg);
+                cursor.write_u16::<NetworkEndian>(pmd_capability);
+                cursor.write_u16::<NetworkEndian>(mau_type);
+            },
+            Dot3Tlv::LinkAggregation { status, port_id } => {
+                cursor.write_u8(3);
+                cursor.write_u8(status);
+                cursor.write_u32::<NetworkEndian>(port_id);
+            },
+            Dot3Tlv::MaxFrameSize(size) => {
+                cursor.write_u8(4);
+                cursor.write_u16::<NetworkEndian>(size);
+            },
+            Dot3Tlv::Unknown { subtype, data } => {
+                cursor.write_u8(subtype);
+                cursor.write_all(&data);
+            }
+        }
+        cursor.into_inner()
+    }
+
+    // Parse the information string following the OUI, None when it lacks
+    // the subtype
+    pub fn from_bytes(bytes: &[u8]) -> Option<Dot3Tlv> {
+        let (&subtype, data) = bytes.split_first()?;
+        let tlv = match (subtype, data.len()) {
+            (1, 5) => Dot3Tlv::MacPhyConfig {
+                autoneg:        data[0],
+                pmd_capability: u16::from_be_bytes([data[1], data[2]]),
+                mau_type:       u16::from_be_bytes([data[3], data[4]])
+            },
+            (3, 5) => Dot3Tlv::LinkAggregation {
+                status:  data[0],
+                port_id: u32::from_be_bytes([data[1], data[2], data[3], data[4]])
+            },
+            (4, 2) =>
+                Dot3Tlv::MaxFrameSize(u16::from_be_bytes([data[0], data[1]])),
+            _ =>
+                Dot3Tlv::Unknown { subtype, data: data.to_vec() }
+        };
+        Some(tlv)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum LldpTlv {
+    End,
+    ChassisId { subtype: u8, id: Vec<u8> },
+    PortId { subtype: u8, id: Vec<u8> },
+    Ttl(u16),
+    PortDescription(String),
+    SystemName(String),
+    SystemDescription(String),
+    Capabilities { capabilities: u16, enabled: u16 },
+    ManagementAddress {
+        address_subtype:   u8,
+        address:           Vec<u8>,
+        interface_subtype: u8,
+        interface_number:  u32,
+        oid:               Vec<u8>
+    },
+    Dot1(Dot1Tlv),
+    Dot3(Dot3Tlv),
+    OrgSpecific { oui: [u8; 3], subtype: u8, data: Vec<u8> },
+    Unknown { tlv_type: u8, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl LldpTlv {
+    // Whether the TLV fits its length fields
+    pub fn is_valid(&self) -> bool {
+        let lengths = match self {
+            LldpTlv::ManagementAddress { address, oid, .. } =>
+                (1..=LLDP_MAX_ADDRESS_LENGTH).contains(&address.len()) && oid.len() <= LLDP_MAX_OID_LENGTH,
+            LldpTlv::Dot1(Dot1Tlv::VlanName { name, .. }) =>
+                name.len() <= LLDP_MAX_VLAN_NAME,
+            _ =>
+                true
+        };
+        lengths && self.clone().as_bytes().len() - 2 <= LLDP_MAX_TLV_LENGTH
+    }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let data: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(data);
+        let tlv_type = match self {
+            LldpTlv::End => 0,
+            LldpTlv::ChassisId { subtype, id } => {
+                cursor.write_u8(subtype);
+                cursor.write_all(&id);
+                1
+            },
+            LldpTlv::PortId { subtype, id } => {
+                cursor.write_u8(subtype);
+                cursor.write_all(&id);
+                2
+            },
+            LldpTlv::Ttl(ttl) => {
+                cursor.write_u16::<NetworkEndian>(ttl);
+                3
+            },
+            LldpTlv::PortDescription(desc) => {
+                cursor.write_all(desc.as_bytes());
+                4
+            },
+            LldpTlv::SystemName(name) => {
+                cursor.write_all(name.as_bytes());
+                5
+            },
+            LldpTlv::SystemDescription(desc) => {
+                cursor.write_all(desc.as_bytes());
+                6
+            },
+            LldpTlv::Capabilities { capabilities, enabled } => {
+                cursor.write_u16::<NetworkEndian>(capabilities);
+                cursor.write_u16::<NetworkEndian>(enabled);
+                7
+            },
+            LldpTlv::ManagementAddress { address_subtype, address, interface_subtype, interface_number, oid } => {
+                cursor.write_u8((address.len() + 1) as u8);
+                cursor.write_u8(address_subtype);
+                cursor.write_all(&address);
+                cursor.write_u8(interface_subtype);
+                cursor.write_u32::<NetworkEndian>(interface_number);
+                cursor.write_u8(oid.len() as u8);
+                cursor.write_all(&oid);
+                8
+            },
+            LldpTlv::Dot1(tlv) => {
+                cursor.write_all(&OUI_IEEE_8021);
+                cursor.write_all(&tlv.as_bytes());
+                127
+            },
+            LldpTlv::Dot3(tlv) => {
+                cursor.write_all(&OUI_IEEE_8023);
+                cursor.write_all(&tlv.as_bytes());
+                127
+            },
+            LldpTlv::OrgSpecific { oui, subtype, data } => {
+                cursor.write_all(&oui);
+                cursor.write_u8(subtype);
+                cursor.write_all(&data);
+                127
+            },
+            LldpTlv::Unknown { tlv_type, data } => {
+                cursor.write_all(&data);
+                tlv_type
+            }
+        };
+        let data = cursor.into_inner();
+
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u16::<NetworkEndian>((tlv_type as u16) << 9 | (data.len() as u16 & 0x01ff));
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], LldpTlv> {
+        let (bytes1, header) = be_u16(bytes)?;
+        let (bytes2, data)   = take((header & 0x01ff) as usize)(bytes1)?;
+        Ok((bytes2, parse_tlv_data((header >> 9) as u8, data)))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Lldp {
+    pub tlvs: Vec<LldpTlv>
+}
+
+impl Default for Lldp {
+    fn default() -> Lldp {
+        Lldp {
+            tlvs: vec![LldpTlv::End]
+        }
+    }
+}
+
+impl fmt::Display for Lldp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let chassis_id = match self.chassis_id() {
+            Some((CHASSIS_ID_MAC_ADDRESS, id)) if id.len() == 6 =>
+                MacAddress::from_bytes(id).unwrap().to_hex_string(),
+            Some((_subtype, id)) =>
+                String::from_utf8_lossy(id).into_owned(),
+            None =>
+                String::new()
+        };
+        let port_id = match self.port_id() {
+            Some((PORT_ID_MAC_ADDRESS, id)) if id.len() == 6 =>
+                MacAddress::from_bytes(id).unwrap().to_hex_string(),
+            Some((_subtype, id)) =>
+                String::from_utf8_lossy(id).into_owned(),
+            None =>
+                String::new()
+        };
+
+        write!(
+            f,
+            "LLDP(\
+             chassis: \"{}\", \
+             port: \"{}\", \
+             ttl: {}\
+             )",
+            chassis_id,
+            port_id,
+            self.ttl().unwrap_or(0)
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Lldp {
+    // Instantiate an empty LLDPDU
+    pub fn new() -> Lldp { Lldp::default() }
+
+    // Build an LLDPDU with the mandatory TLVs identifying a port by its name
+    pub fn with_port(chassis: MacAddress, port: &str, ttl: u16) -> Lldp {
+        Lldp {
+            tlvs: vec![
+                LldpTlv::ChassisId { subtype: CHASSIS_ID_MAC_ADDRESS, id: chassis.as_bytes().to_vec() },
+                LldpTlv::PortId { subtype: PORT_ID_INTERFACE_NAME, id: port.as_bytes().to_vec() },
+                LldpTlv::Ttl(ttl),
+                LldpTlv::End
+            ]
+        }
+    }
+
+    // Insert a TLV before the End TLV. None when the TLV does not fit its
+    // length fields
+    pub fn push_tlv(&mut self, tlv: LldpTlv) -> Option<()> {
+        if !tlv.is_valid() {
+            return None
+        }
+        match self.tlvs.iter().position(|t| *t == LldpTlv::End) {
+            Some(pos) => self.tlvs.insert(pos, tlv),
+            None => self.tlvs.push(tlv)
+        }
+        Some(())
+    }
+
+    pub fn chassis_id(&self) -> Option<(u8, &[u8])> {
+        self.tlvs.iter().find_map(|tlv| match tlv {
+            LldpTlv::ChassisId { subtype, id } => Some((*subtype, id.as_slice())),
+            _ => None
+        })
+    }
+
+    pub fn port_id(&self) -> Option<(u8, &[u8])> {
+        self.tlvs.iter().find_map(|tlv| match tlv {
+            LldpTlv::PortId { subtype, id } => Some((*subtype, id.as_slice())),
+            _ => None
+        })
+    }
+
+    pub fn ttl(&self) -> Option<u16> {
+        self.tlvs.iter().find_map(|tlv| match tlv {
+            LldpTlv::Ttl(ttl) => Some(*ttl),
+            _ => None
+        })
+    }
+
+    pub fn system_name(&self) -> Option<&str> {
+        self.tlvs.iter().find_map(|tlv| match tlv {
+            LldpTlv::SystemName(name) => Some(name.as_str()),
+            _ => None
+        })
+    }
+
+    // Encode the LLDPDU into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        self.tlvs
+            .into_iter()
+            .flat_map(|tlv| tlv.as_bytes())
+            .collect()
+    }
+
+    // Parse a byte slice up to the End TLV
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Lldp> {
+        let mut tlvs = Vec::new();
+        let mut b = bytes;
+
+        loop {
+            let (leftover, tlv) = LldpTlv::from_bytes(b)?;
+            b = leftover;
+
+            if tlv == LldpTlv::End || b.is_empty() {
+                tlvs.push(tlv);
+                return Ok((b, Lldp { tlvs }))
+            }
+
+            tlvs.push(tlv);
+        }
+    }
+}
+
+// private functions
+
+fn parse_tlv_data(tlv_type: u8, data: &[u8]) -> LldpTlv {
+    match (tlv_type, data.len()) {
+        (0, 0) =>
+            LldpTlv::End,
+        (1, len) if len >= 2 =>
+            LldpTlv::ChassisId { subtype: data[0], id: data[1..].to_vec() },
+        (2, len) if len >= 2 =>
+            LldpTlv::PortId { subtype: data[0], id: data[1..].to_vec() },
+        (3, 2) =>
+            LldpTlv::Ttl(u16::from_be_bytes([data[0], data[1]])),
+        (4, _) =>
+            LldpTlv::PortDescription(String::from_utf8_lossy(data).into_owned()),
+        (5, _) =>
+            LldpTlv::SystemName(String::from_utf8_lossy(data).into_owned()),
+        (6, _) =>
+            LldpTlv::SystemDescription(String::from_utf8_lossy(data).into_owned()),
+        (7, 4) => LldpTlv::Capabilities {
+            capabilities: u16::from_be_bytes([data[0], data[1]]),
+            enabled:      u16::from_be_bytes([data[2], data[3]])
+        },
+        (8, len) if len >= 9 =>
+            parse_management_address(data)
+                .unwrap_or_else(|| LldpTlv::Unknown { tlv_type, data: data.to_vec() }),
+        (127, len) if len >= 4 => {
+            let oui = [data[0], data[1], data[2]];
+            let tlv = match oui {
+                OUI_IEEE_8021 => Dot1Tlv::from_bytes(&data[3..]).map(LldpTlv::Dot1),
+                OUI_IEEE_8023 => Dot3Tlv::from_bytes(&data[3..]).map(LldpTlv::Dot3),
+                _ => Some(LldpTlv::OrgSpecific { oui, subtype: data[3], data: data[4..].to_vec() })
+            };
+            tlv.unwrap_or_else(|| LldpTlv::Unknown { tlv_type, data: data.to_vec() })
+        },
+        _ =>
+            LldpTlv::Unknown { tlv_type, data: data.to_vec() }
+    }
+}
+
+fn parse_management_address(data: &[u8]) -> Option<LldpTlv> {
+    let addr_len = data[0] as usize;
+    if addr_len < 1 || data.len() < addr_len + 7 { return None }
+    let oid_len = data[addr_len + 6] as usize;
+    if data.len() != addr_len + 7 + oid_len { return None }
+
+    Some(LldpTlv::ManagementAddress {
+        address_subtype:   data[1],
+        address:           data[2..addr_len + 1].to_vec(),
+        interface_subtype: data[addr_len + 1],
+        interface_number:  u32::from_be_bytes([
+            data[addr_len + 2], data[addr_len + 3], data[addr_len + 4], data[addr_len + 5]
+        ]),
+        oid:               data[addr_len + 7..].to_vec()
+    })
+}
+
+#[cfg(test)]
+mod tests_lldp {
+    use crate::lldp::*;
+    use eui48::MacAddress;
+    use std::str::FromStr;
+
+    #[test]
+    fn parse() {
+        let frame = &mut [
+            0x02,0x07,0x04,0x00,0x19,0x2f,0xa7,0xb2,
+            0x8d,0x04,0x07,0x05,0x47,0x69,0x31,0x2f,
+            0x30,0x31,0x06,0x02,0x00,0x78,0x0a,0x04,
+            0x73,0x77,0x30,0x31,0x0e,0x04,0x00,0x14,
+            0x00,0x04,0x10,0x0c,0x05,0x01,0xc0,0x00,
+            0x02,0x01,0x02,0x00,0x00,0x00,0x01,0x00,
+            0xfe,0x06,0x00,0x80,0xc2,0x01,0x00,0x64,
+            0xfe,0x09,0x00,0x12,0x0f,0x01,0x03,0x6c,
+            0x01,0x00,0x10,0xfe,0x06,0x00,0x12,0x0f,
+            0x04,0x05,0xee,0x00,0x00,0x00,0x00
+        ];
+
+        let (leftover, lldp) = Lldp::from_bytes(frame).unwrap();
+        assert_eq!(vec![0x00, 0x00], leftover.to_vec());
+        assert_eq!(Some((CHASSIS_ID_MAC_ADDRESS, &[0x00, 0x19, 0x2f, 0xa7, 0xb2, 0x8d][..])), lldp.chassis_id());
+        assert_eq!(Some((PORT_ID_INTERFACE_NAME, &b"Gi1/01"[..])), lldp.port_id());
+        assert_eq!(Some(120), lldp.ttl());
+        assert_eq!(Some("sw01"), lldp.system_name());
+        assert_eq!(vec![
+            LldpTlv::Capabilities {
+                capabilities: LLDP_CAP_BRIDGE | LLDP_CAP_ROUTER,
+                enabled:      LLDP_CAP_BRIDGE
+            },
+            LldpTlv::ManagementAddress {
+                address_subtype:   1,
+                address:           vec![192, 0, 2, 1],
+                interface_subtype: 2,
+                interface_number:  1,
+                oid:               Vec::new()
+            },
+            LldpTlv::Dot1(Dot1Tlv::PortVlanId(100)),
+            LldpTlv::Dot3(Dot3Tlv::MacPhyConfig { autoneg: 0x03, pmd_capability: 0x6c01, mau_type: 16 }),
+            LldpTlv::Dot3(Dot3Tlv::MaxFrameSize(1518)),
+            LldpTlv::End
+        ], lldp.tlvs[4..].to_vec());
+        assert_eq!("LLDP(chassis: \"00:19:2f:a7:b2:8d\", port: \"Gi1/01\", ttl: 120)", format!("{}", lldp));
+        assert_eq!(frame[..frame.len() - 2].to_vec(), lldp.as_bytes());
+    }
+
+    #[test]
+    fn build() {
+        let chassis = MacAddress::from_str("02:00:00:00:00:01").unwrap();
+        let mut lldp = Lldp::with_port(chassis, "eth0", 120);
+        lldp.push_tlv(LldpTlv::SystemName(String::from("host01"))).unwrap();
+        lldp.push_tlv(LldpTlv::Dot1(Dot1Tlv::VlanName { vid: 10, name: String::from("mgmt") })).unwrap();
+        lldp.push_tlv(LldpTlv::OrgSpecific { oui: [0x00, 0x00, 0x5e], subtype: 1, data: vec![0xff] }).unwrap();
+
+        let expected = vec![
+            0x02,0x07,0x04,0x02,0x00,0x00,0x00,0x00,
+            0x01,0x04,0x05,0x05,0x65,0x74,0x68,0x30,
+            0x06,0x02,0x00,0x78,0x0a,0x06,0x68,0x6f,
+            0x73,0x74,0x30,0x31,0xfe,0x0b,0x00,0x80,
+            0xc2,0x03,0x00,0x0a,0x04,0x6d,0x67,0x6d,
+            0x74,0xfe,0x05,0x00,0x00,0x5e,0x01,0xff,
+            0x00,0x00
+        ];
+        let bytes = lldp.as_bytes();
+        assert_eq!(expected, bytes);
+
+        let lldp = Lldp::from_bytes(&bytes).unwrap().1;
+        assert_eq!(
+            LldpTlv::Dot1(Dot1Tlv::VlanName { vid: 10, name: String::from("mgmt") }),
+            lldp.tlvs[4]
+        );
+    }
+
+    #[test]
+    fn parse_empty_organizational() {
+        assert_eq!(None, Dot1Tlv::from_bytes(&[]));
+        assert_eq!(None, Dot3Tlv::from_bytes(&[]));
+
+        let tlv = &mut [0xfe,0x03,0x00,0x80,0xc2];
+        let tlv = LldpTlv::from_bytes(tlv).unwrap().1;
+        assert_eq!(LldpTlv::Unknown { tlv_type: 127, data: vec![0x00, 0x80, 0xc2] }, tlv);
+        assert_eq!(vec![0xfe, 0x03, 0x00, 0x80, 0xc2], tlv.as_bytes());
+    }
+
+    #[test]
+    fn build_length() {
+        let mut lldp = Lldp::new();
+        let address = |address: Vec<u8>, oid: Vec<u8>| LldpTlv::ManagementAddress {
+            address_subtype:   1,
+            address,
+            interface_subtype: 2,
+            interface_number:  1,
+            oid
+        };
+        assert!(lldp.push_tlv(address(vec![0x0a; 255], Vec::new())).is_none());
+        assert!(lldp.push_tlv(address(vec![0x0a; 4], vec![0x01; 256])).is_none());
+        assert!(lldp.push_tlv(LldpTlv::SystemDescription("d".repeat(512))).is_none());
+        assert_eq!(vec![LldpTlv::End], lldp.tlvs);
+
+        lldp.push_tlv(address(vec![0x0a; 4], vec![0x01; 8])).unwrap();
+        lldp.push_tlv(LldpTlv::SystemDescription("d".repeat(511))).unwrap();
+        assert_eq!(3, lldp.tlvs.len());
+    }
+}
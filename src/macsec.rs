@@ -0,0 +1,296 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::combinator::{cond, verify};
+use nom::number::complete::{be_u8, be_u16, be_u32, be_u64};
+use nom::{call, do_parse, IResult};
+use std::fmt;
+use std::io::Cursor;
+
+#[cfg(feature = "decrypt")]
+use aes_gcm::aead::{AeadInPlace, KeyInit};
+#[cfg(feature = "decrypt")]
+use aes_gcm::{Aes128Gcm, Aes256Gcm, Nonce, Tag};
+#[cfg(feature = "decrypt")]
+use crate::ethernet::Ethernet;
+
+pub const ETH_P_MACSEC: u16 = 0x88e5;
+
+// Length of the default GCM-AES ICV
+pub const MACSEC_ICV_LENGTH: usize = 16;
+
+// Port identifier of the SCI when the SecTAG does not carry one
+pub const MACSEC_PORT_ES: u16 = 0x0001;
+
+// MACsec Security TAG (IEEE 802.1AE), the ICV is split from the end of the
+// secure data
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Macsec {
+    pub end_station:    bool,
+    pub single_copy:    bool,
+    pub encrypted:      bool,
+    pub changed:        bool,
+    pub an:             u8,
+    pub short_length:   u8,
+    pub packet_number:  u32,
+    pub sci:            Option<u64>,
+    // Inner EtherType, known for integrity only or decrypted frames
+    pub eth_type:       u16,
+    pub decrypted:      bool,
+    pub icv:            Vec<u8>
+}
+
+impl fmt::Display for Macsec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "MACsec(\
+             an: {}, \
+             pn: {}, \
+             sci: 0x{:016x}, \
+             type: 0x{:x}\
+             )",
+            self.an,
+            self.packet_number,
+            self.sci.unwrap_or_default(),
+            self.eth_type
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Macsec {
+    // Instantiate a new SecTAG
+    pub fn new() -> Macsec { Macsec::default() }
+
+    // Instantiate the SecTAG of a confidentiality protected frame
+    pub fn encrypted(sci: u64, an: u8, packet_number: u32) -> Macsec {
+        Macsec {
+            encrypted: true,
+            changed:   true,
+            an:        an & 0x03,
+            packet_number,
+            sci:       Some(sci),
+            ..Macsec::default()
+        }
+    }
+
+    // Whether the secure data is confidentiality protected
+    pub fn is_confidential(&self) -> bool { self.encrypted || self.changed }
+
+    // The SCI of the SecTAG, or the one implied by the source address
+    pub fn sci_or(&self, source: &[u8]) -> u64 {
+        self.sci.unwrap_or_else(|| {
+            let mut sci = [0; 8];
+            sci[..6].copy_from_slice(&source[..6]);
+            sci[6..].copy_from_slice(&MACSEC_PORT_ES.to_be_bytes());
+            u64::from_be_bytes(sci)
+        })
+    }
+
+    // Encode the SecTAG into a byte slice, the EtherType of integrity only
+    // frames follows it
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        let end_station = if self.end_station { 0x40 } else { 0x00 };
+        let has_sci     = if self.sci.is_some() { 0x20 } else { 0x00 };
+        let single_copy = if self.single_copy { 0x10 } else { 0x00 };
+        let encrypted   = if self.encrypted { 0x08 } else { 0x00 };
+        let changed     = if self.changed { 0x04 } else { 0x00 };
+        cursor.write_u8(end_station | has_sci | single_copy | encrypted | changed | (self.an & 0x03));
+        cursor.write_u8(self.short_length & 0x3f);
+        cursor.write_u32::<NetworkEndian>(self.packet_number);
+        if let Some(sci) = self.sci {
+            cursor.write_u64::<NetworkEndian>(sci);
+        }
+        if !self.encrypted && !self.changed {
+            cursor.write_u16::<NetworkEndian>(self.eth_type);
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice, the leftover is the secure data without the ICV
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Macsec> {
+        let (bytes1, macsec) = do_parse!(
+            bytes,
+            tci:           call!(verify(be_u8, |tci| tci & 0x80 == 0)) >>
+            short_length:  be_u8  >>
+            packet_number: be_u32 >>
+            sci:           call!(cond(tci & 0x20 != 0, be_u64)) >>
+                (
+                    Macsec {
+                        end_station:  tci & 0x40 != 0,
+                        single_copy:  tci & 0x10 != 0,
+                        encrypted:    tci & 0x08 != 0,
+                        changed:      tci & 0x04 != 0,
+                        an:           tci & 0x03,
+                        short_length: short_length & 0x3f,
+                        packet_number,
+                        sci,
+                        ..Macsec::default()
+                    }
+                )
+        )?;
+
+        // Frames shorter than the minimum are padded after the ICV
+        let length = match macsec.short_length {
+            0 => bytes1.len().saturating_sub(MACSEC_ICV_LENGTH),
+            n => n as usize
+        };
+        let (bytes2, data) = take(length)(bytes1)?;
+        let (_, icv) = take(MACSEC_ICV_LENGTH)(bytes2)?;
+        let macsec = Macsec { icv: icv.to_vec(), ..macsec };
+
+        if macsec.is_confidential() {
+            return Ok((data, macsec))
+        }
+        let (data, eth_type) = be_u16(data)?;
+        Ok((data, Macsec { eth_type, ..macsec }))
+    }
+}
+
+#[cfg(feature = "decrypt")]
+#[derive(Debug, PartialEq, Clone)]
+pub struct MacsecSa {
+    pub sci: u64,
+    pub an:  u8,
+    // GCM-AES-128 or GCM-AES-256 key
+    pub sak: Vec<u8>
+}
+
+#[cfg(feature = "decrypt")]
+impl MacsecSa {
+    pub fn new(sci: u64, an: u8, sak: Vec<u8>) -> MacsecSa {
+        MacsecSa { sci, an: an & 0x03, sak }
+    }
+
+    // Decrypt the secure data following the SecTAG, the SecTAG is returned
+    // with the inner EtherType along with the inner packet. None when the
+    // ICV does not verify
+    pub fn decrypt(&self, macsec: &Macsec, ethernet: &Ethernet, bytes: &[u8]) -> Option<(Macsec, Vec<u8>)> {
+        if !macsec.is_confidential() || macsec.icv.len() != MACSEC_ICV_LENGTH {
+            return None
+        }
+        let aad = aad(macsec, ethernet);
+        let nonce = nonce(macsec, ethernet);
+        let mut buf = bytes.to_vec();
+        let tag = Tag::from_slice(&macsec.icv);
+        match self.sak.len() {
+            16 => Aes128Gcm::new_from_slice(&self.sak).ok()?
+                .decrypt_in_place_detached(&nonce, &aad, &mut buf, tag).ok()?,
+            32 => Aes256Gcm::new_from_slice(&self.sak).ok()?
+                .decrypt_in_place_detached(&nonce, &aad, &mut buf, tag).ok()?,
+            _ => return None
+        };
+        let eth_type = u16::from_be_bytes([*buf.first()?, *buf.get(1)?]);
+        Some((
+            Macsec {
+                eth_type,
+                decrypted: true,
+                ..macsec.clone()
+            },
+            buf.split_off(2)
+        ))
+    }
+
+    // Encrypt the inner packet of the EtherType, returning the SecTAG,
+    // secure data and ICV following the Ethernet header
+    pub fn encrypt(&self, macsec: &Macsec, ethernet: &Ethernet, eth_type: u16, payload: &[u8]) -> Option<Vec<u8>> {
+        let aad = aad(macsec, ethernet);
+        let nonce = nonce(macsec, ethernet);
+        let mut buf = [&eth_type.to_be_bytes()[..], payload].concat();
+        let icv = match self.sak.len() {
+            16 => Aes128Gcm::new_from_slice(&self.sak).ok()?
+                .encrypt_in_place_detached(&nonce, &aad, &mut buf).ok()?,
+            32 => Aes256Gcm::new_from_slice(&self.sak).ok()?
+                .encrypt_in_place_detached(&nonce, &aad, &mut buf).ok()?,
+            _ => return None
+        };
+        Some([macsec.clone().as_bytes(), buf, icv.to_vec()].concat())
+    }
+}
+
+// private functions
+
+// Destination and source addresses, MACsec EtherType and SecTAG
+#[cfg(feature = "decrypt")]
+fn aad(macsec: &Macsec, ethernet: &Ethernet) -> Vec<u8> {
+    [
+        ethernet.destination.as_bytes(),
+        ethernet.source.as_bytes(),
+        &ETH_P_MACSEC.to_be_bytes(),
+        &macsec.clone().as_bytes()
+    ].concat()
+}
+
+// SCI followed by the packet number
+#[cfg(feature = "decrypt")]
+fn nonce(macsec: &Macsec, ethernet: &Ethernet) -> Nonce<aes_gcm::aead::consts::U12> {
+    let sci = macsec.sci_or(ethernet.source.as_bytes());
+    *Nonce::from_slice(&[&sci.to_be_bytes()[..], &macsec.packet_number.to_be_bytes()].concat())
+}
+
+#[cfg(test)]
+mod tests_macsec {
+    use crate::macsec::*;
+
+    #[test]
+    fn parse() {
+        let frame = &mut [
+            0x2c,0x00,0x00,0x00,0x00,0x07,0x00,0x0c,
+            0x29,0x00,0x00,0x01,0x00,0x01,0xde,0xad,
+            0xbe,0xef,0x00,0x01,0x02,0x03,0x04,0x05,
+            0x06,0x07,0x08,0x09,0x0a,0x0b,0x0c,0x0d,
+            0x0e,0x0f
+        ];
+
+        let (leftover, macsec) = Macsec::from_bytes(frame).unwrap();
+        assert_eq!(&[0xde, 0xad, 0xbe, 0xef], leftover);
+        assert!(macsec.is_confidential());
+        assert_eq!(Macsec {
+            icv: (0..16).collect(),
+            ..Macsec::encrypted(0x000c_2900_0001_0001, 0, 7)
+        }, macsec);
+        assert_eq!("MACsec(an: 0, pn: 7, sci: 0x000c290000010001, type: 0x0)", format!("{}", macsec));
+        assert_eq!(frame[..14].to_vec(), macsec.as_bytes());
+    }
+
+    #[test]
+    fn parse_integrity_only() {
+        let frame = &mut [
+            0x40,0x06,0x00,0x00,0x00,0x01,0x08,0x00,
+            0x45,0x00,0x00,0x00,0x00,0x01,0x02,0x03,
+            0x04,0x05,0x06,0x07,0x08,0x09,0x0a,0x0b,
+            0x0c,0x0d,0x0e,0x0f,0x00,0x00
+        ];
+
+        let (leftover, macsec) = Macsec::from_bytes(frame).unwrap();
+        assert_eq!(&[0x45, 0x00, 0x00, 0x00], leftover);
+        assert!(!macsec.is_confidential());
+        assert_eq!(None, macsec.sci);
+        assert_eq!(0x0800, macsec.eth_type);
+        assert_eq!((0..16).collect::<Vec<u8>>(), macsec.icv);
+        assert_eq!(0x000c_2900_0001_0001, macsec.sci_or(&[0x00, 0x0c, 0x29, 0x00, 0x00, 0x01]));
+    }
+
+    #[cfg(feature = "decrypt")]
+    #[test]
+    fn decrypt() {
+        let ethernet = Ethernet { eth_type: ETH_P_MACSEC, ..Ethernet::default() };
+        let payload = &[0x45, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x00, 0x40, 0x3b];
+
+        for sak in [vec![0x01; 16], vec![0x02; 32]] {
+            let sa = MacsecSa::new(0x000c_2900_0001_0001, 0, sak);
+            let bytes = sa.encrypt(&Macsec::encrypted(sa.sci, 0, 1), &ethernet, 0x0800, payload).unwrap();
+            let (leftover, macsec) = Macsec::from_bytes(&bytes).unwrap();
+            let (macsec, plaintext) = sa.decrypt(&macsec, &ethernet, leftover).unwrap();
+            assert!(macsec.decrypted);
+            assert_eq!(0x0800, macsec.eth_type);
+            assert_eq!(payload.to_vec(), plaintext);
+
+            let mut tampered = leftover.to_vec();
+            tampered[0] ^= 1;
+            assert_eq!(None, sa.decrypt(&macsec, &ethernet, &tampered));
+        }
+    }
+}
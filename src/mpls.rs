@@ -0,0 +1,227 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::number::complete::be_u32;
+use nom::{do_parse, IResult};
+use std::fmt;
+use std::io::Cursor;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MplsLabel {
+    pub label: u32,
+    pub tc:    u8,
+    pub bos:   bool,
+    pub ttl:   u8
+}
+
+#[allow(unused_must_use)]
+impl MplsLabel {
+    // Instantiate a new label stack entry
+    pub fn new(label: u32, tc: u8, ttl: u8) -> MplsLabel {
+        MplsLabel { label, tc, bos: false, ttl }
+    }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        let bos = if self.bos { 0x100 } else { 0x000 };
+        cursor.write_u32::<NetworkEndian>(
+            (self.label & 0x000f_ffff) << 12 |
+            ((self.tc & 0x07) as u32) << 9 |
+            bos |
+            self.ttl as u32
+        );
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], MplsLabel> {
+        do_parse!(
+            bytes,
+            entry: be_u32 >>
+                (
+                    MplsLabel {
+                        label: entry >> 12,
+                        tc:    ((entry >> 9) & 0x07) as u8,
+                        bos:   entry & 0x100 != 0,
+                        ttl:   entry as u8
+                    }
+                )
+        )
+    }
+}
+
+// Pseudowire control word (RFC 4385)
+#[derive(Debug, PartialEq, Clone)]
+pub struct PwControlWord {
+    pub flags:    u8,
+    pub frag:     u8,
+    pub length:   u8,
+    pub sequence: u16
+}
+
+#[allow(unused_must_use)]
+impl PwControlWord {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u32::<NetworkEndian>(
+            ((self.flags & 0x0f) as u32) << 24 |
+            ((self.frag & 0x03) as u32) << 22 |
+            ((self.length & 0x3f) as u32) << 16 |
+            self.sequence as u32
+        );
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], PwControlWord> {
+        do_parse!(
+            bytes,
+            word: be_u32 >>
+                (
+                    PwControlWord {
+                        flags:    ((word >> 24) & 0x0f) as u8,
+                        frag:     ((word >> 22) & 0x03) as u8,
+                        length:   ((word >> 16) & 0x3f) as u8,
+                        sequence: word as u16
+                    }
+                )
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct Mpls {
+    // label stack, top of stack first
+    pub labels:       Vec<MplsLabel>,
+    // present when the payload is an Ethernet pseudowire with a control word
+    pub control_word: Option<PwControlWord>
+}
+
+impl fmt::Display for Mpls {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let labels: Vec<String> = self.labels
+            .iter()
+            .map(|entry| entry.label.to_string())
+            .collect();
+
+        write!(
+            f,
+            "MPLS(\
+             labels: [{}], \
+             cw: {}\
+             )",
+            labels.join(", "),
+            self.control_word.is_some()
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Mpls {
+    // Instantiate an empty label stack
+    pub fn new() -> Mpls { Mpls::default() }
+
+    // Push a label on top of the stack
+    pub fn push(&mut self, label: MplsLabel) {
+        let mut label = label;
+        label.bos = self.labels.is_empty();
+        self.labels.insert(0, label);
+    }
+
+    // Pop the label on top of the stack
+    pub fn pop(&mut self) -> Option<MplsLabel> {
+        if self.labels.is_empty() { return None }
+        let label = self.labels.remove(0);
+        if let Some(bottom) = self.labels.last_mut() { bottom.bos = true }
+        Some(label)
+    }
+
+    // Encode the label stack into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let mut buf: Vec<u8> = self.labels
+            .into_iter()
+            .flat_map(|label| label.as_bytes())
+            .collect();
+        if let Some(cw) = self.control_word {
+            buf.extend(cw.as_bytes());
+        }
+        buf
+    }
+
+    // Parse a byte slice up to the bottom of stack
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Mpls> {
+        let mut labels = Vec::new();
+        let mut b = bytes;
+
+        loop {
+            let (leftover, label) = MplsLabel::from_bytes(b)?;
+            let bos = label.bos;
+            labels.push(label);
+            b = leftover;
+            if bos { break }
+        }
+
+        // The first nibble of a control word is zero, which is neither IPv4 nor IPv6
+        let (b, control_word) = match b.first() {
+            Some(nibble) if nibble >> 4 == 0 => {
+                let (leftover, cw) = PwControlWord::from_bytes(b)?;
+                (leftover, Some(cw))
+            },
+            _ => (b, None)
+        };
+
+        Ok((b, Mpls { labels, control_word }))
+    }
+}
+
+#[cfg(test)]
+mod tests_mpls {
+    use crate::mpls::*;
+
+    #[test]
+    fn parse() {
+        let frame = &mut [
+            0x00,0x01,0x20,0xfe,0x00,0x01,0x31,0xfe,
+            0x45,0x00,0x00,0x14,0x00,0x00,0x00,0x00,
+            0x40,0x06,0x00,0x00,0x7f,0x00,0x00,0x01,
+            0x7f,0x00,0x00,0x01
+        ];
+
+        let (leftover, mpls) = Mpls::from_bytes(frame).unwrap();
+        assert_eq!(vec![
+            MplsLabel { label: 18, tc: 0, bos: false, ttl: 254 },
+            MplsLabel { label: 19, tc: 0, bos: true,  ttl: 254 }
+        ], mpls.labels);
+        assert_eq!(None, mpls.control_word);
+        assert_eq!(0x45, leftover[0]);
+        assert_eq!("MPLS(labels: [18, 19], cw: false)", format!("{}", mpls));
+        assert_eq!(frame[..8].to_vec(), mpls.as_bytes());
+    }
+
+    #[test]
+    fn push_pop() {
+        let mut mpls = Mpls::new();
+        mpls.push(MplsLabel::new(100, 0, 64));
+        mpls.push(MplsLabel::new(200, 5, 64));
+        assert_eq!(vec![0x00,0x0c,0x8a,0x40,0x00,0x06,0x41,0x40], Mpls {
+            labels:       mpls.labels.clone(),
+            control_word: None
+        }.as_bytes());
+
+        assert_eq!(Some(MplsLabel { label: 200, tc: 5, bos: false, ttl: 64 }), mpls.pop());
+        assert_eq!(vec![MplsLabel { label: 100, tc: 0, bos: true, ttl: 64 }], mpls.labels);
+        assert!(mpls.pop().is_some());
+        assert_eq!(None, mpls.pop());
+    }
+
+    #[test]
+    fn parse_control_word() {
+        let frame = &mut [
+            0x00,0x3e,0x81,0x40,0x00,0x00,0x00,0x07,
+            0xff,0xff,0xff,0xff,0xff,0xff
+        ];
+
+        let (leftover, mpls) = Mpls::from_bytes(frame).unwrap();
+        assert_eq!(1000, mpls.labels[0].label);
+        assert_eq!(Some(PwControlWord { flags: 0, frag: 0, length: 0, sequence: 7 }), mpls.control_word);
+        assert_eq!(6, leftover.len());
+    }
+}
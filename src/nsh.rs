@@ -0,0 +1,314 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::combinator::{all_consuming, verify};
+use nom::multi::many0;
+use nom::number::complete::{be_u8, be_u16, be_u24};
+use nom::{call, do_parse, IResult};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Cursor, Write};
+
+pub const ETH_P_NSH: u16 = 0x894f;
+
+// Next protocols, shared with VXLAN-GPE
+pub const NSH_NP_IPV4:     u8 = 0x01;
+pub const NSH_NP_IPV6:     u8 = 0x02;
+pub const NSH_NP_ETHERNET: u8 = 0x03;
+pub const NSH_NP_NSH:      u8 = 0x04;
+pub const NSH_NP_MPLS:     u8 = 0x05;
+
+// MD types
+pub const NSH_MD_TYPE_1: u8 = 0x01;
+pub const NSH_MD_TYPE_2: u8 = 0x02;
+
+// Largest padded context data in bytes and header length in 4 byte words
+pub const NSH_MAX_CONTEXT_LENGTH: usize = 0x7f;
+pub const NSH_MAX_LENGTH:         u8    = 0x3f;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct NshContext {
+    pub class:    u16,
+    pub ctx_type: u8,
+    // length of the unpadded data in bytes
+    pub length:   u8,
+    pub data:     Vec<u8>
+}
+
+#[allow(unused_must_use)]
+impl NshContext {
+    // Instantiate a variable length context header. None when the padded
+    // data does not fit the 7 bit length
+    pub fn new(class: u16, ctx_type: u8, data: Vec<u8>) -> Option<NshContext> {
+        if data.len().div_ceil(4) * 4 > NSH_MAX_CONTEXT_LENGTH {
+            return None
+        }
+        Some(NshContext {
+            class,
+            ctx_type,
+            length: data.len() as u8,
+            data
+        })
+    }
+
+    // Length of the context header including the padding in 4 byte words
+    pub fn words(&self) -> u8 { 1 + (self.length as usize).div_ceil(4) as u8 }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u16::<NetworkEndian>(self.class);
+        cursor.write_u8(self.ctx_type);
+        cursor.write_u8(self.length & 0x7f);
+        cursor.write_all(&self.data);
+        cursor.write_all(&vec![0x00; (4 - self.data.len() % 4) % 4]);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], NshContext> {
+        do_parse!(
+            bytes,
+            class:    be_u16 >>
+            ctx_type: be_u8  >>
+            length:   be_u8  >>
+            data:     call!(take((length & 0x7f) as usize)) >>
+            _padding: call!(take((4 - (length & 0x7f) as usize % 4) % 4)) >>
+                (
+                    NshContext {
+                        class,
+                        ctx_type,
+                        length: length & 0x7f,
+                        data:   data.to_vec()
+                    }
+                )
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum NshMetadata {
+    // MD type 1: 16 byte fixed length context
+    Fixed([u8; 16]),
+    // MD type 2: variable length context headers
+    Variable(Vec<NshContext>),
+    Unknown(Vec<u8>)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Nsh {
+    pub version:       u8,
+    pub oam:           bool,
+    pub ttl:           u8,
+    // length of the header in 4 byte words
+    pub length:        u8,
+    pub md_type:       u8,
+    pub next_protocol: u8,
+    pub spi:           u32,
+    pub si:            u8,
+    pub metadata:      NshMetadata
+}
+
+impl Default for Nsh {
+    fn default() -> Nsh {
+        Nsh {
+            version:       0,
+            oam:           false,
+            ttl:           63,
+            length:        2,
+            md_type:       NSH_MD_TYPE_2,
+            next_protocol: NSH_NP_ETHERNET,
+            spi:           0,
+            si:            255,
+            metadata:      NshMetadata::Variable(Vec::new())
+        }
+    }
+}
+
+impl fmt::Display for Nsh {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "NSH(\
+             spi: {}, \
+             si: {}, \
+             md_type: {}, \
+             next_protocol: {}\
+             )",
+            self.spi,
+            self.si,
+            self.md_type,
+            self.next_protocol
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Nsh {
+    // Instantiate a new NSH header
+    pub fn new() -> Nsh { Nsh::default() }
+
+    // Instantiate an NSH header for the service path
+    pub fn service_path(spi: u32, si: u8, next_protocol: u8) -> Nsh {
+        Nsh { spi: spi & 0x00ff_ffff, si, next_protocol, ..Nsh::default() }
+    }
+
+    // Set the MD type 1 fixed context and update the length
+    pub fn fixed_context(&mut self, context: [u8; 16]) {
+        self.md_type  = NSH_MD_TYPE_1;
+        self.length   = 6;
+        self.metadata = NshMetadata::Fixed(context);
+    }
+
+    // Append an MD type 2 context header and update the length. None when
+    // the header would exceed the 6 bit length
+    pub fn push_context(&mut self, context: NshContext) -> Option<()> {
+        let base = match self.metadata {
+            NshMetadata::Variable(_) => self.length,
+            _ => 2
+        };
+        let length = base as usize + context.words() as usize;
+        if length > NSH_MAX_LENGTH as usize {
+            return None
+        }
+        self.length = length as u8;
+        if let NshMetadata::Variable(contexts) = &mut self.metadata {
+            contexts.push(context);
+        } else {
+            self.md_type  = NSH_MD_TYPE_2;
+            self.metadata = NshMetadata::Variable(vec![context]);
+        }
+        Some(())
+    }
+
+    // Decrement the service index when a service function was applied
+    pub fn decrement_si(&mut self) {
+        self.si = self.si.saturating_sub(1);
+    }
+
+    // Encode the NSH header into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        let oam = if self.oam { 0x2000 } else { 0x0000 };
+        let ttl = (self.ttl as u16 & 0x3f) << 6;
+        cursor.write_u16::<NetworkEndian>(((self.version as u16 & 0x03) << 14) | oam | ttl | (self.length as u16 & 0x3f));
+        cursor.write_u8(self.md_type & 0x0f);
+        cursor.write_u8(self.next_protocol);
+        cursor.write_u24::<NetworkEndian>(self.spi);
+        cursor.write_u8(self.si);
+        match self.metadata {
+            NshMetadata::Fixed(context) => {
+                cursor.write_all(&context);
+            },
+            NshMetadata::Variable(contexts) => {
+                for context in contexts {
+                    cursor.write_all(&context.as_bytes());
+                }
+            },
+            NshMetadata::Unknown(data) => {
+                cursor.write_all(&data);
+            }
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Nsh> {
+        do_parse!(
+            bytes,
+            base:          call!(verify(be_u16, |base| base & 0x3f >= 2)) >>
+            md_type:       be_u8  >>
+            next_protocol: be_u8  >>
+            spi:           be_u24 >>
+            si:            be_u8  >>
+            metadata:      call!(parse_metadata, md_type & 0x0f, (base & 0x3f) as usize * 4 - 8) >>
+                (
+                    Nsh {
+                        version: (base >> 14) as u8,
+                        oam:     base & 0x2000 != 0,
+                        ttl:     ((base >> 6) & 0x3f) as u8,
+                        length:  (base & 0x3f) as u8,
+                        md_type: md_type & 0x0f,
+                        next_protocol,
+                        spi,
+                        si,
+                        metadata
+                    }
+                )
+        )
+    }
+}
+
+// private functions
+
+fn parse_metadata(bytes: &[u8], md_type: u8, length: usize) -> IResult<&[u8], NshMetadata> {
+    let (bytes1, data) = take(length)(bytes)?;
+    match (md_type, <[u8; 16]>::try_from(data)) {
+        (NSH_MD_TYPE_1, Ok(context)) =>
+            Ok((bytes1, NshMetadata::Fixed(context))),
+        (NSH_MD_TYPE_2, _) => {
+            let (_, contexts) = all_consuming(many0(NshContext::from_bytes))(data)?;
+            Ok((bytes1, NshMetadata::Variable(contexts)))
+        },
+        _ =>
+            Ok((bytes1, NshMetadata::Unknown(data.to_vec())))
+    }
+}
+
+#[cfg(test)]
+mod tests_nsh {
+    use crate::nsh::*;
+
+    #[test]
+    fn parse_md_type_1() {
+        let frame = &mut [
+            0x0f,0xc6,0x01,0x01,0x00,0x00,0x2a,0xff,
+            0x00,0x00,0x00,0x01,0x00,0x00,0x00,0x02,
+            0x00,0x00,0x00,0x03,0x00,0x00,0x00,0x04,
+            0x45,0x00
+        ];
+
+        let (leftover, nsh) = Nsh::from_bytes(frame).unwrap();
+        assert_eq!(vec![0x45, 0x00], leftover);
+        assert_eq!(63, nsh.ttl);
+        assert_eq!(6, nsh.length);
+        assert_eq!(NSH_NP_IPV4, nsh.next_protocol);
+        assert_eq!(42, nsh.spi);
+        assert_eq!(255, nsh.si);
+        assert_eq!(NshMetadata::Fixed([0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4]), nsh.metadata);
+        assert_eq!("NSH(spi: 42, si: 255, md_type: 1, next_protocol: 1)", format!("{}", nsh));
+        assert_eq!(frame[..24].to_vec(), nsh.as_bytes());
+    }
+
+    #[test]
+    fn build_md_type_2() {
+        let mut nsh = Nsh::service_path(100, 254, NSH_NP_ETHERNET);
+        nsh.push_context(NshContext::new(0x0101, 0x01, vec![0xaa, 0xbb, 0xcc, 0xdd]).unwrap()).unwrap();
+        nsh.push_context(NshContext::new(0xffff, 0x02, vec![0xee]).unwrap()).unwrap();
+        nsh.decrement_si();
+
+        let bytes = nsh.as_bytes();
+        assert_eq!(24, bytes.len());
+
+        let nsh = Nsh::from_bytes(&bytes).unwrap().1;
+        assert_eq!(6, nsh.length);
+        assert_eq!(NSH_MD_TYPE_2, nsh.md_type);
+        assert_eq!(253, nsh.si);
+        assert_eq!(NshMetadata::Variable(vec![
+            NshContext::new(0x0101, 0x01, vec![0xaa, 0xbb, 0xcc, 0xdd]).unwrap(),
+            NshContext::new(0xffff, 0x02, vec![0xee]).unwrap()
+        ]), nsh.metadata);
+    }
+
+    #[test]
+    fn build_length() {
+        assert!(NshContext::new(0x0101, 0x01, vec![0x00; 125]).is_none());
+
+        let mut nsh = Nsh::service_path(100, 255, NSH_NP_IPV4);
+        let context = NshContext::new(0x0101, 0x01, vec![0x00; 124]).unwrap();
+        nsh.push_context(context.clone()).unwrap();
+        assert_eq!(34, nsh.length);
+        assert!(nsh.push_context(context).is_none());
+        assert_eq!(34, nsh.length);
+        assert_eq!(34 * 4, nsh.as_bytes().len());
+    }
+}
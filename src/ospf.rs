@@ -0,0 +1,1425 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::multi::count;
+use nom::number::complete::{be_u8, be_u16, be_u24, be_u32};
+use nom::{call, do_parse, tag, take, IResult};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Cursor, Write};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::checksum::{checksum, fletcher_checksum};
+use crate::util::{parse_all, parse_ip4addr, parse_ip6addr};
+
+pub const ALL_SPF_ROUTERS: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 5);
+pub const ALL_D_ROUTERS:   Ipv4Addr = Ipv4Addr::new(224, 0, 0, 6);
+
+// Packet types
+pub const OSPF_HELLO:           u8 = 1;
+pub const OSPF_DB_DESCRIPTION:  u8 = 2;
+pub const OSPF_LS_REQUEST:      u8 = 3;
+pub const OSPF_LS_UPDATE:       u8 = 4;
+pub const OSPF_LS_ACK:          u8 = 5;
+
+// Authentication types
+pub const OSPF_AUTH_NONE:          u16 = 0;
+pub const OSPF_AUTH_SIMPLE:        u16 = 1;
+pub const OSPF_AUTH_CRYPTOGRAPHIC: u16 = 2;
+
+// Database Description flags
+pub const DD_FLAG_MS: u8 = 0x01;
+pub const DD_FLAG_M:  u8 = 0x02;
+pub const DD_FLAG_I:  u8 = 0x04;
+
+// OSPFv2 LS types
+pub const LSA_ROUTER:          u8 = 1;
+pub const LSA_NETWORK:         u8 = 2;
+pub const LSA_SUMMARY_NETWORK: u8 = 3;
+pub const LSA_SUMMARY_ASBR:    u8 = 4;
+pub const LSA_AS_EXTERNAL:     u8 = 5;
+pub const LSA_NSSA:            u8 = 7;
+pub const LSA_OPAQUE_LINK:     u8 = 9;
+pub const LSA_OPAQUE_AREA:     u8 = 10;
+pub const LSA_OPAQUE_AS:       u8 = 11;
+
+// Length of the LSA header, the smallest LSA of both versions
+pub const LSA_HEADER_LENGTH: usize = 20;
+
+// OSPFv3 LS function codes with their flooding scope
+pub const LSA3_ROUTER:            u16 = 0x2001;
+pub const LSA3_NETWORK:           u16 = 0x2002;
+pub const LSA3_INTER_AREA_PREFIX: u16 = 0x2003;
+pub const LSA3_INTER_AREA_ROUTER: u16 = 0x2004;
+pub const LSA3_AS_EXTERNAL:       u16 = 0x4005;
+pub const LSA3_NSSA:              u16 = 0x2007;
+pub const LSA3_LINK:              u16 = 0x0008;
+pub const LSA3_INTRA_AREA_PREFIX: u16 = 0x2009;
+
+// Router LSA link types
+pub const ROUTER_LINK_P2P:     u8 = 1;
+pub const ROUTER_LINK_TRANSIT: u8 = 2;
+pub const ROUTER_LINK_STUB:    u8 = 3;
+pub const ROUTER_LINK_VIRTUAL: u8 = 4;
+
+// Link State Request entry, the LS type is 16 bits wide in OSPFv3
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LsRequest {
+    pub ls_type:            u32,
+    pub ls_id:              Ipv4Addr,
+    pub advertising_router: Ipv4Addr
+}
+
+#[allow(unused_must_use)]
+impl LsRequest {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u32::<NetworkEndian>(self.ls_type);
+        cursor.write_all(&self.ls_id.octets());
+        cursor.write_all(&self.advertising_router.octets());
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], LsRequest> {
+        do_parse!(
+            bytes,
+            ls_type:            be_u32 >>
+            ls_id:              parse_ip4addr >>
+            advertising_router: parse_ip4addr >>
+                (
+                    LsRequest {
+                        ls_type,
+                        ls_id,
+                        advertising_router
+                    }
+                )
+        )
+    }
+}
+
+// OSPFv2 LSA header
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LsaHeader {
+    pub age:                u16,
+    pub options:            u8,
+    pub ls_type:            u8,
+    pub ls_id:              Ipv4Addr,
+    pub advertising_router: Ipv4Addr,
+    pub sequence:           u32,
+    pub checksum:           u16,
+    pub length:             u16
+}
+
+impl Default for LsaHeader {
+    fn default() -> LsaHeader {
+        LsaHeader {
+            age:                0,
+            options:            0,
+            ls_type:            LSA_ROUTER,
+            ls_id:              Ipv4Addr::UNSPECIFIED,
+            advertising_router: Ipv4Addr::UNSPECIFIED,
+            sequence:           0x8000_0001,
+            checksum:           0,
+            length:             20
+        }
+    }
+}
+
+#[allow(unused_must_use)]
+impl LsaHeader {
+    // Opaque type of Opaque LSAs
+    pub fn opaque_type(&self) -> u8 { self.ls_id.octets()[0] }
+
+    // Opaque ID of Opaque LSAs
+    pub fn opaque_id(&self) -> u32 { u32::from(self.ls_id) & 0x00ff_ffff }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u16::<NetworkEndian>(self.age);
+        cursor.write_u8(self.options);
+        cursor.write_u8(self.ls_type);
+        cursor.write_all(&self.ls_id.octets());
+        cursor.write_all(&self.advertising_router.octets());
+        cursor.write_u32::<NetworkEndian>(self.sequence);
+        cursor.write_u16::<NetworkEndian>(self.checksum);
+        cursor.write_u16::<NetworkEndian>(self.length);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], LsaHeader> {
+        do_parse!(
+            bytes,
+            age:                be_u16 >>
+            options:            be_u8 >>
+            ls_type:            be_u8 >>
+            ls_id:              parse_ip4addr >>
+            advertising_router: parse_ip4addr >>
+            sequence:           be_u32 >>
+            checksum:           be_u16 >>
+            length:             be_u16 >>
+                (
+                    LsaHeader {
+                        age,
+                        options,
+                        ls_type,
+                        ls_id,
+                        advertising_router,
+                        sequence,
+                        checksum,
+                        length
+                    }
+                )
+        )
+    }
+}
+
+// Link description of an OSPFv2 Router LSA
+#[derive(Debug, PartialEq, Clone)]
+pub struct RouterLink {
+    pub link_id:   Ipv4Addr,
+    pub link_data: Ipv4Addr,
+    pub link_type: u8,
+    pub metric:    u16,
+    // TOS and TOS metric pairs
+    pub tos:       Vec<(u8, u16)>
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum LsaBody {
+    Router { flags: u8, links: Vec<RouterLink> },
+    Network { mask: Ipv4Addr, attached_routers: Vec<Ipv4Addr> },
+    // Network and ASBR summary LSAs
+    Summary { mask: Ipv4Addr, metric: u32 },
+    // AS-External and NSSA LSAs
+    AsExternal {
+        mask:               Ipv4Addr,
+        // Type 2 external metric when set
+        external:           bool,
+        metric:             u32,
+        forwarding_address: Ipv4Addr,
+        route_tag:          u32
+    },
+    Opaque(Vec<u8>),
+    Unknown(Vec<u8>)
+}
+
+#[allow(unused_must_use)]
+impl LsaBody {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        match self {
+            LsaBody::Router { flags, links } => {
+                cursor.write_u8(flags);
+                cursor.write_u8(0);
+                cursor.write_u16::<NetworkEndian>(links.len() as u16);
+                for link in links {
+                    cursor.write_all(&link.link_id.octets());
+                    cursor.write_all(&link.link_data.octets());
+                    cursor.write_u8(link.link_type);
+                    cursor.write_u8(link.tos.len() as u8);
+                    cursor.write_u16::<NetworkEndian>(link.metric);
+                    for (tos, metric) in link.tos {
+                        cursor.write_u8(tos);
+                        cursor.write_u8(0);
+                        cursor.write_u16::<NetworkEndian>(metric);
+                    }
+                }
+            },
+            LsaBody::Network { mask, attached_routers } => {
+                cursor.write_all(&mask.octets());
+                for router in attached_routers {
+                    cursor.write_all(&router.octets());
+                }
+            },
+            LsaBody::Summary { mask, metric } => {
+                cursor.write_all(&mask.octets());
+                cursor.write_u32::<NetworkEndian>(metric & 0x00ff_ffff);
+            },
+            LsaBody::AsExternal { mask, external, metric, forwarding_address, route_tag } => {
+                cursor.write_all(&mask.octets());
+                cursor.write_u8(if external { 0x80 } else { 0 });
+                cursor.write_u24::<NetworkEndian>(metric & 0x00ff_ffff);
+                cursor.write_all(&forwarding_address.octets());
+                cursor.write_u32::<NetworkEndian>(route_tag);
+            },
+            LsaBody::Opaque(data) | LsaBody::Unknown(data) => {
+                cursor.write_all(&data);
+            }
+        }
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8], ls_type: u8) -> IResult<&[u8], LsaBody> {
+        match ls_type {
+            LSA_ROUTER => {
+                let (bytes1, flags)   = be_u8(bytes)?;
+                let (bytes2, _)       = be_u8(bytes1)?;
+                let (bytes3, n_links) = be_u16(bytes2)?;
+                let (bytes4, links)   = count(parse_router_link, n_links as usize)(bytes3)?;
+                Ok((bytes4, LsaBody::Router { flags, links }))
+            },
+            LSA_NETWORK => {
+                let (bytes1, mask)    = parse_ip4addr(bytes)?;
+                let (bytes2, routers) = parse_ip4addrs(bytes1)?;
+                Ok((bytes2, LsaBody::Network { mask, attached_routers: routers }))
+            },
+            LSA_SUMMARY_NETWORK | LSA_SUMMARY_ASBR => {
+                let (bytes1, mask)   = parse_ip4addr(bytes)?;
+                let (bytes2, metric) = be_u32(bytes1)?;
+                Ok((bytes2, LsaBody::Summary { mask, metric: metric & 0x00ff_ffff }))
+            },
+            LSA_AS_EXTERNAL | LSA_NSSA => do_parse!(
+                bytes,
+                mask:               parse_ip4addr >>
+                e_bit:              be_u8 >>
+                metric:             be_u24 >>
+                forwarding_address: parse_ip4addr >>
+                route_tag:          be_u32 >>
+                    (
+                        LsaBody::AsExternal {
+                            mask,
+                            external: e_bit & 0x80 != 0,
+                            metric,
+                            forwarding_address,
+                            route_tag
+                        }
+                    )
+            ),
+            LSA_OPAQUE_LINK | LSA_OPAQUE_AREA | LSA_OPAQUE_AS =>
+                Ok((&[], LsaBody::Opaque(bytes.to_vec()))),
+            _ =>
+                Ok((&[], LsaBody::Unknown(bytes.to_vec())))
+        }
+    }
+}
+
+// OSPFv2 Link State Advertisement
+#[derive(Debug, PartialEq, Clone)]
+pub struct Lsa {
+    pub header: LsaHeader,
+    pub body:   LsaBody
+}
+
+#[allow(unused_must_use)]
+impl Lsa {
+    // Fletcher checksum over the LSA except the LS age
+    pub fn calculate_lsa_checksum(&self) -> u16 {
+        fletcher_checksum(&self.clone().as_bytes()[2..], 14)
+    }
+
+    pub fn verify_checksum(&self) -> bool {
+        self.header.checksum == self.calculate_lsa_checksum()
+    }
+
+    // Fill in the length and checksum of the LSA header
+    pub fn set_checksum(&mut self) {
+        self.header.length = self.body.clone().as_bytes().len() as u16 + 20;
+        self.header.checksum = self.calculate_lsa_checksum();
+    }
+
+    // Encode the LSA into a byte slice, the LSA length is taken from the body
+    pub fn as_bytes(self) -> Vec<u8> {
+        let body = self.body.as_bytes();
+        let header = LsaHeader { length: body.len() as u16 + 20, ..self.header };
+        [header.as_bytes(), body].concat()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Lsa> {
+        let (bytes1, header) = verify(LsaHeader::from_bytes, |h| h.length >= 20)(bytes)?;
+        let (bytes2, data)   = take(header.length as usize - 20)(bytes1)?;
+        let body = match LsaBody::from_bytes(data, header.ls_type) {
+            Ok(([], body)) => body,
+            _ => LsaBody::Unknown(data.to_vec())
+        };
+        Ok((bytes2, Lsa { header, body }))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Ospfv2Message {
+    Hello {
+        network_mask:             Ipv4Addr,
+        hello_interval:           u16,
+        options:                  u8,
+        priority:                 u8,
+        dead_interval:            u32,
+        designated_router:        Ipv4Addr,
+        backup_designated_router: Ipv4Addr,
+        neighbors:                Vec<Ipv4Addr>
+    },
+    DatabaseDescription {
+        mtu:         u16,
+        options:     u8,
+        flags:       u8,
+        sequence:    u32,
+        lsa_headers: Vec<LsaHeader>
+    },
+    LinkStateRequest(Vec<LsRequest>),
+    LinkStateUpdate(Vec<Lsa>),
+    LinkStateAck(Vec<LsaHeader>),
+    Unknown { packet_type: u8, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl Ospfv2Message {
+    pub fn packet_type(&self) -> u8 {
+        match self {
+            Ospfv2Message::Hello { .. } => OSPF_HELLO,
+            Ospfv2Message::DatabaseDescription { .. } => OSPF_DB_DESCRIPTION,
+            Ospfv2Message::LinkStateRequest(_) => OSPF_LS_REQUEST,
+            Ospfv2Message::LinkStateUpdate(_) => OSPF_LS_UPDATE,
+            Ospfv2Message::LinkStateAck(_) => OSPF_LS_ACK,
+            Ospfv2Message::Unknown { packet_type, .. } => *packet_type
+        }
+    }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        match self {
+            Ospfv2Message::Hello {
+                network_mask, hello_interval, options, priority, dead_interval,
+                designated_router, backup_designated_router, neighbors
+            } => {
+                cursor.write_all(&network_mask.octets());
+                cursor.write_u16::<NetworkEndian>(hello_interval);
+                cursor.write_u8(options);
+                cursor.write_u8(priority);
+                cursor.write_u32::<NetworkEndian>(dead_interval);
+                cursor.write_all(&designated_router.octets());
+                cursor.write_all(&backup_designated_router.octets());
+                for neighbor in neighbors {
+                    cursor.write_all(&neighbor.octets());
+                }
+            },
+            Ospfv2Message::DatabaseDescription { mtu, options, flags, sequence, lsa_headers } => {
+                cursor.write_u16::<NetworkEndian>(mtu);
+                cursor.write_u8(options);
+                cursor.write_u8(flags);
+                cursor.write_u32::<NetworkEndian>(sequence);
+                for header in lsa_headers {
+                    cursor.write_all(&header.as_bytes());
+                }
+            },
+            Ospfv2Message::LinkStateRequest(requests) => {
+                for request in requests {
+                    cursor.write_all(&request.as_bytes());
+                }
+            },
+            Ospfv2Message::LinkStateUpdate(lsas) => {
+                cursor.write_u32::<NetworkEndian>(lsas.len() as u32);
+                for lsa in lsas {
+                    cursor.write_all(&lsa.as_bytes());
+                }
+            },
+            Ospfv2Message::LinkStateAck(lsa_headers) => {
+                for header in lsa_headers {
+                    cursor.write_all(&header.as_bytes());
+                }
+            },
+            Ospfv2Message::Unknown { data, .. } => {
+                cursor.write_all(&data);
+            }
+        }
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8], packet_type: u8) -> IResult<&[u8], Ospfv2Message> {
+        match packet_type {
+            OSPF_HELLO => do_parse!(
+                bytes,
+                network_mask:             parse_ip4addr >>
+                hello_interval:           be_u16 >>
+                options:                  be_u8 >>
+                priority:                 be_u8 >>
+                dead_interval:            be_u32 >>
+                designated_router:        parse_ip4addr >>
+                backup_designated_router: parse_ip4addr >>
+                neighbors:                parse_ip4addrs >>
+                    (
+                        Ospfv2Message::Hello {
+                            network_mask,
+                            hello_interval,
+                            options,
+                            priority,
+                            dead_interval,
+                            designated_router,
+                            backup_designated_router,
+                            neighbors
+                        }
+                    )
+            ),
+            OSPF_DB_DESCRIPTION => do_parse!(
+                bytes,
+                mtu:         be_u16 >>
+                options:     be_u8 >>
+                flags:       be_u8 >>
+                sequence:    be_u32 >>
+                lsa_headers: call!(parse_all, LsaHeader::from_bytes) >>
+                    (
+                        Ospfv2Message::DatabaseDescription {
+                            mtu,
+                            options,
+                            flags,
+                            sequence,
+                            lsa_headers
+                        }
+                    )
+            ),
+            OSPF_LS_REQUEST => {
+                let (bytes1, requests) = parse_all(bytes, LsRequest::from_bytes)?;
+                Ok((bytes1, Ospfv2Message::LinkStateRequest(requests)))
+            },
+            OSPF_LS_UPDATE => {
+                let (bytes1, n_lsas) = verify(be_u32, |n_lsas| *n_lsas as usize <= bytes.len() / LSA_HEADER_LENGTH)(bytes)?;
+                let (bytes2, lsas)   = count(Lsa::from_bytes, n_lsas as usize)(bytes1)?;
+                Ok((bytes2, Ospfv2Message::LinkStateUpdate(lsas)))
+            },
+            OSPF_LS_ACK => {
+                let (bytes1, lsa_headers) = parse_all(bytes, LsaHeader::from_bytes)?;
+                Ok((bytes1, Ospfv2Message::LinkStateAck(lsa_headers)))
+            },
+            _ =>
+                Ok((&[], Ospfv2Message::Unknown { packet_type, data: bytes.to_vec() }))
+        }
+    }
+}
+
+// OSPF version 2 (RFC 2328)
+#[derive(Debug, PartialEq)]
+pub struct Ospfv2 {
+    pub router_id:      Ipv4Addr,
+    pub area_id:        Ipv4Addr,
+    pub checksum:       u16,
+    pub au_type:        u16,
+    pub authentication: [u8; 8],
+    pub message:        Ospfv2Message
+}
+
+impl Default for Ospfv2 {
+    fn default() -> Ospfv2 {
+        Ospfv2 {
+            router_id:      Ipv4Addr::UNSPECIFIED,
+            area_id:        Ipv4Addr::UNSPECIFIED,
+            checksum:       0,
+            au_type:        OSPF_AUTH_NONE,
+            authentication: [0; 8],
+            message:        Ospfv2Message::Hello {
+                network_mask:             Ipv4Addr::UNSPECIFIED,
+                hello_interval:           10,
+                options:                  0x02,
+                priority:                 1,
+                dead_interval:            40,
+                designated_router:        Ipv4Addr::UNSPECIFIED,
+                backup_designated_router: Ipv4Addr::UNSPECIFIED,
+                neighbors:                Vec::new()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Ospfv2 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "OSPFv2(\
+             type: {}, \
+             router_id: {}, \
+             area_id: {}, \
+             checksum: 0x{:04x}\
+             )",
+            self.message.packet_type(),
+            self.router_id,
+            self.area_id,
+            self.checksum
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Ospfv2 {
+    // Instantiate a new OSPFv2 Hello packet
+    pub fn new() -> Ospfv2 { Ospfv2::default() }
+
+    // Instantiate a Hello packet with the default intervals
+    pub fn hello(router_id: Ipv4Addr, area_id: Ipv4Addr, network_mask: Ipv4Addr, neighbors: Vec<Ipv4Addr>) -> Ospfv2 {
+        let mut ospf = Ospfv2 { router_id, area_id, ..Ospfv2::default() };
+        if let Ospfv2Message::Hello { network_mask: mask, neighbors: nbrs, .. } = &mut ospf.message {
+            *mask = network_mask;
+            *nbrs = neighbors;
+        }
+        ospf.set_checksum();
+        ospf
+    }
+
+    // Instantiate a Link State Update packet carrying LSAs
+    pub fn link_state_update(router_id: Ipv4Addr, area_id: Ipv4Addr, lsas: Vec<Lsa>) -> Ospfv2 {
+        let mut ospf = Ospfv2 {
+            router_id,
+            area_id,
+            message: Ospfv2Message::LinkStateUpdate(lsas),
+            ..Ospfv2::default()
+        };
+        ospf.set_checksum();
+        ospf
+    }
+
+    // Internet checksum over the packet except the authentication field
+    pub fn calculate_ospf_checksum(&self) -> u16 {
+        let ospf = Ospfv2 {
+            checksum:       0,
+            authentication: [0; 8],
+            message:        self.message.clone(),
+            ..*self
+        };
+        let bytes = ospf.as_bytes();
+        checksum(&[&bytes[..16], &bytes[24..]].concat())
+    }
+
+    // The checksum is not calculated with cryptographic authentication
+    pub fn verify_checksum(&self) -> bool {
+        self.au_type == OSPF_AUTH_CRYPTOGRAPHIC || self.checksum == self.calculate_ospf_checksum()
+    }
+
+    pub fn set_checksum(&mut self) {
+        self.checksum = match self.au_type {
+            OSPF_AUTH_CRYPTOGRAPHIC => 0,
+            _ => self.calculate_ospf_checksum()
+        };
+    }
+
+    // Encode the OSPFv2 packet into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let message = self.message.clone().as_bytes();
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(2);
+        cursor.write_u8(self.message.packet_type());
+        cursor.write_u16::<NetworkEndian>(message.len() as u16 + 24);
+        cursor.write_all(&self.router_id.octets());
+        cursor.write_all(&self.area_id.octets());
+        cursor.write_u16::<NetworkEndian>(self.checksum);
+        cursor.write_u16::<NetworkEndian>(self.au_type);
+        cursor.write_all(&self.authentication);
+        cursor.write_all(&message);
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice, a cryptographic authentication trailer is left over
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Ospfv2> {
+        let (bytes1, (packet_type, length, router_id, area_id, checksum, au_type, authentication)) = do_parse!(
+            bytes,
+            _version:       tag!([2]) >>
+            packet_type:    be_u8 >>
+            length:         call!(verify(be_u16, |length| *length >= 24)) >>
+            router_id:      parse_ip4addr >>
+            area_id:        parse_ip4addr >>
+            checksum:       be_u16 >>
+            au_type:        be_u16 >>
+            authentication: take!(8) >>
+                ((packet_type, length, router_id, area_id, checksum, au_type, authentication))
+        )?;
+        let (bytes2, data) = take(length as usize - 24)(bytes1)?;
+        let message = match Ospfv2Message::from_bytes(data, packet_type) {
+            Ok(([], message)) => message,
+            _ => Ospfv2Message::Unknown { packet_type, data: data.to_vec() }
+        };
+
+        Ok((
+            bytes2,
+            Ospfv2 {
+                router_id,
+                area_id,
+                checksum,
+                au_type,
+                authentication: <[u8; 8]>::try_from(authentication).unwrap(),
+                message
+            }
+        ))
+    }
+}
+
+// OSPFv3 LSA header
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Ospfv3LsaHeader {
+    pub age:                u16,
+    pub ls_type:            u16,
+    pub ls_id:              Ipv4Addr,
+    pub advertising_router: Ipv4Addr,
+    pub sequence:           u32,
+    pub checksum:           u16,
+    pub length:             u16
+}
+
+impl Default for Ospfv3LsaHeader {
+    fn default() -> Ospfv3LsaHeader {
+        Ospfv3LsaHeader {
+            age:                0,
+            ls_type:            LSA3_ROUTER,
+            ls_id:              Ipv4Addr::UNSPECIFIED,
+            advertising_router: Ipv4Addr::UNSPECIFIED,
+            sequence:           0x8000_0001,
+            checksum:           0,
+            length:             20
+        }
+    }
+}
+
+#[allow(unused_must_use)]
+impl Ospfv3LsaHeader {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u16::<NetworkEndian>(self.age);
+        cursor.write_u16::<NetworkEndian>(self.ls_type);
+        cursor.write_all(&self.ls_id.octets());
+        cursor.write_all(&self.advertising_router.octets());
+        cursor.write_u32::<NetworkEndian>(self.sequence);
+        cursor.write_u16::<NetworkEndian>(self.checksum);
+        cursor.write_u16::<NetworkEndian>(self.length);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Ospfv3LsaHeader> {
+        do_parse!(
+            bytes,
+            age:                be_u16 >>
+            ls_type:            be_u16 >>
+            ls_id:              parse_ip4addr >>
+            advertising_router: parse_ip4addr >>
+            sequence:           be_u32 >>
+            checksum:           be_u16 >>
+            length:             be_u16 >>
+                (
+                    Ospfv3LsaHeader {
+                        age,
+                        ls_type,
+                        ls_id,
+                        advertising_router,
+                        sequence,
+                        checksum,
+                        length
+                    }
+                )
+        )
+    }
+}
+
+// Interface description of an OSPFv3 Router LSA
+#[derive(Debug, PartialEq, Clone)]
+pub struct Ospfv3RouterLink {
+    pub link_type:             u8,
+    pub metric:                u16,
+    pub interface_id:          u32,
+    pub neighbor_interface_id: u32,
+    pub neighbor_router_id:    Ipv4Addr
+}
+
+// OSPFv3 address prefix
+#[derive(Debug, PartialEq, Clone)]
+pub struct Ospfv3Prefix {
+    pub length:  u8,
+    pub options: u8,
+    // Metric in Intra-Area-Prefix LSAs, referenced LS type in AS-External
+    // LSAs and zero otherwise
+    pub metric:  u16,
+    pub prefix:  Ipv6Addr
+}
+
+#[allow(unused_must_use)]
+impl Ospfv3Prefix {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.length);
+        cursor.write_u8(self.options);
+        cursor.write_u16::<NetworkEndian>(self.metric);
+        cursor.write_all(&self.prefix.octets()[..prefix_len(self.length)]);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Ospfv3Prefix> {
+        let (bytes1, length)  = verify(be_u8, |length| *length <= 128)(bytes)?;
+        let (bytes2, options) = be_u8(bytes1)?;
+        let (bytes3, metric)  = be_u16(bytes2)?;
+        let (bytes4, prefix)  = take(prefix_len(length))(bytes3)?;
+        let mut octets = [0; 16];
+        octets[..prefix.len()].copy_from_slice(prefix);
+        Ok((bytes4, Ospfv3Prefix { length, options, metric, prefix: Ipv6Addr::from(octets) }))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Ospfv3LsaBody {
+    Router { flags: u8, options: u32, links: Vec<Ospfv3RouterLink> },
+    Network { options: u32, attached_routers: Vec<Ipv4Addr> },
+    InterAreaPrefix { metric: u32, prefix: Ospfv3Prefix },
+    // AS-External and NSSA LSAs
+    AsExternal {
+        flags:              u8,
+        metric:             u32,
+        prefix:             Ospfv3Prefix,
+        forwarding_address: Option<Ipv6Addr>,
+        route_tag:          Option<u32>,
+        referenced_ls_id:   Option<u32>
+    },
+    Link {
+        priority:   u8,
+        options:    u32,
+        link_local: Ipv6Addr,
+        prefixes:   Vec<Ospfv3Prefix>
+    },
+    IntraAreaPrefix {
+        referenced_ls_type:            u16,
+        referenced_ls_id:              Ipv4Addr,
+        referenced_advertising_router: Ipv4Addr,
+        prefixes:                      Vec<Ospfv3Prefix>
+    },
+    Unknown(Vec<u8>)
+}
+
+#[allow(unused_must_use)]
+impl Ospfv3LsaBody {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        match self {
+            Ospfv3LsaBody::Router { flags, options, links } => {
+                cursor.write_u8(flags);
+                cursor.write_u24::<NetworkEndian>(options);
+                for link in links {
+                    cursor.write_u8(link.link_type);
+                    cursor.write_u8(0);
+                    cursor.write_u16::<NetworkEndian>(link.metric);
+                    cursor.write_u32::<NetworkEndian>(link.interface_id);
+                    cursor.write_u32::<NetworkEndian>(link.neighbor_interface_id);
+                    cursor.write_all(&link.neighbor_router_id.octets());
+                }
+            },
+            Ospfv3LsaBody::Network { options, attached_routers } => {
+                cursor.write_u32::<NetworkEndian>(options & 0x00ff_ffff);
+                for router in attached_routers {
+                    cursor.write_all(&router.octets());
+                }
+            },
+            Ospfv3LsaBody::InterAreaPrefix { metric, prefix } => {
+                cursor.write_u32::<NetworkEndian>(metric & 0x00ff_ffff);
+                cursor.write_all(&prefix.as_bytes());
+            },
+            Ospfv3LsaBody::AsExternal { flags, metric, prefix, forwarding_address, route_tag, referenced_ls_id } => {
+                let flags = (flags & 0xf8) |
+                    (forwarding_address.is_some() as u8) << 1 |
+                    route_tag.is_some() as u8;
+                cursor.write_u8(flags);
+                cursor.write_u24::<NetworkEndian>(metric);
+                cursor.write_all(&prefix.as_bytes());
+                if let Some(forwarding_address) = forwarding_address {
+                    cursor.write_all(&forwarding_address.octets());
+                }
+                if let Some(route_tag) = route_tag {
+                    cursor.write_u32::<NetworkEndian>(route_tag);
+                }
+                if let Some(referenced_ls_id) = referenced_ls_id {
+                    cursor.write_u32::<NetworkEndian>(referenced_ls_id);
+                }
+            },
+            Ospfv3LsaBody::Link { priority, options, link_local, prefixes } => {
+                cursor.write_u8(priority);
+                cursor.write_u24::<NetworkEndian>(options);
+                cursor.write_all(&link_local.octets());
+                cursor.write_u32::<NetworkEndian>(prefixes.len() as u32);
+                for prefix in prefixes {
+                    cursor.write_all(&prefix.as_bytes());
+                }
+            },
+            Ospfv3LsaBody::IntraAreaPrefix {
+                referenced_ls_type, referenced_ls_id, referenced_advertising_router, prefixes
+            } => {
+                cursor.write_u16::<NetworkEndian>(prefixes.len() as u16);
+                cursor.write_u16::<NetworkEndian>(referenced_ls_type);
+                cursor.write_all(&referenced_ls_id.octets());
+                cursor.write_all(&referenced_advertising_router.octets());
+                for prefix in prefixes {
+                    cursor.write_all(&prefix.as_bytes());
+                }
+            },
+            Ospfv3LsaBody::Unknown(data) => {
+                cursor.write_all(&data);
+            }
+        }
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8], ls_type: u16) -> IResult<&[u8], Ospfv3LsaBody> {
+        match ls_type {
+            LSA3_ROUTER => {
+                let (bytes1, flags)   = be_u8(bytes)?;
+                let (bytes2, options) = be_u24(bytes1)?;
+                let (bytes3, links)   = parse_all(bytes2, parse_ospfv3_router_link)?;
+                Ok((bytes3, Ospfv3LsaBody::Router { flags, options, links }))
+            },
+            LSA3_NETWORK => {
+                let (bytes1, options) = be_u32(bytes)?;
+                let (bytes2, routers) = parse_ip4addrs(bytes1)?;
+                Ok((bytes2, Ospfv3LsaBody::Network { options: options & 0x00ff_ffff, attached_routers: routers }))
+            },
+            LSA3_INTER_AREA_PREFIX => {
+                let (bytes1, metric) = be_u32(bytes)?;
+                let (bytes2, prefix) = Ospfv3Prefix::from_bytes(bytes1)?;
+                Ok((bytes2, Ospfv3LsaBody::InterAreaPrefix { metric: metric & 0x00ff_ffff, prefix }))
+            },
+            LSA3_AS_EXTERNAL | LSA3_NSSA => {
+                let (bytes1, flags)  = be_u8(bytes)?;
+                let (bytes2, metric) = be_u24(bytes1)?;
+                let (bytes3, prefix) = Ospfv3Prefix::from_bytes(bytes2)?;
+                let (bytes4, forwarding_address) = match flags & 0x02 {
+                    0 => (bytes3, None),
+                    _ => parse_ip6addr(bytes3).map(|(b, addr)| (b, Some(addr)))?
+                };
+                let (bytes5, route_tag) = match flags & 0x01 {
+                    0 => (bytes4, None),
+                    _ => be_u32(bytes4).map(|(b, tag)| (b, Some(tag)))?
+                };
+                let (bytes6, referenced_ls_id) = match prefix.metric {
+                    0 => (bytes5, None),
+                    _ => be_u32(bytes5).map(|(b, id)| (b, Some(id)))?
+                };
+                Ok((
+                    bytes6,
+                    Ospfv3LsaBody::AsExternal { flags, metric, prefix, forwarding_address, route_tag, referenced_ls_id }
+                ))
+            },
+            LSA3_LINK => do_parse!(
+                bytes,
+                priority:   be_u8 >>
+                options:    be_u24 >>
+                link_local: parse_ip6addr >>
+                n_prefixes: be_u32 >>
+                prefixes:   call!(count(Ospfv3Prefix::from_bytes, n_prefixes as usize)) >>
+                    (
+                        Ospfv3LsaBody::Link {
+                            priority,
+                            options,
+                            link_local,
+                            prefixes
+                        }
+                    )
+            ),
+            LSA3_INTRA_AREA_PREFIX => do_parse!(
+                bytes,
+                n_prefixes:                    be_u16 >>
+                referenced_ls_type:            be_u16 >>
+                referenced_ls_id:              parse_ip4addr >>
+                referenced_advertising_router: parse_ip4addr >>
+                prefixes:                      call!(count(Ospfv3Prefix::from_bytes, n_prefixes as usize)) >>
+                    (
+                        Ospfv3LsaBody::IntraAreaPrefix {
+                            referenced_ls_type,
+                            referenced_ls_id,
+                            referenced_advertising_router,
+                            prefixes
+                        }
+                    )
+            ),
+            _ =>
+                Ok((&[], Ospfv3LsaBody::Unknown(bytes.to_vec())))
+        }
+    }
+}
+
+// OSPFv3 Link State Advertisement
+#[derive(Debug, PartialEq, Clone)]
+pub struct Ospfv3Lsa {
+    pub header: Ospfv3LsaHeader,
+    pub body:   Ospfv3LsaBody
+}
+
+#[allow(unused_must_use)]
+impl Ospfv3Lsa {
+    // Fletcher checksum over the LSA except the LS age
+    pub fn calculate_lsa_checksum(&self) -> u16 {
+        fletcher_checksum(&self.clone().as_bytes()[2..], 14)
+    }
+
+    pub fn verify_checksum(&self) -> bool {
+        self.header.checksum == self.calculate_lsa_checksum()
+    }
+
+    // Fill in the length and checksum of the LSA header
+    pub fn set_checksum(&mut self) {
+        self.header.length = self.body.clone().as_bytes().len() as u16 + 20;
+        self.header.checksum = self.calculate_lsa_checksum();
+    }
+
+    // Encode the LSA into a byte slice, the LSA length is taken from the body
+    pub fn as_bytes(self) -> Vec<u8> {
+        let body = self.body.as_bytes();
+        let header = Ospfv3LsaHeader { length: body.len() as u16 + 20, ..self.header };
+        [header.as_bytes(), body].concat()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Ospfv3Lsa> {
+        let (bytes1, header) = verify(Ospfv3LsaHeader::from_bytes, |h| h.length >= 20)(bytes)?;
+        let (bytes2, data)   = take(header.length as usize - 20)(bytes1)?;
+        let body = match Ospfv3LsaBody::from_bytes(data, header.ls_type) {
+            Ok(([], body)) => body,
+            _ => Ospfv3LsaBody::Unknown(data.to_vec())
+        };
+        Ok((bytes2, Ospfv3Lsa { header, body }))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Ospfv3Message {
+    Hello {
+        interface_id:             u32,
+        priority:                 u8,
+        options:                  u32,
+        hello_interval:           u16,
+        dead_interval:            u16,
+        designated_router:        Ipv4Addr,
+        backup_designated_router: Ipv4Addr,
+        neighbors:                Vec<Ipv4Addr>
+    },
+    DatabaseDescription {
+        options:     u32,
+        mtu:         u16,
+        flags:       u8,
+        sequence:    u32,
+        lsa_headers: Vec<Ospfv3LsaHeader>
+    },
+    LinkStateRequest(Vec<LsRequest>),
+    LinkStateUpdate(Vec<Ospfv3Lsa>),
+    LinkStateAck(Vec<Ospfv3LsaHeader>),
+    Unknown { packet_type: u8, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl Ospfv3Message {
+    pub fn packet_type(&self) -> u8 {
+        match self {
+            Ospfv3Message::Hello { .. } => OSPF_HELLO,
+            Ospfv3Message::DatabaseDescription { .. } => OSPF_DB_DESCRIPTION,
+            Ospfv3Message::LinkStateRequest(_) => OSPF_LS_REQUEST,
+            Ospfv3Message::LinkStateUpdate(_) => OSPF_LS_UPDATE,
+            Ospfv3Message::LinkStateAck(_) => OSPF_LS_ACK,
+            Ospfv3Message::Unknown { packet_type, .. } => *packet_type
+        }
+    }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        match self {
+            Ospfv3Message::Hello {
+                interface_id, priority, options, hello_interval, dead_interval,
+                designated_router, backup_designated_router, neighbors
+            } => {
+                cursor.write_u32::<NetworkEndian>(interface_id);
+                cursor.write_u8(priority);
+                cursor.write_u24::<NetworkEndian>(options);
+                cursor.write_u16::<NetworkEndian>(hello_interval);
+                cursor.write_u16::<NetworkEndian>(dead_interval);
+                cursor.write_all(&designated_router.octets());
+                cursor.write_all(&backup_designated_router.octets());
+                for neighbor in neighbors {
+                    cursor.write_all(&neighbor.octets());
+                }
+            },
+            Ospfv3Message::DatabaseDescription { options, mtu, flags, sequence, lsa_headers } => {
+                cursor.write_u32::<NetworkEndian>(options & 0x00ff_ffff);
+                cursor.write_u16::<NetworkEndian>(mtu);
+                cursor.write_u8(0);
+                cursor.write_u8(flags);
+                cursor.write_u32::<NetworkEndian>(sequence);
+                for header in lsa_headers {
+                    cursor.write_all(&header.as_bytes());
+                }
+            },
+            Ospfv3Message::LinkStateRequest(requests) => {
+                for request in requests {
+                    cursor.write_all(&request.as_bytes());
+                }
+            },
+            Ospfv3Message::LinkStateUpdate(lsas) => {
+                cursor.write_u32::<NetworkEndian>(lsas.len() as u32);
+                for lsa in lsas {
+                    cursor.write_all(&lsa.as_bytes());
+                }
+            },
+            Ospfv3Message::LinkStateAck(lsa_headers) => {
+                for header in lsa_headers {
+                    cursor.write_all(&header.as_bytes());
+                }
+            },
+            Ospfv3Message::Unknown { data, .. } => {
+                cursor.write_all(&data);
+            }
+        }
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8], packet_type: u8) -> IResult<&[u8], Ospfv3Message> {
+        match packet_type {
+            OSPF_HELLO => do_parse!(
+                bytes,
+                interface_id:             be_u32 >>
+                priority:                 be_u8 >>
+                options:                  be_u24 >>
+                hello_interval:           be_u16 >>
+                dead_interval:            be_u16 >>
+                designated_router:        parse_ip4addr >>
+                backup_designated_router: parse_ip4addr >>
+                neighbors:                parse_ip4addrs >>
+                    (
+                        Ospfv3Message::Hello {
+                            interface_id,
+                            priority,
+                            options,
+                            hello_interval,
+                            dead_interval,
+                            designated_router,
+                            backup_designated_router,
+                            neighbors
+                        }
+                    )
+            ),
+            OSPF_DB_DESCRIPTION => do_parse!(
+                bytes,
+                options:     be_u32 >>
+                mtu:         be_u16 >>
+                _reserved:   be_u8 >>
+                flags:       be_u8 >>
+                sequence:    be_u32 >>
+                lsa_headers: call!(parse_all, Ospfv3LsaHeader::from_bytes) >>
+                    (
+                        Ospfv3Message::DatabaseDescription {
+                            options: options & 0x00ff_ffff,
+                            mtu,
+                            flags,
+                            sequence,
+                            lsa_headers
+                        }
+                    )
+            ),
+            OSPF_LS_REQUEST => {
+                let (bytes1, requests) = parse_all(bytes, LsRequest::from_bytes)?;
+                Ok((bytes1, Ospfv3Message::LinkStateRequest(requests)))
+            },
+            OSPF_LS_UPDATE => {
+                let (bytes1, n_lsas) = verify(be_u32, |n_lsas| *n_lsas as usize <= bytes.len() / LSA_HEADER_LENGTH)(bytes)?;
+                let (bytes2, lsas)   = count(Ospfv3Lsa::from_bytes, n_lsas as usize)(bytes1)?;
+                Ok((bytes2, Ospfv3Message::LinkStateUpdate(lsas)))
+            },
+            OSPF_LS_ACK => {
+                let (bytes1, lsa_headers) = parse_all(bytes, Ospfv3LsaHeader::from_bytes)?;
+                Ok((bytes1, Ospfv3Message::LinkStateAck(lsa_headers)))
+            },
+            _ =>
+                Ok((&[], Ospfv3Message::Unknown { packet_type, data: bytes.to_vec() }))
+        }
+    }
+}
+
+// OSPF version 3 (RFC 5340)
+#[derive(Debug, PartialEq)]
+pub struct Ospfv3 {
+    pub router_id:   Ipv4Addr,
+    pub area_id:     Ipv4Addr,
+    pub checksum:    u16,
+    pub instance_id: u8,
+    pub message:     Ospfv3Message
+}
+
+impl Default for Ospfv3 {
+    fn default() -> Ospfv3 {
+        Ospfv3 {
+            router_id:   Ipv4Addr::UNSPECIFIED,
+            area_id:     Ipv4Addr::UNSPECIFIED,
+            checksum:    0,
+            instance_id: 0,
+            message:     Ospfv3Message::Hello {
+                interface_id:             0,
+                priority:                 1,
+                options:                  0x13,
+                hello_interval:           10,
+                dead_interval:            40,
+                designated_router:        Ipv4Addr::UNSPECIFIED,
+                backup_designated_router: Ipv4Addr::UNSPECIFIED,
+                neighbors:                Vec::new()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Ospfv3 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "OSPFv3(\
+             type: {}, \
+             router_id: {}, \
+             area_id: {}, \
+             instance_id: {}\
+             )",
+            self.message.packet_type(),
+            self.router_id,
+            self.area_id,
+            self.instance_id
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Ospfv3 {
+    // Instantiate a new OSPFv3 Hello packet
+    pub fn new() -> Ospfv3 { Ospfv3::default() }
+
+    // Internet checksum including the IPv6 pseudo header
+    pub fn calculate_ospf_checksum(&self, source: Ipv6Addr, destination: Ipv6Addr) -> u16 {
+        let ospf = Ospfv3 {
+            checksum: 0,
+            message:  self.message.clone(),
+            ..*self
+        };
+        let bytes = ospf.as_bytes();
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_all(&source.octets());
+        cursor.write_all(&destination.octets());
+        cursor.write_u32::<NetworkEndian>(bytes.len() as u32);
+        cursor.write_u32::<NetworkEndian>(89);
+        cursor.write_all(&bytes);
+        checksum(&cursor.into_inner())
+    }
+
+    pub fn verify_checksum(&self, source: Ipv6Addr, destination: Ipv6Addr) -> bool {
+        self.checksum == self.calculate_ospf_checksum(source, destination)
+    }
+
+    pub fn set_checksum(&mut self, source: Ipv6Addr, destination: Ipv6Addr) {
+        self.checksum = self.calculate_ospf_checksum(source, destination);
+    }
+
+    // Encode the OSPFv3 packet into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let message = self.message.clone().as_bytes();
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(3);
+        cursor.write_u8(self.message.packet_type());
+        cursor.write_u16::<NetworkEndian>(message.len() as u16 + 16);
+        cursor.write_all(&self.router_id.octets());
+        cursor.write_all(&self.area_id.octets());
+        cursor.write_u16::<NetworkEndian>(self.checksum);
+        cursor.write_u8(self.instance_id);
+        cursor.write_u8(0);
+        cursor.write_all(&message);
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice, an authentication trailer is left over
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Ospfv3> {
+        let (bytes1, (packet_type, length, router_id, area_id, checksum, instance_id)) = do_parse!(
+            bytes,
+            _version:    tag!([3]) >>
+            packet_type: be_u8 >>
+            length:      call!(verify(be_u16, |length| *length >= 16)) >>
+            router_id:   parse_ip4addr >>
+            area_id:     parse_ip4addr >>
+            checksum:    be_u16 >>
+            instance_id: be_u8 >>
+            _reserved:   be_u8 >>
+                ((packet_type, length, router_id, area_id, checksum, instance_id))
+        )?;
+        let (bytes2, data) = take(length as usize - 16)(bytes1)?;
+        let message = match Ospfv3Message::from_bytes(data, packet_type) {
+            Ok(([], message)) => message,
+            _ => Ospfv3Message::Unknown { packet_type, data: data.to_vec() }
+        };
+
+        Ok((bytes2, Ospfv3 { router_id, area_id, checksum, instance_id, message }))
+    }
+}
+
+// private functions
+
+// Octets of an OSPFv3 prefix, padded to a 32-bit boundary
+fn prefix_len(length: u8) -> usize { (length as usize).div_ceil(32) * 4 }
+
+fn parse_ip4addrs(bytes: &[u8]) -> IResult<&[u8], Vec<Ipv4Addr>> {
+    parse_all(bytes, parse_ip4addr)
+}
+
+fn parse_router_link(bytes: &[u8]) -> IResult<&[u8], RouterLink> {
+    do_parse!(
+        bytes,
+        link_id:   parse_ip4addr >>
+        link_data: parse_ip4addr >>
+        link_type: be_u8 >>
+        n_tos:     be_u8 >>
+        metric:    be_u16 >>
+        tos:       call!(count(parse_tos_metric, n_tos as usize)) >>
+            (
+                RouterLink {
+                    link_id,
+                    link_data,
+                    link_type,
+                    metric,
+                    tos
+                }
+            )
+    )
+}
+
+fn parse_tos_metric(bytes: &[u8]) -> IResult<&[u8], (u8, u16)> {
+    do_parse!(
+        bytes,
+        tos:       be_u8 >>
+        _reserved: be_u8 >>
+        metric:    be_u16 >>
+            ((tos, metric))
+    )
+}
+
+fn parse_ospfv3_router_link(bytes: &[u8]) -> IResult<&[u8], Ospfv3RouterLink> {
+    do_parse!(
+        bytes,
+        link_type:             be_u8 >>
+        _reserved:             be_u8 >>
+        metric:                be_u16 >>
+        interface_id:          be_u32 >>
+        neighbor_interface_id: be_u32 >>
+        neighbor_router_id:    parse_ip4addr >>
+            (
+                Ospfv3RouterLink {
+                    link_type,
+                    metric,
+                    interface_id,
+                    neighbor_interface_id,
+                    neighbor_router_id
+                }
+            )
+    )
+}
+
+#[cfg(test)]
+mod tests_ospf {
+    use crate::ospf::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn parse_hello() {
+        let frame = &mut [
+            0x02,0x01,0x00,0x2c,0xc0,0xa8,0xaa,0x08,
+            0x00,0x00,0x00,0x01,0x27,0x3b,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0xff,0xff,0xff,0x00,0x00,0x0a,0x02,0x01,
+            0x00,0x00,0x00,0x28,0xc0,0xa8,0xaa,0x08,
+            0x00,0x00,0x00,0x00
+        ];
+
+        let (leftover, ospf) = Ospfv2::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert!(ospf.verify_checksum());
+        assert_eq!(Ospfv2Message::Hello {
+            network_mask:             Ipv4Addr::new(255, 255, 255, 0),
+            hello_interval:           10,
+            options:                  0x02,
+            priority:                 1,
+            dead_interval:            40,
+            designated_router:        Ipv4Addr::new(192, 168, 170, 8),
+            backup_designated_router: Ipv4Addr::UNSPECIFIED,
+            neighbors:                vec![]
+        }, ospf.message);
+        assert_eq!("OSPFv2(type: 1, router_id: 192.168.170.8, area_id: 0.0.0.1, checksum: 0x273b)", format!("{}", ospf));
+        assert_eq!(frame.to_vec(), ospf.as_bytes());
+    }
+
+    #[test]
+    fn parse_ls_update() {
+        let frame = &mut [
+            0x02,0x04,0x00,0x40,0xc0,0xa8,0xaa,0x03,
+            0x00,0x00,0x00,0x01,0xb1,0xed,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x01,0x00,0x01,0x02,0x01,
+            0xc0,0xa8,0xaa,0x03,0xc0,0xa8,0xaa,0x03,
+            0x80,0x00,0x00,0x01,0x1c,0xea,0x00,0x24,
+            0x00,0x00,0x00,0x01,0xc0,0xa8,0xaa,0x00,
+            0xff,0xff,0xff,0x00,0x03,0x00,0x00,0x0a
+        ];
+
+        let (leftover, ospf) = Ospfv2::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert!(ospf.verify_checksum());
+        let lsas = match &ospf.message {
+            Ospfv2Message::LinkStateUpdate(lsas) => lsas,
+            other => panic!("unexpected message: {:?}", other)
+        };
+        assert_eq!(1, lsas.len());
+        assert!(lsas[0].verify_checksum());
+        assert_eq!(LsaBody::Router {
+            flags: 0,
+            links: vec![RouterLink {
+                link_id:   Ipv4Addr::new(192, 168, 170, 0),
+                link_data: Ipv4Addr::new(255, 255, 255, 0),
+                link_type: ROUTER_LINK_STUB,
+                metric:    10,
+                tos:       vec![]
+            }]
+        }, lsas[0].body);
+
+        let mut lsa = Lsa { header: LsaHeader { checksum: 0, length: 0, ..lsas[0].header }, body: lsas[0].body.clone() };
+        lsa.set_checksum();
+        assert_eq!(lsas[0], lsa);
+        let update = Ospfv2::link_state_update(ospf.router_id, ospf.area_id, vec![lsa]);
+        assert_eq!(ospf, update);
+        assert_eq!(frame.to_vec(), update.as_bytes());
+    }
+
+    #[test]
+    fn parse_ls_update_count() {
+        let frame = &mut [
+            0x02,0x04,0x00,0x1c,0xc0,0xa8,0xaa,0x03,
+            0x00,0x00,0x00,0x01,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0xff,0xff,0xff,0xff
+        ];
+
+        let (leftover, ospf) = Ospfv2::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(Ospfv2Message::Unknown { packet_type: OSPF_LS_UPDATE, data: vec![0xff; 4] }, ospf.message);
+    }
+
+    #[test]
+    fn parse_v3_hello() {
+        let frame = &mut [
+            0x03,0x01,0x00,0x24,0x01,0x01,0x01,0x01,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x05,0x01,0x00,0x00,0x13,
+            0x00,0x0a,0x00,0x28,0x01,0x01,0x01,0x01,
+            0x00,0x00,0x00,0x00
+        ];
+        let source = Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1);
+        let destination = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 5);
+
+        let (leftover, mut ospf) = Ospfv3::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        match &ospf.message {
+            Ospfv3Message::Hello { interface_id, options, designated_router, .. } => {
+                assert_eq!(5, *interface_id);
+                assert_eq!(0x13, *options);
+                assert_eq!(Ipv4Addr::new(1, 1, 1, 1), *designated_router);
+            },
+            other => panic!("unexpected message: {:?}", other)
+        }
+        assert!(!ospf.verify_checksum(source, destination));
+        ospf.set_checksum(source, destination);
+        assert!(ospf.verify_checksum(source, destination));
+        assert_eq!("OSPFv3(type: 1, router_id: 1.1.1.1, area_id: 0.0.0.0, instance_id: 0)", format!("{}", ospf));
+        assert_eq!(frame[16..].to_vec(), ospf.as_bytes()[16..].to_vec());
+    }
+}
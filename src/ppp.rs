@@ -0,0 +1,577 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::number::complete::{be_u8, be_u16, be_u32};
+use nom::{call, do_parse, take, IResult};
+use std::fmt;
+use std::io::{Cursor, Write};
+use std::net::Ipv4Addr;
+
+// Protocol numbers
+pub const PPP_IP:     u16 = 0x0021;
+pub const PPP_IPV6:   u16 = 0x0057;
+pub const PPP_IPCP:   u16 = 0x8021;
+pub const PPP_IPV6CP: u16 = 0x8057;
+pub const PPP_LCP:    u16 = 0xc021;
+pub const PPP_PAP:    u16 = 0xc023;
+pub const PPP_CHAP:   u16 = 0xc223;
+
+// Control protocol codes, 8 to 11 are LCP only
+pub const PPP_CONFIGURE_REQUEST: u8 = 1;
+pub const PPP_CONFIGURE_ACK:     u8 = 2;
+pub const PPP_CONFIGURE_NAK:     u8 = 3;
+pub const PPP_CONFIGURE_REJECT:  u8 = 4;
+pub const PPP_TERMINATE_REQUEST: u8 = 5;
+pub const PPP_TERMINATE_ACK:     u8 = 6;
+pub const PPP_CODE_REJECT:       u8 = 7;
+pub const PPP_PROTOCOL_REJECT:   u8 = 8;
+pub const PPP_ECHO_REQUEST:      u8 = 9;
+pub const PPP_ECHO_REPLY:        u8 = 10;
+pub const PPP_DISCARD_REQUEST:   u8 = 11;
+
+// PAP codes
+pub const PAP_AUTHENTICATE_REQUEST: u8 = 1;
+pub const PAP_AUTHENTICATE_ACK:     u8 = 2;
+pub const PAP_AUTHENTICATE_NAK:     u8 = 3;
+
+// CHAP codes
+pub const CHAP_CHALLENGE: u8 = 1;
+pub const CHAP_RESPONSE:  u8 = 2;
+pub const CHAP_SUCCESS:   u8 = 3;
+pub const CHAP_FAILURE:   u8 = 4;
+
+// PPP header (RFC 1661), the address and control fields precede the
+// protocol on HDLC-like framed links
+#[derive(Debug, PartialEq)]
+pub struct Ppp {
+    pub address_control: bool,
+    pub protocol:        u16
+}
+
+impl Default for Ppp {
+    fn default() -> Ppp {
+        Ppp {
+            address_control: false,
+            protocol:        PPP_IP
+        }
+    }
+}
+
+impl fmt::Display for Ppp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PPP(protocol: 0x{:04x})", self.protocol)
+    }
+}
+
+#[allow(unused_must_use)]
+impl Ppp {
+    // Instantiate a new PPP header carrying IPv4
+    pub fn new() -> Ppp { Ppp::default() }
+
+    pub fn protocol(self, protocol: u16) -> Ppp {
+        Ppp { protocol, ..self }
+    }
+
+    // Encode the PPP header into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        if self.address_control {
+            cursor.write_u8(0xff);
+            cursor.write_u8(0x03);
+        }
+        cursor.write_u16::<NetworkEndian>(self.protocol);
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice, a protocol with an odd first octet is the
+    // single octet form of Protocol-Field-Compression
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Ppp> {
+        let address_control = bytes.starts_with(&[0xff, 0x03]);
+        let bytes1 = if address_control { &bytes[2..] } else { bytes };
+        let (bytes2, protocol) = match bytes1.first() {
+            Some(octet) if octet & 0x01 != 0 => be_u8(bytes1).map(|(b, p)| (b, p as u16))?,
+            _ => be_u16(bytes1)?
+        };
+        Ok((bytes2, Ppp { address_control, protocol }))
+    }
+}
+
+// Configuration options of LCP, IPCP and IPv6CP
+#[derive(Debug, PartialEq, Clone)]
+pub enum PppOption {
+    // LCP
+    Mru(u16),
+    AuthProtocol { protocol: u16, data: Vec<u8> },
+    MagicNumber(u32),
+    ProtocolFieldCompression,
+    AddressControlFieldCompression,
+    // IPCP
+    IpAddress(Ipv4Addr),
+    PrimaryDns(Ipv4Addr),
+    SecondaryDns(Ipv4Addr),
+    // IPv6CP
+    InterfaceIdentifier(u64),
+    Unknown { option_type: u8, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl PppOption {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let (option_type, data) = match self {
+            PppOption::Mru(mru) =>
+                (1, mru.to_be_bytes().to_vec()),
+            PppOption::AuthProtocol { protocol, data } =>
+                (3, [protocol.to_be_bytes().to_vec(), data].concat()),
+            PppOption::MagicNumber(magic_number) =>
+                (5, magic_number.to_be_bytes().to_vec()),
+            PppOption::ProtocolFieldCompression =>
+                (7, Vec::new()),
+            PppOption::AddressControlFieldCompression =>
+                (8, Vec::new()),
+            PppOption::IpAddress(address) =>
+                (3, address.octets().to_vec()),
+            PppOption::PrimaryDns(address) =>
+                (129, address.octets().to_vec()),
+            PppOption::SecondaryDns(address) =>
+                (131, address.octets().to_vec()),
+            PppOption::InterfaceIdentifier(identifier) =>
+                (1, identifier.to_be_bytes().to_vec()),
+            PppOption::Unknown { option_type, data } =>
+                (option_type, data)
+        };
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(option_type);
+        cursor.write_u8(data.len() as u8 + 2);
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    // Option types overlap between protocols, so the control protocol
+    // carrying the option selects the decoding
+    pub fn from_bytes(bytes: &[u8], protocol: u16) -> IResult<&[u8], PppOption> {
+        let (bytes1, option_type) = be_u8(bytes)?;
+        let (bytes2, length)      = verify(be_u8, |length| *length >= 2)(bytes1)?;
+        let (bytes3, data)        = take(length - 2)(bytes2)?;
+        let option = match (protocol, option_type, data.len()) {
+            (PPP_LCP, 1, 2) =>
+                PppOption::Mru(u16::from_be_bytes([data[0], data[1]])),
+            (PPP_LCP, 3, 2..=253) => PppOption::AuthProtocol {
+                protocol: u16::from_be_bytes([data[0], data[1]]),
+                data:     data[2..].to_vec()
+            },
+            (PPP_LCP, 5, 4) =>
+                PppOption::MagicNumber(u32::from_be_bytes([data[0], data[1], data[2], data[3]])),
+            (PPP_LCP, 7, 0) =>
+                PppOption::ProtocolFieldCompression,
+            (PPP_LCP, 8, 0) =>
+                PppOption::AddressControlFieldCompression,
+            (PPP_IPCP, 3, 4) =>
+                PppOption::IpAddress(Ipv4Addr::new(data[0], data[1], data[2], data[3])),
+            (PPP_IPCP, 129, 4) =>
+                PppOption::PrimaryDns(Ipv4Addr::new(data[0], data[1], data[2], data[3])),
+            (PPP_IPCP, 131, 4) =>
+                PppOption::SecondaryDns(Ipv4Addr::new(data[0], data[1], data[2], data[3])),
+            (PPP_IPV6CP, 1, 8) => {
+                let mut identifier = [0; 8];
+                identifier.copy_from_slice(data);
+                PppOption::InterfaceIdentifier(u64::from_be_bytes(identifier))
+            },
+            _ =>
+                PppOption::Unknown { option_type, data: data.to_vec() }
+        };
+        Ok((bytes3, option))
+    }
+}
+
+// LCP (RFC 1661), IPCP (RFC 1332) and IPv6CP (RFC 5072) packet
+#[derive(Debug, PartialEq)]
+pub struct PppControl {
+    pub protocol:     u16,
+    pub code:         u8,
+    pub identifier:   u8,
+    // Configure-Request, -Ack, -Nak and -Reject
+    pub options:      Vec<PppOption>,
+    // Echo-Request, Echo-Reply and Discard-Request
+    pub magic_number: u32,
+    pub data:         Vec<u8>
+}
+
+impl Default for PppControl {
+    fn default() -> PppControl {
+        PppControl {
+            protocol:     PPP_LCP,
+            code:         PPP_CONFIGURE_REQUEST,
+            identifier:   0,
+            options:      Vec::new(),
+            magic_number: 0,
+            data:         Vec::new()
+        }
+    }
+}
+
+impl fmt::Display for PppControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self.protocol {
+            PPP_IPCP => "IPCP",
+            PPP_IPV6CP => "IPv6CP",
+            _ => "LCP"
+        };
+        write!(
+            f,
+            "{}(\
+             code: {}, \
+             identifier: {}, \
+             options: {}\
+             )",
+            name,
+            self.code,
+            self.identifier,
+            self.options.len()
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl PppControl {
+    // Instantiate a new empty LCP Configure-Request
+    pub fn new() -> PppControl { PppControl::default() }
+
+    // Instantiate a Configure-Request of the given control protocol
+    pub fn configure_request(protocol: u16, identifier: u8, options: Vec<PppOption>) -> PppControl {
+        PppControl { protocol, identifier, options, ..PppControl::default() }
+    }
+
+    // Instantiate an LCP Echo-Request
+    pub fn echo_request(identifier: u8, magic_number: u32) -> PppControl {
+        PppControl { code: PPP_ECHO_REQUEST, identifier, magic_number, ..PppControl::default() }
+    }
+
+    pub fn is_configure(&self) -> bool {
+        (PPP_CONFIGURE_REQUEST..=PPP_CONFIGURE_REJECT).contains(&self.code)
+    }
+
+    pub fn is_echo(&self) -> bool {
+        self.protocol == PPP_LCP && (PPP_ECHO_REQUEST..=PPP_DISCARD_REQUEST).contains(&self.code)
+    }
+
+    // Encode the control packet into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let data: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(data);
+        if self.is_configure() {
+            for option in self.options.iter().cloned() {
+                cursor.write_all(&option.as_bytes());
+            }
+        } else if self.is_echo() {
+            cursor.write_u32::<NetworkEndian>(self.magic_number);
+        }
+        cursor.write_all(&self.data);
+        let data = cursor.into_inner();
+
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.code);
+        cursor.write_u8(self.identifier);
+        cursor.write_u16::<NetworkEndian>(data.len() as u16 + 4);
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice carried in the given control protocol
+    pub fn from_bytes(bytes: &[u8], protocol: u16) -> IResult<&[u8], PppControl> {
+        let (bytes1, (code, identifier, data)) = parse_control(bytes)?;
+        let control = PppControl { protocol, code, identifier, ..PppControl::default() };
+
+        if control.is_configure() {
+            let mut options = Vec::new();
+            let mut b = data;
+            while !b.is_empty() {
+                let (leftover, option) = PppOption::from_bytes(b, protocol)?;
+                options.push(option);
+                b = leftover;
+            }
+            return Ok((bytes1, PppControl { options, ..control }))
+        }
+
+        if control.is_echo() {
+            let (data, magic_number) = be_u32(data)?;
+            return Ok((bytes1, PppControl { magic_number, data: data.to_vec(), ..control }))
+        }
+
+        Ok((bytes1, PppControl { data: data.to_vec(), ..control }))
+    }
+}
+
+// Password Authentication Protocol packet (RFC 1334)
+#[derive(Debug, PartialEq, Default)]
+pub struct Pap {
+    pub code:       u8,
+    pub identifier: u8,
+    // Authenticate-Request
+    pub peer_id:    Vec<u8>,
+    pub password:   Vec<u8>,
+    // Authenticate-Ack and -Nak
+    pub message:    Vec<u8>
+}
+
+impl fmt::Display for Pap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "PAP(\
+             code: {}, \
+             identifier: {}, \
+             peer_id: {}\
+             )",
+            self.code,
+            self.identifier,
+            String::from_utf8_lossy(&self.peer_id)
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Pap {
+    // Instantiate a new empty PAP packet
+    pub fn new() -> Pap { Pap::default() }
+
+    // Instantiate an Authenticate-Request
+    pub fn authenticate_request(identifier: u8, peer_id: &[u8], password: &[u8]) -> Pap {
+        Pap {
+            code:     PAP_AUTHENTICATE_REQUEST,
+            identifier,
+            peer_id:  peer_id.to_vec(),
+            password: password.to_vec(),
+            message:  Vec::new()
+        }
+    }
+
+    // Encode the PAP packet into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let data: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(data);
+        if self.code == PAP_AUTHENTICATE_REQUEST {
+            cursor.write_u8(self.peer_id.len() as u8);
+            cursor.write_all(&self.peer_id);
+            cursor.write_u8(self.password.len() as u8);
+            cursor.write_all(&self.password);
+        } else {
+            cursor.write_u8(self.message.len() as u8);
+            cursor.write_all(&self.message);
+        }
+        let data = cursor.into_inner();
+
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.code);
+        cursor.write_u8(self.identifier);
+        cursor.write_u16::<NetworkEndian>(data.len() as u16 + 4);
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Pap> {
+        let (bytes1, (code, identifier, data)) = parse_control(bytes)?;
+        let pap = Pap { code, identifier, ..Pap::default() };
+
+        if code == PAP_AUTHENTICATE_REQUEST {
+            let (data, peer_id) = parse_length_value(data)?;
+            let (_, password)   = parse_length_value(data)?;
+            return Ok((bytes1, Pap { peer_id: peer_id.to_vec(), password: password.to_vec(), ..pap }))
+        }
+
+        let (_, message) = parse_length_value(data)?;
+        Ok((bytes1, Pap { message: message.to_vec(), ..pap }))
+    }
+}
+
+// Challenge Handshake Authentication Protocol packet (RFC 1994)
+#[derive(Debug, PartialEq, Default)]
+pub struct Chap {
+    pub code:       u8,
+    pub identifier: u8,
+    // Challenge and Response
+    pub value:      Vec<u8>,
+    pub name:       Vec<u8>,
+    // Success and Failure
+    pub message:    Vec<u8>
+}
+
+impl fmt::Display for Chap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "CHAP(\
+             code: {}, \
+             identifier: {}, \
+             name: {}\
+             )",
+            self.code,
+            self.identifier,
+            String::from_utf8_lossy(&self.name)
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Chap {
+    // Instantiate a new empty CHAP packet
+    pub fn new() -> Chap { Chap::default() }
+
+    // Instantiate a Challenge
+    pub fn challenge(identifier: u8, value: &[u8], name: &[u8]) -> Chap {
+        Chap {
+            code:    CHAP_CHALLENGE,
+            identifier,
+            value:   value.to_vec(),
+            name:    name.to_vec(),
+            message: Vec::new()
+        }
+    }
+
+    pub fn is_challenge_or_response(&self) -> bool {
+        self.code == CHAP_CHALLENGE || self.code == CHAP_RESPONSE
+    }
+
+    // Encode the CHAP packet into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let data: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(data);
+        if self.is_challenge_or_response() {
+            cursor.write_u8(self.value.len() as u8);
+            cursor.write_all(&self.value);
+            cursor.write_all(&self.name);
+        } else {
+            cursor.write_all(&self.message);
+        }
+        let data = cursor.into_inner();
+
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.code);
+        cursor.write_u8(self.identifier);
+        cursor.write_u16::<NetworkEndian>(data.len() as u16 + 4);
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Chap> {
+        let (bytes1, (code, identifier, data)) = parse_control(bytes)?;
+        let chap = Chap { code, identifier, ..Chap::default() };
+
+        if chap.is_challenge_or_response() {
+            let (name, value) = parse_length_value(data)?;
+            return Ok((bytes1, Chap { value: value.to_vec(), name: name.to_vec(), ..chap }))
+        }
+
+        Ok((bytes1, Chap { message: data.to_vec(), ..chap }))
+    }
+}
+
+// private functions
+
+// Code, identifier and the data bounded by the length field
+fn parse_control(bytes: &[u8]) -> IResult<&[u8], (u8, u8, &[u8])> {
+    do_parse!(
+        bytes,
+        code:       be_u8 >>
+        identifier: be_u8 >>
+        length:     call!(verify(be_u16, |length| *length >= 4)) >>
+        data:       take!(length - 4) >>
+            ((code, identifier, data))
+    )
+}
+
+fn parse_length_value(bytes: &[u8]) -> IResult<&[u8], &[u8]> {
+    let (bytes1, length) = be_u8(bytes)?;
+    take(length)(bytes1)
+}
+
+#[cfg(test)]
+mod tests_ppp {
+    use crate::ppp::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn parse_compressed_protocol() {
+        let (leftover, ppp) = Ppp::from_bytes(&[0xff, 0x03, 0x21, 0x45]).unwrap();
+        assert_eq!(vec![0x45], leftover.to_vec());
+        assert_eq!(Ppp { address_control: true, protocol: PPP_IP }, ppp);
+
+        let (leftover, ppp) = Ppp::from_bytes(&[0x57, 0x60]).unwrap();
+        assert_eq!(vec![0x60], leftover.to_vec());
+        assert_eq!(Ppp::new().protocol(PPP_IPV6), ppp);
+        assert_eq!(vec![0x00, 0x57], ppp.as_bytes());
+
+        let (leftover, ppp) = Ppp::from_bytes(&[0xc0, 0x21, 0x09]).unwrap();
+        assert_eq!(vec![0x09], leftover.to_vec());
+        assert_eq!(Ppp::new().protocol(PPP_LCP), ppp);
+    }
+
+    #[test]
+    fn parse_lcp() {
+        let frame = &mut [
+            0x01,0x01,0x00,0x0e,0x01,0x04,0x05,0xd4,
+            0x05,0x06,0x12,0x34,0x56,0x78
+        ];
+
+        let (leftover, lcp) = PppControl::from_bytes(frame, PPP_LCP).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(PppControl::configure_request(PPP_LCP, 1, vec![
+            PppOption::Mru(1492),
+            PppOption::MagicNumber(0x12345678)
+        ]), lcp);
+        assert_eq!("LCP(code: 1, identifier: 1, options: 2)", format!("{}", lcp));
+        assert_eq!(frame.to_vec(), lcp.as_bytes());
+
+        let echo = PppControl::echo_request(2, 0x12345678);
+        let bytes = echo.as_bytes();
+        assert_eq!(vec![0x09, 0x02, 0x00, 0x08, 0x12, 0x34, 0x56, 0x78], bytes);
+        assert_eq!(PppControl::echo_request(2, 0x12345678), PppControl::from_bytes(&bytes, PPP_LCP).unwrap().1);
+    }
+
+    #[test]
+    fn parse_ipcp() {
+        let frame = &mut [
+            0x03,0x02,0x00,0x10,0x03,0x06,0x0a,0x00,
+            0x00,0x02,0x81,0x06,0x08,0x08,0x08,0x08
+        ];
+
+        let (leftover, ipcp) = PppControl::from_bytes(frame, PPP_IPCP).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(PPP_CONFIGURE_NAK, ipcp.code);
+        assert_eq!(vec![
+            PppOption::IpAddress(Ipv4Addr::new(10, 0, 0, 2)),
+            PppOption::PrimaryDns(Ipv4Addr::new(8, 8, 8, 8))
+        ], ipcp.options);
+        assert_eq!(frame.to_vec(), ipcp.as_bytes());
+    }
+
+    #[test]
+    fn parse_pap_chap() {
+        let frame = &mut [
+            0x01,0x07,0x00,0x0e,0x04,0x75,0x73,0x65,
+            0x72,0x04,0x70,0x61,0x73,0x73
+        ];
+
+        let (leftover, pap) = Pap::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(Pap::authenticate_request(7, b"user", b"pass"), pap);
+        assert_eq!("PAP(code: 1, identifier: 7, peer_id: user)", format!("{}", pap));
+        assert_eq!(frame.to_vec(), pap.as_bytes());
+
+        let frame = &mut [
+            0x01,0x01,0x00,0x0d,0x04,0xde,0xad,0xbe,
+            0xef,0x42,0x52,0x41,0x53
+        ];
+
+        let (leftover, chap) = Chap::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(Chap::challenge(1, &[0xde, 0xad, 0xbe, 0xef], b"BRAS"), chap);
+        assert_eq!(frame.to_vec(), chap.as_bytes());
+    }
+}
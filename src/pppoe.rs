@@ -0,0 +1,290 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::number::complete::{be_u8, be_u16};
+use nom::{do_parse, IResult};
+use std::fmt;
+use std::io::{Cursor, Write};
+
+pub const ETH_P_PPP_DISC: u16 = 0x8863;
+pub const ETH_P_PPP_SES:  u16 = 0x8864;
+
+// Codes
+pub const PPPOE_SESSION: u8 = 0x00;
+pub const PPPOE_PADO:    u8 = 0x07;
+pub const PPPOE_PADI:    u8 = 0x09;
+pub const PPPOE_PADR:    u8 = 0x19;
+pub const PPPOE_PADS:    u8 = 0x65;
+pub const PPPOE_PADT:    u8 = 0xa7;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PppoeTag {
+    EndOfList,
+    ServiceName(String),
+    AcName(String),
+    HostUniq(Vec<u8>),
+    AcCookie(Vec<u8>),
+    VendorSpecific { vendor_id: u32, data: Vec<u8> },
+    RelaySessionId(Vec<u8>),
+    PppMaxPayload(u16),
+    ServiceNameError(String),
+    AcSystemError(String),
+    GenericError(String),
+    Unknown { tag_type: u16, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl PppoeTag {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let (tag_type, data) = match self {
+            PppoeTag::EndOfList =>
+                (0x0000, Vec::new()),
+            PppoeTag::ServiceName(name) =>
+                (0x0101, name.into_bytes()),
+            PppoeTag::AcName(name) =>
+                (0x0102, name.into_bytes()),
+            PppoeTag::HostUniq(data) =>
+                (0x0103, data),
+            PppoeTag::AcCookie(data) =>
+                (0x0104, data),
+            PppoeTag::VendorSpecific { vendor_id, data } =>
+                (0x0105, [vendor_id.to_be_bytes().to_vec(), data].concat()),
+            PppoeTag::RelaySessionId(data) =>
+                (0x0110, data),
+            PppoeTag::PppMaxPayload(max_payload) =>
+                (0x0120, max_payload.to_be_bytes().to_vec()),
+            PppoeTag::ServiceNameError(message) =>
+                (0x0201, message.into_bytes()),
+            PppoeTag::AcSystemError(message) =>
+                (0x0202, message.into_bytes()),
+            PppoeTag::GenericError(message) =>
+                (0x0203, message.into_bytes()),
+            PppoeTag::Unknown { tag_type, data } =>
+                (tag_type, data)
+        };
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u16::<NetworkEndian>(tag_type);
+        cursor.write_u16::<NetworkEndian>(data.len() as u16);
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], PppoeTag> {
+        let (bytes1, tag_type) = be_u16(bytes)?;
+        let (bytes2, length)   = be_u16(bytes1)?;
+        let (bytes3, data)     = take(length)(bytes2)?;
+        let text = String::from_utf8(data.to_vec());
+        let tag = match (tag_type, data.len(), text) {
+            (0x0000, 0, _) =>
+                PppoeTag::EndOfList,
+            (0x0101, _, Ok(name)) =>
+                PppoeTag::ServiceName(name),
+            (0x0102, _, Ok(name)) =>
+                PppoeTag::AcName(name),
+            (0x0103, _, _) =>
+                PppoeTag::HostUniq(data.to_vec()),
+            (0x0104, _, _) =>
+                PppoeTag::AcCookie(data.to_vec()),
+            (0x0105, 4..=65535, _) => PppoeTag::VendorSpecific {
+                vendor_id: u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+                data:      data[4..].to_vec()
+            },
+            (0x0110, _, _) =>
+                PppoeTag::RelaySessionId(data.to_vec()),
+            (0x0120, 2, _) =>
+                PppoeTag::PppMaxPayload(u16::from_be_bytes([data[0], data[1]])),
+            (0x0201, _, Ok(message)) =>
+                PppoeTag::ServiceNameError(message),
+            (0x0202, _, Ok(message)) =>
+                PppoeTag::AcSystemError(message),
+            (0x0203, _, Ok(message)) =>
+                PppoeTag::GenericError(message),
+            _ =>
+                PppoeTag::Unknown { tag_type, data: data.to_vec() }
+        };
+        Ok((bytes3, tag))
+    }
+}
+
+// PPP over Ethernet header (RFC 2516)
+#[derive(Debug, PartialEq)]
+pub struct Pppoe {
+    pub version:    u8,
+    pub pppoe_type: u8,
+    pub code:       u8,
+    pub session_id: u16,
+    pub length:     u16,
+    // Discovery stage only
+    pub tags:       Vec<PppoeTag>
+}
+
+impl Default for Pppoe {
+    fn default() -> Pppoe {
+        Pppoe {
+            version:    1,
+            pppoe_type: 1,
+            code:       PPPOE_PADI,
+            session_id: 0,
+            length:     0,
+            tags:       Vec::new()
+        }
+    }
+}
+
+impl fmt::Display for Pppoe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "PPPoE(\
+             code: 0x{:02x}, \
+             session_id: 0x{:04x}, \
+             length: {}, \
+             tags: {}\
+             )",
+            self.code,
+            self.session_id,
+            self.length,
+            self.tags.len()
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Pppoe {
+    // Instantiate a new PADI without tags
+    pub fn new() -> Pppoe { Pppoe::default() }
+
+    // Instantiate a discovery packet, the length is taken from the tags
+    pub fn discovery(code: u8, session_id: u16, tags: Vec<PppoeTag>) -> Pppoe {
+        let length = tags.iter().cloned().map(|tag| tag.as_bytes().len()).sum::<usize>() as u16;
+        Pppoe { code, session_id, length, tags, ..Pppoe::default() }
+    }
+
+    // Instantiate a PADI requesting any service
+    pub fn padi(host_uniq: Vec<u8>) -> Pppoe {
+        Pppoe::discovery(
+            PPPOE_PADI,
+            0,
+            vec![PppoeTag::ServiceName(String::new()), PppoeTag::HostUniq(host_uniq)]
+        )
+    }
+
+    // Instantiate a PADT terminating the session
+    pub fn padt(session_id: u16) -> Pppoe {
+        Pppoe::discovery(PPPOE_PADT, session_id, Vec::new())
+    }
+
+    // Instantiate a session header for a PPP frame of the given length
+    pub fn session(session_id: u16, length: u16) -> Pppoe {
+        Pppoe { code: PPPOE_SESSION, session_id, length, ..Pppoe::default() }
+    }
+
+    pub fn is_session(&self) -> bool { self.code == PPPOE_SESSION }
+
+    pub fn service_name(&self) -> Option<&str> {
+        self.tags.iter().find_map(|tag| match tag {
+            PppoeTag::ServiceName(name) => Some(name.as_str()),
+            _ => None
+        })
+    }
+
+    pub fn ac_name(&self) -> Option<&str> {
+        self.tags.iter().find_map(|tag| match tag {
+            PppoeTag::AcName(name) => Some(name.as_str()),
+            _ => None
+        })
+    }
+
+    // Encode the PPPoE header and discovery tags into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.version << 4 | (self.pppoe_type & 0x0f));
+        cursor.write_u8(self.code);
+        cursor.write_u16::<NetworkEndian>(self.session_id);
+        cursor.write_u16::<NetworkEndian>(self.length);
+        for tag in self.tags {
+            cursor.write_all(&tag.as_bytes());
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice, the PPP frame of a session packet is left over
+    // without the Ethernet padding
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Pppoe> {
+        let (bytes1, pppoe) = do_parse!(
+            bytes,
+            ver_type:   be_u8 >>
+            code:       be_u8 >>
+            session_id: be_u16 >>
+            length:     be_u16 >>
+                (
+                    Pppoe {
+                        version:    ver_type >> 4,
+                        pppoe_type: ver_type & 0x0f,
+                        code,
+                        session_id,
+                        length,
+                        tags:       Vec::new()
+                    }
+                )
+        )?;
+        let payload = &bytes1[..bytes1.len().min(pppoe.length as usize)];
+
+        if pppoe.is_session() {
+            return Ok((payload, pppoe))
+        }
+
+        let mut tags = Vec::new();
+        let mut b = payload;
+        while !b.is_empty() {
+            let (leftover, tag) = PppoeTag::from_bytes(b)?;
+            tags.push(tag);
+            b = leftover;
+        }
+
+        Ok((b, Pppoe { tags, ..pppoe }))
+    }
+}
+
+#[cfg(test)]
+mod tests_pppoe {
+    use crate::pppoe::*;
+
+    #[test]
+    fn parse_discovery() {
+        let frame = &mut [
+            0x11,0x07,0x00,0x00,0x00,0x1c,0x01,0x01,
+            0x00,0x00,0x01,0x03,0x00,0x04,0x12,0x34,
+            0x56,0x78,0x01,0x02,0x00,0x04,0x42,0x52,
+            0x41,0x53,0x01,0x04,0x00,0x04,0xde,0xad,
+            0xbe,0xef
+        ];
+
+        let (leftover, pppoe) = Pppoe::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(Pppoe::discovery(PPPOE_PADO, 0, vec![
+            PppoeTag::ServiceName(String::new()),
+            PppoeTag::HostUniq(vec![0x12, 0x34, 0x56, 0x78]),
+            PppoeTag::AcName("BRAS".to_string()),
+            PppoeTag::AcCookie(vec![0xde, 0xad, 0xbe, 0xef])
+        ]), pppoe);
+        assert_eq!(Some(""), pppoe.service_name());
+        assert_eq!(Some("BRAS"), pppoe.ac_name());
+        assert_eq!("PPPoE(code: 0x07, session_id: 0x0000, length: 28, tags: 4)", format!("{}", pppoe));
+        assert_eq!(frame.to_vec(), pppoe.as_bytes());
+    }
+
+    #[test]
+    fn parse_session() {
+        let frame = &mut [
+            0x11,0x00,0x00,0x2a,0x00,0x02,0xc0,0x21,
+            0x00,0x00,0x00
+        ];
+
+        let (leftover, pppoe) = Pppoe::from_bytes(frame).unwrap();
+        assert_eq!(Pppoe::session(0x2a, 2), pppoe);
+        assert_eq!(&[0xc0, 0x21], leftover);
+        assert_eq!(frame[..6].to_vec(), pppoe.as_bytes());
+    }
+}
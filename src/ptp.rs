@@ -0,0 +1,574 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::combinator::complete;
+use nom::multi::many0;
+use nom::number::complete::{be_i8, be_i16, be_i64, be_u8, be_u16, be_u32, be_u64};
+use nom::{call, do_parse, take, IResult};
+use std::fmt;
+use std::io::{Cursor, Write};
+
+pub const ETH_P_1588: u16 = 0x88f7;
+pub const PTP_EVENT_PORT: u16 = 319;
+pub const PTP_GENERAL_PORT: u16 = 320;
+
+// Length of the common header
+pub const PTP_HEADER_LENGTH: u16 = 34;
+
+// Message types
+pub const PTP_SYNC:                  u8 = 0x0;
+pub const PTP_DELAY_REQ:             u8 = 0x1;
+pub const PTP_PDELAY_REQ:            u8 = 0x2;
+pub const PTP_PDELAY_RESP:           u8 = 0x3;
+pub const PTP_FOLLOW_UP:             u8 = 0x8;
+pub const PTP_DELAY_RESP:            u8 = 0x9;
+pub const PTP_PDELAY_RESP_FOLLOW_UP: u8 = 0xa;
+pub const PTP_ANNOUNCE:              u8 = 0xb;
+pub const PTP_SIGNALING:             u8 = 0xc;
+pub const PTP_MANAGEMENT:            u8 = 0xd;
+
+// Flags
+pub const PTP_FLAG_ALTERNATE_MASTER:  u16 = 0x0100;
+pub const PTP_FLAG_TWO_STEP:          u16 = 0x0200;
+pub const PTP_FLAG_UNICAST:           u16 = 0x0400;
+pub const PTP_FLAG_LEAP61:            u16 = 0x0001;
+pub const PTP_FLAG_LEAP59:            u16 = 0x0002;
+pub const PTP_FLAG_UTC_OFFSET_VALID:  u16 = 0x0004;
+pub const PTP_FLAG_PTP_TIMESCALE:     u16 = 0x0008;
+pub const PTP_FLAG_TIME_TRACEABLE:    u16 = 0x0010;
+pub const PTP_FLAG_FREQ_TRACEABLE:    u16 = 0x0020;
+
+// 48 bit seconds and 32 bit nanoseconds
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct PtpTimestamp {
+    pub seconds:     u64,
+    pub nanoseconds: u32
+}
+
+#[allow(unused_must_use)]
+impl PtpTimestamp {
+    pub fn new(seconds: u64, nanoseconds: u32) -> PtpTimestamp {
+        PtpTimestamp { seconds: seconds & 0xffff_ffff_ffff, nanoseconds }
+    }
+
+    // Nanoseconds since the epoch
+    pub fn as_nanos(&self) -> u128 {
+        self.seconds as u128 * 1_000_000_000 + self.nanoseconds as u128
+    }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u48::<NetworkEndian>(self.seconds & 0xffff_ffff_ffff);
+        cursor.write_u32::<NetworkEndian>(self.nanoseconds);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], PtpTimestamp> {
+        do_parse!(
+            bytes,
+            seconds_msb: be_u16 >>
+            seconds_lsb: be_u32 >>
+            nanoseconds: be_u32 >>
+                (
+                    PtpTimestamp {
+                        seconds: (seconds_msb as u64) << 32 | seconds_lsb as u64,
+                        nanoseconds
+                    }
+                )
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct PortIdentity {
+    pub clock_identity: u64,
+    pub port_number:    u16
+}
+
+#[allow(unused_must_use)]
+impl PortIdentity {
+    pub fn new(clock_identity: u64, port_number: u16) -> PortIdentity {
+        PortIdentity { clock_identity, port_number }
+    }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u64::<NetworkEndian>(self.clock_identity);
+        cursor.write_u16::<NetworkEndian>(self.port_number);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], PortIdentity> {
+        do_parse!(
+            bytes,
+            clock_identity: be_u64 >>
+            port_number:    be_u16 >>
+                ( PortIdentity { clock_identity, port_number } )
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct ClockQuality {
+    pub clock_class:                u8,
+    pub clock_accuracy:             u8,
+    pub offset_scaled_log_variance: u16
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PtpBody {
+    Sync { origin_timestamp: PtpTimestamp },
+    DelayReq { origin_timestamp: PtpTimestamp },
+    PdelayReq { origin_timestamp: PtpTimestamp },
+    PdelayResp { request_receipt_timestamp: PtpTimestamp, requesting_port_identity: PortIdentity },
+    FollowUp { precise_origin_timestamp: PtpTimestamp },
+    DelayResp { receive_timestamp: PtpTimestamp, requesting_port_identity: PortIdentity },
+    PdelayRespFollowUp { response_origin_timestamp: PtpTimestamp, requesting_port_identity: PortIdentity },
+    Announce {
+        origin_timestamp:        PtpTimestamp,
+        current_utc_offset:      i16,
+        grandmaster_priority1:   u8,
+        grandmaster_quality:     ClockQuality,
+        grandmaster_priority2:   u8,
+        grandmaster_identity:    u64,
+        steps_removed:           u16,
+        time_source:             u8
+    },
+    Signaling { target_port_identity: PortIdentity },
+    Unknown(Vec<u8>)
+}
+
+#[allow(unused_must_use)]
+impl PtpBody {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        match self {
+            PtpBody::Sync { origin_timestamp } |
+            PtpBody::DelayReq { origin_timestamp } => {
+                cursor.write_all(&origin_timestamp.as_bytes());
+            },
+            PtpBody::PdelayReq { origin_timestamp } => {
+                cursor.write_all(&origin_timestamp.as_bytes());
+                cursor.write_all(&[0x00; 10]);
+            },
+            PtpBody::FollowUp { precise_origin_timestamp } => {
+                cursor.write_all(&precise_origin_timestamp.as_bytes());
+            },
+            PtpBody::PdelayResp { request_receipt_timestamp: timestamp, requesting_port_identity } |
+            PtpBody::DelayResp { receive_timestamp: timestamp, requesting_port_identity } |
+            PtpBody::PdelayRespFollowUp { response_origin_timestamp: timestamp, requesting_port_identity } => {
+                cursor.write_all(&timestamp.as_bytes());
+                cursor.write_all(&requesting_port_identity.as_bytes());
+            },
+            PtpBody::Announce {
+                origin_timestamp,
+                current_utc_offset,
+                grandmaster_priority1,
+                grandmaster_quality,
+                grandmaster_priority2,
+                grandmaster_identity,
+                steps_removed,
+                time_source
+            } => {
+                cursor.write_all(&origin_timestamp.as_bytes());
+                cursor.write_i16::<NetworkEndian>(current_utc_offset);
+                cursor.write_u8(0x00);
+                cursor.write_u8(grandmaster_priority1);
+                cursor.write_u8(grandmaster_quality.clock_class);
+                cursor.write_u8(grandmaster_quality.clock_accuracy);
+                cursor.write_u16::<NetworkEndian>(grandmaster_quality.offset_scaled_log_variance);
+                cursor.write_u8(grandmaster_priority2);
+                cursor.write_u64::<NetworkEndian>(grandmaster_identity);
+                cursor.write_u16::<NetworkEndian>(steps_removed);
+                cursor.write_u8(time_source);
+            },
+            PtpBody::Signaling { target_port_identity } => {
+                cursor.write_all(&target_port_identity.as_bytes());
+            },
+            PtpBody::Unknown(data) => {
+                cursor.write_all(&data);
+            }
+        }
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8], message_type: u8) -> IResult<&[u8], PtpBody> {
+        match message_type {
+            PTP_SYNC => {
+                let (bytes1, origin_timestamp) = PtpTimestamp::from_bytes(bytes)?;
+                Ok((bytes1, PtpBody::Sync { origin_timestamp }))
+            },
+            PTP_DELAY_REQ => {
+                let (bytes1, origin_timestamp) = PtpTimestamp::from_bytes(bytes)?;
+                Ok((bytes1, PtpBody::DelayReq { origin_timestamp }))
+            },
+            PTP_PDELAY_REQ => {
+                let (bytes1, origin_timestamp) = PtpTimestamp::from_bytes(bytes)?;
+                let (bytes2, _reserved) = take(10usize)(bytes1)?;
+                Ok((bytes2, PtpBody::PdelayReq { origin_timestamp }))
+            },
+            PTP_FOLLOW_UP => {
+                let (bytes1, precise_origin_timestamp) = PtpTimestamp::from_bytes(bytes)?;
+                Ok((bytes1, PtpBody::FollowUp { precise_origin_timestamp }))
+            },
+            PTP_PDELAY_RESP | PTP_DELAY_RESP | PTP_PDELAY_RESP_FOLLOW_UP => {
+                let (bytes1, timestamp) = PtpTimestamp::from_bytes(bytes)?;
+                let (bytes2, requesting_port_identity) = PortIdentity::from_bytes(bytes1)?;
+                let body = match message_type {
+                    PTP_PDELAY_RESP =>
+                        PtpBody::PdelayResp { request_receipt_timestamp: timestamp, requesting_port_identity },
+                    PTP_DELAY_RESP =>
+                        PtpBody::DelayResp { receive_timestamp: timestamp, requesting_port_identity },
+                    _ =>
+                        PtpBody::PdelayRespFollowUp { response_origin_timestamp: timestamp, requesting_port_identity }
+                };
+                Ok((bytes2, body))
+            },
+            PTP_ANNOUNCE => do_parse!(
+                bytes,
+                origin_timestamp:           call!(PtpTimestamp::from_bytes) >>
+                current_utc_offset:         be_i16 >>
+                _reserved:                  be_u8  >>
+                grandmaster_priority1:      be_u8  >>
+                clock_class:                be_u8  >>
+                clock_accuracy:             be_u8  >>
+                offset_scaled_log_variance: be_u16 >>
+                grandmaster_priority2:      be_u8  >>
+                grandmaster_identity:       be_u64 >>
+                steps_removed:              be_u16 >>
+                time_source:                be_u8  >>
+                    (
+                        PtpBody::Announce {
+                            origin_timestamp,
+                            current_utc_offset,
+                            grandmaster_priority1,
+                            grandmaster_quality: ClockQuality {
+                                clock_class,
+                                clock_accuracy,
+                                offset_scaled_log_variance
+                            },
+                            grandmaster_priority2,
+                            grandmaster_identity,
+                            steps_removed,
+                            time_source
+                        }
+                    )
+            ),
+            PTP_SIGNALING => {
+                let (bytes1, target_port_identity) = PortIdentity::from_bytes(bytes)?;
+                Ok((bytes1, PtpBody::Signaling { target_port_identity }))
+            },
+            _ =>
+                Ok((&[], PtpBody::Unknown(bytes.to_vec())))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PtpTlv {
+    pub tlv_type: u16,
+    pub value:    Vec<u8>
+}
+
+#[allow(unused_must_use)]
+impl PtpTlv {
+    pub fn new(tlv_type: u16, value: Vec<u8>) -> PtpTlv {
+        PtpTlv { tlv_type, value }
+    }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u16::<NetworkEndian>(self.tlv_type);
+        cursor.write_u16::<NetworkEndian>(self.value.len() as u16);
+        cursor.write_all(&self.value);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], PtpTlv> {
+        do_parse!(
+            bytes,
+            tlv_type: be_u16 >>
+            length:   be_u16 >>
+            value:    take!(length) >>
+                ( PtpTlv { tlv_type, value: value.to_vec() } )
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Ptp {
+    pub transport_specific:   u8,
+    pub message_type:         u8,
+    pub version:              u8,
+    pub minor_version:        u8,
+    pub message_length:       u16,
+    pub domain:               u8,
+    pub flags:                u16,
+    // nanoseconds multiplied by 2^16
+    pub correction:           i64,
+    pub source_port_identity: PortIdentity,
+    pub sequence_id:          u16,
+    pub control:              u8,
+    pub log_message_interval: i8,
+    pub body:                 PtpBody,
+    pub tlvs:                 Vec<PtpTlv>
+}
+
+impl Default for Ptp {
+    fn default() -> Ptp {
+        Ptp {
+            transport_specific:   0,
+            message_type:         PTP_SYNC,
+            version:              2,
+            minor_version:        0,
+            message_length:       PTP_HEADER_LENGTH + 10,
+            domain:               0,
+            flags:                0,
+            correction:           0,
+            source_port_identity: PortIdentity::default(),
+            sequence_id:          0,
+            control:              0,
+            log_message_interval: 0x7f,
+            body:                 PtpBody::Sync { origin_timestamp: PtpTimestamp::default() },
+            tlvs:                 Vec::new()
+        }
+    }
+}
+
+impl fmt::Display for Ptp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "PTP(\
+             type: {}, \
+             domain: {}, \
+             sequence: {}, \
+             source: 0x{:016x}:{}\
+             )",
+            self.message_type,
+            self.domain,
+            self.sequence_id,
+            self.source_port_identity.clock_identity,
+            self.source_port_identity.port_number
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Ptp {
+    // Instantiate a new Sync message
+    pub fn new() -> Ptp { Ptp::default() }
+
+    // Instantiate a message with the body, the message type, control field
+    // and length follow the body
+    pub fn message(body: PtpBody, source_port_identity: PortIdentity, sequence_id: u16) -> Ptp {
+        let message_type = match &body {
+            PtpBody::Sync { .. } => PTP_SYNC,
+            PtpBody::DelayReq { .. } => PTP_DELAY_REQ,
+            PtpBody::PdelayReq { .. } => PTP_PDELAY_REQ,
+            PtpBody::PdelayResp { .. } => PTP_PDELAY_RESP,
+            PtpBody::FollowUp { .. } => PTP_FOLLOW_UP,
+            PtpBody::DelayResp { .. } => PTP_DELAY_RESP,
+            PtpBody::PdelayRespFollowUp { .. } => PTP_PDELAY_RESP_FOLLOW_UP,
+            PtpBody::Announce { .. } => PTP_ANNOUNCE,
+            PtpBody::Signaling { .. } => PTP_SIGNALING,
+            PtpBody::Unknown(_) => PTP_MANAGEMENT
+        };
+        // Control field of PTPv1 hardware
+        let control = match message_type {
+            PTP_SYNC => 0,
+            PTP_DELAY_REQ => 1,
+            PTP_FOLLOW_UP => 2,
+            PTP_DELAY_RESP => 3,
+            PTP_MANAGEMENT => 4,
+            _ => 5
+        };
+        Ptp {
+            message_type,
+            message_length: PTP_HEADER_LENGTH + body.clone().as_bytes().len() as u16,
+            source_port_identity,
+            sequence_id,
+            control,
+            body,
+            ..Ptp::default()
+        }
+    }
+
+    // Append a TLV and update the message length
+    pub fn push_tlv(&mut self, tlv: PtpTlv) {
+        self.message_length += 4 + tlv.value.len() as u16;
+        self.tlvs.push(tlv);
+    }
+
+    // Whether the precise timestamp follows in a Follow_Up message
+    pub fn is_two_step(&self) -> bool { self.flags & PTP_FLAG_TWO_STEP != 0 }
+
+    // Whether the message is sent to the event port
+    pub fn is_event(&self) -> bool { self.message_type < 0x8 }
+
+    // Correction field in nanoseconds
+    pub fn correction_ns(&self) -> f64 { self.correction as f64 / 65536.0 }
+
+    // Encode the PTP message into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8((self.transport_specific << 4) | (self.message_type & 0x0f));
+        cursor.write_u8((self.minor_version << 4) | (self.version & 0x0f));
+        cursor.write_u16::<NetworkEndian>(self.message_length);
+        cursor.write_u8(self.domain);
+        cursor.write_u8(0x00);
+        cursor.write_u16::<NetworkEndian>(self.flags);
+        cursor.write_i64::<NetworkEndian>(self.correction);
+        cursor.write_u32::<NetworkEndian>(0x00000000);
+        cursor.write_all(&self.source_port_identity.as_bytes());
+        cursor.write_u16::<NetworkEndian>(self.sequence_id);
+        cursor.write_u8(self.control);
+        cursor.write_i8(self.log_message_interval);
+        cursor.write_all(&self.body.as_bytes());
+        for tlv in self.tlvs {
+            cursor.write_all(&tlv.as_bytes());
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice, trailing octets of the message that are not a TLV
+    // are left over
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Ptp> {
+        let (bytes1, ptp) = do_parse!(
+            bytes,
+            type_specific:        be_u8  >>
+            version:              be_u8  >>
+            message_length:       be_u16 >>
+            domain:               be_u8  >>
+            _reserved1:           be_u8  >>
+            flags:                be_u16 >>
+            correction:           be_i64 >>
+            _reserved2:           be_u32 >>
+            source_port_identity: call!(PortIdentity::from_bytes) >>
+            sequence_id:          be_u16 >>
+            control:              be_u8  >>
+            log_message_interval: be_i8  >>
+                (
+                    Ptp {
+                        transport_specific: type_specific >> 4,
+                        message_type:       type_specific & 0x0f,
+                        version:            version & 0x0f,
+                        minor_version:      version >> 4,
+                        message_length,
+                        domain,
+                        flags,
+                        correction,
+                        source_port_identity,
+                        sequence_id,
+                        control,
+                        log_message_interval,
+                        ..Ptp::default()
+                    }
+                )
+        )?;
+
+        let length = (ptp.message_length as usize).saturating_sub(PTP_HEADER_LENGTH as usize);
+        let (_, message) = take(length)(bytes1)?;
+        let (tlvs, body) = PtpBody::from_bytes(message, ptp.message_type)?;
+        let (rest, tlvs) = many0(complete(PtpTlv::from_bytes))(tlvs)?;
+        Ok((&bytes1[length - rest.len()..], Ptp { body, tlvs, ..ptp }))
+    }
+}
+
+#[cfg(test)]
+mod tests_ptp {
+    use crate::ptp::*;
+
+    #[test]
+    fn parse_announce() {
+        let frame = &mut [
+            0x0b,0x02,0x00,0x4c,0x00,0x00,0x00,0x08,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x0c,0x29,0xff,
+            0xfe,0x00,0x00,0x01,0x00,0x01,0x00,0x2a,
+            0x05,0x01,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x25,0x00,0x80,
+            0x06,0x21,0x4e,0x5d,0x80,0x00,0x0c,0x29,
+            0xff,0xfe,0x00,0x00,0x01,0x00,0x00,0xa0,
+            0x00,0x08,0x00,0x08,0x00,0x0c,0x29,0xff,
+            0xfe,0x00,0x00,0x01
+        ];
+
+        let (leftover, ptp) = Ptp::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(PTP_ANNOUNCE, ptp.message_type);
+        assert_eq!(2, ptp.version);
+        assert_eq!(PTP_FLAG_PTP_TIMESCALE, ptp.flags);
+        assert_eq!(PortIdentity::new(0x000c_29ff_fe00_0001, 1), ptp.source_port_identity);
+        assert_eq!(42, ptp.sequence_id);
+        assert_eq!(1, ptp.log_message_interval);
+        assert_eq!(PtpBody::Announce {
+            origin_timestamp:      PtpTimestamp::default(),
+            current_utc_offset:    37,
+            grandmaster_priority1: 128,
+            grandmaster_quality:   ClockQuality {
+                clock_class:                6,
+                clock_accuracy:             0x21,
+                offset_scaled_log_variance: 0x4e5d
+            },
+            grandmaster_priority2: 128,
+            grandmaster_identity:  0x000c_29ff_fe00_0001,
+            steps_removed:         0,
+            time_source:           0xa0
+        }, ptp.body);
+        assert_eq!(vec![PtpTlv::new(0x0008, vec![0x00, 0x0c, 0x29, 0xff, 0xfe, 0x00, 0x00, 0x01])], ptp.tlvs);
+        assert_eq!("PTP(type: 11, domain: 0, sequence: 42, source: 0x000c29fffe000001:1)", format!("{}", ptp));
+        assert_eq!(frame.to_vec(), ptp.as_bytes());
+    }
+
+    #[test]
+    fn parse_trailing_octets() {
+        let frame = &mut [
+            0x00,0x12,0x00,0x31,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x0c,0x29,0xff,
+            0xfe,0x00,0x00,0x01,0x00,0x01,0x00,0x07,
+            0x00,0x7f,0x00,0x00,0x65,0x53,0xf1,0x00,
+            0x00,0x00,0x01,0xf4,0x00,0x03,0x00,0x05,
+            0xaa,0xbb
+        ];
+
+        let (leftover, ptp) = Ptp::from_bytes(frame).unwrap();
+        assert_eq!(vec![0x00, 0x03, 0x00, 0x05, 0xaa, 0xbb], leftover.to_vec());
+        assert_eq!(2, ptp.version);
+        assert_eq!(1, ptp.minor_version);
+        assert_eq!(PtpBody::Sync { origin_timestamp: PtpTimestamp::new(1_700_000_000, 500) }, ptp.body);
+        assert!(ptp.tlvs.is_empty());
+        assert_eq!(frame[..44].to_vec(), ptp.as_bytes());
+    }
+
+    #[test]
+    fn build_follow_up() {
+        let timestamp = PtpTimestamp::new(1_700_000_000, 123_456_789);
+        let mut ptp = Ptp::message(
+            PtpBody::FollowUp { precise_origin_timestamp: timestamp },
+            PortIdentity::new(0x000c_29ff_fe00_0001, 1),
+            7
+        );
+        ptp.correction = 1 << 16;
+        ptp.push_tlv(PtpTlv::new(0x0003, vec![0x00, 0x80, 0xc2, 0x00, 0x00, 0x01]));
+
+        let bytes = ptp.as_bytes();
+        assert_eq!(54, bytes.len());
+
+        let ptp = Ptp::from_bytes(&bytes).unwrap().1;
+        assert_eq!(PTP_FOLLOW_UP, ptp.message_type);
+        assert_eq!(2, ptp.control);
+        assert!(!ptp.is_event());
+        assert_eq!(1.0, ptp.correction_ns());
+        assert_eq!(1_700_000_000_123_456_789, match ptp.body {
+            PtpBody::FollowUp { precise_origin_timestamp } => precise_origin_timestamp.as_nanos(),
+            _ => 0
+        });
+        assert_eq!(1, ptp.tlvs.len());
+    }
+}
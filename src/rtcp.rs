@@ -0,0 +1,479 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::combinator::{all_consuming, verify};
+use nom::multi::{count, many0, many1};
+use nom::number::complete::{be_u8, be_u16, be_u24, be_u32, be_u64};
+use nom::{call, do_parse, IResult};
+use std::fmt;
+use std::io::{Cursor, Write};
+
+// Packet types
+pub const RTCP_SR:    u8 = 200;
+pub const RTCP_RR:    u8 = 201;
+pub const RTCP_SDES:  u8 = 202;
+pub const RTCP_BYE:   u8 = 203;
+pub const RTCP_APP:   u8 = 204;
+pub const RTCP_RTPFB: u8 = 205;
+pub const RTCP_PSFB:  u8 = 206;
+pub const RTCP_XR:    u8 = 207;
+
+// SDES items
+pub const RTCP_SDES_END:   u8 = 0;
+pub const RTCP_SDES_CNAME: u8 = 1;
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ReportBlock {
+    pub ssrc:             u32,
+    pub fraction_lost:    u8,
+    // 24 bit signed
+    pub cumulative_lost:  i32,
+    pub highest_sequence: u32,
+    pub jitter:           u32,
+    pub last_sr:          u32,
+    pub delay_since_sr:   u32
+}
+
+#[allow(unused_must_use)]
+impl ReportBlock {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u32::<NetworkEndian>(self.ssrc);
+        cursor.write_u8(self.fraction_lost);
+        cursor.write_i24::<NetworkEndian>(self.cumulative_lost);
+        cursor.write_u32::<NetworkEndian>(self.highest_sequence);
+        cursor.write_u32::<NetworkEndian>(self.jitter);
+        cursor.write_u32::<NetworkEndian>(self.last_sr);
+        cursor.write_u32::<NetworkEndian>(self.delay_since_sr);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], ReportBlock> {
+        do_parse!(
+            bytes,
+            ssrc:             be_u32 >>
+            fraction_lost:    be_u8  >>
+            cumulative_lost:  be_u24 >>
+            highest_sequence: be_u32 >>
+            jitter:           be_u32 >>
+            last_sr:          be_u32 >>
+            delay_since_sr:   be_u32 >>
+                (
+                    ReportBlock {
+                        ssrc,
+                        fraction_lost,
+                        cumulative_lost: ((cumulative_lost << 8) as i32) >> 8,
+                        highest_sequence,
+                        jitter,
+                        last_sr,
+                        delay_since_sr
+                    }
+                )
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SdesItem {
+    pub item_type: u8,
+    pub value:     String
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SdesChunk {
+    pub ssrc:  u32,
+    pub items: Vec<SdesItem>
+}
+
+#[allow(unused_must_use)]
+impl SdesChunk {
+    // Instantiate a chunk with the canonical name of the source
+    pub fn cname(ssrc: u32, cname: &str) -> SdesChunk {
+        SdesChunk { ssrc, items: vec![SdesItem { item_type: RTCP_SDES_CNAME, value: cname.to_string() }] }
+    }
+
+    // Encode the chunk terminated and padded to a multiple of 4 bytes
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u32::<NetworkEndian>(self.ssrc);
+        for item in self.items {
+            cursor.write_u8(item.item_type);
+            cursor.write_u8(item.value.len() as u8);
+            cursor.write_all(item.value.as_bytes());
+        }
+        let length = cursor.get_ref().len() + 1;
+        cursor.write_all(&vec![RTCP_SDES_END; 1 + length.div_ceil(4) * 4 - length]);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], SdesChunk> {
+        let (mut b, ssrc) = be_u32(bytes)?;
+        let mut items = Vec::new();
+        loop {
+            let (bytes1, item_type) = be_u8(b)?;
+            if item_type == RTCP_SDES_END {
+                // The END item is followed by the padding to the next 32 bit boundary
+                let consumed = bytes.len() - bytes1.len();
+                let (bytes2, _padding) = take(consumed.div_ceil(4) * 4 - consumed)(bytes1)?;
+                return Ok((bytes2, SdesChunk { ssrc, items }))
+            }
+            let (bytes2, length) = be_u8(bytes1)?;
+            let (bytes3, value)  = take(length)(bytes2)?;
+            items.push(SdesItem { item_type, value: String::from_utf8_lossy(value).to_string() });
+            b = bytes3;
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct XrBlock {
+    pub block_type:    u8,
+    pub type_specific: u8,
+    pub data:          Vec<u8>
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum RtcpPacket {
+    SenderReport {
+        ssrc:          u32,
+        ntp_timestamp: u64,
+        rtp_timestamp: u32,
+        packet_count:  u32,
+        octet_count:   u32,
+        reports:       Vec<ReportBlock>
+    },
+    ReceiverReport { ssrc: u32, reports: Vec<ReportBlock> },
+    SourceDescription(Vec<SdesChunk>),
+    Goodbye { ssrcs: Vec<u32>, reason: Option<String> },
+    App { subtype: u8, ssrc: u32, name: String, data: Vec<u8> },
+    // Transport layer and payload specific feedback
+    Feedback { packet_type: u8, fmt: u8, sender_ssrc: u32, media_ssrc: u32, fci: Vec<u8> },
+    ExtendedReport { ssrc: u32, blocks: Vec<XrBlock> },
+    Unknown { packet_type: u8, count: u8, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl RtcpPacket {
+    pub fn packet_type(&self) -> u8 {
+        match self {
+            RtcpPacket::SenderReport { .. } => RTCP_SR,
+            RtcpPacket::ReceiverReport { .. } => RTCP_RR,
+            RtcpPacket::SourceDescription(_) => RTCP_SDES,
+            RtcpPacket::Goodbye { .. } => RTCP_BYE,
+            RtcpPacket::App { .. } => RTCP_APP,
+            RtcpPacket::Feedback { packet_type, .. } |
+            RtcpPacket::Unknown { packet_type, .. } => *packet_type,
+            RtcpPacket::ExtendedReport { .. } => RTCP_XR
+        }
+    }
+
+    // Encode the packet, the count and length follow the body
+    pub fn as_bytes(self) -> Vec<u8> {
+        let packet_type = self.packet_type();
+        let buf: Vec<u8> = Vec::new();
+        let mut body = Cursor::new(buf);
+        let count = match self {
+            RtcpPacket::SenderReport { ssrc, ntp_timestamp, rtp_timestamp, packet_count, octet_count, reports } => {
+                body.write_u32::<NetworkEndian>(ssrc);
+                body.write_u64::<NetworkEndian>(ntp_timestamp);
+                body.write_u32::<NetworkEndian>(rtp_timestamp);
+                body.write_u32::<NetworkEndian>(packet_count);
+                body.write_u32::<NetworkEndian>(octet_count);
+                let count = reports.len();
+                for report in reports {
+                    body.write_all(&report.as_bytes());
+                }
+                count as u8
+            },
+            RtcpPacket::ReceiverReport { ssrc, reports } => {
+                body.write_u32::<NetworkEndian>(ssrc);
+                let count = reports.len();
+                for report in reports {
+                    body.write_all(&report.as_bytes());
+                }
+                count as u8
+            },
+            RtcpPacket::SourceDescription(chunks) => {
+                let count = chunks.len();
+                for chunk in chunks {
+                    body.write_all(&chunk.as_bytes());
+                }
+                count as u8
+            },
+            RtcpPacket::Goodbye { ssrcs, reason } => {
+                let count = ssrcs.len();
+                for ssrc in ssrcs {
+                    body.write_u32::<NetworkEndian>(ssrc);
+                }
+                if let Some(reason) = reason {
+                    body.write_u8(reason.len() as u8);
+                    body.write_all(reason.as_bytes());
+                }
+                count as u8
+            },
+            RtcpPacket::App { subtype, ssrc, name, data } => {
+                body.write_u32::<NetworkEndian>(ssrc);
+                let mut name = name.into_bytes();
+                name.resize(4, b' ');
+                body.write_all(&name);
+                body.write_all(&data);
+                subtype
+            },
+            RtcpPacket::Feedback { fmt, sender_ssrc, media_ssrc, fci, .. } => {
+                body.write_u32::<NetworkEndian>(sender_ssrc);
+                body.write_u32::<NetworkEndian>(media_ssrc);
+                body.write_all(&fci);
+                fmt
+            },
+            RtcpPacket::ExtendedReport { ssrc, blocks } => {
+                body.write_u32::<NetworkEndian>(ssrc);
+                for block in blocks {
+                    body.write_u8(block.block_type);
+                    body.write_u8(block.type_specific);
+                    body.write_u16::<NetworkEndian>((block.data.len() / 4) as u16);
+                    body.write_all(&block.data);
+                }
+                0
+            },
+            RtcpPacket::Unknown { count, data, .. } => {
+                body.write_all(&data);
+                count
+            }
+        };
+        let mut body = body.into_inner();
+        body.resize(body.len().div_ceil(4) * 4, 0);
+
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(0x80 | (count & 0x1f));
+        cursor.write_u8(packet_type);
+        cursor.write_u16::<NetworkEndian>((body.len() / 4) as u16);
+        cursor.write_all(&body);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], RtcpPacket> {
+        let (bytes1, (flags, packet_type, body)) = do_parse!(
+            bytes,
+            flags:       call!(verify(be_u8, |flags| flags >> 6 == 2)) >>
+            packet_type: call!(verify(be_u8, |packet_type| (192..=223).contains(packet_type))) >>
+            length:      be_u16 >>
+            body:        call!(take(length as usize * 4)) >>
+                ( (flags, packet_type, body) )
+        )?;
+
+        // The last octet of a padded packet counts the padding
+        let body = match (flags & 0x20 != 0, body.last()) {
+            (true, Some(&padding)) if padding as usize <= body.len() => &body[..body.len() - padding as usize],
+            _ => body
+        };
+        let count = flags & 0x1f;
+        let (_, packet) = parse_body(body, packet_type, count)?;
+        Ok((bytes1, packet))
+    }
+}
+
+// RTCP compound packet
+#[derive(Debug, PartialEq)]
+pub struct Rtcp {
+    pub packets: Vec<RtcpPacket>
+}
+
+impl fmt::Display for Rtcp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "RTCP(types: {:?})",
+            self.packets.iter().map(|packet| packet.packet_type()).collect::<Vec<u8>>()
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Rtcp {
+    // Instantiate a compound packet of the packets
+    pub fn new(packets: Vec<RtcpPacket>) -> Rtcp { Rtcp { packets } }
+
+    // The canonical name of the source from the SDES packets
+    pub fn cname(&self, ssrc: u32) -> Option<&str> {
+        self.packets.iter()
+            .filter_map(|packet| match packet {
+                RtcpPacket::SourceDescription(chunks) => Some(chunks),
+                _ => None
+            })
+            .flatten()
+            .filter(|chunk| chunk.ssrc == ssrc)
+            .flat_map(|chunk| chunk.items.iter())
+            .find(|item| item.item_type == RTCP_SDES_CNAME)
+            .map(|item| item.value.as_str())
+    }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        self.packets.into_iter().flat_map(|packet| packet.as_bytes()).collect()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Rtcp> {
+        let (bytes1, packets) = many1(RtcpPacket::from_bytes)(bytes)?;
+        Ok((bytes1, Rtcp { packets }))
+    }
+}
+
+// private functions
+
+fn parse_body(bytes: &[u8], packet_type: u8, item_count: u8) -> IResult<&[u8], RtcpPacket> {
+    match packet_type {
+        RTCP_SR => do_parse!(
+            bytes,
+            ssrc:          be_u32 >>
+            ntp_timestamp: be_u64 >>
+            rtp_timestamp: be_u32 >>
+            packet_count:  be_u32 >>
+            octet_count:   be_u32 >>
+            reports:       call!(count(ReportBlock::from_bytes, item_count as usize)) >>
+                (
+                    RtcpPacket::SenderReport {
+                        ssrc,
+                        ntp_timestamp,
+                        rtp_timestamp,
+                        packet_count,
+                        octet_count,
+                        reports
+                    }
+                )
+        ),
+        RTCP_RR => do_parse!(
+            bytes,
+            ssrc:    be_u32 >>
+            reports: call!(count(ReportBlock::from_bytes, item_count as usize)) >>
+                ( RtcpPacket::ReceiverReport { ssrc, reports } )
+        ),
+        RTCP_SDES => {
+            let (bytes1, chunks) = count(SdesChunk::from_bytes, item_count as usize)(bytes)?;
+            Ok((bytes1, RtcpPacket::SourceDescription(chunks)))
+        },
+        RTCP_BYE => {
+            let (bytes1, ssrcs) = count(be_u32, item_count as usize)(bytes)?;
+            let (bytes2, reason) = match bytes1 {
+                [length, reason @ ..] if *length as usize <= reason.len() => {
+                    let (bytes2, reason) = take(*length)(reason)?;
+                    (bytes2, Some(String::from_utf8_lossy(reason).to_string()))
+                },
+                _ => (bytes1, None)
+            };
+            Ok((bytes2, RtcpPacket::Goodbye { ssrcs, reason }))
+        },
+        RTCP_APP => do_parse!(
+            bytes,
+            ssrc: be_u32 >>
+            name: call!(take(4usize)) >>
+                (
+                    RtcpPacket::App {
+                        subtype: item_count,
+                        ssrc,
+                        name:    String::from_utf8_lossy(name).to_string(),
+                        data:    bytes[8..].to_vec()
+                    }
+                )
+        ),
+        RTCP_RTPFB | RTCP_PSFB => do_parse!(
+            bytes,
+            sender_ssrc: be_u32 >>
+            media_ssrc:  be_u32 >>
+                (
+                    RtcpPacket::Feedback {
+                        packet_type,
+                        fmt: item_count,
+                        sender_ssrc,
+                        media_ssrc,
+                        fci: bytes[8..].to_vec()
+                    }
+                )
+        ),
+        RTCP_XR => do_parse!(
+            bytes,
+            ssrc:   be_u32 >>
+            blocks: call!(all_consuming(many0(parse_xr_block))) >>
+                ( RtcpPacket::ExtendedReport { ssrc, blocks } )
+        ),
+        _ =>
+            Ok((&[], RtcpPacket::Unknown { packet_type, count: item_count, data: bytes.to_vec() }))
+    }
+}
+
+fn parse_xr_block(bytes: &[u8]) -> IResult<&[u8], XrBlock> {
+    do_parse!(
+        bytes,
+        block_type:    be_u8  >>
+        type_specific: be_u8  >>
+        length:        be_u16 >>
+        data:          call!(take(length as usize * 4)) >>
+            ( XrBlock { block_type, type_specific, data: data.to_vec() } )
+    )
+}
+
+#[cfg(test)]
+mod tests_rtcp {
+    use crate::rtcp::*;
+
+    #[test]
+    fn parse_compound() {
+        let frame = &mut [
+            0x81,0xc8,0x00,0x0c,0xde,0xad,0xbe,0xef,
+            0xe8,0x9a,0x6d,0x80,0x00,0x00,0x00,0x00,
+            0x00,0x01,0xe2,0x40,0x00,0x00,0x00,0x0a,
+            0x00,0x00,0x06,0x40,0x00,0x00,0x12,0x34,
+            0x05,0xff,0xff,0xff,0x00,0x00,0x30,0x39,
+            0x00,0x00,0x00,0x10,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x81,0xca,0x00,0x03,
+            0xde,0xad,0xbe,0xef,0x01,0x05,0x61,0x6c,
+            0x69,0x63,0x65,0x00,0x81,0xcb,0x00,0x01,
+            0xde,0xad,0xbe,0xef
+        ];
+
+        let (leftover, rtcp) = Rtcp::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(3, rtcp.packets.len());
+        assert_eq!(RtcpPacket::SenderReport {
+            ssrc:          0xdeadbeef,
+            ntp_timestamp: 0xe89a_6d80_0000_0000,
+            rtp_timestamp: 123456,
+            packet_count:  10,
+            octet_count:   1600,
+            reports:       vec![ReportBlock {
+                ssrc:             0x1234,
+                fraction_lost:    5,
+                cumulative_lost:  -1,
+                highest_sequence: 12345,
+                jitter:           16,
+                ..ReportBlock::default()
+            }]
+        }, rtcp.packets[0]);
+        assert_eq!(Some("alice"), rtcp.cname(0xdeadbeef));
+        assert_eq!(RtcpPacket::Goodbye { ssrcs: vec![0xdeadbeef], reason: None }, rtcp.packets[2]);
+        assert_eq!("RTCP(types: [200, 202, 203])", format!("{}", rtcp));
+        assert_eq!(frame.to_vec(), rtcp.as_bytes());
+    }
+
+    #[test]
+    fn build() {
+        let rtcp = Rtcp::new(vec![
+            RtcpPacket::ReceiverReport { ssrc: 1, reports: vec![] },
+            RtcpPacket::Goodbye { ssrcs: vec![1], reason: Some("done".to_string()) },
+            RtcpPacket::App { subtype: 1, ssrc: 1, name: "TEST".to_string(), data: vec![0x01, 0x02, 0x03, 0x04] },
+            RtcpPacket::ExtendedReport { ssrc: 1, blocks: vec![XrBlock { block_type: 4, type_specific: 0, data: vec![0; 8] }] }
+        ]);
+
+        let bytes = rtcp.as_bytes();
+        assert_eq!(8 + 16 + 16 + 20, bytes.len());
+
+        let rtcp2 = Rtcp::from_bytes(&bytes).unwrap().1;
+        assert_eq!(RtcpPacket::Goodbye { ssrcs: vec![1], reason: Some("done".to_string()) }, rtcp2.packets[1]);
+        assert_eq!(RtcpPacket::App { subtype: 1, ssrc: 1, name: "TEST".to_string(), data: vec![0x01, 0x02, 0x03, 0x04] }, rtcp2.packets[2]);
+        assert_eq!(4, match &rtcp2.packets[3] {
+            RtcpPacket::ExtendedReport { blocks, .. } => blocks[0].block_type,
+            _ => 0
+        });
+    }
+}
@@ -0,0 +1,271 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::combinator::{cond, verify};
+use nom::multi::count;
+use nom::number::complete::{be_u8, be_u16, be_u32};
+use nom::{call, do_parse, IResult};
+use std::fmt;
+use std::io::{Cursor, Write};
+
+// Profiles of the RFC 8285 header extensions
+pub const RTP_EXT_ONE_BYTE: u16 = 0xbede;
+pub const RTP_EXT_TWO_BYTE: u16 = 0x1000;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RtpExtensionElement {
+    pub id:   u8,
+    pub data: Vec<u8>
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum RtpHeaderExtension {
+    // Elements of up to 16 bytes with ids 1 to 14
+    OneByte(Vec<RtpExtensionElement>),
+    // Elements of up to 255 bytes, the profile carries 4 application bits
+    TwoByte { app_bits: u8, elements: Vec<RtpExtensionElement> },
+    Raw { profile: u16, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl RtpHeaderExtension {
+    // Encode the extension padded to a multiple of 4 bytes
+    pub fn as_bytes(self) -> Vec<u8> {
+        let (profile, mut data) = match self {
+            RtpHeaderExtension::OneByte(elements) =>
+                (RTP_EXT_ONE_BYTE, elements.into_iter()
+                    .flat_map(|element| [vec![(element.id << 4) | ((element.data.len() as u8).saturating_sub(1) & 0x0f)], element.data].concat())
+                    .collect::<Vec<u8>>()),
+            RtpHeaderExtension::TwoByte { app_bits, elements } =>
+                (RTP_EXT_TWO_BYTE | (app_bits as u16 & 0x0f), elements.into_iter()
+                    .flat_map(|element| [vec![element.id, element.data.len() as u8], element.data].concat())
+                    .collect::<Vec<u8>>()),
+            RtpHeaderExtension::Raw { profile, data } =>
+                (profile, data)
+        };
+        data.resize(data.len().div_ceil(4) * 4, 0);
+
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u16::<NetworkEndian>(profile);
+        cursor.write_u16::<NetworkEndian>((data.len() / 4) as u16);
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], RtpHeaderExtension> {
+        let (bytes1, profile) = be_u16(bytes)?;
+        let (bytes2, length)  = be_u16(bytes1)?;
+        let (bytes3, data)    = take(length as usize * 4)(bytes2)?;
+        let extension = match profile {
+            RTP_EXT_ONE_BYTE =>
+                RtpHeaderExtension::OneByte(parse_elements(data, true)),
+            _ if profile & 0xfff0 == RTP_EXT_TWO_BYTE =>
+                RtpHeaderExtension::TwoByte { app_bits: (profile & 0x0f) as u8, elements: parse_elements(data, false) },
+            _ =>
+                RtpHeaderExtension::Raw { profile, data: data.to_vec() }
+        };
+        Ok((bytes3, extension))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Rtp {
+    pub version:        u8,
+    // number of padding bytes trailing the payload, the padding bit is set
+    // when non zero
+    pub padding_length: u8,
+    pub marker:         bool,
+    pub payload_type:   u8,
+    pub sequence:       u16,
+    pub timestamp:      u32,
+    pub ssrc:           u32,
+    pub csrcs:          Vec<u32>,
+    pub extension:      Option<RtpHeaderExtension>
+}
+
+impl Default for Rtp {
+    fn default() -> Rtp {
+        Rtp {
+            version:        2,
+            padding_length: 0,
+            marker:         false,
+            payload_type:   0,
+            sequence:       0,
+            timestamp:      0,
+            ssrc:           0,
+            csrcs:          Vec::new(),
+            extension:      None
+        }
+    }
+}
+
+impl fmt::Display for Rtp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "RTP(\
+             pt: {}, \
+             seq: {}, \
+             ts: {}, \
+             ssrc: 0x{:08x}\
+             )",
+            self.payload_type,
+            self.sequence,
+            self.timestamp,
+            self.ssrc
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Rtp {
+    // Instantiate a new RTP header
+    pub fn new() -> Rtp { Rtp::default() }
+
+    // Instantiate an RTP header of the stream
+    pub fn stream(payload_type: u8, ssrc: u32, sequence: u16, timestamp: u32) -> Rtp {
+        Rtp { payload_type: payload_type & 0x7f, ssrc, sequence, timestamp, ..Rtp::default() }
+    }
+
+    // Find the element of an RFC 8285 header extension
+    pub fn extension_element(&self, id: u8) -> Option<&RtpExtensionElement> {
+        match &self.extension {
+            Some(RtpHeaderExtension::OneByte(elements)) |
+            Some(RtpHeaderExtension::TwoByte { elements, .. }) =>
+                elements.iter().find(|element| element.id == id),
+            _ =>
+                None
+        }
+    }
+
+    // Encode the RTP header into a byte slice, the padding trails the
+    // payload and is not included
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        let padding   = if self.padding_length > 0 { 0x20 } else { 0x00 };
+        let extension = if self.extension.is_some() { 0x10 } else { 0x00 };
+        let marker    = if self.marker { 0x80 } else { 0x00 };
+        cursor.write_u8((self.version << 6) | padding | extension | (self.csrcs.len() as u8 & 0x0f));
+        cursor.write_u8(marker | (self.payload_type & 0x7f));
+        cursor.write_u16::<NetworkEndian>(self.sequence);
+        cursor.write_u32::<NetworkEndian>(self.timestamp);
+        cursor.write_u32::<NetworkEndian>(self.ssrc);
+        for csrc in self.csrcs {
+            cursor.write_u32::<NetworkEndian>(csrc);
+        }
+        if let Some(extension) = self.extension {
+            cursor.write_all(&extension.as_bytes());
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice, the leftover is the payload without the padding
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Rtp> {
+        let (bytes1, (rtp, has_padding)) = do_parse!(
+            bytes,
+            flags:     call!(verify(be_u8, |flags| flags >> 6 == 2)) >>
+            pt:        be_u8  >>
+            sequence:  be_u16 >>
+            timestamp: be_u32 >>
+            ssrc:      be_u32 >>
+            csrcs:     call!(count(be_u32, (flags & 0x0f) as usize)) >>
+            extension: call!(cond(flags & 0x10 != 0, RtpHeaderExtension::from_bytes)) >>
+                (
+                    (
+                        Rtp {
+                            version:      flags >> 6,
+                            marker:       pt & 0x80 != 0,
+                            payload_type: pt & 0x7f,
+                            sequence,
+                            timestamp,
+                            ssrc,
+                            csrcs,
+                            extension,
+                            ..Rtp::default()
+                        },
+                        flags & 0x20 != 0
+                    )
+                )
+        )?;
+
+        match (has_padding, bytes1.last()) {
+            (true, Some(&padding_length)) if padding_length as usize <= bytes1.len() => {
+                let payload = &bytes1[..bytes1.len() - padding_length as usize];
+                Ok((payload, Rtp { padding_length, ..rtp }))
+            },
+            _ =>
+                Ok((bytes1, rtp))
+        }
+    }
+}
+
+// private functions
+
+fn parse_elements(bytes: &[u8], one_byte: bool) -> Vec<RtpExtensionElement> {
+    let mut elements = Vec::new();
+    let mut b = bytes;
+    while let [id_length, rest @ ..] = b {
+        // Padding
+        if *id_length == 0 {
+            b = rest;
+            continue;
+        }
+        let (id, length, rest) = match (one_byte, rest) {
+            // The stop id ends the extension
+            (true, _) if id_length >> 4 == 15 => break,
+            (true, rest) => (id_length >> 4, (id_length & 0x0f) as usize + 1, rest),
+            (false, [length, rest @ ..]) => (*id_length, *length as usize, rest),
+            (false, []) => break
+        };
+        if length > rest.len() {
+            break;
+        }
+        elements.push(RtpExtensionElement { id, data: rest[..length].to_vec() });
+        b = &rest[length..];
+    }
+    elements
+}
+
+#[cfg(test)]
+mod tests_rtp {
+    use crate::rtp::*;
+
+    #[test]
+    fn parse() {
+        let frame = &mut [
+            0xb1,0xe0,0x30,0x39,0x00,0x01,0xe2,0x40,
+            0xde,0xad,0xbe,0xef,0x00,0x00,0x00,0x01,
+            0xbe,0xde,0x00,0x01,0x11,0xaa,0xbb,0x00,
+            0x01,0x02,0x03,0x00,0x00,0x02
+        ];
+
+        let (leftover, rtp) = Rtp::from_bytes(frame).unwrap();
+        assert_eq!(&[0x01, 0x02, 0x03, 0x00], leftover);
+        assert_eq!(2, rtp.padding_length);
+        assert!(rtp.marker);
+        assert_eq!(96, rtp.payload_type);
+        assert_eq!(12345, rtp.sequence);
+        assert_eq!(123456, rtp.timestamp);
+        assert_eq!(vec![1], rtp.csrcs);
+        assert_eq!(Some(&RtpExtensionElement { id: 1, data: vec![0xaa, 0xbb] }), rtp.extension_element(1));
+        assert_eq!("RTP(pt: 96, seq: 12345, ts: 123456, ssrc: 0xdeadbeef)", format!("{}", rtp));
+        assert_eq!(frame[..24].to_vec(), rtp.as_bytes());
+    }
+
+    #[test]
+    fn build_two_byte_extension() {
+        let mut rtp = Rtp::stream(111, 0x1234, 1, 960);
+        rtp.extension = Some(RtpHeaderExtension::TwoByte {
+            app_bits: 0,
+            elements: vec![RtpExtensionElement { id: 3, data: vec![0x01; 20] }]
+        });
+
+        let bytes = rtp.as_bytes();
+        assert_eq!(12 + 4 + 24, bytes.len());
+
+        let rtp = Rtp::from_bytes(&bytes).unwrap().1;
+        assert_eq!(111, rtp.payload_type);
+        assert_eq!(Some(&RtpExtensionElement { id: 3, data: vec![0x01; 20] }), rtp.extension_element(3));
+    }
+}
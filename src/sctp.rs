@@ -0,0 +1,668 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::number::complete::{be_u8, be_u16, be_u32};
+use nom::{do_parse, IResult};
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::io::{Cursor, Write};
+
+use crate::checksum::crc32c;
+
+// Payload protocol identifiers (IANA SCTP-parameters)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PayloadProtocol {
+    Iua,
+    M2ua,
+    M3ua,
+    Sua,
+    M2pa,
+    H248,
+    S1ap,
+    X2ap,
+    Diameter,
+    DiameterDtls,
+    WebRtcDcep,
+    WebRtcString,
+    WebRtcBinary,
+    Ngap,
+    Xnap,
+    F1ap,
+    E1ap,
+    Other(u32)
+}
+
+impl From<u32> for PayloadProtocol {
+    fn from(value: u32) -> PayloadProtocol {
+        match value {
+            1  => PayloadProtocol::Iua,
+            2  => PayloadProtocol::M2ua,
+            3  => PayloadProtocol::M3ua,
+            4  => PayloadProtocol::Sua,
+            5  => PayloadProtocol::M2pa,
+            7  => PayloadProtocol::H248,
+            18 => PayloadProtocol::S1ap,
+            27 => PayloadProtocol::X2ap,
+            46 => PayloadProtocol::Diameter,
+            47 => PayloadProtocol::DiameterDtls,
+            50 => PayloadProtocol::WebRtcDcep,
+            51 => PayloadProtocol::WebRtcString,
+            53 => PayloadProtocol::WebRtcBinary,
+            60 => PayloadProtocol::Ngap,
+            61 => PayloadProtocol::Xnap,
+            62 => PayloadProtocol::F1ap,
+            64 => PayloadProtocol::E1ap,
+            other => PayloadProtocol::Other(other)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SctpParameter {
+    HeartbeatInfo(Vec<u8>),
+    Ipv4Address(Ipv4Addr),
+    Ipv6Address(Ipv6Addr),
+    StateCookie(Vec<u8>),
+    CookiePreservative(u32),
+    SupportedAddressTypes(Vec<u16>),
+    SupportedExtensions(Vec<u8>),
+    ForwardTsnSupported,
+    Unknown { param_type: u16, value: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl SctpParameter {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let (param_type, value) = match self {
+            SctpParameter::HeartbeatInfo(info) =>
+                (1, info),
+            SctpParameter::Ipv4Address(addr) =>
+                (5, addr.octets().to_vec()),
+            SctpParameter::Ipv6Address(addr) =>
+                (6, addr.octets().to_vec()),
+            SctpParameter::StateCookie(cookie) =>
+                (7, cookie),
+            SctpParameter::CookiePreservative(increment) =>
+                (9, increment.to_be_bytes().to_vec()),
+            SctpParameter::SupportedAddressTypes(types) =>
+                (12, types.iter().flat_map(|t| t.to_be_bytes().to_vec()).collect()),
+            SctpParameter::SupportedExtensions(chunk_types) =>
+                (0x8008, chunk_types),
+            SctpParameter::ForwardTsnSupported =>
+                (0xc000, Vec::new()),
+            SctpParameter::Unknown { param_type, value } =>
+                (param_type, value)
+        };
+        tlv_as_bytes(param_type, &value)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], SctpParameter> {
+        let (bytes1, (param_type, value)) = parse_tlv(bytes)?;
+        let param = match (param_type, value.len()) {
+            (1, _) =>
+                SctpParameter::HeartbeatInfo(value.to_vec()),
+            (5, 4) =>
+                SctpParameter::Ipv4Address(Ipv4Addr::new(value[0], value[1], value[2], value[3])),
+            (6, 16) =>
+                SctpParameter::Ipv6Address(Ipv6Addr::from(<[u8; 16]>::try_from(value).unwrap())),
+            (7, _) =>
+                SctpParameter::StateCookie(value.to_vec()),
+            (9, 4) =>
+                SctpParameter::CookiePreservative(u32::from_be_bytes([value[0], value[1], value[2], value[3]])),
+            (12, len) if len % 2 == 0 => SctpParameter::SupportedAddressTypes(
+                value.chunks_exact(2).map(|t| u16::from_be_bytes([t[0], t[1]])).collect()
+            ),
+            (0x8008, _) =>
+                SctpParameter::SupportedExtensions(value.to_vec()),
+            (0xc000, 0) =>
+                SctpParameter::ForwardTsnSupported,
+            _ =>
+                SctpParameter::Unknown { param_type, value: value.to_vec() }
+        };
+        Ok((bytes1, param))
+    }
+}
+
+// Error cause carried by ABORT and ERROR chunks
+#[derive(Debug, PartialEq, Clone)]
+pub struct SctpErrorCause {
+    pub code: u16,
+    pub info: Vec<u8>
+}
+
+impl SctpErrorCause {
+    pub fn as_bytes(self) -> Vec<u8> { tlv_as_bytes(self.code, &self.info) }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], SctpErrorCause> {
+        let (bytes1, (code, info)) = parse_tlv(bytes)?;
+        Ok((bytes1, SctpErrorCause { code, info: info.to_vec() }))
+    }
+}
+
+// Fixed fields of INIT and INIT ACK chunks
+#[derive(Debug, PartialEq, Clone)]
+pub struct SctpInit {
+    pub initiate_tag:     u32,
+    pub a_rwnd:           u32,
+    pub outbound_streams: u16,
+    pub inbound_streams:  u16,
+    pub initial_tsn:      u32,
+    pub params:           Vec<SctpParameter>
+}
+
+#[allow(unused_must_use)]
+impl SctpInit {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u32::<NetworkEndian>(self.initiate_tag);
+        cursor.write_u32::<NetworkEndian>(self.a_rwnd);
+        cursor.write_u16::<NetworkEndian>(self.outbound_streams);
+        cursor.write_u16::<NetworkEndian>(self.inbound_streams);
+        cursor.write_u32::<NetworkEndian>(self.initial_tsn);
+        for param in self.params {
+            cursor.write_all(&param.as_bytes());
+        }
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], SctpInit> {
+        do_parse!(
+            bytes,
+            initiate_tag:     be_u32 >>
+            a_rwnd:           be_u32 >>
+            outbound_streams: be_u16 >>
+            inbound_streams:  be_u16 >>
+            initial_tsn:      be_u32 >>
+            params:           parse_params >>
+                (
+                    SctpInit {
+                        initiate_tag,
+                        a_rwnd,
+                        outbound_streams,
+                        inbound_streams,
+                        initial_tsn,
+                        params
+                    }
+                )
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SctpChunk {
+    Data {
+        immediate:  bool,
+        unordered:  bool,
+        beginning:  bool,
+        ending:     bool,
+        tsn:        u32,
+        stream_id:  u16,
+        stream_seq: u16,
+        ppid:       u32,
+        payload:    Vec<u8>
+    },
+    Init(SctpInit),
+    InitAck(SctpInit),
+    Sack {
+        cum_tsn_ack: u32,
+        a_rwnd:      u32,
+        // start and end offsets from the cumulative TSN ack
+        gap_blocks:  Vec<(u16, u16)>,
+        dup_tsns:    Vec<u32>
+    },
+    Heartbeat(Vec<SctpParameter>),
+    HeartbeatAck(Vec<SctpParameter>),
+    Abort { t_bit: bool, causes: Vec<SctpErrorCause> },
+    Shutdown { cum_tsn_ack: u32 },
+    ShutdownAck,
+    Error(Vec<SctpErrorCause>),
+    CookieEcho(Vec<u8>),
+    CookieAck,
+    ShutdownComplete { t_bit: bool },
+    IData {
+        immediate: bool,
+        unordered: bool,
+        beginning: bool,
+        ending:    bool,
+        tsn:       u32,
+        stream_id: u16,
+        mid:       u32,
+        // PPID on the first fragment, FSN on the others
+        ppid_fsn:  u32,
+        payload:   Vec<u8>
+    },
+    Unknown { chunk_type: u8, flags: u8, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl SctpChunk {
+    // Instantiate a complete, ordered DATA chunk
+    pub fn data(tsn: u32, stream_id: u16, stream_seq: u16, ppid: u32, payload: Vec<u8>) -> SctpChunk {
+        SctpChunk::Data {
+            immediate: false,
+            unordered: false,
+            beginning: true,
+            ending:    true,
+            tsn,
+            stream_id,
+            stream_seq,
+            ppid,
+            payload
+        }
+    }
+
+    // Upper layer protocol of the user data of DATA and first I-DATA fragments
+    pub fn payload_protocol(&self) -> Option<PayloadProtocol> {
+        match self {
+            SctpChunk::Data { ppid, .. } => Some(PayloadProtocol::from(*ppid)),
+            SctpChunk::IData { beginning: true, ppid_fsn, .. } => Some(PayloadProtocol::from(*ppid_fsn)),
+            _ => None
+        }
+    }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let data: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(data);
+        let (chunk_type, flags) = match self {
+            SctpChunk::Data { immediate, unordered, beginning, ending, tsn, stream_id, stream_seq, ppid, payload } => {
+                cursor.write_u32::<NetworkEndian>(tsn);
+                cursor.write_u16::<NetworkEndian>(stream_id);
+                cursor.write_u16::<NetworkEndian>(stream_seq);
+                cursor.write_u32::<NetworkEndian>(ppid);
+                cursor.write_all(&payload);
+                (0, data_flags(immediate, unordered, beginning, ending))
+            },
+            SctpChunk::Init(init) => {
+                cursor.write_all(&init.as_bytes());
+                (1, 0)
+            },
+            SctpChunk::InitAck(init) => {
+                cursor.write_all(&init.as_bytes());
+                (2, 0)
+            },
+            SctpChunk::Sack { cum_tsn_ack, a_rwnd, gap_blocks, dup_tsns } => {
+                cursor.write_u32::<NetworkEndian>(cum_tsn_ack);
+                cursor.write_u32::<NetworkEndian>(a_rwnd);
+                cursor.write_u16::<NetworkEndian>(gap_blocks.len() as u16);
+                cursor.write_u16::<NetworkEndian>(dup_tsns.len() as u16);
+                for (start, end) in gap_blocks {
+                    cursor.write_u16::<NetworkEndian>(start);
+                    cursor.write_u16::<NetworkEndian>(end);
+                }
+                for tsn in dup_tsns {
+                    cursor.write_u32::<NetworkEndian>(tsn);
+                }
+                (3, 0)
+            },
+            SctpChunk::Heartbeat(params) => {
+                for param in params {
+                    cursor.write_all(&param.as_bytes());
+                }
+                (4, 0)
+            },
+            SctpChunk::HeartbeatAck(params) => {
+                for param in params {
+                    cursor.write_all(&param.as_bytes());
+                }
+                (5, 0)
+            },
+            SctpChunk::Abort { t_bit, causes } => {
+                for cause in causes {
+                    cursor.write_all(&cause.as_bytes());
+                }
+                (6, t_bit as u8)
+            },
+            SctpChunk::Shutdown { cum_tsn_ack } => {
+                cursor.write_u32::<NetworkEndian>(cum_tsn_ack);
+                (7, 0)
+            },
+            SctpChunk::ShutdownAck =>
+                (8, 0),
+            SctpChunk::Error(causes) => {
+                for cause in causes {
+                    cursor.write_all(&cause.as_bytes());
+                }
+                (9, 0)
+            },
+            SctpChunk::CookieEcho(cookie) => {
+                cursor.write_all(&cookie);
+                (10, 0)
+            },
+            SctpChunk::CookieAck =>
+                (11, 0),
+            SctpChunk::ShutdownComplete { t_bit } =>
+                (14, t_bit as u8),
+            SctpChunk::IData { immediate, unordered, beginning, ending, tsn, stream_id, mid, ppid_fsn, payload } => {
+                cursor.write_u32::<NetworkEndian>(tsn);
+                cursor.write_u16::<NetworkEndian>(stream_id);
+                cursor.write_u16::<NetworkEndian>(0);
+                cursor.write_u32::<NetworkEndian>(mid);
+                cursor.write_u32::<NetworkEndian>(ppid_fsn);
+                cursor.write_all(&payload);
+                (64, data_flags(immediate, unordered, beginning, ending))
+            },
+            SctpChunk::Unknown { chunk_type, flags, data } => {
+                cursor.write_all(&data);
+                (chunk_type, flags)
+            }
+        };
+        let data = cursor.into_inner();
+
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(chunk_type);
+        cursor.write_u8(flags);
+        cursor.write_u16::<NetworkEndian>(data.len() as u16 + 4);
+        cursor.write_all(&data);
+        cursor.write_all(&vec![0; padding(data.len())]);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], SctpChunk> {
+        let (bytes1, chunk_type) = be_u8(bytes)?;
+        let (bytes2, flags)      = be_u8(bytes1)?;
+        let (bytes3, length)     = verify(be_u16, |length| *length >= 4)(bytes2)?;
+        let (bytes4, data)       = take(length as usize - 4)(bytes3)?;
+        let (bytes5, _padding)   = take(padding(data.len()).min(bytes4.len()))(bytes4)?;
+        let chunk = match parse_chunk_data(chunk_type, flags, data) {
+            Ok(chunk) => chunk,
+            Err(_e) => SctpChunk::Unknown { chunk_type, flags, data: data.to_vec() }
+        };
+        Ok((bytes5, chunk))
+    }
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct Sctp {
+    pub source:           u16,
+    pub destination:      u16,
+    pub verification_tag: u32,
+    // CRC32c read in network byte order
+    pub checksum:         u32,
+    pub chunks:           Vec<SctpChunk>
+}
+
+impl fmt::Display for Sctp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "SCTP(\
+             source: {}, \
+             destination: {}, \
+             vtag: 0x{:08x}, \
+             chunks: {}\
+             )",
+            self.source,
+            self.destination,
+            self.verification_tag,
+            self.chunks.len()
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Sctp {
+    // Instantiate a new SCTP packet
+    pub fn new() -> Sctp { Sctp::default() }
+
+    // CRC32c of the packet with a zero checksum field, as read in network
+    // byte order (the CRC is transmitted least significant byte first)
+    pub fn calculate_sctp_checksum(&self) -> u32 {
+        let sctp = Sctp {
+            checksum: 0,
+            chunks:   self.chunks.clone(),
+            ..*self
+        };
+        crc32c(&sctp.as_bytes()).swap_bytes()
+    }
+
+    pub fn verify_checksum(&self) -> bool {
+        self.checksum == self.calculate_sctp_checksum()
+    }
+
+    // Fill in the checksum field
+    pub fn set_checksum(&mut self) {
+        self.checksum = self.calculate_sctp_checksum();
+    }
+
+    // Encode the SCTP packet into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u16::<NetworkEndian>(self.source);
+        cursor.write_u16::<NetworkEndian>(self.destination);
+        cursor.write_u32::<NetworkEndian>(self.verification_tag);
+        cursor.write_u32::<NetworkEndian>(self.checksum);
+        for chunk in self.chunks {
+            cursor.write_all(&chunk.as_bytes());
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice holding a whole SCTP packet
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Sctp> {
+        do_parse!(
+            bytes,
+            source:           be_u16 >>
+            destination:      be_u16 >>
+            verification_tag: be_u32 >>
+            checksum:         be_u32 >>
+            chunks:           parse_chunks >>
+                (
+                    Sctp {
+                        source,
+                        destination,
+                        verification_tag,
+                        checksum,
+                        chunks
+                    }
+                )
+        )
+    }
+}
+
+// private functions
+
+fn padding(len: usize) -> usize { (4 - len % 4) % 4 }
+
+fn data_flags(immediate: bool, unordered: bool, beginning: bool, ending: bool) -> u8 {
+    (immediate as u8) << 3 | (unordered as u8) << 2 | (beginning as u8) << 1 | ending as u8
+}
+
+#[allow(unused_must_use)]
+fn tlv_as_bytes(tlv_type: u16, value: &[u8]) -> Vec<u8> {
+    let buf: Vec<u8> = Vec::new();
+    let mut cursor = Cursor::new(buf);
+    cursor.write_u16::<NetworkEndian>(tlv_type);
+    cursor.write_u16::<NetworkEndian>(value.len() as u16 + 4);
+    cursor.write_all(value);
+    cursor.write_all(&vec![0; padding(value.len())]);
+    cursor.into_inner()
+}
+
+// Parameters and error causes share the same padded TLV layout
+fn parse_tlv(bytes: &[u8]) -> IResult<&[u8], (u16, &[u8])> {
+    let (bytes1, tlv_type) = be_u16(bytes)?;
+    let (bytes2, length)   = be_u16(bytes1)?;
+    let (bytes3, value)    = take((length as usize).saturating_sub(4))(bytes2)?;
+    let (bytes4, _padding) = take(padding(value.len()).min(bytes3.len()))(bytes3)?;
+    Ok((bytes4, (tlv_type, value)))
+}
+
+fn parse_params(bytes: &[u8]) -> IResult<&[u8], Vec<SctpParameter>> {
+    let mut acc = Vec::new();
+    let mut b = bytes;
+
+    while !b.is_empty() {
+        let (leftover, param) = SctpParameter::from_bytes(b)?;
+        acc.push(param);
+        b = leftover;
+    }
+
+    Ok((b, acc))
+}
+
+fn parse_causes(bytes: &[u8]) -> IResult<&[u8], Vec<SctpErrorCause>> {
+    let mut acc = Vec::new();
+    let mut b = bytes;
+
+    while !b.is_empty() {
+        let (leftover, cause) = SctpErrorCause::from_bytes(b)?;
+        acc.push(cause);
+        b = leftover;
+    }
+
+    Ok((b, acc))
+}
+
+fn parse_chunks(bytes: &[u8]) -> IResult<&[u8], Vec<SctpChunk>> {
+    let mut acc = Vec::new();
+    let mut b = bytes;
+
+    // stop at Ethernet padding or a truncated chunk
+    while let Ok((leftover, chunk)) = SctpChunk::from_bytes(b) {
+        acc.push(chunk);
+        b = leftover;
+    }
+
+    Ok((b, acc))
+}
+
+fn parse_chunk_data(chunk_type: u8, flags: u8, data: &[u8]) -> Result<SctpChunk, nom::Err<nom::error::Error<&[u8]>>> {
+    let immediate = flags & 0x08 != 0;
+    let unordered = flags & 0x04 != 0;
+    let beginning = flags & 0x02 != 0;
+    let ending    = flags & 0x01 != 0;
+
+    let chunk = match chunk_type {
+        0 => {
+            let (bytes1, tsn)        = be_u32(data)?;
+            let (bytes2, stream_id)  = be_u16(bytes1)?;
+            let (bytes3, stream_seq) = be_u16(bytes2)?;
+            let (bytes4, ppid)       = be_u32(bytes3)?;
+            SctpChunk::Data {
+                immediate, unordered, beginning, ending,
+                tsn, stream_id, stream_seq, ppid,
+                payload: bytes4.to_vec()
+            }
+        },
+        1 => SctpChunk::Init(SctpInit::from_bytes(data)?.1),
+        2 => SctpChunk::InitAck(SctpInit::from_bytes(data)?.1),
+        3 => {
+            let (bytes1, cum_tsn_ack) = be_u32(data)?;
+            let (bytes2, a_rwnd)      = be_u32(bytes1)?;
+            let (bytes3, num_gaps)    = be_u16(bytes2)?;
+            let (bytes4, num_dups)    = be_u16(bytes3)?;
+            let (bytes5, gaps)        = take(num_gaps as usize * 4)(bytes4)?;
+            let (_, dups)             = take(num_dups as usize * 4)(bytes5)?;
+            SctpChunk::Sack {
+                cum_tsn_ack,
+                a_rwnd,
+                gap_blocks: gaps
+                    .chunks_exact(4)
+                    .map(|gap| (u16::from_be_bytes([gap[0], gap[1]]), u16::from_be_bytes([gap[2], gap[3]])))
+                    .collect(),
+                dup_tsns: dups
+                    .chunks_exact(4)
+                    .map(|tsn| u32::from_be_bytes([tsn[0], tsn[1], tsn[2], tsn[3]]))
+                    .collect()
+            }
+        },
+        4  => SctpChunk::Heartbeat(parse_params(data)?.1),
+        5  => SctpChunk::HeartbeatAck(parse_params(data)?.1),
+        6  => SctpChunk::Abort { t_bit: flags & 0x01 != 0, causes: parse_causes(data)?.1 },
+        7  => SctpChunk::Shutdown { cum_tsn_ack: be_u32(data)?.1 },
+        8  => SctpChunk::ShutdownAck,
+        9  => SctpChunk::Error(parse_causes(data)?.1),
+        10 => SctpChunk::CookieEcho(data.to_vec()),
+        11 => SctpChunk::CookieAck,
+        14 => SctpChunk::ShutdownComplete { t_bit: flags & 0x01 != 0 },
+        64 => {
+            let (bytes1, tsn)       = be_u32(data)?;
+            let (bytes2, stream_id) = be_u16(bytes1)?;
+            let (bytes3, _reserved) = be_u16(bytes2)?;
+            let (bytes4, mid)       = be_u32(bytes3)?;
+            let (bytes5, ppid_fsn)  = be_u32(bytes4)?;
+            SctpChunk::IData {
+                immediate, unordered, beginning, ending,
+                tsn, stream_id, mid, ppid_fsn,
+                payload: bytes5.to_vec()
+            }
+        },
+        _ => SctpChunk::Unknown { chunk_type, flags, data: data.to_vec() }
+    };
+    Ok(chunk)
+}
+
+#[cfg(test)]
+mod tests_sctp {
+    use crate::sctp::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn parse_init() {
+        let frame = &mut [
+            0x0b,0x59,0x0b,0x59,0x00,0x00,0x00,0x00,
+            0x55,0x26,0x02,0xe6,0x01,0x00,0x00,0x20,
+            0x1c,0x2f,0x6a,0x3e,0x00,0x01,0x00,0x00,
+            0x00,0x0a,0xff,0xff,0x6f,0x39,0x0b,0x68,
+            0x00,0x05,0x00,0x08,0xc0,0xa8,0x01,0x0a,
+            0x80,0x00,0x00,0x04
+        ];
+
+        let (leftover, sctp) = Sctp::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(2905, sctp.source);
+        assert_eq!(0, sctp.verification_tag);
+        assert_eq!(vec![SctpChunk::Init(SctpInit {
+            initiate_tag:     0x1c2f6a3e,
+            a_rwnd:           65536,
+            outbound_streams: 10,
+            inbound_streams:  65535,
+            initial_tsn:      0x6f390b68,
+            params:           vec![
+                SctpParameter::Ipv4Address(Ipv4Addr::new(192, 168, 1, 10)),
+                SctpParameter::Unknown { param_type: 0x8000, value: vec![] }
+            ]
+        })], sctp.chunks);
+        assert!(sctp.verify_checksum());
+        assert_eq!("SCTP(source: 2905, destination: 2905, vtag: 0x00000000, chunks: 1)", format!("{}", sctp));
+        assert_eq!(frame.to_vec(), sctp.as_bytes());
+    }
+
+    #[test]
+    fn parse_chunks() {
+        let frame = &mut [
+            0x0b,0x59,0x0b,0x59,0x1c,0x2f,0x6a,0x3e,
+            0x00,0x00,0x00,0x00,0x00,0x03,0x00,0x13,
+            0x6f,0x39,0x0b,0x68,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x03,0x01,0x02,0x03,0x00,
+            0x03,0x00,0x00,0x18,0x6f,0x39,0x0b,0x67,
+            0x00,0x01,0x00,0x00,0x00,0x01,0x00,0x01,
+            0x00,0x02,0x00,0x03,0x6f,0x39,0x0b,0x60,
+            0x06,0x01,0x00,0x08,0x00,0x0c,0x00,0x04,
+            0x0e,0x00,0x00,0x04
+        ];
+
+        let (leftover, mut sctp) = Sctp::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(vec![
+            SctpChunk::data(0x6f390b68, 0, 0, 3, vec![0x01, 0x02, 0x03]),
+            SctpChunk::Sack {
+                cum_tsn_ack: 0x6f390b67,
+                a_rwnd:      65536,
+                gap_blocks:  vec![(2, 3)],
+                dup_tsns:    vec![0x6f390b60]
+            },
+            SctpChunk::Abort { t_bit: true, causes: vec![SctpErrorCause { code: 12, info: vec![] }] },
+            SctpChunk::ShutdownComplete { t_bit: false }
+        ], sctp.chunks);
+        assert_eq!(Some(PayloadProtocol::M3ua), sctp.chunks[0].payload_protocol());
+        assert!(!sctp.verify_checksum());
+        sctp.set_checksum();
+        assert!(sctp.verify_checksum());
+        assert_eq!(frame[12..].to_vec(), sctp.as_bytes()[12..].to_vec());
+    }
+}
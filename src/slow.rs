@@ -0,0 +1,348 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use eui48::MacAddress;
+use nom::bytes::complete::take;
+use nom::number::complete::{be_u8, be_u16, be_u32};
+use nom::{call, do_parse, tag, take, IResult};
+use std::fmt;
+use std::io::{Cursor, Write};
+
+pub const SLOW_PROTOCOLS_MULTICAST: [u8; 6] = [0x01, 0x80, 0xc2, 0x00, 0x00, 0x02];
+
+pub const SLOW_SUBTYPE_LACP:   u8 = 1;
+pub const SLOW_SUBTYPE_MARKER: u8 = 2;
+
+// Actor/Partner state bits
+pub const LACP_STATE_ACTIVITY:        u8 = 0x01;
+pub const LACP_STATE_TIMEOUT:         u8 = 0x02;
+pub const LACP_STATE_AGGREGATION:     u8 = 0x04;
+pub const LACP_STATE_SYNCHRONIZATION: u8 = 0x08;
+pub const LACP_STATE_COLLECTING:      u8 = 0x10;
+pub const LACP_STATE_DISTRIBUTING:    u8 = 0x20;
+pub const LACP_STATE_DEFAULTED:       u8 = 0x40;
+pub const LACP_STATE_EXPIRED:         u8 = 0x80;
+
+// Actor or Partner information of a LACPDU
+#[derive(Debug, PartialEq, Clone)]
+pub struct LacpInfo {
+    pub system_priority: u16,
+    pub system:          MacAddress,
+    pub key:             u16,
+    pub port_priority:   u16,
+    pub port:            u16,
+    pub state:           u8
+}
+
+impl Default for LacpInfo {
+    fn default() -> LacpInfo {
+        LacpInfo {
+            system_priority: 0,
+            system:          MacAddress::nil(),
+            key:             0,
+            port_priority:   0,
+            port:            0,
+            state:           0
+        }
+    }
+}
+
+#[allow(unused_must_use)]
+impl LacpInfo {
+    pub fn as_bytes(self, tlv_type: u8) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(tlv_type);
+        cursor.write_u8(20);
+        cursor.write_u16::<NetworkEndian>(self.system_priority);
+        cursor.write_all(self.system.as_bytes());
+        cursor.write_u16::<NetworkEndian>(self.key);
+        cursor.write_u16::<NetworkEndian>(self.port_priority);
+        cursor.write_u16::<NetworkEndian>(self.port);
+        cursor.write_u8(self.state);
+        cursor.write_all(&[0; 3]);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8], tlv_type: u8) -> IResult<&[u8], LacpInfo> {
+        do_parse!(
+            bytes,
+            _tlv_type:       tag!([tlv_type, 20]) >>
+            system_priority: be_u16 >>
+            system:          take!(6) >>
+            key:             be_u16 >>
+            port_priority:   be_u16 >>
+            port:            be_u16 >>
+            state:           be_u8  >>
+            _reserved:       take!(3) >>
+                (
+                    LacpInfo {
+                        system_priority,
+                        system: MacAddress::from_bytes(system).unwrap(),
+                        key,
+                        port_priority,
+                        port,
+                        state
+                    }
+                )
+        )
+    }
+}
+
+// Link Aggregation Control Protocol (IEEE 802.1AX)
+#[derive(Debug, PartialEq)]
+pub struct Lacp {
+    pub version:             u8,
+    pub actor:               LacpInfo,
+    pub partner:             LacpInfo,
+    pub collector_max_delay: u16
+}
+
+impl Default for Lacp {
+    fn default() -> Lacp {
+        Lacp {
+            version:             1,
+            actor:               LacpInfo::default(),
+            partner:             LacpInfo::default(),
+            collector_max_delay: 0
+        }
+    }
+}
+
+impl fmt::Display for Lacp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "LACP(\
+             actor: \"{}\", \
+             actor_port: {}, \
+             actor_state: 0x{:02x}, \
+             partner: \"{}\", \
+             partner_port: {}, \
+             partner_state: 0x{:02x}\
+             )",
+            self.actor.system.to_hex_string(),
+            self.actor.port,
+            self.actor.state,
+            self.partner.system.to_hex_string(),
+            self.partner.port,
+            self.partner.state
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Lacp {
+    // Instantiate a new LACPDU
+    pub fn new() -> Lacp { Lacp::default() }
+
+    // Encode the LACPDU into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(SLOW_SUBTYPE_LACP);
+        cursor.write_u8(self.version);
+        cursor.write_all(&self.actor.as_bytes(1));
+        cursor.write_all(&self.partner.as_bytes(2));
+        cursor.write_u8(3);
+        cursor.write_u8(16);
+        cursor.write_u16::<NetworkEndian>(self.collector_max_delay);
+        cursor.write_all(&[0; 12]);
+        // Terminator and reserved
+        cursor.write_all(&[0; 52]);
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Lacp> {
+        do_parse!(
+            bytes,
+            _subtype:            tag!([SLOW_SUBTYPE_LACP]) >>
+            version:             be_u8 >>
+            actor:               call!(LacpInfo::from_bytes, 1) >>
+            partner:             call!(LacpInfo::from_bytes, 2) >>
+            _collector:          tag!([3, 16]) >>
+            collector_max_delay: be_u16 >>
+            _reserved:           take!(12) >>
+            _terminator:         tag!([0, 0]) >>
+            _pad:                call!(parse_reserved, 50) >>
+                (
+                    Lacp {
+                        version,
+                        actor,
+                        partner,
+                        collector_max_delay
+                    }
+                )
+        )
+    }
+}
+
+// Marker Protocol (IEEE 802.1AX)
+#[derive(Debug, PartialEq)]
+pub struct Marker {
+    pub version:          u8,
+    // Marker Response when set, Marker Information otherwise
+    pub response:         bool,
+    pub requester_port:   u16,
+    pub requester_system: MacAddress,
+    pub transaction_id:   u32
+}
+
+impl Default for Marker {
+    fn default() -> Marker {
+        Marker {
+            version:          1,
+            response:         false,
+            requester_port:   0,
+            requester_system: MacAddress::nil(),
+            transaction_id:   0
+        }
+    }
+}
+
+impl fmt::Display for Marker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Marker(\
+             response: {}, \
+             port: {}, \
+             system: \"{}\", \
+             transaction_id: {}\
+             )",
+            self.response,
+            self.requester_port,
+            self.requester_system.to_hex_string(),
+            self.transaction_id
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Marker {
+    // Instantiate a new Marker Information PDU
+    pub fn new() -> Marker { Marker::default() }
+
+    // Build a Marker Response to a Marker Information PDU
+    pub fn response(marker: &Marker) -> Marker {
+        Marker {
+            response:         true,
+            requester_port:   marker.requester_port,
+            requester_system: marker.requester_system,
+            transaction_id:   marker.transaction_id,
+            ..Marker::default()
+        }
+    }
+
+    // Encode the Marker PDU into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(SLOW_SUBTYPE_MARKER);
+        cursor.write_u8(self.version);
+        cursor.write_u8(if self.response { 2 } else { 1 });
+        cursor.write_u8(16);
+        cursor.write_u16::<NetworkEndian>(self.requester_port);
+        cursor.write_all(self.requester_system.as_bytes());
+        cursor.write_u32::<NetworkEndian>(self.transaction_id);
+        cursor.write_all(&[0; 2]);
+        // Terminator and reserved
+        cursor.write_all(&[0; 92]);
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Marker> {
+        do_parse!(
+            bytes,
+            _subtype:         tag!([SLOW_SUBTYPE_MARKER]) >>
+            version:          be_u8 >>
+            tlv_type:         be_u8 >>
+            _length:          tag!([16]) >>
+            requester_port:   be_u16 >>
+            requester_system: take!(6) >>
+            transaction_id:   be_u32 >>
+            _pad:             take!(2) >>
+            _terminator:      tag!([0, 0]) >>
+            _reserved:        call!(parse_reserved, 90) >>
+                (
+                    Marker {
+                        version,
+                        response:         tlv_type == 2,
+                        requester_port,
+                        requester_system: MacAddress::from_bytes(requester_system).unwrap(),
+                        transaction_id
+                    }
+                )
+        )
+    }
+}
+
+// private functions
+
+// Reserved octets at the end of the PDU, tolerating captures that strip them
+fn parse_reserved(bytes: &[u8], len: usize) -> IResult<&[u8], &[u8]> {
+    take(len.min(bytes.len()))(bytes)
+}
+
+#[cfg(test)]
+mod tests_slow {
+    use crate::slow::*;
+    use eui48::MacAddress;
+    use std::str::FromStr;
+
+    #[test]
+    fn parse_lacp() {
+        let mut frame = vec![
+            0x01,0x01,0x01,0x14,0xff,0xff,0x00,0x1b,
+            0x21,0x3c,0x9d,0xf8,0x00,0x0f,0x00,0xff,
+            0x00,0x01,0x3d,0x00,0x00,0x00,0x02,0x14,
+            0x80,0x00,0x00,0x1e,0x49,0x2f,0x4b,0xc0,
+            0x01,0x5b,0x80,0x00,0x00,0x01,0x3f,0x00,
+            0x00,0x00,0x03,0x10,0x00,0x32
+        ];
+        frame.extend(vec![0; 64]);
+
+        let (leftover, lacp) = Lacp::from_bytes(&frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(LacpInfo {
+            system_priority: 65535,
+            system:          MacAddress::from_str("00:1b:21:3c:9d:f8").unwrap(),
+            key:             15,
+            port_priority:   255,
+            port:            1,
+            state:           LACP_STATE_ACTIVITY | LACP_STATE_AGGREGATION | LACP_STATE_SYNCHRONIZATION |
+                             LACP_STATE_COLLECTING | LACP_STATE_DISTRIBUTING
+        }, lacp.actor);
+        assert_eq!(32768, lacp.partner.system_priority);
+        assert_eq!(0x3f, lacp.partner.state);
+        assert_eq!(50, lacp.collector_max_delay);
+        assert_eq!(
+            "LACP(actor: \"00:1b:21:3c:9d:f8\", actor_port: 1, actor_state: 0x3d, \
+             partner: \"00:1e:49:2f:4b:c0\", partner_port: 1, partner_state: 0x3f)",
+            format!("{}", lacp)
+        );
+        assert_eq!(frame, lacp.as_bytes());
+    }
+
+    #[test]
+    fn parse_marker() {
+        let mut frame = vec![
+            0x02,0x01,0x01,0x10,0x00,0x05,0x02,0x00,
+            0x00,0x00,0x00,0x01,0x00,0x00,0x00,0x2a,
+            0x00,0x00
+        ];
+        frame.extend(vec![0; 92]);
+
+        let (leftover, marker) = Marker::from_bytes(&frame).unwrap();
+        assert!(leftover.is_empty());
+        assert!(!marker.response);
+        assert_eq!(5, marker.requester_port);
+        assert_eq!(42, marker.transaction_id);
+
+        let response = Marker::response(&marker);
+        assert!(response.response);
+        assert_eq!(marker.requester_system, response.requester_system);
+        assert_eq!(0x02, response.as_bytes()[2]);
+        assert_eq!(frame, marker.as_bytes());
+    }
+}
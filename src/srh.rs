@@ -0,0 +1,405 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::multi::count;
+use nom::number::complete::{be_u8, be_u16};
+use nom::{call, do_parse, take, IResult};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Cursor, Write};
+use std::net::Ipv6Addr;
+
+use crate::ipv6::IPv6;
+use crate::util::parse_ip6addr;
+
+pub const IPPROTO_ROUTING: u8 = 43;
+pub const IPPROTO_ETHERNET: u8 = 143;
+
+pub const ROUTING_TYPE_SRH: u8 = 4;
+
+// Largest header in octets, the length counts 8 octet units beyond the
+// first, and largest segment list
+pub const SRH_MAX_LENGTH:   usize = 256 * 8;
+pub const SRH_MAX_SEGMENTS: usize = 256;
+
+// TLV types
+pub const SRH_TLV_PAD1: u8 = 0;
+pub const SRH_TLV_PADN: u8 = 4;
+pub const SRH_TLV_HMAC: u8 = 5;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SrhTlv {
+    Pad1,
+    PadN(usize),
+    // The D flag disables the verification of the destination address
+    Hmac { d_flag: bool, key_id: u32, hmac: Vec<u8> },
+    Unknown { tlv_type: u8, data: Vec<u8> }
+}
+
+#[allow(unused_must_use)]
+impl SrhTlv {
+    // Instantiate an HMAC TLV, None when the HMAC does not fit the length
+    pub fn hmac(d_flag: bool, key_id: u32, hmac: Vec<u8>) -> Option<SrhTlv> {
+        let tlv = SrhTlv::Hmac { d_flag, key_id, hmac };
+        if !tlv.is_valid() {
+            return None
+        }
+        Some(tlv)
+    }
+
+    // Whether the data fits the 8 bit length
+    pub fn is_valid(&self) -> bool {
+        match self {
+            SrhTlv::Pad1 => true,
+            SrhTlv::PadN(length) => *length <= 255,
+            SrhTlv::Hmac { hmac, .. } => hmac.len() <= 255 - 6,
+            SrhTlv::Unknown { data, .. } => data.len() <= 255
+        }
+    }
+
+    pub fn as_bytes(self) -> Vec<u8> {
+        let (tlv_type, data) = match self {
+            SrhTlv::Pad1 =>
+                return vec![SRH_TLV_PAD1],
+            SrhTlv::PadN(length) =>
+                (SRH_TLV_PADN, vec![0; length]),
+            SrhTlv::Hmac { d_flag, key_id, hmac } => {
+                let flags: u16 = if d_flag { 0x8000 } else { 0 };
+                (SRH_TLV_HMAC, [flags.to_be_bytes().to_vec(), key_id.to_be_bytes().to_vec(), hmac].concat())
+            },
+            SrhTlv::Unknown { tlv_type, data } =>
+                (tlv_type, data)
+        };
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(tlv_type);
+        cursor.write_u8(data.len() as u8);
+        cursor.write_all(&data);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], SrhTlv> {
+        let (bytes1, tlv_type) = be_u8(bytes)?;
+        if tlv_type == SRH_TLV_PAD1 {
+            return Ok((bytes1, SrhTlv::Pad1))
+        }
+        let (bytes2, length) = be_u8(bytes1)?;
+        let (bytes3, data)   = take(length)(bytes2)?;
+        let tlv = match (tlv_type, data.len()) {
+            (SRH_TLV_PADN, length) =>
+                SrhTlv::PadN(length),
+            (SRH_TLV_HMAC, 6..=255) => SrhTlv::Hmac {
+                d_flag: data[0] & 0x80 != 0,
+                key_id: u32::from_be_bytes([data[2], data[3], data[4], data[5]]),
+                hmac:   data[6..].to_vec()
+            },
+            _ =>
+                SrhTlv::Unknown { tlv_type, data: data.to_vec() }
+        };
+        Ok((bytes3, tlv))
+    }
+}
+
+// IPv6 Segment Routing Header (RFC 8754), the segment list is stored in
+// wire order with the last segment first
+#[derive(Debug, PartialEq)]
+pub struct Srh {
+    pub next_header:   u8,
+    pub segments_left: u8,
+    pub last_entry:    u8,
+    pub flags:         u8,
+    pub tag:           u16,
+    pub segments:      Vec<Ipv6Addr>,
+    pub tlvs:          Vec<SrhTlv>
+}
+
+impl Default for Srh {
+    fn default() -> Srh {
+        Srh {
+            next_header:   59,
+            segments_left: 0,
+            last_entry:    0,
+            flags:         0,
+            tag:           0,
+            segments:      Vec::new(),
+            tlvs:          Vec::new()
+        }
+    }
+}
+
+impl fmt::Display for Srh {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "SRH(\
+             next_header: {}, \
+             segments_left: {}, \
+             last_entry: {}, \
+             tag: {}, \
+             segments: {:?}\
+             )",
+            self.next_header,
+            self.segments_left,
+            self.last_entry,
+            self.tag,
+            self.segments
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Srh {
+    // Instantiate a new SRH without segments
+    pub fn new() -> Srh { Srh::default() }
+
+    // Instantiate an SRH steering through the segments in path order. None
+    // without segments or when the header would exceed its length
+    pub fn path(next_header: u8, segments: &[Ipv6Addr]) -> Option<Srh> {
+        let last_entry = u8::try_from(segments.len().checked_sub(1)?).ok()?;
+        let srh = Srh {
+            next_header,
+            segments_left: last_entry,
+            last_entry,
+            segments:      segments.iter().rev().cloned().collect(),
+            ..Srh::default()
+        };
+        if !srh.is_valid() {
+            return None
+        }
+        Some(srh)
+    }
+
+    // Append a TLV, None when it or the header would exceed its length
+    pub fn push_tlv(&mut self, tlv: SrhTlv) -> Option<()> {
+        self.tlvs.push(tlv);
+        if !self.is_valid() {
+            self.tlvs.pop();
+            return None
+        }
+        Some(())
+    }
+
+    // Whether the segment list, the TLVs and the header fit their lengths,
+    // the encoding of an invalid header is undefined
+    pub fn is_valid(&self) -> bool {
+        (1..=SRH_MAX_SEGMENTS).contains(&self.segments.len()) &&
+            self.tlvs.iter().all(|tlv| tlv.is_valid()) &&
+            self.length() <= SRH_MAX_LENGTH
+    }
+
+    // Insert an SRH into the packet, the original destination becomes the
+    // last segment and the first segment the destination. None when the
+    // payload length overflows
+    pub fn insert(ipv6: IPv6, segments: &[Ipv6Addr]) -> Option<(IPv6, Srh)> {
+        let path = [segments, &[ipv6.destination]].concat();
+        let srh = Srh::path(ipv6.next_header, &path)?;
+        let ipv6 = IPv6 {
+            payload_length: ipv6.payload_length.checked_add(u16::try_from(srh.length()).ok()?)?,
+            next_header:    IPPROTO_ROUTING,
+            destination:    path[0],
+            ..ipv6
+        };
+        Some((ipv6, srh))
+    }
+
+    // Encapsulate an inner packet of the protocol and length into an outer
+    // IPv6 header with an SRH. None without segments or when the payload
+    // length overflows
+    pub fn encapsulate(source: Ipv6Addr, segments: &[Ipv6Addr], next_header: u8, payload_length: u16) -> Option<(IPv6, Srh)> {
+        let destination = *segments.first()?;
+        let srh = Srh::path(next_header, segments)?;
+        let ipv6 = IPv6 {
+            payload_length: payload_length.checked_add(u16::try_from(srh.length()).ok()?)?,
+            next_header:    IPPROTO_ROUTING,
+            source,
+            destination,
+            ..IPv6::default()
+        };
+        Some((ipv6, srh))
+    }
+
+    // The segment the packet is currently steered to
+    pub fn active_segment(&self) -> Option<Ipv6Addr> {
+        self.segments.get(self.segments_left as usize).cloned()
+    }
+
+    // The segments still to be visited after the active one, in path order
+    pub fn remaining_segments(&self) -> Vec<Ipv6Addr> {
+        self.segments.iter().take(self.segments_left as usize).rev().cloned().collect()
+    }
+
+    // Process the SRH at the active segment, moving the destination to the
+    // next segment. None when no segment is left
+    pub fn advance(self, ipv6: IPv6) -> Option<(IPv6, Srh)> {
+        let segments_left = self.segments_left.checked_sub(1)?;
+        let destination = *self.segments.get(segments_left as usize)?;
+        Some((IPv6 { destination, ..ipv6 }, Srh { segments_left, ..self }))
+    }
+
+    // Length of the encoded header including the padding
+    pub fn length(&self) -> usize {
+        let length = 8 + self.segments.len() * 16 + self.tlvs.iter().cloned().map(|tlv| tlv.as_bytes().len()).sum::<usize>();
+        length.div_ceil(8) * 8
+    }
+
+    // Encode the SRH into a byte slice, padding the TLVs to a multiple of
+    // 8 octets
+    pub fn as_bytes(self) -> Vec<u8> {
+        let length = self.length();
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.next_header);
+        cursor.write_u8((length / 8 - 1) as u8);
+        cursor.write_u8(ROUTING_TYPE_SRH);
+        cursor.write_u8(self.segments_left);
+        cursor.write_u8(self.last_entry);
+        cursor.write_u8(self.flags);
+        cursor.write_u16::<NetworkEndian>(self.tag);
+        for segment in self.segments {
+            cursor.write_all(&segment.octets());
+        }
+        for tlv in self.tlvs {
+            cursor.write_all(&tlv.as_bytes());
+        }
+        let padding = length - cursor.get_ref().len();
+        match padding {
+            0 => (),
+            1 => { cursor.write_all(&SrhTlv::Pad1.as_bytes()); },
+            n => { cursor.write_all(&SrhTlv::PadN(n - 2).as_bytes()); }
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Srh> {
+        let (bytes1, (srh, body)) = do_parse!(
+            bytes,
+            next_header:    be_u8 >>
+            hdr_ext_len:    be_u8 >>
+            _routing_type:  call!(verify(be_u8, |routing_type| *routing_type == ROUTING_TYPE_SRH)) >>
+            segments_left:  be_u8 >>
+            last_entry:     be_u8 >>
+            flags:          be_u8 >>
+            tag:            be_u16 >>
+            body:           take!(hdr_ext_len as usize * 8) >>
+                (
+                    (
+                        Srh {
+                            next_header,
+                            segments_left,
+                            last_entry,
+                            flags,
+                            tag,
+                            segments: Vec::new(),
+                            tlvs:     Vec::new()
+                        },
+                        body
+                    )
+                )
+        )?;
+
+        let (mut b, segments) = count(parse_ip6addr, srh.last_entry as usize + 1)(body)?;
+        let mut tlvs = Vec::new();
+        while !b.is_empty() {
+            let (leftover, tlv) = SrhTlv::from_bytes(b)?;
+            // Padding is added back on encoding
+            match tlv {
+                SrhTlv::Pad1 | SrhTlv::PadN(_) => (),
+                tlv => tlvs.push(tlv)
+            }
+            b = leftover;
+        }
+
+        Ok((bytes1, Srh { segments, tlvs, ..srh }))
+    }
+}
+
+#[cfg(test)]
+mod tests_srh {
+    use crate::ipv6::IPv6;
+    use crate::srh::*;
+    use std::net::Ipv6Addr;
+
+    #[test]
+    fn parse() {
+        let frame = &mut [
+            0x29,0x04,0x04,0x01,0x01,0x00,0x00,0x00,
+            0xfc,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x02,
+            0xfc,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x01
+        ];
+
+        let (leftover, srh) = Srh::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        let first = Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 1);
+        let last = Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 2);
+        assert_eq!(Srh::path(41, &[first, last]).as_ref(), Some(&srh));
+        assert_eq!(Some(first), srh.active_segment());
+        assert_eq!(vec![last], srh.remaining_segments());
+        assert_eq!(
+            "SRH(next_header: 41, segments_left: 1, last_entry: 1, tag: 0, segments: [fc00::2, fc00::1])",
+            format!("{}", srh)
+        );
+        assert_eq!(frame.to_vec(), srh.as_bytes());
+    }
+
+    #[test]
+    fn insert_hmac() {
+        let destination = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2);
+        let segment = Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 1);
+        let ipv6 = IPv6 { next_header: 17, payload_length: 8, destination, ..IPv6::new() };
+
+        let (ipv6, mut srh) = Srh::insert(ipv6, &[segment]).unwrap();
+        assert_eq!(IPPROTO_ROUTING, ipv6.next_header);
+        assert_eq!(segment, ipv6.destination);
+        assert_eq!(17, srh.next_header);
+        assert_eq!(8 + 40, ipv6.payload_length);
+        assert_eq!(vec![destination], srh.remaining_segments());
+
+        srh.push_tlv(SrhTlv::hmac(false, 1, vec![0xaa; 32]).unwrap()).unwrap();
+        let bytes = srh.as_bytes();
+        assert_eq!(80, bytes.len());
+        let (_, srh) = Srh::from_bytes(&bytes).unwrap();
+        assert_eq!(1, srh.tlvs.len());
+
+        let (ipv6, srh) = srh.advance(ipv6).unwrap();
+        assert_eq!(destination, ipv6.destination);
+        assert_eq!(0, srh.segments_left);
+        assert!(srh.advance(ipv6).is_none());
+    }
+
+    #[test]
+    fn encapsulate() {
+        let source = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+        let segment = Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 1);
+
+        let (ipv6, srh) = Srh::encapsulate(source, &[segment], 41, 40).unwrap();
+        assert_eq!(segment, ipv6.destination);
+        assert_eq!(40 + 24, ipv6.payload_length);
+        assert_eq!(Some(segment), srh.active_segment());
+
+        assert!(Srh::encapsulate(source, &[], 41, 40).is_none());
+        assert!(Srh::encapsulate(source, &[segment], 41, 65520).is_none());
+        let ipv6 = IPv6 { payload_length: 65500, destination: source, ..IPv6::new() };
+        assert!(Srh::insert(ipv6, &[segment]).is_none());
+    }
+
+    #[test]
+    fn build_length() {
+        let segment = Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 1);
+
+        assert!(Srh::path(41, &[]).is_none());
+        assert!(Srh::path(41, &[segment; 128]).is_none());
+        assert!(SrhTlv::hmac(false, 1, vec![0xaa; 250]).is_none());
+
+        let srh = Srh::path(41, &[segment; 127]).unwrap();
+        assert_eq!(8 + 127 * 16, srh.length());
+        assert_eq!(0xfe, srh.as_bytes()[1]);
+
+        let mut srh = Srh::path(41, &[segment; 120]).unwrap();
+        srh.push_tlv(SrhTlv::hmac(false, 1, vec![0xaa; 32]).unwrap()).unwrap();
+        assert!(srh.push_tlv(SrhTlv::hmac(false, 1, vec![0xaa; 249]).unwrap()).is_none());
+        assert_eq!(1, srh.tlvs.len());
+    }
+}
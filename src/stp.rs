@@ -0,0 +1,421 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::number::complete::{be_u8, be_u16, be_u32, be_u64};
+use nom::{do_parse, take, IResult};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Cursor, Write};
+
+pub const STP_VERSION:  u8 = 0;
+pub const RSTP_VERSION: u8 = 2;
+pub const MSTP_VERSION: u8 = 3;
+
+pub const BPDU_TYPE_CONFIG: u8 = 0x00;
+pub const BPDU_TYPE_RST:    u8 = 0x02;
+pub const BPDU_TYPE_TCN:    u8 = 0x80;
+
+pub const BPDU_FLAG_TC:          u8 = 0x01;
+pub const BPDU_FLAG_PROPOSAL:    u8 = 0x02;
+pub const BPDU_FLAG_LEARNING:    u8 = 0x10;
+pub const BPDU_FLAG_FORWARDING:  u8 = 0x20;
+pub const BPDU_FLAG_AGREEMENT:   u8 = 0x40;
+pub const BPDU_FLAG_TC_ACK:      u8 = 0x80;
+
+pub const PORT_ROLE_UNKNOWN:          u8 = 0;
+pub const PORT_ROLE_ALTERNATE_BACKUP: u8 = 1;
+pub const PORT_ROLE_ROOT:             u8 = 2;
+pub const PORT_ROLE_DESIGNATED:       u8 = 3;
+
+// Length of the MST fields preceding the MSTI configuration messages
+const MST_CONFIG_LENGTH: u16 = 64;
+
+// MSTI configuration message
+#[derive(Debug, PartialEq, Clone)]
+pub struct MstiRecord {
+    pub flags:                   u8,
+    pub regional_root_id:        u64,
+    pub internal_root_path_cost: u32,
+    pub bridge_priority:         u8,
+    pub port_priority:           u8,
+    pub remaining_hops:          u8
+}
+
+#[allow(unused_must_use)]
+impl MstiRecord {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.flags);
+        cursor.write_u64::<NetworkEndian>(self.regional_root_id);
+        cursor.write_u32::<NetworkEndian>(self.internal_root_path_cost);
+        cursor.write_u8(self.bridge_priority);
+        cursor.write_u8(self.port_priority);
+        cursor.write_u8(self.remaining_hops);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], MstiRecord> {
+        do_parse!(
+            bytes,
+            flags:                   be_u8  >>
+            regional_root_id:        be_u64 >>
+            internal_root_path_cost: be_u32 >>
+            bridge_priority:         be_u8  >>
+            port_priority:           be_u8  >>
+            remaining_hops:          be_u8  >>
+                (
+                    MstiRecord {
+                        flags,
+                        regional_root_id,
+                        internal_root_path_cost,
+                        bridge_priority,
+                        port_priority,
+                        remaining_hops
+                    }
+                )
+        )
+    }
+}
+
+// MST specific part of an MST BPDU
+#[derive(Debug, PartialEq, Clone)]
+pub struct Mst {
+    pub config_format:                u8,
+    pub config_name:                  String,
+    pub revision:                     u16,
+    pub digest:                       [u8; 16],
+    pub cist_internal_root_path_cost: u32,
+    pub cist_bridge_id:               u64,
+    pub cist_remaining_hops:          u8,
+    pub msti:                         Vec<MstiRecord>
+}
+
+#[allow(unused_must_use)]
+impl Mst {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        let mut name = self.config_name.into_bytes();
+        name.resize(32, 0);
+        cursor.write_u16::<NetworkEndian>(MST_CONFIG_LENGTH + 16 * self.msti.len() as u16);
+        cursor.write_u8(self.config_format);
+        cursor.write_all(&name);
+        cursor.write_u16::<NetworkEndian>(self.revision);
+        cursor.write_all(&self.digest);
+        cursor.write_u32::<NetworkEndian>(self.cist_internal_root_path_cost);
+        cursor.write_u64::<NetworkEndian>(self.cist_bridge_id);
+        cursor.write_u8(self.cist_remaining_hops);
+        for record in self.msti {
+            cursor.write_all(&record.as_bytes());
+        }
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Mst> {
+        do_parse!(
+            bytes,
+            v3_length:                    be_u16   >>
+            config_format:                be_u8    >>
+            config_name:                  take!(32) >>
+            revision:                     be_u16   >>
+            digest:                       take!(16) >>
+            cist_internal_root_path_cost: be_u32   >>
+            cist_bridge_id:               be_u64   >>
+            cist_remaining_hops:          be_u8    >>
+            msti:                         take!(v3_length.saturating_sub(MST_CONFIG_LENGTH) as usize) >>
+                (
+                    Mst {
+                        config_format,
+                        config_name: null_terminated(config_name),
+                        revision,
+                        digest: <[u8; 16]>::try_from(digest).unwrap(),
+                        cist_internal_root_path_cost,
+                        cist_bridge_id,
+                        cist_remaining_hops,
+                        msti: parse_msti(msti)
+                    }
+                )
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Bpdu {
+    pub protocol_id:     u16,
+    pub version:         u8,
+    pub bpdu_type:       u8,
+    // configuration and RST/MST BPDUs only
+    pub flags:           u8,
+    pub root_id:         u64,
+    pub root_path_cost:  u32,
+    pub bridge_id:       u64,
+    pub port_id:         u16,
+    // in units of 1/256 seconds
+    pub message_age:     u16,
+    pub max_age:         u16,
+    pub hello_time:      u16,
+    pub forward_delay:   u16,
+    // RST/MST BPDUs only
+    pub version1_length: u8,
+    // MST BPDUs only
+    pub mst:             Option<Mst>
+}
+
+impl Default for Bpdu {
+    fn default() -> Bpdu {
+        Bpdu {
+            protocol_id:     0,
+            version:         STP_VERSION,
+            bpdu_type:       BPDU_TYPE_CONFIG,
+            flags:           0,
+            root_id:         0,
+            root_path_cost:  0,
+            bridge_id:       0,
+            port_id:         0,
+            message_age:     0,
+            max_age:         20 * 256,
+            hello_time:      2 * 256,
+            forward_delay:   15 * 256,
+            version1_length: 0,
+            mst:             None
+        }
+    }
+}
+
+impl fmt::Display for Bpdu {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (priority, mac) = split_bridge_id(self.root_id);
+        write!(
+            f,
+            "BPDU(\
+             version: {}, \
+             type: 0x{:02x}, \
+             flags: 0x{:02x}, \
+             root: {}/{:012x}, \
+             cost: {}, \
+             port: 0x{:04x}\
+             )",
+            self.version,
+            self.bpdu_type,
+            self.flags,
+            priority,
+            mac,
+            self.root_path_cost,
+            self.port_id
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Bpdu {
+    // Instantiate a new configuration BPDU
+    pub fn new() -> Bpdu { Bpdu::default() }
+
+    // Instantiate a topology change notification BPDU
+    pub fn tcn() -> Bpdu {
+        Bpdu { bpdu_type: BPDU_TYPE_TCN, ..Bpdu::default() }
+    }
+
+    // Instantiate a rapid spanning tree BPDU
+    pub fn rst() -> Bpdu {
+        Bpdu { version: RSTP_VERSION, bpdu_type: BPDU_TYPE_RST, ..Bpdu::default() }
+    }
+
+    // Port role encoded in the flags of RST/MST BPDUs
+    pub fn port_role(&self) -> u8 { (self.flags >> 2) & 0x03 }
+
+    pub fn set_port_role(&mut self, role: u8) {
+        self.flags = (self.flags & !0x0c) | (role & 0x03) << 2;
+    }
+
+    // Encode the BPDU into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u16::<NetworkEndian>(self.protocol_id);
+        cursor.write_u8(self.version);
+        cursor.write_u8(self.bpdu_type);
+        if self.bpdu_type == BPDU_TYPE_TCN {
+            return cursor.into_inner()
+        }
+        cursor.write_u8(self.flags);
+        cursor.write_u64::<NetworkEndian>(self.root_id);
+        cursor.write_u32::<NetworkEndian>(self.root_path_cost);
+        cursor.write_u64::<NetworkEndian>(self.bridge_id);
+        cursor.write_u16::<NetworkEndian>(self.port_id);
+        cursor.write_u16::<NetworkEndian>(self.message_age);
+        cursor.write_u16::<NetworkEndian>(self.max_age);
+        cursor.write_u16::<NetworkEndian>(self.hello_time);
+        cursor.write_u16::<NetworkEndian>(self.forward_delay);
+        if self.version >= RSTP_VERSION {
+            cursor.write_u8(self.version1_length);
+        }
+        if let Some(mst) = self.mst {
+            cursor.write_all(&mst.as_bytes());
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Bpdu> {
+        let (bytes1, (protocol_id, version, bpdu_type)) = do_parse!(
+            bytes,
+            protocol_id: be_u16 >>
+            version:     be_u8  >>
+            bpdu_type:   be_u8  >>
+                ((protocol_id, version, bpdu_type))
+        )?;
+
+        let bpdu = Bpdu { protocol_id, version, bpdu_type, ..Bpdu::default() };
+        if bpdu_type == BPDU_TYPE_TCN {
+            return Ok((bytes1, bpdu))
+        }
+
+        let (bytes2, bpdu) = do_parse!(
+            bytes1,
+            flags:          be_u8  >>
+            root_id:        be_u64 >>
+            root_path_cost: be_u32 >>
+            bridge_id:      be_u64 >>
+            port_id:        be_u16 >>
+            message_age:    be_u16 >>
+            max_age:        be_u16 >>
+            hello_time:     be_u16 >>
+            forward_delay:  be_u16 >>
+                (
+                    Bpdu {
+                        flags,
+                        root_id,
+                        root_path_cost,
+                        bridge_id,
+                        port_id,
+                        message_age,
+                        max_age,
+                        hello_time,
+                        forward_delay,
+                        ..bpdu
+                    }
+                )
+        )?;
+        if version < RSTP_VERSION {
+            return Ok((bytes2, bpdu))
+        }
+
+        let (bytes3, version1_length) = be_u8(bytes2)?;
+        if version < MSTP_VERSION || bytes3.is_empty() {
+            return Ok((bytes3, Bpdu { version1_length, ..bpdu }))
+        }
+
+        let (bytes4, mst) = Mst::from_bytes(bytes3)?;
+        Ok((bytes4, Bpdu { version1_length, mst: Some(mst), ..bpdu }))
+    }
+}
+
+// Compose a bridge identifier from its priority and MAC address
+pub fn bridge_id(priority: u16, mac: u64) -> u64 {
+    (priority as u64) << 48 | (mac & 0xffff_ffff_ffff)
+}
+
+// Split a bridge identifier into its priority and MAC address
+pub fn split_bridge_id(id: u64) -> (u16, u64) {
+    ((id >> 48) as u16, id & 0xffff_ffff_ffff)
+}
+
+// private functions
+
+fn parse_msti(bytes: &[u8]) -> Vec<MstiRecord> {
+    let mut acc = Vec::new();
+    let mut b = bytes;
+
+    while let Ok((leftover, record)) = MstiRecord::from_bytes(b) {
+        acc.push(record);
+        b = leftover;
+    }
+
+    acc
+}
+
+fn null_terminated(bytes: &[u8]) -> String {
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+#[cfg(test)]
+mod tests_stp {
+    use crate::stp::*;
+
+    #[test]
+    fn parse() {
+        let frame = &mut [
+            0x00,0x00,0x02,0x02,0x3c,0x80,0x00,0x00,
+            0x1c,0x0e,0x87,0x78,0x00,0x00,0x00,0x00,
+            0x04,0x80,0x00,0x00,0x1c,0x0e,0x87,0x85,
+            0x00,0x80,0x04,0x01,0x00,0x14,0x00,0x02,
+            0x00,0x0f,0x00,0x00,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00
+        ];
+
+        let (leftover, bpdu) = Bpdu::from_bytes(frame).unwrap();
+        assert_eq!(RSTP_VERSION, bpdu.version);
+        assert_eq!(BPDU_TYPE_RST, bpdu.bpdu_type);
+        assert_eq!(PORT_ROLE_DESIGNATED, bpdu.port_role());
+        assert_eq!(BPDU_FLAG_LEARNING | BPDU_FLAG_FORWARDING, bpdu.flags & 0x30);
+        assert_eq!(bridge_id(32768, 0x001c0e877800), bpdu.root_id);
+        assert_eq!(4, bpdu.root_path_cost);
+        assert_eq!(0x8004, bpdu.port_id);
+        assert_eq!(256, bpdu.message_age);
+        assert_eq!(None, bpdu.mst);
+        assert_eq!(7, leftover.len());
+        assert_eq!(
+            "BPDU(version: 2, type: 0x02, flags: 0x3c, root: 32768/001c0e877800, cost: 4, port: 0x8004)",
+            format!("{}", bpdu)
+        );
+        assert_eq!(frame[..36].to_vec(), bpdu.as_bytes());
+
+        let tcn = Bpdu::from_bytes(&[0x00, 0x00, 0x00, 0x80]).unwrap().1;
+        assert_eq!(Bpdu::tcn(), tcn);
+        assert_eq!(vec![0x00, 0x00, 0x00, 0x80], tcn.as_bytes());
+    }
+
+    #[test]
+    fn parse_mst() {
+        let mut frame = vec![
+            0x00,0x00,0x03,0x02,0x7c,0x80,0x00,0x00,
+            0x1c,0x0e,0x87,0x78,0x00,0x00,0x00,0x00,
+            0x00,0x80,0x00,0x00,0x1c,0x0e,0x87,0x78,
+            0x00,0x80,0x04,0x00,0x00,0x14,0x00,0x02,
+            0x00,0x0f,0x00,0x00,0x00,0x50,0x00,0x72,
+            0x65,0x67,0x69,0x6f,0x6e,0x31
+        ];
+        frame.extend(vec![0; 25]);
+        frame.extend(vec![0x00, 0x01]);
+        frame.extend(vec![0xac; 16]);
+        frame.extend(vec![
+            0x00,0x00,0x00,0x00,0x80,0x00,0x00,0x1c,
+            0x0e,0x87,0x78,0x00,0x14,0x7c,0x80,0x01,
+            0x00,0x1c,0x0e,0x87,0x78,0x00,0x00,0x00,
+            0x00,0x00,0x80,0x80,0x14
+        ]);
+
+        let (leftover, bpdu) = Bpdu::from_bytes(&frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(MSTP_VERSION, bpdu.version);
+        assert_eq!(Some(Mst {
+            config_format:                0,
+            config_name:                  String::from("region1"),
+            revision:                     1,
+            digest:                       [0xac; 16],
+            cist_internal_root_path_cost: 0,
+            cist_bridge_id:               bridge_id(32768, 0x001c0e877800),
+            cist_remaining_hops:          20,
+            msti:                         vec![MstiRecord {
+                flags:                   0x7c,
+                regional_root_id:        bridge_id(32769, 0x001c0e877800),
+                internal_root_path_cost: 0,
+                bridge_priority:         0x80,
+                port_priority:           0x80,
+                remaining_hops:          20
+            }]
+        }), bpdu.mst);
+        assert_eq!(frame, bpdu.as_bytes());
+    }
+}
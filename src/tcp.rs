@@ -101,8 +101,8 @@ impl Tcp {
         let mut acc = Vec::new();
         let mut b = bytes;
 
-        loop {
-            let (leftover, option) = TcpOption::from_bytes(b).unwrap();
+        // Segments without options or without an End-of-Option list
+        while let Ok((leftover, option)) = TcpOption::from_bytes(b) {
 
             if option.number == 0 {
                 acc.push(option);
@@ -112,6 +112,8 @@ impl Tcp {
             acc.push(option);
             b = leftover;
         }
+
+        acc
     }
 }
 
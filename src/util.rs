@@ -0,0 +1,47 @@
+use nom::bytes::complete::take;
+use nom::IResult;
+use std::convert::TryFrom;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+// Octets of an address of either family
+pub(crate) fn ip_octets(ip: IpAddr) -> Vec<u8> {
+    match ip {
+        IpAddr::V4(ip) => ip.octets().to_vec(),
+        IpAddr::V6(ip) => ip.octets().to_vec()
+    }
+}
+
+// Address of the family given by the slice length, 4 or 16 octets
+pub(crate) fn parse_ip(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes.len() {
+        4 => Some(IpAddr::V4(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]))),
+        16 => Some(IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(bytes).unwrap()))),
+        _ => None
+    }
+}
+
+pub(crate) fn parse_ip4addr(bytes: &[u8]) -> IResult<&[u8], Ipv4Addr> {
+    let (bytes1, value) = take(4usize)(bytes)?;
+    let ip4addr = Ipv4Addr::new(value[0], value[1], value[2], value[3]);
+    Ok((bytes1, ip4addr))
+}
+
+pub(crate) fn parse_ip6addr(bytes: &[u8]) -> IResult<&[u8], Ipv6Addr> {
+    let (bytes1, value) = take(16usize)(bytes)?;
+    let ip6addr = Ipv6Addr::from(<[u8; 16]>::try_from(value).unwrap());
+    Ok((bytes1, ip6addr))
+}
+
+// Apply a parser until the input is exhausted
+pub(crate) fn parse_all<'a, T>(bytes: &'a [u8], parser: fn(&'a [u8]) -> IResult<&'a [u8], T>) -> IResult<&'a [u8], Vec<T>> {
+    let mut acc = Vec::new();
+    let mut b = bytes;
+
+    while !b.is_empty() {
+        let (leftover, item) = parser(b)?;
+        acc.push(item);
+        b = leftover;
+    }
+
+    Ok((b, acc))
+}
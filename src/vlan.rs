@@ -4,17 +4,48 @@ use nom::{do_parse, IResult};
 use std::fmt;
 use std::io::Cursor;
 
+// Tag protocol identifiers
+pub const ETH_P_8021Q:  u16 = 0x8100; // C-tag
+pub const ETH_P_8021AD: u16 = 0x88a8; // S-tag
+pub const ETH_P_QINQ1:  u16 = 0x9100; // legacy QinQ S-tag
+
+pub const MAX_PCP: u8  = 7;
+pub const MAX_VID: u16 = 4094;
+
+#[derive(Debug, PartialEq)]
+pub enum Dot1QError {
+    InvalidPcp(u8),
+    InvalidVid(u16)
+}
+
+impl fmt::Display for Dot1QError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Dot1QError::InvalidPcp(pcp) =>
+                write!(f, "invalid PCP {} (must be 0..={})", pcp, MAX_PCP),
+            Dot1QError::InvalidVid(vid) =>
+                write!(f, "invalid VID {} (must be 0..={})", vid, MAX_VID)
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Dot1Q {
-    pub tpid: u16,
-    pub tci:  u16
+    // TPID that introduced this tag. It is carried by the preceding header
+    // (the Ethernet type or the eth_type of the outer tag), so it is not
+    // part of the encoded tag.
+    pub tpid:     u16,
+    pub tci:      u16,
+    // EtherType of the encapsulated frame or the TPID of the next tag
+    pub eth_type: u16
 }
 
 impl Default for Dot1Q {
     fn default() -> Dot1Q {
         Dot1Q {
-            tpid: u16::default(),
-            tci:  u16::default()
+            tpid:     ETH_P_8021Q,
+            tci:      u16::default(),
+            eth_type: u16::default()
         }
     }
 }
@@ -26,11 +57,15 @@ impl fmt::Display for Dot1Q {
             "Dot1Q(\
              tpid: 0x{:x}, \
              pcp: {}, \
-             vid: {}\
+             dei: {}, \
+             vid: {}, \
+             type: 0x{:x}\
              )",
             self.tpid,
-            self.tci >> 13,
-            self.tci & 0x1fff
+            self.pcp(),
+            self.dei() as u8,
+            self.vid(),
+            self.eth_type
         )
     }
 }
@@ -39,23 +74,65 @@ impl fmt::Display for Dot1Q {
 impl Dot1Q {
     pub fn new() -> Dot1Q { Dot1Q::default() }
 
+    // Priority code point
+    pub fn pcp(&self) -> u8 { (self.tci >> 13) as u8 }
+
+    // Drop eligible indicator
+    pub fn dei(&self) -> bool { self.tci & 0x1000 != 0 }
+
+    // VLAN identifier
+    pub fn vid(&self) -> u16 { self.tci & 0x0fff }
+
+    pub fn set_pcp(&mut self, pcp: u8) -> Result<(), Dot1QError> {
+        if pcp > MAX_PCP { return Err(Dot1QError::InvalidPcp(pcp)) }
+        self.tci = (self.tci & 0x1fff) | (pcp as u16) << 13;
+        Ok(())
+    }
+
+    pub fn set_dei(&mut self, dei: bool) {
+        self.tci = (self.tci & 0xefff) | if dei { 0x1000 } else { 0x0000 };
+    }
+
+    pub fn set_vid(&mut self, vid: u16) -> Result<(), Dot1QError> {
+        if vid > MAX_VID { return Err(Dot1QError::InvalidVid(vid)) }
+        self.tci = (self.tci & 0xf000) | vid;
+        Ok(())
+    }
+
+    // Whether the tag is a service (outer) tag of a QinQ stack
+    pub fn is_service_tag(&self) -> bool {
+        self.tpid == ETH_P_8021AD || self.tpid == ETH_P_QINQ1
+    }
+
+    // Whether the EtherType is a TPID of another tag
+    pub fn is_tpid(eth_type: u16) -> bool {
+        eth_type == ETH_P_8021Q || eth_type == ETH_P_8021AD || eth_type == ETH_P_QINQ1
+    }
+
     pub fn as_bytes(self) -> Vec<u8> {
         let buf: Vec<u8> = Vec::new();
         let mut cursor = Cursor::new(buf);
         cursor.write_u16::<NetworkEndian>(self.tci);
-        cursor.write_u16::<NetworkEndian>(self.tpid);
+        cursor.write_u16::<NetworkEndian>(self.eth_type);
         cursor.into_inner()
     }
 
+    // Parse a byte slice following a 0x8100 TPID
     pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Dot1Q> {
+        Self::from_bytes_with_tpid(bytes, ETH_P_8021Q)
+    }
+
+    // Parse a byte slice following the given TPID
+    pub fn from_bytes_with_tpid(bytes: &[u8], tpid: u16) -> IResult<&[u8], Dot1Q> {
         do_parse!(
             bytes,
-            tci:  be_u16 >>
-            tpid: be_u16 >>
+            tci:      be_u16 >>
+            eth_type: be_u16 >>
                 (
                     Dot1Q {
-                        tci:  tci,
-                        tpid: tpid
+                        tpid,
+                        tci,
+                        eth_type
                     }
                 )
         )
@@ -64,7 +141,7 @@ impl Dot1Q {
 
 #[cfg(test)]
 mod tests_dot1q {
-    use crate::vlan::Dot1Q;
+    use crate::vlan::*;
 
     #[test]
     fn parse() {
@@ -81,7 +158,26 @@ mod tests_dot1q {
         let vlan = Dot1Q::from_bytes(frame).unwrap().1;
         let vlan = Dot1Q::from_bytes(&vlan.as_bytes()).unwrap().1;
         assert_eq!(0x8100, vlan.tpid);
-        assert_eq!(100, vlan.tci & 0x1fff); // vlan vid + cfi bit
-        assert_eq!(0, vlan.tci >> 13); // vlan pcp
+        assert_eq!(0x8100, vlan.eth_type);
+        assert_eq!(100, vlan.vid());
+        assert!(!vlan.dei());
+        assert_eq!(0, vlan.pcp());
+    }
+
+    #[test]
+    fn setters() {
+        let mut vlan = Dot1Q::from_bytes_with_tpid(&[0xb0, 0x0a, 0x08, 0x00], ETH_P_8021AD).unwrap().1;
+        assert!(vlan.is_service_tag());
+        assert_eq!(5, vlan.pcp());
+        assert!(vlan.dei());
+        assert_eq!(10, vlan.vid());
+        assert_eq!("Dot1Q(tpid: 0x88a8, pcp: 5, dei: 1, vid: 10, type: 0x800)", format!("{}", vlan));
+
+        assert_eq!(Err(Dot1QError::InvalidPcp(8)), vlan.set_pcp(8));
+        assert_eq!(Err(Dot1QError::InvalidVid(4095)), vlan.set_vid(4095));
+        assert_eq!(Ok(()), vlan.set_pcp(3));
+        assert_eq!(Ok(()), vlan.set_vid(4094));
+        vlan.set_dei(false);
+        assert_eq!(vec![0x6f, 0xfe, 0x08, 0x00], vlan.as_bytes());
     }
 }
@@ -0,0 +1,257 @@
+use byteorder::{NetworkEndian, WriteBytesExt};
+use nom::bytes::complete::take;
+use nom::number::complete::{be_u8, be_u16};
+use nom::{do_parse, IResult};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Cursor, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::checksum::checksum;
+use crate::util::{ip_octets, parse_ip};
+
+pub const VRRP_MULTICAST_V4: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 18);
+pub const VRRP_MULTICAST_V6: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 0x12);
+
+pub const VRRP_ADVERTISEMENT: u8 = 1;
+
+pub const VRRP_PRIORITY_DEFAULT: u8 = 100;
+pub const VRRP_PRIORITY_OWNER:   u8 = 255;
+
+// VRRPv2 authentication types
+pub const VRRP_AUTH_NONE:   u8 = 0;
+pub const VRRP_AUTH_SIMPLE: u8 = 1;
+
+// Virtual Router Redundancy Protocol version 2 (RFC 3768) and 3 (RFC 5798)
+#[derive(Debug, PartialEq)]
+pub struct Vrrp {
+    pub version:   u8,
+    pub vr_type:   u8,
+    pub vrid:      u8,
+    pub priority:  u8,
+    // VRRPv2 only
+    pub auth_type: u8,
+    // Seconds in VRRPv2, centiseconds in VRRPv3
+    pub adver_int: u16,
+    pub checksum:  u16,
+    pub addresses: Vec<IpAddr>,
+    // VRRPv2 only
+    pub auth_data: [u8; 8]
+}
+
+impl Default for Vrrp {
+    fn default() -> Vrrp {
+        Vrrp {
+            version:   3,
+            vr_type:   VRRP_ADVERTISEMENT,
+            vrid:      1,
+            priority:  VRRP_PRIORITY_DEFAULT,
+            auth_type: VRRP_AUTH_NONE,
+            adver_int: 100,
+            checksum:  0,
+            addresses: Vec::new(),
+            auth_data: [0; 8]
+        }
+    }
+}
+
+impl fmt::Display for Vrrp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "VRRP(\
+             version: {}, \
+             vrid: {}, \
+             priority: {}, \
+             adver_int: {}, \
+             addresses: {:?}\
+             )",
+            self.version,
+            self.vrid,
+            self.priority,
+            self.adver_int,
+            self.addresses
+        )
+    }
+}
+
+#[allow(unused_must_use)]
+impl Vrrp {
+    // Instantiate a new VRRPv3 advertisement
+    pub fn new() -> Vrrp { Vrrp::default() }
+
+    // Instantiate a VRRPv2 advertisement with a one second interval
+    pub fn advertisement_v2(vrid: u8, priority: u8, addresses: Vec<Ipv4Addr>) -> Vrrp {
+        let mut vrrp = Vrrp {
+            version:   2,
+            vrid,
+            priority,
+            adver_int: 1,
+            addresses: addresses.into_iter().map(IpAddr::V4).collect(),
+            ..Vrrp::default()
+        };
+        vrrp.checksum = vrrp.calculate_vrrp_checksum(IpAddr::V4(Ipv4Addr::UNSPECIFIED), IpAddr::V4(VRRP_MULTICAST_V4));
+        vrrp
+    }
+
+    // Instantiate a VRRPv3 advertisement sent from the source address to
+    // the VRRP multicast group of its family
+    pub fn advertisement_v3(vrid: u8, priority: u8, addresses: Vec<IpAddr>, source: IpAddr) -> Vrrp {
+        let mut vrrp = Vrrp { vrid, priority, addresses, ..Vrrp::default() };
+        let destination = match source {
+            IpAddr::V4(_) => IpAddr::V4(VRRP_MULTICAST_V4),
+            IpAddr::V6(_) => IpAddr::V6(VRRP_MULTICAST_V6)
+        };
+        vrrp.checksum = vrrp.calculate_vrrp_checksum(source, destination);
+        vrrp
+    }
+
+    // VRRPv2 sums the message only, VRRPv3 includes the IP pseudo header
+    pub fn calculate_vrrp_checksum(&self, source: IpAddr, destination: IpAddr) -> u16 {
+        let vrrp = Vrrp {
+            checksum:  0,
+            addresses: self.addresses.clone(),
+            ..*self
+        };
+        let bytes = vrrp.as_bytes();
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        match (self.version, source, destination) {
+            (2, _, _) => (),
+            (_, IpAddr::V4(source), IpAddr::V4(destination)) => {
+                cursor.write_all(&source.octets());
+                cursor.write_all(&destination.octets());
+                cursor.write_u16::<NetworkEndian>(112);
+                cursor.write_u16::<NetworkEndian>(bytes.len() as u16);
+            },
+            (_, source, destination) => {
+                cursor.write_all(&ip_octets(source));
+                cursor.write_all(&ip_octets(destination));
+                cursor.write_u32::<NetworkEndian>(bytes.len() as u32);
+                cursor.write_u32::<NetworkEndian>(112);
+            }
+        }
+        cursor.write_all(&bytes);
+        checksum(&cursor.into_inner())
+    }
+
+    pub fn verify_checksum(&self, source: IpAddr, destination: IpAddr) -> bool {
+        self.checksum == self.calculate_vrrp_checksum(source, destination)
+    }
+
+    // Encode the VRRP advertisement into a byte slice
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        cursor.write_u8(self.version << 4 | (self.vr_type & 0x0f));
+        cursor.write_u8(self.vrid);
+        cursor.write_u8(self.priority);
+        cursor.write_u8(self.addresses.len() as u8);
+        if self.version == 2 {
+            cursor.write_u8(self.auth_type);
+            cursor.write_u8(self.adver_int as u8);
+        } else {
+            cursor.write_u16::<NetworkEndian>(self.adver_int & 0x0fff);
+        }
+        cursor.write_u16::<NetworkEndian>(self.checksum);
+        for address in self.addresses {
+            cursor.write_all(&ip_octets(address));
+        }
+        if self.version == 2 {
+            cursor.write_all(&self.auth_data);
+        }
+        cursor.into_inner()
+    }
+
+    // Parse a byte slice holding the IPv4 payload
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Vrrp> {
+        parse_vrrp(bytes, 4)
+    }
+
+    // Parse a byte slice holding the IPv6 payload, the addresses are IPv6
+    pub fn from_bytes_v6(bytes: &[u8]) -> IResult<&[u8], Vrrp> {
+        parse_vrrp(bytes, 16)
+    }
+}
+
+// private functions
+
+fn parse_vrrp(bytes: &[u8], width: usize) -> IResult<&[u8], Vrrp> {
+    let (bytes1, (version_type, vrid, priority, count, adver_int, checksum)) = do_parse!(
+        bytes,
+        version_type: be_u8 >>
+        vrid:         be_u8 >>
+        priority:     be_u8 >>
+        count:        be_u8 >>
+        adver_int:    be_u16 >>
+        checksum:     be_u16 >>
+            ((version_type, vrid, priority, count, adver_int, checksum))
+    )?;
+    let version = version_type >> 4;
+    let count = count as usize;
+
+    let (bytes2, addresses) = take(count * width)(bytes1)?;
+    let (bytes3, auth_data) = match version {
+        2 => take(8usize)(bytes2)?,
+        _ => (bytes2, &[0; 8][..])
+    };
+
+    Ok((
+        bytes3,
+        Vrrp {
+            version,
+            vr_type:   version_type & 0x0f,
+            vrid,
+            priority,
+            auth_type: if version == 2 { (adver_int >> 8) as u8 } else { 0 },
+            adver_int: if version == 2 { adver_int & 0x00ff } else { adver_int & 0x0fff },
+            checksum,
+            addresses: addresses.chunks_exact(width).filter_map(parse_ip).collect(),
+            auth_data: <[u8; 8]>::try_from(auth_data).unwrap()
+        }
+    ))
+}
+
+#[cfg(test)]
+mod tests_vrrp {
+    use crate::vrrp::*;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn parse_v2() {
+        let frame = &mut [
+            0x21,0x01,0x64,0x01,0x00,0x01,0xba,0x52,
+            0xc0,0xa8,0x00,0x01,0x00,0x00,0x00,0x00,
+            0x00,0x00,0x00,0x00
+        ];
+
+        let (leftover, vrrp) = Vrrp::from_bytes(frame).unwrap();
+        assert!(leftover.is_empty());
+        assert_eq!(Vrrp::advertisement_v2(1, 100, vec![Ipv4Addr::new(192, 168, 0, 1)]), vrrp);
+        assert!(vrrp.verify_checksum(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 2)), IpAddr::V4(VRRP_MULTICAST_V4)));
+        assert_eq!(
+            "VRRP(version: 2, vrid: 1, priority: 100, adver_int: 1, addresses: [192.168.0.1])",
+            format!("{}", vrrp)
+        );
+        assert_eq!(frame.to_vec(), vrrp.as_bytes());
+    }
+
+    #[test]
+    fn parse_v3() {
+        let source = IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1));
+        let address = IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0x100));
+        let vrrp = Vrrp::advertisement_v3(10, 200, vec![address], source);
+        let mut bytes = vrrp.as_bytes();
+        assert_eq!(24, bytes.len());
+
+        // the leftover is not mistaken for addresses
+        bytes.extend_from_slice(&[0x00; 4]);
+        let (leftover, vrrp) = Vrrp::from_bytes_v6(&bytes).unwrap();
+        assert_eq!(&[0x00; 4], leftover);
+        assert_eq!(3, vrrp.version);
+        assert_eq!(100, vrrp.adver_int);
+        assert_eq!(vec![address], vrrp.addresses);
+        assert!(vrrp.verify_checksum(source, IpAddr::V6(VRRP_MULTICAST_V6)));
+        assert!(!vrrp.verify_checksum(IpAddr::V6(Ipv6Addr::LOCALHOST), IpAddr::V6(VRRP_MULTICAST_V6)));
+    }
+}
@@ -63,9 +63,75 @@ impl Vxlan {
     }
 }
 
+// VXLAN Generic Protocol Extension, the next protocol values are shared with NSH
+#[derive(Debug, PartialEq)]
+pub struct VxlanGpe {
+    pub version:           u8,
+    pub has_vni:           bool,
+    pub has_next_protocol: bool,
+    pub bum:               bool,
+    pub oam:               bool,
+    pub next_protocol:     u8,
+    pub vni:               u32
+}
+
+impl Default for VxlanGpe {
+    fn default() -> VxlanGpe {
+        VxlanGpe {
+            version:           0,
+            has_vni:           true,
+            has_next_protocol: true,
+            bum:               false,
+            oam:               false,
+            next_protocol:     0x04,
+            vni:               u32::default()
+        }
+    }
+}
+
+#[allow(unused_must_use)]
+impl VxlanGpe {
+    pub fn as_bytes(self) -> Vec<u8> {
+        let buf: Vec<u8> = Vec::new();
+        let mut cursor = Cursor::new(buf);
+        let has_vni           = if self.has_vni { 0x08 } else { 0x00 };
+        let has_next_protocol = if self.has_next_protocol { 0x04 } else { 0x00 };
+        let bum               = if self.bum { 0x02 } else { 0x00 };
+        let oam               = if self.oam { 0x01 } else { 0x00 };
+        cursor.write_u8(((self.version & 0x03) << 4) | has_vni | has_next_protocol | bum | oam);
+        cursor.write_u16::<NetworkEndian>(0x0000);
+        cursor.write_u8(self.next_protocol);
+        cursor.write_u24::<NetworkEndian>(self.vni);
+        cursor.write_u8(0x00);
+        cursor.into_inner()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], VxlanGpe> {
+        do_parse!(
+            bytes,
+            flags:         be_u8  >>
+            _res1:         be_u16 >>
+            next_protocol: be_u8  >>
+            vni:           be_u24 >>
+            _res2:         be_u8  >>
+                (
+                    VxlanGpe {
+                        version:           (flags >> 4) & 0x03,
+                        has_vni:           flags & 0x08 > 0,
+                        has_next_protocol: flags & 0x04 > 0,
+                        bum:               flags & 0x02 > 0,
+                        oam:               flags & 0x01 > 0,
+                        next_protocol,
+                        vni
+                    }
+                )
+        )
+    }
+}
+
 #[cfg(test)]
 mod test_vxlan {
-    use crate::vxlan::Vxlan;
+    use crate::vxlan::{Vxlan, VxlanGpe};
 
     #[test]
     fn parse() {
@@ -96,4 +162,15 @@ mod test_vxlan {
             group_policy_id: 128
         }, vxlan1);
     }
+
+    #[test]
+    fn parse_gpe() {
+        let frame = &mut [
+            0x0c,0x00,0x00,0x04,0x00,0x01,0x00,0x00
+        ];
+
+        let vxlan = VxlanGpe::from_bytes(frame).unwrap().1;
+        assert_eq!(VxlanGpe { vni: 256, ..VxlanGpe::default() }, vxlan);
+        assert_eq!(frame.to_vec(), vxlan.as_bytes());
+    }
 }